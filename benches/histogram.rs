@@ -0,0 +1,109 @@
+//! The ways of counting windows into a histogram, run with `cargo bench --bench histogram`.
+
+use std::{fs, hint::black_box};
+
+use binviz::{calculate_histogram, calculate_histogram_from_bytes, Histogram};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// The benchmarks only use some of the fixtures.
+#[allow(dead_code)]
+#[path = "../src/test_support.rs"]
+mod test_support;
+
+use test_support::{file_with, noise};
+
+/// The amount of bytes counted per iteration.
+const LEN: usize = 8 << 20;
+
+/// The histogram counted window by window into the tree, allocating a key for every window.
+fn sparse_histogram(buf: &[u8], dimension: usize) -> Histogram<u8> {
+    let mut histogram = Histogram::new();
+    for window in buf.windows(dimension) {
+        *histogram.entry(window.to_vec()).or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// Single bytes and byte pairs counted into dense arrays against counted into the tree.
+fn dense_versus_sparse(c: &mut Criterion) {
+    let buf = noise(LEN);
+    let mut group = c.benchmark_group("dense_versus_sparse");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.sample_size(10);
+    for dimension in [1, 2] {
+        group.bench_with_input(
+            BenchmarkId::new("dense", dimension),
+            &dimension,
+            |b, &dimension| {
+                b.iter(|| calculate_histogram_from_bytes(black_box(&buf), dimension, 1))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("sparse", dimension),
+            &dimension,
+            |b, &dimension| b.iter(|| sparse_histogram(black_box(&buf), dimension)),
+        );
+    }
+    group.finish();
+}
+
+/// Triples and quadruples counted by borrowed windows against allocating a key for every window.
+fn borrowed_versus_allocated(c: &mut Criterion) {
+    // Text repeats its windows, as most files do, so few of them are new keys.
+    let text = b"Every window of this sentence recurs in the buffer, so only the first allocates. ";
+    let buf: Vec<u8> = text.iter().copied().cycle().take(LEN).collect();
+    let mut group = c.benchmark_group("borrowed_versus_allocated");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.sample_size(10);
+    for dimension in [3, 4] {
+        group.bench_with_input(
+            BenchmarkId::new("borrowed", dimension),
+            &dimension,
+            |b, &dimension| {
+                b.iter(|| calculate_histogram_from_bytes(black_box(&buf), dimension, 1))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("allocated", dimension),
+            &dimension,
+            |b, &dimension| b.iter(|| sparse_histogram(black_box(&buf), dimension)),
+        );
+    }
+    group.finish();
+}
+
+/// A file read in chunks of `CHUNK_SIZE` bytes against read into memory at once before counting it.
+fn chunks_versus_whole_file(c: &mut Criterion) {
+    let file = file_with(&noise(LEN));
+    let mut group = c.benchmark_group("chunks_versus_whole_file");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.sample_size(10);
+    for dimension in [1, 2, 3] {
+        group.bench_with_input(
+            BenchmarkId::new("chunks", dimension),
+            &dimension,
+            |b, &dimension| {
+                b.iter(|| calculate_histogram(black_box(file.path()), dimension, 1).unwrap())
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("whole_file", dimension),
+            &dimension,
+            |b, &dimension| {
+                b.iter(|| {
+                    let buf = fs::read(black_box(file.path())).unwrap();
+                    calculate_histogram_from_bytes(&buf, dimension, 1)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    dense_versus_sparse,
+    borrowed_versus_allocated,
+    chunks_versus_whole_file
+);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+//! Generating the digraph image of a large file, run with `cargo bench --bench image`.
+
+use std::hint::black_box;
+
+use binviz::{
+    calculate_histogram, generate_image, generate_image_streaming, Normalization, ScaleMode,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tempfile::NamedTempFile;
+
+// The benchmarks only use some of the fixtures.
+#[allow(dead_code)]
+#[path = "../src/test_support.rs"]
+mod test_support;
+
+use test_support::{file_with, noise};
+
+/// The size of the file the image is generated of.
+const LEN: usize = 64 << 20;
+
+/// A file of `len` mostly zero bytes with a random byte every 4096 bytes, like a sparse disk image.
+fn sparse_file(len: usize) -> NamedTempFile {
+    let mut buf = vec![0; len];
+    for (byte, random) in buf.iter_mut().step_by(4096).zip(noise(len.div_ceil(4096))) {
+        *byte = random;
+    }
+    file_with(&buf)
+}
+
+/// The image generated directly from a flat table of pair counts against from the histogram of pairs.
+///
+/// Both paths read the file in chunks, so their memory use stays flat however large the file is,
+/// but the histogram path looks every pair up in the tree of the histogram.
+fn streaming_versus_histogram(c: &mut Criterion) {
+    let file = sparse_file(LEN);
+    let mut group = c.benchmark_group("streaming_versus_histogram");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.sample_size(10);
+    group.bench_function("streaming", |b| {
+        b.iter(|| {
+            generate_image_streaming(
+                black_box(file.path()),
+                ScaleMode::Linear,
+                Normalization::Average,
+            )
+            .unwrap()
+        })
+    });
+    group.bench_function("histogram", |b| {
+        b.iter(|| {
+            let histogram = calculate_histogram(black_box(file.path()), 2, 1).unwrap();
+            generate_image(&histogram, ScaleMode::Linear, Normalization::Average).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, streaming_versus_histogram);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+/// Generate the C header `binviz.h` in `OUT_DIR` declaring the functions exported with the `ffi` feature,
+/// and compile the C test program `tests/ffi.c` against it into a static library, linked by `tests/ffi.rs` only.
+#[cfg(feature = "ffi")]
+fn main() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=tests/ffi.c");
+    let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR for build scripts");
+    let config = cbindgen::Config::from_file("cbindgen.toml").expect("cbindgen.toml is valid");
+    // Only the ffi module, the rest of the crate isn't part of the C interface.
+    cbindgen::Builder::new()
+        .with_src("src/ffi.rs")
+        .with_config(config)
+        .generate()
+        .expect("the ffi module declares valid C")
+        .write_to_file(format!("{}/binviz.h", out_dir));
+    // Without the metadata the library itself isn't linked to the test program.
+    cc::Build::new()
+        .file("tests/ffi.c")
+        .include(&out_dir)
+        .cargo_metadata(false)
+        .compile("binviz_ffi_test");
+    println!("cargo:rustc-link-search=native={}", out_dir);
+}
+
+#[cfg(not(feature = "ffi"))]
+fn main() {}
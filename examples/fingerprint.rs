@@ -0,0 +1,54 @@
+//! Rebuild a built-in fingerprint of `binviz classify` from its sample files, replacing the saved histograms
+//! `src/fingerprints/<name>.1.bvzh` and `src/fingerprints/<name>.2.bvzh`:
+//! `cargo run --example fingerprint -- <name> <files>...`.
+//!
+//! The files are concatenated and the 32 KiB in the middle are the sample. The samples of the built-in fingerprints
+//! are made from these files, see `binviz::builtin_fingerprints`:
+//!
+//! - `ascii-text`: `/usr/share/common-licenses/GPL-3` of Debian.
+//! - `utf8-text`: `tutor.ru.utf-8`, `tutor.el.utf-8`, `tutor.ja.utf-8` and `tutor.zh.utf-8` in `runtime/tutor` of Vim.
+//! - `x86-64`: the `.text` section of GNU coreutils `ls`, from `objcopy -O binary --only-section=.text /bin/ls ls.text`.
+//! - `jpeg`: the `.jpg` files in `tests/images` of the `image` crate and in `tests/reftest/images` of `jpeg-decoder`.
+//! - `deflate`: every file in `/usr/share/common-licenses` of Debian compressed by `gzip -9`.
+//! - `random`: `head -c 32768 /dev/urandom > random.bin`.
+//! - `zero`: `head -c 32768 /dev/zero > zero.bin`.
+
+use std::{env, error::Error, fs, path::PathBuf, process};
+
+use binviz::{save_histogram, Fingerprint, Format, Label};
+
+/// The amount of bytes in the sample of a fingerprint.
+const SAMPLE_SIZE: usize = 32 * 1024;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let (Some(name), files) = (args.next(), args.map(PathBuf::from).collect::<Vec<_>>()) else {
+        eprintln!("usage: cargo run --example fingerprint -- <name> <files>...");
+        process::exit(2);
+    };
+    let label: Label = name.parse()?;
+    if matches!(label, Label::Custom(_)) || files.is_empty() {
+        eprintln!("expected the name of a built-in fingerprint and at least one file");
+        process::exit(2);
+    }
+    let mut bytes = Vec::new();
+    for file in &files {
+        bytes.extend(fs::read(file).map_err(|err| format!("`{}`: {}", file.display(), err))?);
+    }
+    let start = bytes.len().saturating_sub(SAMPLE_SIZE) / 2;
+    let sample = &bytes[start..bytes.len().min(start + SAMPLE_SIZE)];
+    let fingerprint = Fingerprint::from_bytes(label, sample);
+    let folder = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/fingerprints");
+    for (dimension, histogram) in [(1, &fingerprint.monograph), (2, &fingerprint.digraph)] {
+        let path = folder.join(format!("{}.{}.bvzh", name, dimension));
+        save_histogram(&path, histogram, Format::Bincode, true)?;
+        println!("saved `{}`", path.display());
+    }
+    println!(
+        "the sample of `{}` has `{}` bytes, `{}` distinct",
+        name,
+        sample.len(),
+        fingerprint.monograph.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::Histogram;
+#[cfg(feature = "tables")]
+use crate::{get_most_frequent_bytes, SortOrder};
+
+/// The byte values in 0..=255 that don't occur in a 1-dimensional histogram, in increasing order.
+///
+/// Keys of other lengths are ignored, as are keys with a count of 0.
+pub fn get_missing_bytes(histogram: &Histogram<u8>) -> Vec<u8> {
+    (0..=u8::MAX)
+        .filter(|&byte| histogram.get(&vec![byte]).is_none_or(|&freq| freq == 0))
+        .collect()
+}
+
+/// Format increasing bytes as comma separated hexadecimal values, with runs of consecutive bytes
+/// compressed to inclusive ranges, e.g. `0x00, 0x0e-0x1f, 0x7f`.
+pub fn format_byte_ranges(bytes: &[u8]) -> String {
+    let mut ranges: Vec<(u8, u8)> = Vec::new();
+    for &byte in bytes {
+        match ranges.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(byte) => *end = byte,
+            _ => ranges.push((byte, byte)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                format!("{:#04x}", start)
+            } else {
+                format!("{:#04x}-{:#04x}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Display the alphabet size of a 1-dimensional histogram, the bytes missing from it,
+/// and its `least` least frequent bytes with their counts.
+#[cfg(feature = "tables")]
+pub fn display_alphabet(histogram: &Histogram<u8>, least: usize) -> String {
+    let missing = get_missing_bytes(histogram);
+    let mut lines = vec![format!("Alphabet size is {}/256.", 256 - missing.len())];
+    if !missing.is_empty() {
+        lines.push(format!("Missing bytes: {}.", format_byte_ranges(&missing)));
+    }
+    let least_frequent: Vec<String> = get_most_frequent_bytes(histogram, SortOrder::CountAscending)
+        .into_iter()
+        .filter(|(key, &freq)| key.len() == 1 && freq > 0)
+        .take(least)
+        .map(|(key, freq)| format!("{:#04x} ({})", key[0], freq))
+        .collect();
+    if !least_frequent.is_empty() {
+        lines.push(format!(
+            "Least frequent bytes: {}.",
+            least_frequent.join(", ")
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_bytes_are_listed_separately() {
+        assert_eq!(format_byte_ranges(&[0x00, 0x02, 0x7f]), "0x00, 0x02, 0x7f");
+        assert_eq!(format_byte_ranges(&[0xff]), "0xff");
+        assert_eq!(format_byte_ranges(&[]), "");
+    }
+
+    #[test]
+    fn runs_are_compressed_to_ranges() {
+        let mut bytes = vec![0x00];
+        bytes.extend(0x0e..=0x1f);
+        bytes.push(0x7f);
+        assert_eq!(format_byte_ranges(&bytes), "0x00, 0x0e-0x1f, 0x7f");
+        assert_eq!(format_byte_ranges(&[0x41, 0x42]), "0x41-0x42");
+        let all: Vec<u8> = (0..=u8::MAX).collect();
+        assert_eq!(format_byte_ranges(&all), "0x00-0xff");
+    }
+
+    #[test]
+    fn ranges_do_not_wrap_around() {
+        assert_eq!(format_byte_ranges(&[0xfe, 0xff, 0x00]), "0xfe-0xff, 0x00");
+    }
+
+    #[test]
+    fn missing_bytes_ignore_zero_counts_and_other_lengths() {
+        let histogram: Histogram<u8> = (0..=u8::MAX)
+            .filter(|byte| !(0x10..0x20).contains(byte))
+            .map(|byte| (vec![byte], 1))
+            .chain([(vec![0x40], 0), (vec![0x10, 0x11], 5)])
+            .collect();
+        let mut expected: Vec<u8> = (0x10..0x20).collect();
+        expected.push(0x40);
+        assert_eq!(get_missing_bytes(&histogram), expected);
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn alphabet_lists_missing_and_least_frequent_bytes() {
+        let histogram: Histogram<u8> = (0x20..0x7f)
+            .map(|byte| (vec![byte], 10))
+            .chain([(vec![b'x'], 1), (vec![b'y'], 2)])
+            .collect();
+        assert_eq!(
+            display_alphabet(&histogram, 2),
+            "Alphabet size is 95/256.\n\
+             Missing bytes: 0x00-0x1f, 0x7f-0xff.\n\
+             Least frequent bytes: 0x78 (1), 0x79 (2)."
+        );
+    }
+}
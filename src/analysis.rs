@@ -0,0 +1,653 @@
+use std::{
+    collections::HashMap,
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use image::{ImageBuffer, Luma, Pixel, Rgb, RgbaImage};
+use log::info;
+use serde::Serialize;
+
+use crate::{
+    calculate_entropy_histogram, chi_square, display_alphabet, display_byte_stats,
+    display_chi_square, display_lz_complexity, display_monte_carlo_pi, display_most_frequent,
+    display_remainder, display_run_lengths, display_serial_correlation, display_strings,
+    frequency_table, generate_color_image, generate_image, histogram_serde,
+    html::HtmlReport,
+    profile::{window_entropy, WindowProfile},
+    read_file_with_progress, render_entropy_map, save_image,
+    statistics::{byte_stats_rows, BYTE_STATS_HEADER},
+    validate_window, write_counts, write_image_metadata, write_output, BinvizError, BitDepth,
+    ByteStats, Charset, Colormap, CountsFormat, EntropyEntry, EntropyUnit, FileStats, Histogram,
+    HistogramBuilder, ImageFormat, ImageMetadata, LzComplexity, MapLayout, MonteCarloPi,
+    Normalization, OutputFormat, ProgressEvent, RunLengthReport, RunLengths, ScaleMode,
+    SerialCorrelation, SortOrder, SourceFile, Stage, StringExtractor, TableCells,
+    ENTROPY_MAP_BLOCK_SIZE, FULL_ANALYSIS_FOLDER, FULL_ANALYSIS_PERCENTILE, HTML_FREQUENCY_COUNT,
+    HTML_REPORT_FILE, LEAST_FREQUENT_COUNT, MAX_STRING_LENGTH, MIN_STRING_LENGTH,
+    PROGRESS_GRANULARITY, RUN_LENGTH_COUNT, RUN_THRESHOLD,
+};
+#[cfg(feature = "compression")]
+use crate::{display_compression, Codec, Compressor};
+
+/// What [`full_analysis`](crate::full_analysis) produces for every file, built with [`AnalysisOptions::builder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisOptions {
+    /// The format the images are saved in.
+    pub image_format: ImageFormat,
+    /// The dimensions of the entropies in the entropy table, 1 up to 3 by default.
+    pub entropy_dimensions: RangeInclusive<usize>,
+    /// Also generate the digraph image, `image.png`, and the counts behind it, `counts.csv`. On by default.
+    pub digraph: bool,
+    /// Also generate the trigraph color image, `image_color.png`. On by default, but counting the byte triples
+    /// of a large file takes long and a lot of memory.
+    pub trigraph: bool,
+    /// Also write a self-contained HTML report, [`HTML_REPORT_FILE`], with the tables, statistics and images
+    /// of a file on one page, and an [`HTML_INDEX_FILE`](crate::HTML_INDEX_FILE) linking the reports of all files.
+    pub html: bool,
+    /// Write the results to text files and images, on by default. Turned off with `html` only the reports
+    /// are written.
+    pub text_and_images: bool,
+    /// The folder the folders of the files go in, [`FULL_ANALYSIS_FOLDER`] by default.
+    pub output_dir: PathBuf,
+    /// Only list this many of the most frequent bytes. By default `most_frequent.txt` lists all of them
+    /// and the HTML report [`HTML_FREQUENCY_COUNT`].
+    pub top_n: Option<usize>,
+}
+
+impl AnalysisOptions {
+    /// Start building options from the defaults.
+    pub fn builder() -> AnalysisOptionsBuilder {
+        AnalysisOptionsBuilder::default()
+    }
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        AnalysisOptions {
+            image_format: ImageFormat::default(),
+            entropy_dimensions: 1..=3,
+            digraph: true,
+            trigraph: true,
+            html: false,
+            text_and_images: true,
+            output_dir: PathBuf::from(FULL_ANALYSIS_FOLDER),
+            top_n: None,
+        }
+    }
+}
+
+/// Builds [`AnalysisOptions`], every option not set keeps its default.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisOptionsBuilder {
+    options: AnalysisOptions,
+}
+
+impl AnalysisOptionsBuilder {
+    /// Set [`AnalysisOptions::image_format`].
+    pub fn image_format(mut self, image_format: ImageFormat) -> Self {
+        self.options.image_format = image_format;
+        self
+    }
+
+    /// Set [`AnalysisOptions::entropy_dimensions`].
+    pub fn entropy_dimensions(mut self, entropy_dimensions: RangeInclusive<usize>) -> Self {
+        self.options.entropy_dimensions = entropy_dimensions;
+        self
+    }
+
+    /// Set [`AnalysisOptions::digraph`].
+    pub fn digraph(mut self, digraph: bool) -> Self {
+        self.options.digraph = digraph;
+        self
+    }
+
+    /// Set [`AnalysisOptions::trigraph`].
+    pub fn trigraph(mut self, trigraph: bool) -> Self {
+        self.options.trigraph = trigraph;
+        self
+    }
+
+    /// Set [`AnalysisOptions::html`].
+    pub fn html(mut self, html: bool) -> Self {
+        self.options.html = html;
+        self
+    }
+
+    /// Set [`AnalysisOptions::text_and_images`].
+    pub fn text_and_images(mut self, text_and_images: bool) -> Self {
+        self.options.text_and_images = text_and_images;
+        self
+    }
+
+    /// Set [`AnalysisOptions::output_dir`].
+    pub fn output_dir<P>(mut self, output_dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.options.output_dir = output_dir.into();
+        self
+    }
+
+    /// Set [`AnalysisOptions::top_n`].
+    pub fn top_n(mut self, top_n: usize) -> Self {
+        self.options.top_n = Some(top_n);
+        self
+    }
+
+    /// The options built.
+    pub fn build(self) -> AnalysisOptions {
+        self.options
+    }
+}
+
+/// A visualization in a [`FileAnalysis`], of which only the counts are serialized.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisImage<P>
+where
+    P: Pixel<Subpixel = u16>,
+{
+    /// The image, not serialized.
+    #[serde(skip)]
+    pub image: ImageBuffer<P, Vec<u16>>,
+    /// The amount of windows in the image.
+    pub total: usize,
+    /// The amount of windows at a location that is shown at full brightness.
+    pub full_brightness: f64,
+}
+
+/// Everything the full analysis finds out about a file, see [`analyze_file`].
+///
+/// Serializing it gives the numbers without the images and the histograms behind them.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileAnalysis {
+    /// The path the file was read from.
+    pub path: PathBuf,
+    /// The summary statistics of the file.
+    pub stats: FileStats,
+    /// The entropies of the [`AnalysisOptions::entropy_dimensions`], in bits.
+    pub entropies: Vec<EntropyEntry>,
+    /// The serial correlation coefficient, `None` if every byte is the same.
+    pub serial_correlation: Option<f64>,
+    /// The Monte Carlo estimate of pi and its error, `None` for input too short for a single point.
+    pub monte_carlo_pi: Option<(f64, f64)>,
+    /// The amount of phrases of the Lempel-Ziv complexity and their amount relative to random input.
+    pub lz_complexity: (usize, f64),
+    /// The original and the compressed size.
+    #[cfg(feature = "compression")]
+    pub compression_sizes: (u64, u64),
+    /// The entropy of every block of [`ENTROPY_MAP_BLOCK_SIZE`] bytes, by offset.
+    pub entropy_profile: Vec<(u64, f64)>,
+    /// The entropy profile drawn along a Hilbert curve, not serialized.
+    #[serde(skip)]
+    pub entropy_map: RgbaImage,
+    /// The 1-dimensional histogram.
+    #[serde(with = "histogram_serde")]
+    pub histogram: Histogram<u8>,
+    /// The chi-square statistic of the bytes against the uniform distribution and its p-value.
+    pub chi_square: (f64, f64),
+    /// The moments of the byte values.
+    pub byte_stats: ByteStats,
+    /// The runs of repeated bytes.
+    pub run_lengths: RunLengthReport,
+    /// The ASCII strings of at least [`MIN_STRING_LENGTH`] characters, with their offsets.
+    pub strings: Vec<(u64, String)>,
+    /// The 2-dimensional histogram behind the visualization, if [`AnalysisOptions::digraph`] asked for it,
+    /// not serialized.
+    #[serde(skip)]
+    pub pair_histogram: Option<Histogram<u8>>,
+    /// The visualization of the byte pairs, if [`AnalysisOptions::digraph`] asked for it.
+    pub digraph: Option<AnalysisImage<Luma<u16>>>,
+    /// The visualization of the byte triples, if [`AnalysisOptions::trigraph`] asked for it.
+    pub trigraph: Option<AnalysisImage<Rgb<u16>>>,
+}
+
+/// Analyze `file` like the full analysis, without writing anything, see [`analyze_file_with_progress`].
+pub fn analyze_file<P>(file: P, options: &AnalysisOptions) -> Result<FileAnalysis, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    analyze_file_with_progress(file, options, |_| {})
+}
+
+/// Analyze `file` like the full analysis, reporting every [`Stage`] that starts and the bytes counted
+/// for the frequencies and the visualizations to `progress`. Of the `options` only the entropy dimensions
+/// and whether to generate the visualizations matter, the others are about writing the results,
+/// see [`write_file_analysis`].
+pub fn analyze_file_with_progress<P, F>(
+    file: P,
+    options: &AnalysisOptions,
+    progress: F,
+) -> Result<FileAnalysis, BinvizError>
+where
+    P: AsRef<Path>,
+    F: Fn(ProgressEvent),
+{
+    let file = file.as_ref();
+    // Name the file the bytes are counted of, as several files are analyzed at once.
+    let counting = |event| match event {
+        ProgressEvent::Bytes { processed, total } => progress(ProgressEvent::FileBytes {
+            file: file.to_path_buf(),
+            processed,
+            total,
+        }),
+        event => progress(event),
+    };
+    let stage = |stage| {
+        progress(ProgressEvent::Stage {
+            file: file.to_path_buf(),
+            stage,
+        })
+    };
+
+    // Perform the Ent subcommand, counting the windows of the frequencies and the visualizations and
+    // calculating the other statistics in the same pass over the file.
+    stage(Stage::Entropy);
+    let mut dimensions: Vec<usize> = options.entropy_dimensions.clone().collect();
+    dimensions.push(1);
+    if options.digraph {
+        dimensions.push(2);
+    }
+    if options.trigraph {
+        dimensions.push(3);
+    }
+    dimensions.sort_unstable();
+    dimensions.dedup();
+    for &dimension in &dimensions {
+        validate_window(dimension, 1)?;
+    }
+    let largest = dimensions.last().copied().unwrap_or(1);
+    let mut builders: Vec<HistogramBuilder> = dimensions
+        .iter()
+        .copied()
+        .map(HistogramBuilder::new)
+        .collect();
+    let mut correlation = SerialCorrelation::new();
+    let mut estimate = MonteCarloPi::new();
+    let mut complexity = LzComplexity::new();
+    #[cfg(feature = "compression")]
+    let mut compressor = Compressor::new(Codec::Deflate);
+    let mut profile = WindowProfile::new(
+        ENTROPY_MAP_BLOCK_SIZE,
+        ENTROPY_MAP_BLOCK_SIZE,
+        window_entropy,
+    );
+    let mut runs = RunLengths::new(RUN_THRESHOLD);
+    let mut extractor = StringExtractor::new(MIN_STRING_LENGTH, MAX_STRING_LENGTH, Charset::Ascii);
+    read_file_with_progress(file, largest, PROGRESS_GRANULARITY, &counting, |chunk| {
+        for builder in &mut builders {
+            builder.update(chunk);
+        }
+        correlation.update(chunk);
+        estimate.update(chunk);
+        complexity.update(chunk);
+        #[cfg(feature = "compression")]
+        compressor.update(chunk);
+        profile.update(chunk);
+        runs.update(chunk);
+        extractor.update(chunk);
+    })?;
+    let mut histograms: HashMap<usize, Histogram<u8>> = dimensions
+        .into_iter()
+        .zip(builders.into_iter().map(HistogramBuilder::finish))
+        .collect();
+    let entropies = options
+        .entropy_dimensions
+        .clone()
+        .map(|dimension| {
+            let entropy = calculate_entropy_histogram(&histograms[&dimension], EntropyUnit::Bits)?;
+            Ok(EntropyEntry {
+                dimension,
+                entropy_bits: entropy,
+                relative_entropy: entropy / (8 * dimension) as f64,
+            })
+        })
+        .collect::<Result<Vec<_>, BinvizError>>()?;
+    let serial_correlation = match correlation.finish() {
+        Ok(coefficient) => Some(coefficient),
+        Err(BinvizError::ZeroVariance) => None,
+        Err(err) => return Err(err),
+    };
+    let monte_carlo_pi = match estimate.finish() {
+        Ok(estimate) => Some(estimate),
+        Err(BinvizError::EmptyInput) => None,
+        Err(err) => return Err(err),
+    };
+    let lz_complexity = complexity.finish();
+    #[cfg(feature = "compression")]
+    let compression_sizes = compressor.finish();
+    let entropy_profile = profile.finish();
+    let entropy_map = render_entropy_map(&entropy_profile, MapLayout::Hilbert, Colormap::Turbo)?;
+
+    // Perform the Fre subcommand.
+    stage(Stage::Frequency);
+    let histogram = histograms
+        .remove(&1)
+        .expect("the bytes are counted with the entropies");
+    let chi_square = chi_square(&histogram)?;
+    let byte_stats = ByteStats::compute(&histogram)?;
+    let stats = FileStats::from_histogram(&histogram)?;
+    let run_lengths = runs.finish();
+
+    // Perform the Strings subcommand.
+    stage(Stage::Strings);
+    let strings = extractor.finish();
+
+    // Perform the Vis subcommand.
+    let (pair_histogram, digraph) = if options.digraph {
+        stage(Stage::Visualization);
+        let pair_histogram = histograms
+            .remove(&2)
+            .expect("the byte pairs are counted with the entropies");
+        let (image, total, full_brightness) = generate_image(
+            &pair_histogram,
+            ScaleMode::Linear,
+            Normalization::Percentile(FULL_ANALYSIS_PERCENTILE),
+        )?;
+        info!(
+            "`{}` byte pairs in the visualization of '{}'.",
+            total,
+            file.display()
+        );
+        info!(
+            "full brightness means `{}` byte pairs at that location in the visualization of '{}'.",
+            full_brightness,
+            file.display()
+        );
+        let digraph = AnalysisImage {
+            image,
+            total,
+            full_brightness,
+        };
+        (Some(pair_histogram), Some(digraph))
+    } else {
+        (None, None)
+    };
+
+    // Perform the Vis subcommand of the trigraph.
+    let trigraph = if options.trigraph {
+        stage(Stage::Trigraph);
+        let trihistogram = histograms
+            .remove(&3)
+            .expect("the byte triples are counted with the entropies");
+        let (image, total, full_brightness) = generate_color_image(
+            &trihistogram,
+            ScaleMode::Linear,
+            Normalization::Percentile(FULL_ANALYSIS_PERCENTILE),
+        )?;
+        info!(
+            "`{}` byte triples in the trigraph of '{}'.",
+            total,
+            file.display()
+        );
+        info!(
+            "full brightness means `{}` byte triples at that location in the trigraph of '{}'.",
+            full_brightness,
+            file.display()
+        );
+        Some(AnalysisImage {
+            image,
+            total,
+            full_brightness,
+        })
+    } else {
+        None
+    };
+
+    Ok(FileAnalysis {
+        path: file.to_path_buf(),
+        stats,
+        entropies,
+        serial_correlation,
+        monte_carlo_pi,
+        lz_complexity,
+        #[cfg(feature = "compression")]
+        compression_sizes,
+        entropy_profile,
+        entropy_map,
+        histogram,
+        chi_square,
+        byte_stats,
+        run_lengths,
+        strings,
+        pair_histogram,
+        digraph,
+        trigraph,
+    })
+}
+
+/// Write the results of `analysis` to `output_folder` as the full analysis does, creating it if needed:
+/// the text files and images unless [`AnalysisOptions::text_and_images`] is off, in the
+/// [`AnalysisOptions::image_format`], and with [`AnalysisOptions::html`] the [`HTML_REPORT_FILE`].
+/// Existing results are only overwritten with `force`.
+pub fn write_file_analysis(
+    analysis: &FileAnalysis,
+    output_folder: &Path,
+    options: &AnalysisOptions,
+    force: bool,
+) -> Result<(), BinvizError> {
+    if !output_folder.exists() {
+        fs::create_dir_all(output_folder).map_err(|err| BinvizError::write(output_folder, err))?;
+    }
+    if options.text_and_images {
+        write_text_and_images(analysis, output_folder, options, force)?;
+    }
+    if options.html {
+        let report_path = output_folder.join(HTML_REPORT_FILE);
+        let report = html_report(analysis, &report_path, options)?;
+        write_output(&report_path, report, force)?;
+    }
+    Ok(())
+}
+
+/// The table of the entropies of a [`FileAnalysis`], like that of [`crate::display_entropies`] in bits.
+fn entropy_rows(entropies: &[EntropyEntry]) -> TableCells {
+    let unit = EntropyUnit::Bits;
+    let header = vec![
+        "Dimension".to_string(),
+        format!("Entropy ({})", unit),
+        "Relative Entropy".to_string(),
+        format!("Entropy Rate ({})", unit),
+    ];
+    let format = OutputFormat::Markdown;
+    let mut previous_entropy = None;
+    let rows = entropies
+        .iter()
+        .map(|entry| {
+            // The rate H_n - H_{n-1} is left out when the table doesn't start at dimension 1, as H_{n-1} is missing.
+            let previous = if entry.dimension == 1 {
+                Some(0.0)
+            } else {
+                previous_entropy
+            };
+            previous_entropy = Some(entry.entropy_bits);
+            let entropy_rate = previous.map_or_else(String::new, |previous| {
+                // Clamp rounding errors, which would show up as -0.00000 when the rate is 0.
+                let entropy_rate = (entry.entropy_bits - previous).max(0.0);
+                format.value(entropy_rate, Some(&format!("{} per byte", unit)))
+            });
+            vec![
+                entry.dimension.to_string(),
+                format.value(
+                    entry.entropy_bits,
+                    Some(&format!("{} per {} byte(s)", unit, entry.dimension)),
+                ),
+                format.value(entry.relative_entropy, None),
+                entropy_rate,
+            ]
+        })
+        .collect();
+    (header, rows)
+}
+
+/// The lines describing the statistics of the whole file in `entropy.txt` and the HTML report.
+fn statistics_lines(analysis: &FileAnalysis) -> Vec<String> {
+    let statistics = [
+        display_serial_correlation(analysis.serial_correlation),
+        display_monte_carlo_pi(analysis.monte_carlo_pi),
+        display_lz_complexity(analysis.lz_complexity),
+    ]
+    .into_iter();
+    #[cfg(feature = "compression")]
+    let statistics = statistics.chain([display_compression(
+        Codec::Deflate,
+        analysis.compression_sizes,
+    )]);
+    statistics.collect()
+}
+
+/// Write the text files and images of `analysis` to `output_folder` as `options` say.
+fn write_text_and_images(
+    analysis: &FileAnalysis,
+    output_folder: &Path,
+    options: &AnalysisOptions,
+    force: bool,
+) -> Result<(), BinvizError> {
+    let image_format = options.image_format;
+    let (entropy_header, entropy_rows) = entropy_rows(&analysis.entropies);
+    let entropy_output = format!(
+        "{}\n\n{}",
+        OutputFormat::Markdown.render(entropy_header, entropy_rows),
+        statistics_lines(analysis).join("\n")
+    );
+    let entropy_path = output_folder.join("entropy.txt");
+    write_output(&entropy_path, entropy_output, force)?;
+    let entropy_map_path = output_folder.join(format!("entropy_map.{}", image_format.extension()));
+    save_image(
+        analysis.entropy_map.clone(),
+        &entropy_map_path,
+        image_format,
+        BitDepth::Sixteen,
+        force,
+    )?;
+
+    let (statistic, p_value) = analysis.chi_square;
+    let most_frequent_output = format!(
+        "{}\n\n{}\n\n{}\n\n{}\n\n{}",
+        display_most_frequent(
+            &analysis.histogram,
+            options.top_n,
+            SortOrder::CountDescending,
+            OutputFormat::Markdown,
+        ),
+        display_chi_square(statistic, p_value),
+        display_byte_stats(&analysis.byte_stats),
+        display_alphabet(&analysis.histogram, LEAST_FREQUENT_COUNT),
+        display_run_lengths(
+            &analysis.run_lengths,
+            RUN_LENGTH_COUNT,
+            OutputFormat::Markdown
+        )
+    );
+    let most_frequent_path = output_folder.join("most_frequent.txt");
+    write_output(&most_frequent_path, most_frequent_output, force)?;
+
+    let strings_path = output_folder.join("strings.txt");
+    write_output(
+        &strings_path,
+        display_strings(&analysis.strings, OutputFormat::Markdown),
+        force,
+    )?;
+
+    if let (Some(digraph), Some(pair_histogram)) = (&analysis.digraph, &analysis.pair_histogram) {
+        let image_path = output_folder.join(format!("image.{}", image_format.extension()));
+        save_visualization(digraph, &analysis.path, 2, &image_path, image_format, force)?;
+        // The exact counts behind the image, as it is normalized.
+        let counts_path = output_folder.join("counts.csv");
+        write_counts(pair_histogram, &counts_path, CountsFormat::Csv, force)?;
+    }
+
+    if let Some(trigraph) = &analysis.trigraph {
+        let image_path = output_folder.join(format!("image_color.{}", image_format.extension()));
+        save_visualization(
+            trigraph,
+            &analysis.path,
+            3,
+            &image_path,
+            image_format,
+            force,
+        )?;
+    }
+    Ok(())
+}
+
+/// Save the visualization of the windows of `dimension` bytes of `file` to `path`, with the metadata to
+/// reproduce it if the format is PNG.
+fn save_visualization<P>(
+    visualization: &AnalysisImage<P>,
+    file: &Path,
+    dimension: usize,
+    path: &Path,
+    image_format: ImageFormat,
+    force: bool,
+) -> Result<(), BinvizError>
+where
+    P: Pixel<Subpixel = u16>,
+    ImageBuffer<P, Vec<u16>>: Into<image::DynamicImage>,
+{
+    save_image(
+        visualization.image.clone(),
+        path,
+        image_format,
+        BitDepth::Sixteen,
+        force,
+    )?;
+    if image_format == ImageFormat::Png {
+        let metadata = ImageMetadata::new(
+            vec![SourceFile::from_path(file)?],
+            dimension,
+            ScaleMode::Linear,
+            Normalization::Percentile(FULL_ANALYSIS_PERCENTILE),
+            visualization.total,
+            visualization.full_brightness,
+        );
+        write_image_metadata(path, &metadata)?;
+    }
+    Ok(())
+}
+
+/// The HTML report of `analysis` as `options` say, failing to encode an image is reported for `report_path`.
+fn html_report(
+    analysis: &FileAnalysis,
+    report_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<String, BinvizError> {
+    let mut report = HtmlReport::new(&analysis.path);
+    let (entropy_header, entropy_rows) = entropy_rows(&analysis.entropies);
+    report.heading("Entropy");
+    report.table(&entropy_header, &entropy_rows);
+    for statistic in statistics_lines(analysis) {
+        report.paragraph(&statistic);
+    }
+    report.image(analysis.entropy_map.clone(), "Entropy map", report_path)?;
+
+    let ((header, rows), remainder) = frequency_table(
+        &analysis.histogram,
+        Some(options.top_n.unwrap_or(HTML_FREQUENCY_COUNT)),
+        SortOrder::CountDescending,
+        OutputFormat::Markdown,
+    );
+    report.heading("Byte frequencies");
+    report.table(header, rows);
+    if let Some(remainder) = remainder {
+        report.paragraph(&display_remainder(
+            remainder,
+            analysis.histogram.values().sum(),
+        ));
+    }
+    let (statistic, p_value) = analysis.chi_square;
+    report.paragraph(&display_chi_square(statistic, p_value));
+    report.heading("Byte statistics");
+    report.table(BYTE_STATS_HEADER, byte_stats_rows(&analysis.byte_stats));
+
+    if analysis.digraph.is_some() || analysis.trigraph.is_some() {
+        report.heading("Visualization");
+    }
+    if let Some(digraph) = &analysis.digraph {
+        report.image(digraph.image.clone(), "Byte pairs", report_path)?;
+    }
+    if let Some(trigraph) = &analysis.trigraph {
+        report.image(trigraph.image.clone(), "Byte triples", report_path)?;
+    }
+    Ok(report.finish())
+}
@@ -0,0 +1,152 @@
+use std::{io::BufWriter, path::Path};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, ImageBuffer, Luma, Rgba, RgbaImage,
+};
+
+use crate::{
+    create_output, to_channel, validate_dimension, BinvizError, Histogram, Normalization, ScaleMode,
+};
+
+/// A frame of the trigraph animation, see [`generate_trigraph_slices`].
+pub type TrigraphSlice = ImageBuffer<Luma<u16>, Vec<u16>>;
+
+/// Generate a slice of a trigraph histogram for every value of the third byte, frame `k` is the digraph image
+/// of the pairs of bytes followed by `k`.
+///
+/// The count at full brightness is chosen by `normalization` over the whole histogram,
+/// so the brightness of the frames is comparable.
+pub fn generate_trigraph_slices(
+    trihistogram: &Histogram<u8>,
+    scale: ScaleMode,
+    normalization: Normalization,
+) -> Result<Vec<TrigraphSlice>, BinvizError> {
+    validate_dimension(trihistogram, 3)?;
+    if trihistogram.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let mut frames = vec![ImageBuffer::new(256, 256); 256];
+    let full_count = normalization.full_count(trihistogram);
+    for (slice, freq) in trihistogram {
+        let brightness = scale.brightness(*freq, full_count);
+        let pixel = Luma([to_channel(brightness)]);
+        frames[slice[2] as usize].put_pixel(slice[0] as u32, slice[1] as u32, pixel);
+    }
+    Ok(frames)
+}
+
+/// Save the frames of [`generate_trigraph_slices`] as a looping animated GIF, showing every frame for `delay_ms`.
+/// Frames without any lit pixel are left out if `skip_empty` is set.
+/// Returns the amount of frames written, an existing file is only overwritten with `force`.
+pub fn save_animation<P>(
+    frames: &[TrigraphSlice],
+    path: P,
+    delay_ms: u32,
+    skip_empty: bool,
+    force: bool,
+) -> Result<usize, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = create_output(path, force)?;
+    let mut encoder = GifEncoder::new(BufWriter::new(file));
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|err| BinvizError::image(path, err))?;
+    let mut written = 0;
+    for frame in frames {
+        if skip_empty && frame.pixels().all(|&Luma([brightness])| brightness == 0) {
+            continue;
+        }
+        // GIF only has 8 bits per channel.
+        let rgba: RgbaImage = ImageBuffer::from_fn(frame.width(), frame.height(), |x, y| {
+            let Luma([brightness]) = *frame.get_pixel(x, y);
+            let value = (brightness >> 8) as u8;
+            Rgba([value, value, value, u8::MAX])
+        });
+        let delay = Delay::from_numer_denom_ms(delay_ms, 1);
+        encoder
+            .encode_frame(Frame::from_parts(rgba, 0, 0, delay))
+            .map_err(|err| BinvizError::image(path, err))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Triples ending in 1, 1 and 7.
+    fn triples() -> Histogram<u8> {
+        [(b"ab\x01", 4), (b"cd\x01", 2), (b"ef\x07", 1)]
+            .into_iter()
+            .map(|(key, count)| (key.to_vec(), count))
+            .collect()
+    }
+
+    #[test]
+    fn only_frames_of_occurring_third_bytes_are_lit() {
+        let frames =
+            generate_trigraph_slices(&triples(), ScaleMode::Linear, Normalization::Maximum)
+                .unwrap();
+        assert_eq!(frames.len(), 256);
+        let lit = |frame: &TrigraphSlice| {
+            frame
+                .enumerate_pixels()
+                .filter(|(_, _, pixel)| pixel[0] > 0)
+                .map(|(x, y, _)| (x as u8, y as u8))
+                .collect::<Vec<_>>()
+        };
+        let non_empty: Vec<usize> = (0..256).filter(|&k| !lit(&frames[k]).is_empty()).collect();
+        assert_eq!(non_empty, [1, 7]);
+        assert_eq!(lit(&frames[1]), [(b'a', b'b'), (b'c', b'd')]);
+        assert_eq!(lit(&frames[7]), [(b'e', b'f')]);
+    }
+
+    #[test]
+    fn frames_share_the_normalization() {
+        let frames =
+            generate_trigraph_slices(&triples(), ScaleMode::Linear, Normalization::Maximum)
+                .unwrap();
+        // The brightest triple of the whole histogram is in frame 1, so frame 7 is darker.
+        assert_eq!(frames[1].get_pixel(b'a'.into(), b'b'.into())[0], u16::MAX);
+        assert_eq!(
+            frames[1].get_pixel(b'c'.into(), b'd'.into())[0],
+            u16::MAX / 2
+        );
+        assert_eq!(
+            frames[7].get_pixel(b'e'.into(), b'f'.into())[0],
+            u16::MAX / 4
+        );
+    }
+
+    #[test]
+    fn animation_skips_empty_frames() {
+        let frames =
+            generate_trigraph_slices(&triples(), ScaleMode::Linear, Normalization::Maximum)
+                .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("slices.gif");
+        assert_eq!(save_animation(&frames, &path, 10, true, false).unwrap(), 2);
+        assert_eq!(
+            save_animation(&frames[..8], &path, 10, false, true).unwrap(),
+            8
+        );
+        assert!(path.is_file());
+    }
+
+    #[test]
+    fn slices_need_triples() {
+        let pairs: Histogram<u8> = [(b"ab".to_vec(), 1)].into_iter().collect();
+        assert!(matches!(
+            generate_trigraph_slices(&pairs, ScaleMode::Linear, Normalization::Maximum),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+use image::{ImageBuffer, Pixel};
+
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+
+/// The size of a font pixel in the labels of the axes.
+const AXIS_SCALE: u32 = 2;
+
+/// The length of the tick marks on the axes, in pixels.
+const TICK_LENGTH: u32 = 4;
+
+/// The space between the parts of the axes, in pixels.
+const AXIS_PADDING: u32 = 6;
+
+/// The byte values at which [`draw_grid`] draws lines, separating control bytes, printable ASCII and high bytes.
+const GRID_LINES: [u32; 2] = [0x20, 0x80];
+
+/// The distance between the lines of a fine grid of [`draw_grid`], in byte values.
+const FINE_GRID_STEP: u32 = 0x10;
+
+/// Format a value labeled on the axes in hexadecimal, with as many digits as the largest of `values`.
+fn axis_label(value: u32, values: u32) -> String {
+    let digits = format!("{:X}", values - 1).len();
+    format!("{:0width$X}", value, width = digits)
+}
+
+/// Add axes in a margin around a digraph image, with the first symbol of every pair along the x axis
+/// and the second symbol along the y axis, both running over `values` values, 256 for bytes.
+///
+/// There is a tick mark every eighth of the values, and the first, last and every quarter of the values
+/// are labeled in hexadecimal, e.g. 00, 40, 80, C0 and FF for bytes. The axes are captioned `symbol[i]` and
+/// `symbol[i+1]` in `color`, the margin is black and the image itself is unchanged.
+pub fn with_axes<P>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    values: u32,
+    symbol: &str,
+    color: P,
+) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel,
+{
+    let values = values.max(1);
+    let labeled: Vec<u32> = (0..4)
+        .map(|quarter| quarter * values / 4)
+        .chain([values - 1])
+        .collect();
+    let ticks: Vec<u32> = (0..8)
+        .map(|eighth| eighth * values / 8)
+        .chain([values - 1])
+        .collect();
+    let label_height = GLYPH_HEIGHT * AXIS_SCALE;
+    let label_width = text_width(&axis_label(values - 1, values), AXIS_SCALE);
+    let x_caption = format!("{}[i]", symbol);
+    let y_caption = format!("{}[i+1]", symbol);
+    let left = AXIS_PADDING + label_width + AXIS_PADDING + TICK_LENGTH;
+    let top = AXIS_PADDING + label_height + AXIS_PADDING;
+    let right = AXIS_PADDING + label_width;
+    let bottom =
+        TICK_LENGTH + AXIS_PADDING + label_height + AXIS_PADDING + label_height + AXIS_PADDING;
+    let width = (left + image.width() + right).max(left + text_width(&y_caption, AXIS_SCALE));
+    let height = top + image.height() + bottom;
+    let mut output = ImageBuffer::new(width, height);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        output.put_pixel(left + x, top + y, *pixel);
+    }
+    // The position of the column or row of a value along an axis of `size` pixels.
+    let position = |value: u32, size: u32| (value as u64 * size as u64 / values as u64) as u32;
+    let x_axis = top + image.height();
+    for &value in &ticks {
+        let x = left + position(value, image.width());
+        for y in x_axis..x_axis + TICK_LENGTH {
+            output.put_pixel(x, y, color);
+        }
+        let y = top + position(value, image.height());
+        for x in left - TICK_LENGTH..left {
+            output.put_pixel(x, y, color);
+        }
+    }
+    for &value in &labeled {
+        let label = axis_label(value, values);
+        let label_width = text_width(&label, AXIS_SCALE);
+        let x = left + position(value, image.width());
+        let label_top = x_axis + TICK_LENGTH + AXIS_PADDING;
+        draw_text(
+            &mut output,
+            x - label_width / 2,
+            label_top,
+            &label,
+            AXIS_SCALE,
+            color,
+        );
+        let y = top + position(value, image.height());
+        let label_left = left - TICK_LENGTH - AXIS_PADDING - label_width;
+        draw_text(
+            &mut output,
+            label_left,
+            y - label_height / 2,
+            &label,
+            AXIS_SCALE,
+            color,
+        );
+    }
+    let caption_width = text_width(&x_caption, AXIS_SCALE);
+    let caption_left = left + image.width().saturating_sub(caption_width) / 2;
+    let caption_top = x_axis + TICK_LENGTH + AXIS_PADDING + label_height + AXIS_PADDING;
+    draw_text(
+        &mut output,
+        caption_left,
+        caption_top,
+        &x_caption,
+        AXIS_SCALE,
+        color,
+    );
+    draw_text(
+        &mut output,
+        left,
+        AXIS_PADDING,
+        &y_caption,
+        AXIS_SCALE,
+        color,
+    );
+    output
+}
+
+/// Draw guide lines of 1 pixel in `color` over a digraph image at the byte values 0x20 and 0x80 along both axes,
+/// separating control bytes, printable ASCII and high bytes, or with `fine` at every multiple of 0x10.
+///
+/// The lines are drawn over the pixels of the image after it is generated and scaled, the counts are unaffected.
+pub fn draw_grid<P>(image: &mut ImageBuffer<P, Vec<P::Subpixel>>, fine: bool, color: P)
+where
+    P: Pixel,
+{
+    let lines: Vec<u32> = if fine {
+        (1..256 / FINE_GRID_STEP)
+            .map(|i| i * FINE_GRID_STEP)
+            .collect()
+    } else {
+        GRID_LINES.to_vec()
+    };
+    let (width, height) = image.dimensions();
+    for value in lines {
+        let x = value * width / 256;
+        for y in 0..height {
+            image.put_pixel(x, y, color);
+        }
+        let y = value * height / 256;
+        for x in 0..width {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, Luma};
+
+    use super::*;
+
+    const GRID: Luma<u16> = Luma([1234]);
+
+    /// A digraph image where every pixel has its own brightness.
+    fn gradient(size: u32) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+        ImageBuffer::from_fn(size, size, |x, y| Luma([(x * size + y + 1) as u16 % 1000]))
+    }
+
+    #[test]
+    fn grid_lines_have_the_grid_color() {
+        let original = gradient(256);
+        let mut image = original.clone();
+        draw_grid(&mut image, false, GRID);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if GRID_LINES.contains(&x) || GRID_LINES.contains(&y) {
+                assert_eq!(*pixel, GRID, "{} {}", x, y);
+            } else {
+                assert_eq!(pixel, original.get_pixel(x, y), "{} {}", x, y);
+            }
+        }
+        // The neighbors of the lines are untouched.
+        for line in GRID_LINES {
+            for neighbor in [line - 1, line + 1] {
+                assert_eq!(
+                    image.get_pixel(neighbor, 5),
+                    original.get_pixel(neighbor, 5)
+                );
+                assert_eq!(
+                    image.get_pixel(5, neighbor),
+                    original.get_pixel(5, neighbor)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fine_grid_has_a_line_every_0x10() {
+        let original = gradient(256);
+        let mut image = original.clone();
+        draw_grid(&mut image, true, GRID);
+        let on_line = |value: u32| value != 0 && value.is_multiple_of(FINE_GRID_STEP);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if on_line(x) || on_line(y) {
+                assert_eq!(*pixel, GRID, "{} {}", x, y);
+            } else {
+                assert_eq!(pixel, original.get_pixel(x, y), "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn grid_follows_scaling() {
+        let original = gradient(512);
+        let mut image = original.clone();
+        draw_grid(&mut image, false, GRID);
+        assert_eq!(*image.get_pixel(0x40, 3), GRID);
+        assert_eq!(*image.get_pixel(3, 0x100), GRID);
+        assert_eq!(image.get_pixel(0x41, 3), original.get_pixel(0x41, 3));
+        assert_eq!(image.get_pixel(0x20, 3), original.get_pixel(0x20, 3));
+    }
+
+    #[test]
+    fn axes_surround_the_unchanged_image() {
+        let image = gradient(256);
+        let color = Luma([u16::MAX]);
+        let output = with_axes(&image, 256, "byte", color);
+        let label_height = GLYPH_HEIGHT * AXIS_SCALE;
+        let label_width = text_width("FF", AXIS_SCALE);
+        let left = AXIS_PADDING + label_width + AXIS_PADDING + TICK_LENGTH;
+        let top = AXIS_PADDING + label_height + AXIS_PADDING;
+        let bottom =
+            TICK_LENGTH + AXIS_PADDING + label_height + AXIS_PADDING + label_height + AXIS_PADDING;
+        assert_eq!(
+            output.dimensions(),
+            (left + 256 + AXIS_PADDING + label_width, top + 256 + bottom)
+        );
+        assert_eq!(output.view(left, top, 256, 256).to_image(), image);
+        // A tick mark every 0x20 and at 0xff below the image and left of it, nothing in between.
+        let ticks: Vec<u32> = (0..8).map(|eighth| eighth * 0x20).chain([0xff]).collect();
+        for value in 0..256 {
+            let below = *output.get_pixel(left + value, top + 256);
+            let beside = *output.get_pixel(left - 1, top + value);
+            let expected = if ticks.contains(&value) {
+                color
+            } else {
+                Luma([0])
+            };
+            assert_eq!((below, beside), (expected, expected), "{:#x}", value);
+        }
+    }
+}
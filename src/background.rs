@@ -0,0 +1,121 @@
+use std::{fmt, str::FromStr};
+
+use image::{ImageBuffer, Pixel, Rgb, Rgba};
+
+use crate::{parse_color, Histogram};
+
+/// What the pixels of pairs that never occur look like in a generated image, see [`with_background`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// An opaque color.
+    Color(Rgb<u8>),
+    /// Fully transparent.
+    Transparent,
+}
+
+impl FromStr for Background {
+    type Err = String;
+
+    /// Parse `transparent` or a color written as `#rrggbb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transparent" => Ok(Background::Transparent),
+            _ => parse_color(s).map(Background::Color).map_err(|_| {
+                format!(
+                    "invalid background `{}`, expected `transparent` or a color as `#rrggbb`",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Background::Color(Rgb([r, g, b])) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Background::Transparent => f.write_str("transparent"),
+        }
+    }
+}
+
+/// Give every pixel of a generated image whose pair of symbols never occurs in `histogram` the `background`,
+/// so they stand apart from pairs with very low counts. The first two symbols of the keys are the coordinates,
+/// both running over `values` values across the image, 256 for bytes.
+///
+/// The image gets an alpha channel, every other pixel is unchanged and opaque.
+pub fn with_background(
+    image: &ImageBuffer<Rgb<u16>, Vec<u16>>,
+    histogram: &Histogram<u8>,
+    values: u32,
+    background: Background,
+) -> ImageBuffer<Rgba<u16>, Vec<u16>> {
+    let values = values.max(1);
+    let mut present = vec![false; values as usize * values as usize];
+    for (key, &count) in histogram {
+        if count > 0 && (key[0] as u32) < values && (key[1] as u32) < values {
+            present[key[1] as usize * values as usize + key[0] as usize] = true;
+        }
+    }
+    let background = match background {
+        Background::Color(color) => Rgb(color.0.map(|channel| channel as u16 * 257)).to_rgba(),
+        Background::Transparent => Rgba([0; 4]),
+    };
+    let (width, height) = image.dimensions();
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let (key_x, key_y) = (x * values / width, y * values / height);
+        if present[(key_y * values + key_x) as usize] {
+            image.get_pixel(x, y).to_rgba()
+        } else {
+            background
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backgrounds_are_parsed_and_displayed() {
+        let color: Background = "#101030".parse().unwrap();
+        assert_eq!(color, Background::Color(Rgb([0x10, 0x10, 0x30])));
+        assert_eq!(color.to_string(), "#101030");
+        assert_eq!("transparent".parse(), Ok(Background::Transparent));
+        assert!("#1010".parse::<Background>().is_err());
+    }
+
+    #[test]
+    fn empty_cells_take_exactly_the_background() {
+        // Two pairs occur, at (1, 2) and (3, 0). Even the black pixel of a present pair keeps its color.
+        let histogram = Histogram::from([(vec![1, 2], 5), (vec![3, 0], 1)]);
+        let mut image = ImageBuffer::from_pixel(4, 4, Rgb([0x1010, 0x1010, 0x3030]));
+        image.put_pixel(3, 0, Rgb([0, 0, 0]));
+        image.put_pixel(1, 2, Rgb([u16::MAX, 0, 0]));
+        let background = Background::Color(Rgb([0x10, 0x10, 0x30]));
+        let colored = with_background(&image, &histogram, 4, background);
+        for (x, y, &pixel) in colored.enumerate_pixels() {
+            let expected = match (x, y) {
+                (3, 0) => Rgba([0, 0, 0, u16::MAX]),
+                (1, 2) => Rgba([u16::MAX, 0, 0, u16::MAX]),
+                _ => Rgba([0x1010, 0x1010, 0x3030, u16::MAX]),
+            };
+            assert_eq!(pixel, expected, "({}, {})", x, y);
+        }
+        let cleared = with_background(&image, &histogram, 4, Background::Transparent);
+        assert_eq!(*cleared.get_pixel(0, 0), Rgba([0; 4]));
+        assert_eq!(*cleared.get_pixel(3, 0), Rgba([0, 0, 0, u16::MAX]));
+    }
+
+    #[test]
+    fn upscaled_pixels_follow_their_pair() {
+        // Every pair is 2×2 pixels of an 8×8 image of 4 values.
+        let histogram = Histogram::from([(vec![1, 2], 1)]);
+        let image = ImageBuffer::from_pixel(8, 8, Rgb([u16::MAX; 3]));
+        let colored = with_background(&image, &histogram, 4, Background::Transparent);
+        for (x, y, &pixel) in colored.enumerate_pixels() {
+            let present = x / 2 == 1 && y / 2 == 2;
+            assert_eq!(pixel[3] == u16::MAX, present, "({}, {})", x, y);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeSet;
+
+use crate::{check_key_lengths, BinvizError, Histogram};
+#[cfg(feature = "tables")]
+use crate::{key_cells, with_remainder, OutputFormat, SortOrder};
+
+/// How the count of a key deviates from the count expected by a baseline distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deviation {
+    pub key: Vec<u8>,
+    /// The relative frequency of the key in the histogram.
+    pub observed: f64,
+    /// The relative frequency of the key in the baseline.
+    pub expected: f64,
+    /// The observed divided by the expected relative frequency, infinite for a key missing in the baseline.
+    pub ratio: f64,
+    /// The difference between the observed and expected count, in binomial standard deviations `sqrt(N p (1 - p))`
+    /// for `N` counted windows and expected relative frequency `p`.
+    pub z_score: f64,
+}
+
+/// Calculate the deviation of every key of `histogram` or `baseline` from the baseline, in key order.
+///
+/// The z-scores of large histograms are large for small deviations, as the expected spread of the relative frequency
+/// shrinks with the amount of windows. A key missing in the baseline has an infinite ratio and z-score.
+pub fn baseline_deviations(
+    histogram: &Histogram<u8>,
+    baseline: &Histogram<u8>,
+) -> Result<Vec<Deviation>, BinvizError> {
+    check_key_lengths(histogram, baseline)?;
+    let total = histogram.values().sum::<usize>() as f64;
+    let baseline_total = baseline.values().sum::<usize>() as f64;
+    if total == 0.0 || baseline_total == 0.0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let keys: BTreeSet<&Vec<u8>> = histogram.keys().chain(baseline.keys()).collect();
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let count = histogram.get(key).copied().unwrap_or(0) as f64;
+            let expected = baseline.get(key).copied().unwrap_or(0) as f64 / baseline_total;
+            let deviation = count - total * expected;
+            let deviation_spread = (total * expected * (1.0 - expected)).sqrt();
+            let z_score = if deviation == 0.0 {
+                0.0
+            } else {
+                deviation / deviation_spread
+            };
+            Deviation {
+                key: key.clone(),
+                observed: count / total,
+                expected,
+                ratio: count / total / expected,
+                z_score,
+            }
+        })
+        .collect())
+}
+
+/// Display the keys of `histogram` or `baseline` with their deviation from the baseline, as a table in the given `format`,
+/// see [`baseline_deviations`].
+///
+/// The rows are sorted in the given `order`, [`SortOrder::Deviation`] puts the largest absolute z-scores first.
+/// With `top` only the first that many rows are shown, like [`crate::display_most_frequent`].
+#[cfg(feature = "tables")]
+pub fn display_baseline_deviation(
+    histogram: &Histogram<u8>,
+    baseline: &Histogram<u8>,
+    top: Option<usize>,
+    order: SortOrder,
+    format: OutputFormat,
+) -> Result<String, BinvizError> {
+    let total: usize = histogram.values().sum();
+    let deviations = baseline_deviations(histogram, baseline)?;
+    let count = |deviation: &Deviation| histogram.get(&deviation.key).copied().unwrap_or(0);
+    let mut sorted: Vec<&Deviation> = deviations.iter().collect();
+    match order {
+        SortOrder::Deviation => sorted.sort_by(|a, b| {
+            b.z_score
+                .abs()
+                .total_cmp(&a.z_score.abs())
+                .then_with(|| a.key.cmp(&b.key))
+        }),
+        _ => sorted.sort_by(|a, b| order.compare((&a.key, count(a)), (&b.key, count(b)))),
+    }
+    let remainder = top.filter(|&top| top < sorted.len()).map(|top| {
+        let removed = sorted.split_off(top);
+        (
+            removed.len(),
+            removed.iter().map(|deviation| count(deviation)).sum(),
+        )
+    });
+    let rows = sorted.into_iter().enumerate().map(|(i, deviation)| {
+        let mut row = vec![format!("{}", i)];
+        row.extend(key_cells(&deviation.key));
+        row.extend([
+            format.value(deviation.observed, None),
+            format.value(deviation.expected, None),
+            format.value(deviation.ratio, None),
+            format.value(deviation.z_score, None),
+        ]);
+        row
+    });
+    let table = format.render(
+        [
+            "Rank",
+            "Byte",
+            "Hex",
+            "Text",
+            "Class",
+            "Relative Frequency",
+            "Expected",
+            "Ratio",
+            "Z-score",
+        ],
+        rows,
+    );
+    Ok(with_remainder(table, remainder, total, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A histogram of every byte `count` times, with `inflated` counted `extra` more times.
+    fn bytes(count: usize, inflated: u8, extra: usize) -> Histogram<u8> {
+        (0..=u8::MAX)
+            .map(|byte| {
+                let count = if byte == inflated {
+                    count + extra
+                } else {
+                    count
+                };
+                (vec![byte], count)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_inflated_byte_is_flagged() {
+        let deviations = baseline_deviations(&bytes(100, b'A', 400), &bytes(100, 0, 0)).unwrap();
+        assert_eq!(deviations.len(), 256);
+        let (flagged, others): (Vec<&Deviation>, Vec<&Deviation>) = deviations
+            .iter()
+            .partition(|deviation| deviation.z_score.abs() > 3.0);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].key, b"A");
+        assert!(flagged[0].z_score > 10.0);
+        assert!((flagged[0].ratio - 500.0 / 26_000.0 * 256.0).abs() < 1e-9);
+        assert!(others.iter().all(|deviation| deviation.ratio < 1.0));
+    }
+
+    #[test]
+    fn same_distribution_deviates_nowhere() {
+        let deviations = baseline_deviations(&bytes(3, 0, 0), &bytes(100, 0, 0)).unwrap();
+        assert!(deviations
+            .iter()
+            .all(|deviation| deviation.z_score == 0.0 && deviation.ratio == 1.0));
+    }
+
+    #[test]
+    fn key_missing_in_the_baseline_is_infinitely_deviating() {
+        let histogram: Histogram<u8> = [(b"a".to_vec(), 1), (b"b".to_vec(), 1)].into();
+        let baseline: Histogram<u8> = [(b"a".to_vec(), 1)].into();
+        let deviations = baseline_deviations(&histogram, &baseline).unwrap();
+        assert_eq!(deviations[1].ratio, f64::INFINITY);
+        assert_eq!(deviations[1].z_score, f64::INFINITY);
+    }
+}
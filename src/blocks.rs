@@ -0,0 +1,317 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{
+    calculate_entropy, count_bytes, profile::window_profile, validate_input_size, validate_window,
+    BinvizError, EntropyUnit, ReportHeader,
+};
+
+/// The statistics of a block of a file, see [`block_stats_from_reader`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockStats {
+    /// The position of the block in the file, counting from 0.
+    pub index: usize,
+    /// The offset of the first byte of the block.
+    pub offset: u64,
+    /// The amount of bytes in the block, only the last block can be shorter than the block size.
+    pub length: u64,
+    /// The entropy of the bytes in the block, in bits per byte.
+    pub entropy: f64,
+    /// The amount of distinct byte values in the block.
+    pub distinct: usize,
+    /// The most frequent byte value in the block, the lowest of equally frequent values.
+    pub dominant: u8,
+    /// The count of the dominant byte relative to the length of the block.
+    pub dominant_share: f64,
+}
+
+impl BlockStats {
+    /// The offset just past the last byte of the block.
+    pub fn end(&self) -> u64 {
+        self.offset + self.length
+    }
+}
+
+/// Calculate the statistics of every block of `block_size` bytes of a given file, see [`block_stats_from_reader`].
+pub fn block_stats<P>(file: P, block_size: usize) -> Result<Vec<BlockStats>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    validate_window(block_size, block_size)?;
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let size = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    validate_input_size(size, 1)?;
+    block_stats_from_reader(handle, block_size).map_err(|err| BinvizError::io(file, err))
+}
+
+/// Calculate the statistics of every block of `block_size` bytes read from `reader`, reading it once.
+/// The last block holds the remaining bytes if the length of the input isn't a multiple of the block size.
+pub fn block_stats_from_reader<R>(reader: R, block_size: usize) -> io::Result<Vec<BlockStats>>
+where
+    R: Read,
+{
+    let profile = window_profile(reader, block_size, block_size, |block| {
+        let counts = count_bytes(block, 1);
+        let total = block.len() as f64;
+        let entropy = 0.0
+            - counts
+                .iter()
+                .map(|&freq| calculate_entropy(freq as f64 / total, EntropyUnit::Bits))
+                .sum::<f64>();
+        // `max_by_key` picks the last of equal maxima, so go from high to low to pick the lowest byte.
+        let dominant = (0..=u8::MAX)
+            .rev()
+            .max_by_key(|&byte| counts[byte as usize])
+            .unwrap_or(0);
+        BlockStats {
+            // Set below, the closure only sees the bytes of the block.
+            index: 0,
+            offset: 0,
+            length: block.len() as u64,
+            entropy,
+            distinct: counts.iter().filter(|&&count| count > 0).count(),
+            dominant,
+            dominant_share: counts[dominant as usize] as f64 / total,
+        }
+    })?;
+    Ok(profile
+        .into_iter()
+        .enumerate()
+        .map(|(index, (offset, block))| BlockStats {
+            index,
+            offset,
+            ..block
+        })
+        .collect())
+}
+
+/// The lowest, highest and mean entropy of the blocks of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockSummary {
+    pub min_entropy: f64,
+    pub max_entropy: f64,
+    /// The mean of the entropies of the blocks, each block counting equally.
+    pub mean_entropy: f64,
+}
+
+impl BlockSummary {
+    /// Summarize the entropy of `blocks`, `None` if there are none.
+    pub fn new(blocks: &[BlockStats]) -> Option<Self> {
+        if blocks.is_empty() {
+            return None;
+        }
+        let entropies = blocks.iter().map(|block| block.entropy);
+        Some(BlockSummary {
+            min_entropy: entropies.clone().fold(f64::INFINITY, f64::min),
+            max_entropy: entropies.clone().fold(f64::NEG_INFINITY, f64::max),
+            mean_entropy: entropies.sum::<f64>() / blocks.len() as f64,
+        })
+    }
+}
+
+/// Which blocks [`display_blocks`] lists, by their entropy in bits per byte.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BlockFilter {
+    /// Every block.
+    #[default]
+    All,
+    /// The blocks with an entropy of at least this many bits per byte, e.g. encrypted or compressed regions.
+    Above(f64),
+    /// The blocks with an entropy of at most this many bits per byte, e.g. padding.
+    Below(f64),
+}
+
+impl BlockFilter {
+    /// Whether `block` is listed.
+    pub fn keeps(self, block: &BlockStats) -> bool {
+        match self {
+            BlockFilter::All => true,
+            BlockFilter::Above(threshold) => block.entropy >= threshold,
+            BlockFilter::Below(threshold) => block.entropy <= threshold,
+        }
+    }
+}
+
+/// The JSON document `binviz blocks --format json` prints:
+///
+/// ```json
+/// {
+///   "header": { "tool": "binviz", "version": "0.9.0", "input": "file.bin" },
+///   "block_size": 4096,
+///   "blocks": [{ "index": 0, "offset": 0, "length": 4096, "entropy": 7.95, "distinct": 256,
+///                "dominant": 0, "dominant_share": 0.01 }],
+///   "summary": { "min_entropy": 0.0, "max_entropy": 7.95, "mean_entropy": 3.9 }
+/// }
+/// ```
+///
+/// The blocks are those kept by the threshold, the summary covers all blocks and is `null` for an empty input.
+/// Numbers are written in full, without units. Fields are only ever added, never renamed or removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlocksReport {
+    pub header: ReportHeader,
+    /// The amount of bytes per block.
+    pub block_size: usize,
+    pub blocks: Vec<BlockStats>,
+    pub summary: Option<BlockSummary>,
+}
+
+impl fmt::Display for BlocksReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string_pretty(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+/// Display the blocks kept by `filter` as a table with the offset ranges in hexadecimal, followed by
+/// a summary of the entropy of all blocks in a markdown table, or as a [`BlocksReport`] in JSON.
+#[cfg(feature = "tables")]
+pub fn display_blocks(
+    blocks: &[BlockStats],
+    block_size: usize,
+    filter: BlockFilter,
+    format: OutputFormat,
+    input: Option<&Path>,
+) -> String {
+    let summary = BlockSummary::new(blocks);
+    let kept = blocks.iter().filter(|block| filter.keeps(block));
+    if format == OutputFormat::Json {
+        return BlocksReport {
+            header: ReportHeader::new(input),
+            block_size,
+            blocks: kept.cloned().collect(),
+            summary,
+        }
+        .to_string();
+    }
+    let rows = kept.clone().map(|block| {
+        vec![
+            block.index.to_string(),
+            format!("{:#x}", block.offset),
+            format!("{:#x}", block.end()),
+            format.value(block.entropy, Some("bits per byte")),
+            block.distinct.to_string(),
+            format!("{:#04x}", block.dominant),
+            format.value(block.dominant_share, None),
+        ]
+    });
+    let table = format.render(
+        [
+            "Block", "Start", "End", "Entropy", "Distinct", "Dominant", "Share",
+        ],
+        rows,
+    );
+    match summary {
+        Some(summary) if format == OutputFormat::Markdown => {
+            let listed = match filter {
+                BlockFilter::All => String::new(),
+                BlockFilter::Above(threshold) => {
+                    format!(", `{}` blocks at or above {}", kept.count(), threshold)
+                }
+                BlockFilter::Below(threshold) => {
+                    format!(", `{}` blocks at or below {}", kept.count(), threshold)
+                }
+            };
+            format!(
+                "{}\n`{}` blocks of `{}` bytes{}. Block entropy: min {:.5}, max {:.5}, mean {:.5} (bits per byte).",
+                table,
+                blocks.len(),
+                block_size,
+                listed,
+                summary.min_entropy,
+                summary.max_entropy,
+                summary.mean_entropy
+            )
+        }
+        _ => table,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::noise;
+
+    /// Blocks of zeros and noise taking turns, 4 blocks of 1024 bytes and a last block of 100 noise bytes.
+    fn striped() -> Vec<BlockStats> {
+        let mut buf = Vec::new();
+        for _ in 0..2 {
+            buf.extend([0; 1024]);
+            buf.extend(noise(1024));
+        }
+        buf.extend(noise(100));
+        block_stats_from_reader(&buf[..], 1024).unwrap()
+    }
+
+    fn flagged(blocks: &[BlockStats], filter: BlockFilter) -> Vec<usize> {
+        blocks
+            .iter()
+            .filter(|block| filter.keeps(block))
+            .map(|block| block.index)
+            .collect()
+    }
+
+    #[test]
+    fn blocks_cover_the_input() {
+        let blocks = striped();
+        let ranges: Vec<(u64, u64)> = blocks
+            .iter()
+            .map(|block| (block.offset, block.end()))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (0, 1024),
+                (1024, 2048),
+                (2048, 3072),
+                (3072, 4096),
+                (4096, 4196)
+            ]
+        );
+        assert_eq!(blocks[0].entropy, 0.0);
+        assert_eq!((blocks[0].distinct, blocks[0].dominant), (1, 0));
+        assert_eq!(blocks[0].dominant_share, 1.0);
+    }
+
+    #[test]
+    fn thresholds_flag_the_noise_or_the_zeros() {
+        let blocks = striped();
+        assert_eq!(flagged(&blocks, BlockFilter::All), [0, 1, 2, 3, 4]);
+        assert_eq!(flagged(&blocks, BlockFilter::Above(6.0)), [1, 3, 4]);
+        assert_eq!(flagged(&blocks, BlockFilter::Below(1.0)), [0, 2]);
+        // Both bounds are inclusive.
+        assert_eq!(flagged(&blocks, BlockFilter::Below(0.0)), [0, 2]);
+        assert_eq!(flagged(&blocks, BlockFilter::Above(8.0)), [] as [usize; 0]);
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn report_lists_the_flagged_blocks_and_summarizes_all() {
+        let blocks = striped();
+        let json = display_blocks(
+            &blocks,
+            1024,
+            BlockFilter::Above(6.0),
+            OutputFormat::Json,
+            None,
+        );
+        let report: BlocksReport = serde_json::from_str(&json).unwrap();
+        let indices: Vec<usize> = report.blocks.iter().map(|block| block.index).collect();
+        assert_eq!(indices, [1, 3, 4]);
+        // The summary also covers the blocks of zeros.
+        let summary = report.summary.unwrap();
+        assert_eq!(summary.min_entropy, 0.0);
+        assert!(summary.max_entropy > 7.0);
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::Write;
+
+use crate::{EntropyUnit, Histogram};
+
+/// The amount of bars in a frequency chart when no amount is given.
+pub const CHART_BAR_COUNT: usize = 16;
+
+/// The size of a chart, in SVG user units.
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 480.0;
+
+/// The space between the plot area and the edges of a chart, for the title, tick labels and axis labels.
+const MARGIN_LEFT: f64 = 80.0;
+const MARGIN_RIGHT: f64 = 30.0;
+const MARGIN_TOP: f64 = 50.0;
+const MARGIN_BOTTOM: f64 = 70.0;
+
+/// The amount of intervals the y axis is divided in by tick marks.
+const Y_TICKS: usize = 4;
+
+/// Escape the characters with a meaning in XML, e.g. in file names used in a title.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Start a chart with its title, the axes and their captions, and a labeled tick mark at every quarter of the y axis
+/// up to `max`, the value at the top of the plot area. The SVG is closed by [`finish_chart`].
+fn start_chart(
+    title: &str,
+    x_caption: &str,
+    y_caption: &str,
+    max: f64,
+    label: impl Fn(f64) -> String,
+) -> String {
+    let (left, right) = (MARGIN_LEFT, CHART_WIDTH - MARGIN_RIGHT);
+    let (top, bottom) = (MARGIN_TOP, CHART_HEIGHT - MARGIN_BOTTOM);
+    let mut svg = String::new();
+    // Writing to a `String` can't fail.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12" style="background: white">"#,
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-size="16">{}</text>"#,
+        CHART_WIDTH / 2.0,
+        MARGIN_TOP / 2.0,
+        escape_xml(title)
+    );
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{left}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="black"/>"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{left}" y1="{top}" x2="{left}" y2="{bottom}" stroke="black"/>"#
+    );
+    for tick in 0..=Y_TICKS {
+        let value = max * tick as f64 / Y_TICKS as f64;
+        let y = bottom - (bottom - top) * tick as f64 / Y_TICKS as f64;
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{y}" x2="{left}" y2="{y}" stroke="black"/>"#,
+            left - 5.0
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+            left - 8.0,
+            y + 4.0,
+            label(value)
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+        (left + right) / 2.0,
+        CHART_HEIGHT - 15.0,
+        escape_xml(x_caption)
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text transform="translate(20 {}) rotate(-90)" text-anchor="middle">{}</text>"#,
+        (top + bottom) / 2.0,
+        escape_xml(y_caption)
+    );
+    svg
+}
+
+/// Close a chart started with [`start_chart`].
+fn finish_chart(mut svg: String) -> String {
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a bar chart of the `top` most frequent keys of a histogram as an SVG document, with the count of
+/// every key above its bar and the key in hexadecimal below it. Keys with equal counts are ordered by key.
+pub fn frequency_chart_svg(histogram: &Histogram<u8>, top: usize, title: &str) -> String {
+    let mut keys: Vec<(&Vec<u8>, usize)> =
+        histogram.iter().map(|(key, &count)| (key, count)).collect();
+    keys.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    keys.truncate(top);
+    let max = keys.first().map_or(0, |&(_, count)| count);
+    // An empty chart still gets a y axis from 0 to 1.
+    let scale_max = max.max(1) as f64;
+    let mut svg = start_chart(title, "Key", "Count", scale_max, |value| {
+        format!("{}", value.round())
+    });
+    let (left, right) = (MARGIN_LEFT, CHART_WIDTH - MARGIN_RIGHT);
+    let (top, bottom) = (MARGIN_TOP, CHART_HEIGHT - MARGIN_BOTTOM);
+    let slot = (right - left) / keys.len().max(1) as f64;
+    for (i, (key, count)) in keys.iter().enumerate() {
+        let height = (bottom - top) * *count as f64 / scale_max;
+        let x = left + slot * i as f64 + slot * 0.1;
+        let center = x + slot * 0.4;
+        let hex: Vec<String> = key.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{x}" y="{}" width="{}" height="{height}" fill="#377eb8"/>"##,
+            bottom - height,
+            slot * 0.8
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{center}" y="{}" text-anchor="middle" font-size="10">{}</text>"#,
+            bottom - height - 4.0,
+            count
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{center}" y="{}" text-anchor="middle" font-size="10">{}</text>"#,
+            bottom + 15.0,
+            hex.join(" ")
+        );
+    }
+    finish_chart(svg)
+}
+
+/// Render a line chart of the entropy per dimension as an SVG document, `entropies[i]` is the entropy of
+/// dimension `i + 1` in `unit`, as calculated for the table of the entropy subcommand.
+pub fn entropy_chart_svg(entropies: &[f64], unit: EntropyUnit, title: &str) -> String {
+    let max = entropies.iter().copied().fold(0.0, f64::max);
+    let scale_max = if max > 0.0 { max } else { 1.0 };
+    let y_caption = format!("Entropy ({})", unit);
+    let mut svg = start_chart(title, "Dimension", &y_caption, scale_max, |value| {
+        format!("{:.2}", value)
+    });
+    let (left, right) = (MARGIN_LEFT, CHART_WIDTH - MARGIN_RIGHT);
+    let (top, bottom) = (MARGIN_TOP, CHART_HEIGHT - MARGIN_BOTTOM);
+    // The dimensions are spread over the x axis, a single dimension is in the middle.
+    let x_of = |i: usize| match entropies.len() {
+        1 => (left + right) / 2.0,
+        len => left + (right - left) * i as f64 / (len - 1) as f64,
+    };
+    let y_of = |entropy: f64| bottom - (bottom - top) * entropy / scale_max;
+    let points: Vec<String> = entropies
+        .iter()
+        .enumerate()
+        .map(|(i, &entropy)| format!("{},{}", x_of(i), y_of(entropy)))
+        .collect();
+    let _ = writeln!(
+        svg,
+        r##"<polyline points="{}" fill="none" stroke="#e41a1c" stroke-width="2"/>"##,
+        points.join(" ")
+    );
+    for (i, &entropy) in entropies.iter().enumerate() {
+        let (x, y) = (x_of(i), y_of(entropy));
+        let _ = writeln!(svg, r##"<circle cx="{x}" cy="{y}" r="3" fill="#e41a1c"/>"##);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x}" y="{}" text-anchor="middle" font-size="10">{:.2}</text>"#,
+            y - 8.0,
+            entropy
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x}" y="{}" text-anchor="middle">{}</text>"#,
+            bottom + 15.0,
+            i + 1
+        );
+    }
+    finish_chart(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_histogram_from_bytes;
+
+    fn count(svg: &str, element: &str) -> usize {
+        svg.matches(&format!("<{} ", element)).count()
+    }
+
+    #[test]
+    fn frequency_chart_has_a_bar_per_key_up_to_top() {
+        let histogram = calculate_histogram_from_bytes(b"aaabbc", 1, 1);
+        let svg = frequency_chart_svg(&histogram, 16, "letters");
+        assert_eq!(count(&svg, "rect"), 3);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        // The most frequent key comes first.
+        let bars: Vec<&str> = svg
+            .lines()
+            .filter(|line| line.starts_with("<rect "))
+            .collect();
+        assert!(bars[0].contains(r#"height="360""#), "{}", bars[0]);
+
+        assert_eq!(
+            count(&frequency_chart_svg(&histogram, 2, "letters"), "rect"),
+            2
+        );
+        let empty = calculate_histogram_from_bytes(b"", 1, 1);
+        assert_eq!(
+            count(&frequency_chart_svg(&empty, 16, "nothing"), "rect"),
+            0
+        );
+    }
+
+    #[test]
+    fn entropy_chart_has_a_line_with_a_point_per_dimension() {
+        let svg = entropy_chart_svg(&[7.5, 7.0, 6.2, 5.1], EntropyUnit::Bits, "noise");
+        assert_eq!(count(&svg, "polyline"), 1);
+        assert_eq!(count(&svg, "circle"), 4);
+        let polyline = svg
+            .lines()
+            .find(|line| line.starts_with("<polyline "))
+            .unwrap();
+        let points = polyline.split('"').nth(1).unwrap();
+        assert_eq!(points.split(' ').count(), 4);
+        assert!(svg.contains("Entropy (bits)"));
+    }
+
+    #[test]
+    fn titles_are_escaped() {
+        let svg = entropy_chart_svg(&[1.0], EntropyUnit::Bits, "a<b & \"c\"");
+        assert!(svg.contains("a&lt;b &amp; &quot;c&quot;"));
+        assert!(!svg.contains("a<b"));
+    }
+}
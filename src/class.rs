@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// A coarse classification of byte values, to tell text from binary data at a glance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ByteClass {
+    /// The zero byte `0x00`.
+    Null,
+    /// The other ASCII control characters `0x01..=0x1f` and `0x7f`, except whitespace.
+    Control,
+    /// ASCII whitespace: tab, line feed, vertical tab, form feed, carriage return and space.
+    Whitespace,
+    /// The printable ASCII characters `0x21..=0x7e`.
+    Printable,
+    /// The bytes `0x80..=0xff` outside ASCII.
+    High,
+}
+
+impl ByteClass {
+    /// Every class, in order.
+    pub const ALL: [ByteClass; 5] = [
+        ByteClass::Null,
+        ByteClass::Control,
+        ByteClass::Whitespace,
+        ByteClass::Printable,
+        ByteClass::High,
+    ];
+}
+
+/// Classify a byte, see [`ByteClass`].
+pub fn classify_byte(byte: u8) -> ByteClass {
+    match byte {
+        0x00 => ByteClass::Null,
+        0x09..=0x0d | 0x20 => ByteClass::Whitespace,
+        0x21..=0x7e => ByteClass::Printable,
+        0x80..=0xff => ByteClass::High,
+        _ => ByteClass::Control,
+    }
+}
+
+impl fmt::Display for ByteClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteClass::Null => f.write_str("null"),
+            ByteClass::Control => f.write_str("control"),
+            ByteClass::Whitespace => f.write_str("whitespace"),
+            ByteClass::Printable => f.write_str("printable"),
+            ByteClass::High => f.write_str("high"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundary_bytes_are_classified() {
+        for (byte, class) in [
+            (0x00, ByteClass::Null),
+            (0x01, ByteClass::Control),
+            (0x08, ByteClass::Control),
+            (0x09, ByteClass::Whitespace),
+            (0x0d, ByteClass::Whitespace),
+            (0x0e, ByteClass::Control),
+            (0x1f, ByteClass::Control),
+            (0x20, ByteClass::Whitespace),
+            (0x21, ByteClass::Printable),
+            (0x7e, ByteClass::Printable),
+            (0x7f, ByteClass::Control),
+            (0x80, ByteClass::High),
+            (0xff, ByteClass::High),
+        ] {
+            assert_eq!(classify_byte(byte), class, "{:#04x}", byte);
+        }
+    }
+
+    #[test]
+    fn every_byte_has_one_of_the_classes() {
+        let counts = ByteClass::ALL.map(|class| {
+            (0..=u8::MAX)
+                .filter(|&byte| classify_byte(byte) == class)
+                .count()
+        });
+        assert_eq!(counts, [1, 27, 6, 94, 128]);
+    }
+}
@@ -0,0 +1,423 @@
+use std::{fmt, path::Path, str::FromStr};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{
+    calculate_histogram, calculate_histogram_from_bytes, jensen_shannon, storage::decode_histogram,
+    BinvizError, Histogram,
+};
+
+/// What a [`Fingerprint`] is the fingerprint of.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Label {
+    /// Plain English text in ASCII.
+    AsciiText,
+    /// Text in UTF-8, mostly outside ASCII such as Cyrillic, Greek and CJK.
+    Utf8Text,
+    /// x86-64 machine code.
+    X86_64,
+    /// The entropy coded image data of JPEG files.
+    Jpeg,
+    /// Deflate compressed data, as in PNG images and gzip and zip files.
+    Deflate,
+    /// Random or encrypted data.
+    Random,
+    /// Zero bytes only, as in padding and sparse files.
+    Zero,
+    /// A fingerprint added by the user.
+    Custom(String),
+}
+
+impl FromStr for Label {
+    type Err = String;
+
+    /// Parse the name of a built-in label, any other name is a custom label.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err("the label is empty".to_string()),
+            "ascii-text" => Ok(Label::AsciiText),
+            "utf8-text" => Ok(Label::Utf8Text),
+            "x86-64" => Ok(Label::X86_64),
+            "jpeg" => Ok(Label::Jpeg),
+            "deflate" => Ok(Label::Deflate),
+            "random" => Ok(Label::Random),
+            "zero" => Ok(Label::Zero),
+            _ => Ok(Label::Custom(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Label::AsciiText => f.write_str("ascii-text"),
+            Label::Utf8Text => f.write_str("utf8-text"),
+            Label::X86_64 => f.write_str("x86-64"),
+            Label::Jpeg => f.write_str("jpeg"),
+            Label::Deflate => f.write_str("deflate"),
+            Label::Random => f.write_str("random"),
+            Label::Zero => f.write_str("zero"),
+            Label::Custom(name) => f.write_str(name),
+        }
+    }
+}
+
+/// The distributions of the bytes and byte pairs of a kind of data, to recognize it by, see [`classify`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    pub label: Label,
+    /// The 1-dimensional histogram of a sample of the data.
+    pub monograph: Histogram<u8>,
+    /// The 2-dimensional histogram of the same sample.
+    pub digraph: Histogram<u8>,
+}
+
+impl Fingerprint {
+    /// The fingerprint of the sample `bytes`.
+    pub fn from_bytes(label: Label, bytes: &[u8]) -> Self {
+        Fingerprint {
+            label,
+            monograph: calculate_histogram_from_bytes(bytes, 1, 1),
+            digraph: calculate_histogram_from_bytes(bytes, 2, 1),
+        }
+    }
+
+    /// The fingerprint of the sample `file`.
+    pub fn from_file<P>(label: Label, file: P) -> Result<Self, BinvizError>
+    where
+        P: AsRef<Path>,
+    {
+        let file = file.as_ref();
+        Ok(Fingerprint {
+            label,
+            monograph: calculate_histogram(file, 1, 1)?,
+            digraph: calculate_histogram(file, 2, 1)?,
+        })
+    }
+}
+
+/// The names of the built-in fingerprints with their saved 1 and 2-dimensional histograms,
+/// see [`builtin_fingerprints`].
+const BUILTIN_FINGERPRINTS: [(&str, &[u8], &[u8]); 7] = [
+    (
+        "ascii-text",
+        include_bytes!("fingerprints/ascii-text.1.bvzh"),
+        include_bytes!("fingerprints/ascii-text.2.bvzh"),
+    ),
+    (
+        "utf8-text",
+        include_bytes!("fingerprints/utf8-text.1.bvzh"),
+        include_bytes!("fingerprints/utf8-text.2.bvzh"),
+    ),
+    (
+        "x86-64",
+        include_bytes!("fingerprints/x86-64.1.bvzh"),
+        include_bytes!("fingerprints/x86-64.2.bvzh"),
+    ),
+    (
+        "jpeg",
+        include_bytes!("fingerprints/jpeg.1.bvzh"),
+        include_bytes!("fingerprints/jpeg.2.bvzh"),
+    ),
+    (
+        "deflate",
+        include_bytes!("fingerprints/deflate.1.bvzh"),
+        include_bytes!("fingerprints/deflate.2.bvzh"),
+    ),
+    (
+        "random",
+        include_bytes!("fingerprints/random.1.bvzh"),
+        include_bytes!("fingerprints/random.2.bvzh"),
+    ),
+    (
+        "zero",
+        include_bytes!("fingerprints/zero.1.bvzh"),
+        include_bytes!("fingerprints/zero.2.bvzh"),
+    ),
+];
+
+/// The built-in fingerprints, of 32 KiB samples:
+///
+/// - `ascii-text`, the middle of the GNU General Public License version 3.
+/// - `utf8-text`, the Russian, Greek, Japanese and Chinese translations of the Vim tutor.
+/// - `x86-64`, the `.text` section of GNU coreutils `ls`.
+/// - `jpeg`, the test images of the `image` and `jpeg-decoder` crates.
+/// - `deflate`, the licenses in `/usr/share/common-licenses` of Debian compressed by `gzip -9`.
+/// - `random`, bytes from `/dev/urandom`.
+/// - `zero`, zero bytes.
+///
+/// The saved histograms are rebuilt from the samples with `cargo run --example fingerprint`.
+pub fn builtin_fingerprints() -> Vec<Fingerprint> {
+    BUILTIN_FINGERPRINTS
+        .iter()
+        .map(|(name, monograph, digraph)| {
+            let decode = |dimension: usize, contents: &[u8]| {
+                let file = format!("{}.{}.bvzh", name, dimension);
+                decode_histogram(Path::new(&file), contents)
+                    .expect("the built-in fingerprints are valid histograms")
+            };
+            Fingerprint {
+                label: name.parse().expect("the built-in names are labels"),
+                monograph: decode(1, monograph),
+                digraph: decode(2, digraph),
+            }
+        })
+        .collect()
+}
+
+/// Rank the `fingerprints` by the Jensen-Shannon distance of their histogram of the same dimension
+/// to `histogram`, the closest first. The dimension is 1 or 2, that of the keys of the histogram.
+///
+/// The distance is the square root of the [`jensen_shannon`] divergence, between 0 for the same distribution
+/// and 1 for histograms without a key in common.
+pub fn classify(
+    histogram: &Histogram<u8>,
+    fingerprints: &[Fingerprint],
+) -> Result<Vec<(Label, f64)>, BinvizError> {
+    let dimension = histogram.keys().next().map_or(0, Vec::len);
+    let mut matches = fingerprints
+        .iter()
+        .map(|fingerprint| {
+            let reference = match dimension {
+                1 => &fingerprint.monograph,
+                2 => &fingerprint.digraph,
+                _ => return Err(BinvizError::InvalidDimension { dimension }),
+            };
+            let distance = jensen_shannon(histogram, reference)?.sqrt();
+            Ok((fingerprint.label.clone(), distance))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    matches.sort_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(matches)
+}
+
+/// How close a file is to a [`Fingerprint`], see [`classify_histograms`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassMatch {
+    pub label: Label,
+    /// The Jensen-Shannon distance between the distributions of bytes.
+    pub monograph_distance: f64,
+    /// The Jensen-Shannon distance between the distributions of byte pairs.
+    pub digraph_distance: f64,
+}
+
+impl ClassMatch {
+    /// How well the file matches, one minus the mean of both distances: 1 for the same distributions.
+    pub fn score(&self) -> f64 {
+        1.0 - (self.monograph_distance + self.digraph_distance) / 2.0
+    }
+}
+
+/// Rank the `fingerprints` by how well the 1-dimensional `monograph` and 2-dimensional `digraph`
+/// of a file match them, the best match first, see [`classify`] and [`ClassMatch::score`].
+///
+/// Compressed, encrypted and JPEG data all have nearly uniform bytes, so their scores are close
+/// and the best match among them is only a hint.
+pub fn classify_histograms(
+    monograph: &Histogram<u8>,
+    digraph: &Histogram<u8>,
+    fingerprints: &[Fingerprint],
+) -> Result<Vec<ClassMatch>, BinvizError> {
+    let mut matches = fingerprints
+        .iter()
+        .map(|fingerprint| {
+            Ok(ClassMatch {
+                label: fingerprint.label.clone(),
+                monograph_distance: jensen_shannon(monograph, &fingerprint.monograph)?.sqrt(),
+                digraph_distance: jensen_shannon(digraph, &fingerprint.digraph)?.sqrt(),
+            })
+        })
+        .collect::<Result<Vec<_>, BinvizError>>()?;
+    matches.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    Ok(matches)
+}
+
+/// Display the ranked matches as a table.
+#[cfg(feature = "tables")]
+pub fn display_classification(matches: &[ClassMatch], format: OutputFormat) -> String {
+    let rows = matches.iter().enumerate().map(|(rank, class_match)| {
+        vec![
+            rank.to_string(),
+            class_match.label.to_string(),
+            format.value(class_match.score(), None),
+            format.value(class_match.monograph_distance, None),
+            format.value(class_match.digraph_distance, None),
+        ]
+    });
+    format.render(
+        ["Rank", "Label", "Score", "Byte distance", "Pair distance"],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::noise;
+
+    /// The matches of `bytes` in both dimensions, the closest first.
+    fn classify_bytes(bytes: &[u8]) -> [Vec<(Label, f64)>; 2] {
+        let fingerprints = builtin_fingerprints();
+        [1, 2].map(|dimension| {
+            let histogram = calculate_histogram_from_bytes(bytes, dimension, 1);
+            classify(&histogram, &fingerprints).unwrap()
+        })
+    }
+
+    fn assert_nearest(bytes: &[u8], label: Label) {
+        for matches in classify_bytes(bytes) {
+            assert_eq!(matches[0].0, label, "{:?}", matches);
+        }
+    }
+
+    /// A PNG image with the row `bytes`, almost all of it is the deflate compressed row.
+    #[cfg(feature = "images")]
+    fn deflate(bytes: &[u8]) -> Vec<u8> {
+        use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+        use image::{ColorType, ImageEncoder};
+
+        let mut png = Vec::new();
+        PngEncoder::new_with_quality(&mut png, CompressionType::Best, FilterType::NoFilter)
+            .write_image(bytes, bytes.len() as u32, 1, ColorType::L8)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn english_is_ascii_text() {
+        let text = "It was a bright cold day in April, and the clocks were striking thirteen. \
+                    Everyone is permitted to copy and distribute verbatim copies of this document, \
+                    but changing it is not allowed.\n";
+        assert_nearest(text.repeat(50).as_bytes(), Label::AsciiText);
+    }
+
+    #[test]
+    fn russian_greek_japanese_and_chinese_are_utf8_text() {
+        let text = "Съешь же ещё этих мягких французских булок, да выпей чаю. \
+                    Ξεσκεπάζω την ψυχοφθόρα βδελυγμία. いろはにほへと ちりぬるを。我能吞下玻璃而不伤身体。\n";
+        assert_nearest(text.repeat(50).as_bytes(), Label::Utf8Text);
+    }
+
+    #[test]
+    fn function_prologues_and_calls_are_x86_64() {
+        let code: &[u8] = &[
+            0x55, 0x48, 0x89, 0xe5, 0x48, 0x83, 0xec, 0x20, 0x89, 0x7d, 0xec, 0x48, 0x89, 0x75,
+            0xe0, 0x8b, 0x45, 0xec, 0x83, 0xc0, 0x01, 0x89, 0x45, 0xfc, 0x48, 0x8b, 0x45, 0xe0,
+            0x48, 0x8b, 0x00, 0x48, 0x89, 0xc7, 0xe8, 0x00, 0x00, 0x00, 0x00, 0x8b, 0x45, 0xfc,
+            0xc9, 0xc3, 0x0f, 0x1f, 0x44, 0x00, 0x00, 0x41, 0x57, 0x41, 0x56, 0x53, 0x48, 0x8d,
+            0x3d, 0x10, 0x02, 0x00, 0x00, 0x31, 0xc0, 0x48, 0x85, 0xff, 0x74, 0x0a, 0x5b, 0x41,
+            0x5e, 0x41, 0x5f, 0xc3,
+        ];
+        assert_nearest(&code.repeat(100), Label::X86_64);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn encoded_image_is_jpeg() {
+        use image::{codecs::jpeg::JpegEncoder, Rgb, RgbImage};
+
+        // A gradient with some grain, so the encoder has detail to code.
+        let grain = noise(256 * 256 * 3);
+        let image = RgbImage::from_fn(256, 256, |x, y| {
+            let i = (y * 256 + x) as usize * 3;
+            Rgb([
+                (x as u8).wrapping_add(grain[i] >> 3),
+                (y as u8).wrapping_add(grain[i + 1] >> 3),
+                ((x + y) / 2) as u8 ^ (grain[i + 2] >> 2),
+            ])
+        });
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, 90)
+            .encode_image(&image)
+            .unwrap();
+        assert_nearest(&jpeg, Label::Jpeg);
+    }
+
+    /// Compressed data is as uniform as random and JPEG data, see [`classify_histograms`],
+    /// so only check that it is among them and about as close as the best match.
+    #[cfg(feature = "images")]
+    #[test]
+    fn compressed_text_is_close_to_deflate() {
+        // Text of random words, which stays the same unlike a source file.
+        let words = [
+            "the",
+            "histogram",
+            "of",
+            "a",
+            "byte",
+            "window",
+            "counts",
+            "every",
+            "pair",
+            "in",
+            "file",
+        ];
+        let text: String = noise(100_000)
+            .iter()
+            .map(|&byte| format!("{} ", words[byte as usize % words.len()]))
+            .collect();
+        for matches in classify_bytes(&deflate(text.as_bytes())) {
+            let uniform = [Label::Deflate, Label::Random, Label::Jpeg];
+            assert!(
+                matches[..3]
+                    .iter()
+                    .all(|(label, _)| uniform.contains(label)),
+                "{:?}",
+                matches
+            );
+            let (_, distance) = matches
+                .iter()
+                .find(|(label, _)| *label == Label::Deflate)
+                .unwrap();
+            assert!(distance - matches[0].1 < 0.02, "{:?}", matches);
+        }
+    }
+
+    #[test]
+    fn noise_is_random() {
+        assert_nearest(&noise(32768), Label::Random);
+    }
+
+    #[test]
+    fn zero_bytes_are_zero() {
+        for matches in classify_bytes(&[0; 4096]) {
+            assert_eq!(matches[0], (Label::Zero, 0.0));
+        }
+    }
+
+    #[test]
+    fn builtin_fingerprints_are_of_32_kib_samples() {
+        // As rebuilt by `examples/fingerprint.rs`, the pairs of the sample overlap.
+        for fingerprint in builtin_fingerprints() {
+            let total = |histogram: &Histogram<u8>| histogram.values().sum::<usize>();
+            assert_eq!(
+                total(&fingerprint.monograph),
+                32768,
+                "{}",
+                fingerprint.label
+            );
+            assert_eq!(total(&fingerprint.digraph), 32767, "{}", fingerprint.label);
+            // The pairs start with the bytes of the sample but its last.
+            for (key, &count) in &fingerprint.monograph {
+                let starting = fingerprint
+                    .digraph
+                    .iter()
+                    .filter(|(pair, _)| pair[0] == key[0])
+                    .map(|(_, &count)| count)
+                    .sum::<usize>();
+                assert!(count - starting <= 1, "{} {:?}", fingerprint.label, key);
+            }
+        }
+    }
+
+    #[test]
+    fn every_builtin_fingerprint_is_nearest_to_itself() {
+        let fingerprints = builtin_fingerprints();
+        for fingerprint in &fingerprints {
+            for histogram in [&fingerprint.monograph, &fingerprint.digraph] {
+                let matches = classify(histogram, &fingerprints).unwrap();
+                assert_eq!(matches[0], (fingerprint.label.clone(), 0.0));
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::{fmt, str::FromStr};
+
+use image::{ImageBuffer, Luma, Rgb};
+
+use crate::{classify_byte, ByteClass};
+
+/// The control points of viridis, evenly spaced from 0 to 1.
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 82, 139],
+    [44, 114, 142],
+    [33, 145, 140],
+    [39, 173, 129],
+    [94, 201, 98],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+/// The control points of inferno, evenly spaced from 0 to 1.
+const INFERNO: [[u8; 3]; 9] = [
+    [0, 0, 4],
+    [31, 12, 72],
+    [85, 15, 109],
+    [136, 34, 106],
+    [186, 54, 85],
+    [227, 89, 51],
+    [249, 140, 10],
+    [249, 201, 50],
+    [252, 255, 164],
+];
+
+/// The control points of turbo, evenly spaced from 0 to 1.
+const TURBO: [[u8; 3]; 11] = [
+    [48, 18, 59],
+    [73, 88, 221],
+    [47, 158, 245],
+    [39, 215, 195],
+    [78, 249, 131],
+    [150, 250, 80],
+    [223, 220, 50],
+    [255, 163, 35],
+    [244, 92, 23],
+    [184, 32, 8],
+    [122, 4, 3],
+];
+
+/// How the brightness of the digraph image is turned into colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colormap {
+    /// From black to white.
+    #[default]
+    Grayscale,
+    /// The perceptually uniform colormap from dark purple through green to yellow.
+    Viridis,
+    /// The perceptually uniform colormap from black through red to pale yellow.
+    Inferno,
+    /// The rainbow colormap from dark blue through green to dark red, showing more detail but not uniform.
+    Turbo,
+}
+
+impl Colormap {
+    /// The color of `value` in this colormap, from 0 to 1, values outside are clamped.
+    /// Colors between the control points of a colormap are interpolated linearly.
+    pub fn rgb16(self, value: f64) -> Rgb<u16> {
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        let points: &[[u8; 3]] = match self {
+            Colormap::Grayscale => return Rgb([(value * u16::MAX as f64).round() as u16; 3]),
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Inferno => &INFERNO,
+            Colormap::Turbo => &TURBO,
+        };
+        let position = value * (points.len() - 1) as f64;
+        let index = (position as usize).min(points.len() - 2);
+        let fraction = position - index as f64;
+        let (low, high) = (points[index], points[index + 1]);
+        Rgb([0, 1, 2].map(|channel| {
+            let low = low[channel] as f64;
+            let high = high[channel] as f64;
+            // Scale by 257 so the u8 control points map onto the whole u16 range.
+            ((low + (high - low) * fraction) * 257.0).round() as u16
+        }))
+    }
+
+    /// The color of `value` in this colormap, see [`Colormap::rgb16`].
+    pub fn rgb8(self, value: f64) -> Rgb<u8> {
+        Rgb(self.rgb16(value).0.map(|channel| (channel >> 8) as u8))
+    }
+}
+
+impl FromStr for Colormap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grayscale" | "greyscale" | "gray" | "grey" => Ok(Colormap::Grayscale),
+            "viridis" => Ok(Colormap::Viridis),
+            "inferno" => Ok(Colormap::Inferno),
+            "turbo" => Ok(Colormap::Turbo),
+            _ => Err(format!(
+                "unknown colormap `{}`, expected `grayscale`, `viridis`, `inferno` or `turbo`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Colormap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Colormap::Grayscale => f.write_str("grayscale"),
+            Colormap::Viridis => f.write_str("viridis"),
+            Colormap::Inferno => f.write_str("inferno"),
+            Colormap::Turbo => f.write_str("turbo"),
+        }
+    }
+}
+
+/// Parse a color written as `#rrggbb` in hexadecimal, e.g. `#101030`.
+pub fn parse_color(value: &str) -> Result<Rgb<u8>, String> {
+    let error = || format!("invalid color `{}`, expected `#rrggbb`", value);
+    let hex = value.strip_prefix('#').ok_or_else(error)?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(error());
+    }
+    let mut channels = [0; 3];
+    for (i, channel) in channels.iter_mut().enumerate() {
+        *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| error())?;
+    }
+    Ok(Rgb(channels))
+}
+
+/// Color a grayscale image, such as the digraph image of [`crate::generate_image`], with a colormap.
+/// Pixels without any count get the color of 0, [`Colormap::Grayscale`] reproduces the image.
+pub fn apply_colormap(
+    image: &ImageBuffer<Luma<u16>, Vec<u16>>,
+    colormap: Colormap,
+) -> ImageBuffer<Rgb<u16>, Vec<u16>> {
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let Luma([brightness]) = *image.get_pixel(x, y);
+        colormap.rgb16(brightness as f64 / u16::MAX as f64)
+    })
+}
+
+/// How every byte of the input is colored in a file map, see [`crate::render_map`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    /// By byte value, from 0 to 255 through the colormap.
+    Value(Colormap),
+    /// By [`ByteClass`]: black for 0x00, white for 0xff, blue for printable ASCII and whitespace,
+    /// green for the other control bytes and red for the other high bytes.
+    ByteClass,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme::Value(Colormap::default())
+    }
+}
+
+impl ColorScheme {
+    /// The color of `byte` in this scheme.
+    pub fn color(self, byte: u8) -> Rgb<u8> {
+        match self {
+            ColorScheme::Value(colormap) => colormap.rgb8(byte as f64 / u8::MAX as f64),
+            ColorScheme::ByteClass => match (byte, classify_byte(byte)) {
+                (0xff, _) => Rgb([255, 255, 255]),
+                (_, ByteClass::Null) => Rgb([0, 0, 0]),
+                (_, ByteClass::Printable | ByteClass::Whitespace) => Rgb([55, 126, 184]),
+                (_, ByteClass::Control) => Rgb([77, 175, 74]),
+                (_, ByteClass::High) => Rgb([228, 26, 28]),
+            },
+        }
+    }
+}
+
+impl FromStr for ColorScheme {
+    type Err = String;
+
+    /// Parse `class`, or the name of a [`Colormap`] to color by value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "class" | "byte-class" => Ok(ColorScheme::ByteClass),
+            _ => s.parse().map(ColorScheme::Value).map_err(|_| {
+                format!(
+                    "unknown color scheme `{}`, expected `class`, `grayscale`, `viridis`, `inferno` or `turbo`",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ColorScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorScheme::Value(colormap) => colormap.fmt(f),
+            ColorScheme::ByteClass => f.write_str("class"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A row of three pixels: black, the middle gray and white.
+    fn gradient() -> ImageBuffer<Luma<u16>, Vec<u16>> {
+        ImageBuffer::from_raw(3, 1, vec![0, 32768, u16::MAX]).unwrap()
+    }
+
+    fn widen(color: [u8; 3]) -> Rgb<u16> {
+        Rgb(color.map(|channel| channel as u16 * 257))
+    }
+
+    #[test]
+    fn grayscale_reproduces_the_image() {
+        let colored = apply_colormap(&gradient(), Colormap::Grayscale);
+        for (Luma([brightness]), Rgb(color)) in gradient().pixels().zip(colored.pixels()) {
+            assert_eq!(*color, [*brightness; 3]);
+        }
+    }
+
+    #[test]
+    fn colormaps_hit_their_control_points_at_the_endpoints_and_midpoint() {
+        for (colormap, points) in [
+            (Colormap::Viridis, &VIRIDIS[..]),
+            (Colormap::Inferno, &INFERNO[..]),
+            (Colormap::Turbo, &TURBO[..]),
+        ] {
+            let colored = apply_colormap(&gradient(), colormap);
+            assert_eq!(*colored.get_pixel(0, 0), widen(points[0]), "{}", colormap);
+            // 32768 is just past the middle of the u16 range, so allow for a rounding step.
+            let Rgb(middle) = *colored.get_pixel(1, 0);
+            let Rgb(expected) = widen(points[points.len() / 2]);
+            for (channel, expected) in middle.into_iter().zip(expected) {
+                assert!(
+                    channel.abs_diff(expected) <= 1,
+                    "{}: {:?}",
+                    colormap,
+                    middle
+                );
+            }
+            assert_eq!(
+                *colored.get_pixel(2, 0),
+                widen(points[points.len() - 1]),
+                "{}",
+                colormap
+            );
+        }
+    }
+
+    #[test]
+    fn byte_classes_have_their_colors() {
+        let (black, white) = (Rgb([0, 0, 0]), Rgb([255, 255, 255]));
+        let (blue, green, red) = (Rgb([55, 126, 184]), Rgb([77, 175, 74]), Rgb([228, 26, 28]));
+        for (byte, expected) in [
+            (0x00, black),
+            (0xff, white),
+            (b'A', blue),
+            (b'~', blue),
+            (b' ', blue),
+            (b'\n', blue),
+            (0x01, green),
+            (0x1f, green),
+            (0x7f, green),
+            (0x80, red),
+            (0xfe, red),
+        ] {
+            assert_eq!(
+                ColorScheme::ByteClass.color(byte),
+                expected,
+                "{:#04x}",
+                byte
+            );
+        }
+    }
+
+    #[test]
+    fn color_schemes_are_parsed() {
+        assert_eq!("class".parse(), Ok(ColorScheme::ByteClass));
+        assert_eq!("viridis".parse(), Ok(ColorScheme::Value(Colormap::Viridis)));
+        assert!("rainbow".parse::<ColorScheme>().is_err());
+        // By value the bytes run through the colormap.
+        let grayscale = ColorScheme::Value(Colormap::Grayscale);
+        assert_eq!(grayscale.color(0x80), Rgb([0x80; 3]));
+    }
+}
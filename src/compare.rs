@@ -0,0 +1,442 @@
+use std::{fmt, path::Path};
+
+#[cfg(feature = "images")]
+use image::{ImageBuffer, Luma, Rgb};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{
+    calculate_entropy_histogram, calculate_histogram, chi_square_homogeneity, cosine_similarity,
+    jensen_shannon, kl_divergence, validate_dimension, BinvizError, EntropyUnit, Histogram,
+    ReportHeader, Smoothing,
+};
+#[cfg(feature = "images")]
+use crate::{
+    font::{draw_text, text_width, GLYPH_HEIGHT, GLYPH_WIDTH},
+    render_digraph, to_channel, Normalization, ScaleMode,
+};
+
+/// The dimensions [`compare_files`] compares two files in.
+pub const COMPARISON_DIMENSIONS: [usize; 2] = [1, 2];
+
+/// How similar two histograms of the same dimension are, see [`compare_histograms`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonMetrics {
+    /// The amount of consecutive bytes per histogram key.
+    pub dimension: usize,
+    /// The Kullback-Leibler divergence of the first histogram from the second in bits, see [`kl_divergence`].
+    pub kl_divergence_a_b: f64,
+    /// The Kullback-Leibler divergence of the second histogram from the first in bits.
+    pub kl_divergence_b_a: f64,
+    /// The Jensen-Shannon divergence in bits, between 0 and 1, see [`jensen_shannon`].
+    pub jensen_shannon: f64,
+    /// The chi-square statistic of the test whether both are samples of one distribution,
+    /// see [`chi_square_homogeneity`].
+    pub chi_square: f64,
+    /// The p-value of the chi-square statistic.
+    pub chi_square_p_value: f64,
+    /// The cosine similarity of the counts, between 0 and 1, see [`cosine_similarity`].
+    pub cosine_similarity: f64,
+    /// The entropy of the first histogram in bits.
+    pub entropy_a: f64,
+    /// The entropy of the second histogram in bits.
+    pub entropy_b: f64,
+}
+
+/// The JSON document `binviz compare --format json` prints:
+///
+/// ```json
+/// {
+///   "header": { "tool": "binviz", "version": "0.9.0", "input": null },
+///   "file_a": "old.bin",
+///   "file_b": "new.bin",
+///   "dimensions": [{ "dimension": 1, "kl_divergence_a_b": 0.02, "jensen_shannon": 0.004, ... }]
+/// }
+/// ```
+///
+/// The header has no input, the compared files are `file_a` and `file_b`.
+/// Numbers are written in full, without units. Fields are only ever added, never renamed or removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    pub header: ReportHeader,
+    /// The path of the first file as given, `-` for standard input.
+    pub file_a: String,
+    /// The path of the second file as given.
+    pub file_b: String,
+    /// The metrics from dimension 1 up.
+    pub dimensions: Vec<ComparisonMetrics>,
+}
+
+impl ComparisonReport {
+    /// The report comparing `file_a` to `file_b` in the given `dimensions`.
+    pub fn new(file_a: &Path, file_b: &Path, dimensions: Vec<ComparisonMetrics>) -> Self {
+        ComparisonReport {
+            header: ReportHeader::new(None),
+            file_a: file_a.to_string_lossy().into_owned(),
+            file_b: file_b.to_string_lossy().into_owned(),
+            dimensions,
+        }
+    }
+}
+
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string_pretty(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+/// Calculate how similar the histograms `a` and `b` of `dimension` are,
+/// with `smoothing` for the keys missing in one of them in the Kullback-Leibler divergences.
+pub fn compare_histograms(
+    a: &Histogram<u8>,
+    b: &Histogram<u8>,
+    dimension: usize,
+    smoothing: Smoothing,
+) -> Result<ComparisonMetrics, BinvizError> {
+    validate_dimension(a, dimension)?;
+    validate_dimension(b, dimension)?;
+    let (chi_square, chi_square_p_value) = chi_square_homogeneity(a, b)?;
+    Ok(ComparisonMetrics {
+        dimension,
+        kl_divergence_a_b: kl_divergence(a, b, smoothing)?,
+        kl_divergence_b_a: kl_divergence(b, a, smoothing)?,
+        jensen_shannon: jensen_shannon(a, b)?,
+        chi_square,
+        chi_square_p_value,
+        cosine_similarity: cosine_similarity(a, b)?,
+        entropy_a: calculate_entropy_histogram(a, EntropyUnit::Bits)?,
+        entropy_b: calculate_entropy_histogram(b, EntropyUnit::Bits)?,
+    })
+}
+
+/// Compare the files `a` and `b` by their histograms of every dimension in [`COMPARISON_DIMENSIONS`],
+/// see [`compare_histograms`].
+pub fn compare_files<P, Q>(
+    a: P,
+    b: Q,
+    smoothing: Smoothing,
+) -> Result<ComparisonReport, BinvizError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let dimensions = COMPARISON_DIMENSIONS
+        .iter()
+        .map(|&dimension| {
+            compare_histograms(
+                &calculate_histogram(a, dimension, 1)?,
+                &calculate_histogram(b, dimension, 1)?,
+                dimension,
+                smoothing,
+            )
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(ComparisonReport::new(a, b, dimensions))
+}
+
+/// Reads a metric from the [`ComparisonMetrics`] of a dimension.
+#[cfg(feature = "tables")]
+type MetricGetter = fn(&ComparisonMetrics) -> f64;
+
+/// Display the metrics of `report` as a table with a row per metric and a column per dimension,
+/// or as the report itself in JSON.
+#[cfg(feature = "tables")]
+pub fn display_comparison(report: &ComparisonReport, format: OutputFormat) -> String {
+    if format == OutputFormat::Json {
+        return report.to_string();
+    }
+    let metrics: [(&str, Option<&str>, MetricGetter); 8] = [
+        ("KL divergence A to B", Some("bits"), |m| {
+            m.kl_divergence_a_b
+        }),
+        ("KL divergence B to A", Some("bits"), |m| {
+            m.kl_divergence_b_a
+        }),
+        ("Jensen-Shannon divergence", Some("bits"), |m| {
+            m.jensen_shannon
+        }),
+        ("Chi-square", None, |m| m.chi_square),
+        ("Chi-square p-value", None, |m| m.chi_square_p_value),
+        ("Cosine similarity", None, |m| m.cosine_similarity),
+        ("Entropy A", Some("bits"), |m| m.entropy_a),
+        ("Entropy B", Some("bits"), |m| m.entropy_b),
+    ];
+    let header = std::iter::once("Metric".to_string()).chain(
+        report
+            .dimensions
+            .iter()
+            .map(|metrics| format!("Dimension {}", metrics.dimension)),
+    );
+    let rows = metrics.iter().map(|(name, unit, metric)| {
+        std::iter::once(name.to_string())
+            .chain(
+                report
+                    .dimensions
+                    .iter()
+                    .map(|metrics| format.value(metric(metrics), *unit)),
+            )
+            .collect()
+    });
+    format.render(header, rows)
+}
+
+/// The width of the divider between the panels of a comparison image, in pixels.
+#[cfg(feature = "images")]
+const DIVIDER_WIDTH: u32 = 4;
+
+/// The size of a font pixel in the names above the panels of a comparison image.
+#[cfg(feature = "images")]
+const NAME_SCALE: u32 = 2;
+
+/// The space around the names above the panels of a comparison image, in pixels.
+#[cfg(feature = "images")]
+const NAME_PADDING: u32 = 4;
+
+/// The difference in pairs below which the scale of [`generate_diff_image`] is linear rather than logarithmic.
+#[cfg(feature = "images")]
+pub const DIFF_LINEAR_THRESHOLD: f64 = 1.0;
+
+/// Shorten `name` to its last characters so it fits in `width` pixels at `scale`, marking the cut with `..`.
+#[cfg(feature = "images")]
+fn fit_name(name: &str, width: u32, scale: u32) -> String {
+    if text_width(name, scale) <= width {
+        return name.to_string();
+    }
+    let fitting = ((width / scale + 1) / (GLYPH_WIDTH + 1)) as usize;
+    let chars: Vec<char> = name.chars().collect();
+    let tail: String = chars[chars.len() - fitting.saturating_sub(2)..]
+        .iter()
+        .collect();
+    format!("..{}", tail)
+}
+
+/// Generate an image with the digraph images of two histograms side by side, separated by a white divider
+/// and with their names above them, so two files can be compared at a glance.
+///
+/// Both panels show the same count at full brightness, the larger of the counts `normalization` chooses for
+/// each histogram, so the brightness of both panels is comparable. Names too long for a panel are cut at the front.
+#[cfg(feature = "images")]
+pub fn generate_comparison_image(
+    a: &Histogram<u8>,
+    b: &Histogram<u8>,
+    names: (&str, &str),
+    scale: ScaleMode,
+    normalization: Normalization,
+) -> Result<ImageBuffer<Luma<u16>, Vec<u16>>, BinvizError> {
+    validate_dimension(a, 2)?;
+    validate_dimension(b, 2)?;
+    if a.is_empty() || b.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let full_count = normalization.full_count(a).max(normalization.full_count(b));
+    let panels = [
+        (render_digraph(a, scale, full_count), names.0),
+        (render_digraph(b, scale, full_count), names.1),
+    ];
+    let top = NAME_PADDING + GLYPH_HEIGHT * NAME_SCALE + NAME_PADDING;
+    let mut image = ImageBuffer::new(256 * 2 + DIVIDER_WIDTH, top + 256);
+    let white = Luma([u16::MAX]);
+    for (i, (panel, name)) in panels.iter().enumerate() {
+        let left = i as u32 * (256 + DIVIDER_WIDTH);
+        for (x, y, pixel) in panel.enumerate_pixels() {
+            image.put_pixel(left + x, top + y, *pixel);
+        }
+        let name = fit_name(name, 256 - 2 * NAME_PADDING, NAME_SCALE);
+        draw_text(
+            &mut image,
+            left + NAME_PADDING,
+            NAME_PADDING,
+            &name,
+            NAME_SCALE,
+            white,
+        );
+    }
+    for x in 256..256 + DIVIDER_WIDTH {
+        for y in 0..image.height() {
+            image.put_pixel(x, y, white);
+        }
+    }
+    Ok(image)
+}
+
+/// Generate an image of the differences between the digraph images of two histograms, pairs more frequent in `b`
+/// than in `a` are red and pairs less frequent are blue, the larger the difference the brighter.
+///
+/// The counts of `b` are scaled to the amount of pairs in `a` first, so files of different sizes can be compared.
+/// A difference of less than half a pair only comes from that scaling and is left out,
+/// so a pair occurring more often in `b` than in an otherwise equal `a` lights up its pixel alone.
+/// The brightness is on a symmetric logarithmic scale, `ln(1 + |difference| / t)` relative to that of the largest
+/// difference, with `t` the [`DIFF_LINEAR_THRESHOLD`], so small differences show up next to large ones.
+/// Identical histograms give a black image.
+#[cfg(feature = "images")]
+pub fn generate_diff_image(
+    a: &Histogram<u8>,
+    b: &Histogram<u8>,
+) -> Result<ImageBuffer<Rgb<u16>, Vec<u16>>, BinvizError> {
+    validate_dimension(a, 2)?;
+    validate_dimension(b, 2)?;
+    let (total_a, total_b) = (a.values().sum::<usize>(), b.values().sum::<usize>());
+    if total_a == 0 || total_b == 0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let scale = total_a as f64 / total_b as f64;
+    let mut differences = vec![0.0; 256 * 256];
+    for (key, &count) in a {
+        differences[key[1] as usize * 256 + key[0] as usize] -= count as f64;
+    }
+    for (key, &count) in b {
+        differences[key[1] as usize * 256 + key[0] as usize] += count as f64 * scale;
+    }
+    for difference in &mut differences {
+        if difference.abs() < 0.5 {
+            *difference = 0.0;
+        }
+    }
+    let symlog = |difference: f64| (difference.abs() / DIFF_LINEAR_THRESHOLD).ln_1p();
+    let max = differences
+        .iter()
+        .map(|&difference| symlog(difference))
+        .fold(0.0, f64::max);
+    Ok(ImageBuffer::from_fn(256, 256, |x, y| {
+        let difference = differences[y as usize * 256 + x as usize];
+        if max == 0.0 || difference == 0.0 {
+            return Rgb([0, 0, 0]);
+        }
+        let brightness = to_channel(symlog(difference) / max);
+        if difference > 0.0 {
+            Rgb([brightness, 0, 0])
+        } else {
+            Rgb([0, 0, brightness])
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "images")]
+    use image::GenericImageView;
+
+    use crate::test_support::{file_with, noise};
+    #[cfg(feature = "images")]
+    use crate::{calculate_histogram_from_bytes, generate_image};
+
+    #[test]
+    fn file_compared_with_itself_has_no_distance() {
+        let file = file_with(&noise(100_000));
+        let report = compare_files(file.path(), file.path(), Smoothing::Additive(1.0)).unwrap();
+        assert_eq!(report.dimensions.len(), COMPARISON_DIMENSIONS.len());
+        for metrics in &report.dimensions {
+            assert!(metrics.kl_divergence_a_b.abs() < 1e-9, "{:?}", metrics);
+            assert!(metrics.kl_divergence_b_a.abs() < 1e-9, "{:?}", metrics);
+            assert!(metrics.jensen_shannon.abs() < 1e-9, "{:?}", metrics);
+            assert!(metrics.chi_square.abs() < 1e-9, "{:?}", metrics);
+            assert!(
+                (metrics.chi_square_p_value - 1.0).abs() < 1e-9,
+                "{:?}",
+                metrics
+            );
+            assert!(
+                (metrics.cosine_similarity - 1.0).abs() < 1e-9,
+                "{:?}",
+                metrics
+            );
+            assert_eq!(metrics.entropy_a, metrics.entropy_b);
+        }
+    }
+
+    #[test]
+    fn different_files_have_a_distance() {
+        let (a, b) = (
+            file_with(&noise(100_000)),
+            file_with(&b"the quick brown fox jumps over the lazy dog. ".repeat(2000)),
+        );
+        let report = compare_files(a.path(), b.path(), Smoothing::Additive(1.0)).unwrap();
+        for metrics in &report.dimensions {
+            assert!(metrics.kl_divergence_a_b > 1.0, "{:?}", metrics);
+            assert!(metrics.kl_divergence_b_a > 1.0, "{:?}", metrics);
+            assert!(metrics.jensen_shannon > 0.5, "{:?}", metrics);
+            assert!(metrics.chi_square_p_value < 1e-6, "{:?}", metrics);
+            assert!(metrics.cosine_similarity < 0.5, "{:?}", metrics);
+            assert!(metrics.entropy_a > metrics.entropy_b, "{:?}", metrics);
+        }
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn panels_match_the_digraph_images_with_the_shared_full_count() {
+        let a = calculate_histogram_from_bytes(&noise(100_000), 2, 1);
+        // Only 64 byte values, so the pairs of `b` are counted more often and set the full count.
+        let b: Vec<u8> = noise(50_000).iter().map(|byte| byte >> 2).collect();
+        let b = calculate_histogram_from_bytes(&b, 2, 1);
+        let (scale, normalization) = (ScaleMode::Linear, Normalization::Maximum);
+        assert!(normalization.full_count(&b) > normalization.full_count(&a));
+        let image =
+            generate_comparison_image(&a, &b, ("a.bin", "b.bin"), scale, normalization).unwrap();
+        assert_eq!(image.width(), 256 * 2 + DIVIDER_WIDTH);
+        let top = image.height() - 256;
+        let panel = |left: u32| image.view(left, top, 256, 256).to_image();
+        // `b` looks as on its own, `a` as on its own but with the full count of `b`.
+        let (alone, _, _) = generate_image(&b, scale, normalization).unwrap();
+        assert_eq!(panel(256 + DIVIDER_WIDTH), alone);
+        let (alone, _, _) = generate_image(&a, scale, normalization).unwrap();
+        assert_ne!(panel(0), alone);
+        assert_eq!(
+            panel(0),
+            render_digraph(&a, scale, normalization.full_count(&b))
+        );
+        // The divider is white from top to bottom.
+        let divider = image.view(256, 0, DIVIDER_WIDTH, image.height()).to_image();
+        assert!(divider.pixels().all(|&pixel| pixel == Luma([u16::MAX])));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn panels_of_equal_histograms_are_the_digraph_image() {
+        let histogram = calculate_histogram_from_bytes(&noise(100_000), 2, 1);
+        let (scale, normalization) = (ScaleMode::Log, Normalization::Average);
+        let image = generate_comparison_image(
+            &histogram,
+            &histogram,
+            ("a.bin", "b.bin"),
+            scale,
+            normalization,
+        )
+        .unwrap();
+        let (alone, _, _) = generate_image(&histogram, scale, normalization).unwrap();
+        let top = image.height() - 256;
+        for left in [0, 256 + DIVIDER_WIDTH] {
+            assert_eq!(image.view(left, top, 256, 256).to_image(), alone);
+        }
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn identical_histograms_give_a_black_diff_image() {
+        let histogram = calculate_histogram_from_bytes(&noise(100_000), 2, 1);
+        let image = generate_diff_image(&histogram, &histogram).unwrap();
+        assert!(image.pixels().all(|&pixel| pixel == Rgb([0, 0, 0])));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn extra_pair_lights_up_one_pixel() {
+        let a = calculate_histogram_from_bytes(&noise(100_000), 2, 1);
+        let mut b = a.clone();
+        *b.entry(vec![0x12, 0x34]).or_default() += 5;
+        let image = generate_diff_image(&a, &b).unwrap();
+        let lit: Vec<_> = image
+            .enumerate_pixels()
+            .filter(|&(_, _, &pixel)| pixel != Rgb([0, 0, 0]))
+            .map(|(x, y, &pixel)| (x, y, pixel))
+            .collect();
+        // The first byte of the pair is the column, the only difference is the brightest red.
+        assert_eq!(lit, [(0x12, 0x34, Rgb([u16::MAX, 0, 0]))]);
+        // The other way around it is blue.
+        let image = generate_diff_image(&b, &a).unwrap();
+        assert_eq!(*image.get_pixel(0x12, 0x34), Rgb([0, 0, u16::MAX]));
+    }
+}
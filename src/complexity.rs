@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::{read_chunks, BinvizError};
+
+/// Incrementally counts the phrases of the LZ78 parsing of the input: the input is split into phrases
+/// that each extend an earlier phrase by a single byte, and repetitive input needs few, long phrases.
+///
+/// Unlike histogram entropy this also notices repetition over long distances, a repeated block of random bytes
+/// has the byte frequencies of random data but a far lower complexity. The dictionary of phrases grows with
+/// the amount of phrases, for random data about `n / log256 n` for an input of `n` bytes.
+#[derive(Debug, Clone, Default)]
+pub struct LzComplexity {
+    /// The child phrase of every phrase extended by a byte, phrase 0 is the empty phrase.
+    children: HashMap<(usize, u8), usize>,
+    /// The phrase the bytes since the end of the last phrase are equal to.
+    current: usize,
+    phrases: usize,
+    count: u64,
+}
+
+impl LzComplexity {
+    /// Create an empty parsing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next bytes of the input.
+    pub fn update(&mut self, buf: &[u8]) {
+        self.count += buf.len() as u64;
+        for &byte in buf {
+            match self.children.get(&(self.current, byte)) {
+                Some(&child) => self.current = child,
+                None => {
+                    self.phrases += 1;
+                    self.children.insert((self.current, byte), self.phrases);
+                    self.current = 0;
+                }
+            }
+        }
+    }
+
+    /// Calculate the amount of phrases, and the complexity normalized to `c log256(n) / n`
+    /// for `c` phrases of an input of `n` bytes.
+    ///
+    /// The normalized complexity is comparable across input sizes: it approaches 1 for random data as the input
+    /// grows, about 0.9 for a megabyte of random bytes, and tends to 0 for repetitive data. A trailing incomplete phrase is counted as a phrase, and an input of fewer than
+    /// 2 bytes has a normalized complexity of 0.
+    pub fn finish(self) -> (usize, f64) {
+        let phrases = self.phrases + usize::from(self.current != 0);
+        if self.count < 2 {
+            return (phrases, 0.0);
+        }
+        let count = self.count as f64;
+        (phrases, phrases as f64 * count.log2() / 8.0 / count)
+    }
+}
+
+/// Calculate the Lempel-Ziv complexity of a byte slice, see [`LzComplexity`].
+pub fn lz_complexity(buf: &[u8]) -> (usize, f64) {
+    let mut complexity = LzComplexity::new();
+    complexity.update(buf);
+    complexity.finish()
+}
+
+/// Calculate the Lempel-Ziv complexity of a given file, reading it in chunks, see [`LzComplexity`].
+pub fn lz_complexity_from_file<P>(file: P) -> Result<(usize, f64), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    lz_complexity_from_reader(handle).map_err(|err| BinvizError::io(file, err))
+}
+
+/// Calculate the Lempel-Ziv complexity of everything `reader` produces, in chunks of [`crate::CHUNK_SIZE`] bytes.
+pub fn lz_complexity_from_reader<R>(reader: R) -> io::Result<(usize, f64)>
+where
+    R: Read,
+{
+    let mut complexity = LzComplexity::new();
+    read_chunks(reader, |chunk| complexity.update(chunk))?;
+    Ok(complexity.finish())
+}
+
+/// Display the Lempel-Ziv complexity, see [`LzComplexity`].
+#[cfg(feature = "tables")]
+pub fn display_lz_complexity((phrases, normalized): (usize, f64)) -> String {
+    format!(
+        "Lempel-Ziv complexity is {} phrases, normalized {:.5} (random data approaches 1.0).",
+        phrases, normalized
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_entropy_histogram, calculate_histogram_from_bytes, test_support::noise,
+        EntropyUnit,
+    };
+
+    #[test]
+    fn repeated_block_is_far_less_complex_than_random_data() {
+        let random = noise(1 << 18);
+        let repeated = random[..1024].repeat(256);
+        let entropy = |buf: &[u8]| {
+            let histogram = calculate_histogram_from_bytes(buf, 1, 1);
+            calculate_entropy_histogram(&histogram, EntropyUnit::Bits).unwrap()
+        };
+        // Their byte frequencies are alike.
+        assert!((entropy(&random) - entropy(&repeated)).abs() < 0.25);
+        let (random_phrases, random_complexity) = lz_complexity(&random);
+        let (repeated_phrases, repeated_complexity) = lz_complexity(&repeated);
+        assert!(repeated_phrases * 4 < random_phrases);
+        assert!(repeated_complexity * 4.0 < random_complexity);
+        assert!(random_complexity > 0.7, "{}", random_complexity);
+    }
+
+    #[test]
+    fn constant_bytes_have_almost_no_complexity() {
+        // Every phrase is one byte longer than the last, 1 + 2 + ... + 44 = 990 bytes, and a trailing phrase.
+        let (phrases, complexity) = lz_complexity(&[0; 1000]);
+        assert_eq!(phrases, 45);
+        assert!(complexity < 0.1, "{}", complexity);
+        assert_eq!(lz_complexity(&[]), (0, 0.0));
+        assert_eq!(lz_complexity(&[7]), (1, 0.0));
+    }
+
+    #[test]
+    fn complexity_is_independent_of_chunking() {
+        let buf = noise(10_000);
+        let mut chunked = LzComplexity::new();
+        for chunk in buf.chunks(333) {
+            chunked.update(chunk);
+        }
+        assert_eq!(chunked.finish(), lz_complexity(&buf));
+        assert_eq!(
+            lz_complexity_from_reader(&buf[..]).unwrap(),
+            lz_complexity(&buf)
+        );
+    }
+}
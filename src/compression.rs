@@ -0,0 +1,210 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use flate2::{write::DeflateEncoder, Compression};
+
+use crate::{
+    profile::window_profile, read_chunks, validate_input_size, validate_window, BinvizError,
+};
+
+/// The compression algorithms inputs can be compressed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    /// Raw deflate, as used by zip and gzip, at the default compression level.
+    #[default]
+    Deflate,
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Codec::Deflate => f.write_str("deflate"),
+        }
+    }
+}
+
+/// Counts the bytes written to it, discarding them.
+#[derive(Debug, Default)]
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Incrementally compresses the input, only keeping track of the compressed size.
+pub struct Compressor {
+    encoder: DeflateEncoder<ByteCounter>,
+    original: u64,
+}
+
+impl Compressor {
+    /// Create a compressor of the given `codec`.
+    pub fn new(codec: Codec) -> Self {
+        match codec {
+            Codec::Deflate => Compressor {
+                encoder: DeflateEncoder::new(ByteCounter::default(), Compression::default()),
+                original: 0,
+            },
+        }
+    }
+
+    /// Add the next bytes of the input.
+    pub fn update(&mut self, buf: &[u8]) {
+        self.original += buf.len() as u64;
+        self.encoder
+            .write_all(buf)
+            .expect("writing to a byte counter can't fail");
+    }
+
+    /// Calculate the original and the compressed size of the input, in bytes.
+    pub fn finish(self) -> (u64, u64) {
+        let counter = self
+            .encoder
+            .finish()
+            .expect("writing to a byte counter can't fail");
+        (self.original, counter.0)
+    }
+}
+
+/// Calculate the original and compressed size of a given file, reading it in chunks, see [`Compressor`].
+pub fn compression_sizes<P>(file: P, codec: Codec) -> Result<(u64, u64), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let mut compressor = Compressor::new(codec);
+    read_chunks(handle, |chunk| compressor.update(chunk))
+        .map_err(|err| BinvizError::io(file, err))?;
+    Ok(compressor.finish())
+}
+
+/// Calculate how well `buf` compresses with `codec`, as the compressed size divided by the original size.
+///
+/// Zeros compress to a tiny fraction, while random and already compressed or encrypted data stays near
+/// or even slightly above 1. An empty input has a ratio of 1.
+pub fn compression_ratio(buf: &[u8], codec: Codec) -> f64 {
+    let mut compressor = Compressor::new(codec);
+    compressor.update(buf);
+    ratio(compressor.finish())
+}
+
+/// The compressed size divided by the original size, 1 for an empty input.
+fn ratio((original, compressed): (u64, u64)) -> f64 {
+    if original == 0 {
+        1.0
+    } else {
+        compressed as f64 / original as f64
+    }
+}
+
+/// Calculate the compression ratio of every window of `window_size` bytes of a given file,
+/// with the windows of [`crate::entropy_profile`].
+pub fn compression_profile<P>(
+    file: P,
+    window_size: usize,
+    step: usize,
+    codec: Codec,
+) -> Result<Vec<(u64, f64)>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    validate_window(window_size, step)?;
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let size = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    validate_input_size(size, 1)?;
+    compression_profile_from_reader(handle, window_size, step, codec)
+        .map_err(|err| BinvizError::io(file, err))
+}
+
+/// Calculate the compression ratio of every window of `window_size` bytes read from `reader`,
+/// with the windows of [`crate::entropy_profile_from_reader`].
+///
+/// Compressed but unencrypted regions have a high entropy like encrypted ones, but often still compress a little.
+pub fn compression_profile_from_reader<R>(
+    reader: R,
+    window_size: usize,
+    step: usize,
+    codec: Codec,
+) -> io::Result<Vec<(u64, f64)>>
+where
+    R: Read,
+{
+    window_profile(reader, window_size, step, |window| {
+        compression_ratio(window, codec)
+    })
+}
+
+/// Display the original and compressed size of the input, see [`Compressor`].
+#[cfg(feature = "tables")]
+pub fn display_compression(codec: Codec, sizes: (u64, u64)) -> String {
+    format!(
+        "Compressing with {} reduces {} bytes to {} bytes, a compression ratio of {:.5}.",
+        codec,
+        sizes.0,
+        sizes.1,
+        ratio(sizes)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::noise;
+
+    #[test]
+    fn zeros_compress_to_a_tiny_fraction() {
+        let ratio = compression_ratio(&[0; 1 << 16], Codec::Deflate);
+        assert!(ratio < 0.01, "{}", ratio);
+    }
+
+    #[test]
+    fn random_bytes_barely_compress() {
+        let ratio = compression_ratio(&noise(1 << 16), Codec::Deflate);
+        assert!((0.99..1.01).contains(&ratio), "{}", ratio);
+    }
+
+    #[test]
+    fn empty_input_has_a_ratio_of_1() {
+        assert_eq!(compression_ratio(&[], Codec::Deflate), 1.0);
+    }
+
+    #[test]
+    fn compression_is_independent_of_chunking() {
+        let buf = [noise(5000), vec![0; 5000]].concat();
+        let mut chunked = Compressor::new(Codec::Deflate);
+        for chunk in buf.chunks(333) {
+            chunked.update(chunk);
+        }
+        let (original, compressed) = chunked.finish();
+        assert_eq!(original, 10_000);
+        let ratio = compressed as f64 / original as f64;
+        assert!((ratio - compression_ratio(&buf, Codec::Deflate)).abs() < 0.01);
+    }
+
+    #[test]
+    fn profile_tells_zeros_from_random_bytes() {
+        let buf = [noise(4096), vec![0; 4096]].concat();
+        let profile =
+            compression_profile_from_reader(&buf[..], 4096, 4096, Codec::Deflate).unwrap();
+        assert_eq!(profile.len(), 2);
+        assert_eq!((profile[0].0, profile[1].0), (0, 4096));
+        assert!(profile[0].1 > 0.99);
+        assert!(profile[1].1 < 0.01);
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::BinvizError;
+
+/// The config file in the current folder, overriding the user config file, see [`load_config`].
+pub const LOCAL_CONFIG_FILE: &str = "binviz.toml";
+
+/// Default values of command line options, read from TOML files like:
+///
+/// ```toml
+/// # Every subcommand with these options uses these defaults.
+/// colormap = "viridis"
+/// scale = "log"
+///
+/// # Only `binviz frequency` uses these.
+/// [frequency]
+/// top = 20
+/// ```
+///
+/// Options are named like their flags, with dashes or underscores. Numbers and booleans can be written
+/// as such, and options that can be repeated as arrays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// The values of the options for every subcommand.
+    pub defaults: BTreeMap<String, Vec<String>>,
+    /// The values of the options for a single subcommand, overriding the `defaults`, by subcommand.
+    pub commands: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+impl Config {
+    /// Parse the `contents` of the config file `path`.
+    pub fn parse(path: &Path, contents: &str) -> Result<Self, BinvizError> {
+        let table: Table = contents
+            .parse()
+            .map_err(|err| BinvizError::serialization(path, err))?;
+        let mut config = Config::default();
+        for (key, value) in table {
+            match value {
+                Value::Table(options) => {
+                    let mut command = BTreeMap::new();
+                    for (option, value) in options {
+                        command.insert(option_name(&option), option_values(path, &option, value)?);
+                    }
+                    config.commands.insert(key, command);
+                }
+                value => {
+                    config
+                        .defaults
+                        .insert(option_name(&key), option_values(path, &key, value)?);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load the config file `path`.
+    pub fn load<P>(path: P) -> Result<Self, BinvizError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|err| BinvizError::io(path, err))?;
+        Config::parse(path, &contents)
+    }
+
+    /// Override the values of this config with those of `other`.
+    pub fn merge(&mut self, other: Config) {
+        self.defaults.extend(other.defaults);
+        for (name, options) in other.commands {
+            self.commands.entry(name).or_default().extend(options);
+        }
+    }
+
+    /// The values of `option` for the subcommand `command`, if any.
+    pub fn get(&self, command: &str, option: &str) -> Option<&[String]> {
+        self.commands
+            .get(command)
+            .and_then(|options| options.get(option))
+            .or_else(|| self.defaults.get(option))
+            .map(Vec::as_slice)
+    }
+
+    /// Whether the config has no values.
+    pub fn is_empty(&self) -> bool {
+        self.defaults.is_empty() && self.commands.values().all(BTreeMap::is_empty)
+    }
+}
+
+/// The name of an option as in the definition of the command line, with underscores rather than dashes.
+fn option_name(key: &str) -> String {
+    key.replace('-', "_")
+}
+
+/// The values of the option `key` as they would be given on the command line.
+fn option_values(path: &Path, key: &str, value: Value) -> Result<Vec<String>, BinvizError> {
+    let values = match value {
+        Value::Array(values) => values,
+        value => vec![value],
+    };
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::String(value) => Ok(value),
+            Value::Integer(value) => Ok(value.to_string()),
+            Value::Float(value) => Ok(value.to_string()),
+            Value::Boolean(value) => Ok(value.to_string()),
+            value => Err(BinvizError::serialization(
+                path,
+                format!(
+                    "the option `{}` has to be a string, number, boolean or an array of those, not a {}",
+                    key,
+                    value.type_str()
+                ),
+            )),
+        })
+        .collect()
+}
+
+/// The user config file, `binviz/config.toml` in `$XDG_CONFIG_HOME` or else in `~/.config`.
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("binviz").join("config.toml"))
+}
+
+/// Load the config: only the file `explicit` if given, otherwise the [`user_config_path`] overridden by
+/// the [`LOCAL_CONFIG_FILE`] in the current folder, either of which may be missing.
+pub fn load_config(explicit: Option<&Path>) -> Result<Config, BinvizError> {
+    if let Some(path) = explicit {
+        return Config::load(path);
+    }
+    let mut config = Config::default();
+    for path in user_config_path()
+        .into_iter()
+        .chain([PathBuf::from(LOCAL_CONFIG_FILE)])
+    {
+        match Config::load(&path) {
+            Ok(file) => config.merge(file),
+            Err(BinvizError::Io { source, .. }) if source.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(config)
+}
@@ -0,0 +1,109 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{create_output, validate_dimension, BinvizError, Histogram};
+
+/// The formats the counts behind a digraph image can be written in, see [`write_counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountsFormat {
+    /// A 256×256 binary PGM image with 16-bit pixels, counts above 65535 are clamped.
+    Pgm,
+    /// A sparse list of `x,y,count` lines with a header, leaving out the pairs that never occur.
+    Csv,
+    /// A sparse JSON array of `{"x": .., "y": .., "count": ..}` objects, leaving out the pairs that never occur.
+    Json,
+}
+
+impl CountsFormat {
+    /// Pick the format based on the extension of `path`: CSV for `.csv`, JSON for `.json` and PGM otherwise.
+    pub fn from_path<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        match path.as_ref().extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => CountsFormat::Csv,
+            Some(extension) if extension.eq_ignore_ascii_case("json") => CountsFormat::Json,
+            _ => CountsFormat::Pgm,
+        }
+    }
+}
+
+/// A pair of bytes at (`x`, `y`) in the digraph image and its exact count.
+#[derive(Debug, Serialize)]
+struct CountEntry {
+    x: u8,
+    y: u8,
+    count: usize,
+}
+
+/// Write the exact counts behind the digraph image of a histogram to `path` in the given format,
+/// the first byte of every pair is the `x` coordinate and the second byte the `y` coordinate.
+/// An existing file is only overwritten with `force`.
+pub fn write_counts<P>(
+    dihistogram: &Histogram<u8>,
+    path: P,
+    format: CountsFormat,
+    force: bool,
+) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    validate_dimension(dihistogram, 2)?;
+    let file = create_output(path, force)?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        CountsFormat::Pgm => {
+            write_pgm(dihistogram, &mut writer).map_err(|err| BinvizError::write(path, err))?
+        }
+        CountsFormat::Csv => {
+            write_csv(dihistogram, &mut writer).map_err(|err| BinvizError::write(path, err))?
+        }
+        CountsFormat::Json => {
+            let entries: Vec<CountEntry> = dihistogram
+                .iter()
+                .filter(|(_, &count)| count > 0)
+                .map(|(key, &count)| CountEntry {
+                    x: key[0],
+                    y: key[1],
+                    count,
+                })
+                .collect();
+            serde_json::to_writer(&mut writer, &entries)
+                .map_err(|err| BinvizError::serialization(path, err))?
+        }
+    }
+    writer.flush().map_err(|err| BinvizError::write(path, err))
+}
+
+/// Write the counts as a binary 16-bit PGM image, with the most significant byte first as PGM requires.
+fn write_pgm<W>(dihistogram: &Histogram<u8>, writer: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut pixels = vec![0u16; 256 * 256];
+    for (key, &count) in dihistogram {
+        pixels[key[1] as usize * 256 + key[0] as usize] = count.min(u16::MAX as usize) as u16;
+    }
+    write!(writer, "P5\n256 256\n{}\n", u16::MAX)?;
+    for pixel in pixels {
+        writer.write_all(&pixel.to_be_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write the counts as `x,y,count` lines, sorted by `x` and then `y`.
+fn write_csv<W>(dihistogram: &Histogram<u8>, writer: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "x,y,count")?;
+    for (key, &count) in dihistogram.iter().filter(|(_, &count)| count > 0) {
+        writeln!(writer, "{},{},{}", key[0], key[1], count)?;
+    }
+    Ok(())
+}
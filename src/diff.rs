@@ -0,0 +1,168 @@
+#[cfg(feature = "tables")]
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "tables")]
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+
+#[cfg(feature = "tables")]
+use crate::storage::to_hex;
+use crate::{check_key_lengths, BinvizError, Histogram};
+
+/// Calculate the signed difference of the counts of every key in `a` or `b`, as the count in `a`
+/// minus the count in `b`. A key only present in `b` has a negative difference.
+pub fn diff_histograms(
+    a: &Histogram<u8>,
+    b: &Histogram<u8>,
+) -> Result<BTreeMap<Vec<u8>, i64>, BinvizError> {
+    check_key_lengths(a, b)?;
+    let mut diff: BTreeMap<Vec<u8>, i64> = a
+        .iter()
+        .map(|(key, freq)| (key.clone(), *freq as i64))
+        .collect();
+    for (key, freq) in b {
+        *diff.entry(key.clone()).or_insert(0) -= *freq as i64;
+    }
+    Ok(diff)
+}
+
+/// Calculate the ratio of the counts of every key in `a` or `b`, as the count in `a` divided by the count in `b`.
+///
+/// `smoothing` is added to both counts, so keys missing on one side get a finite, non-zero ratio.
+/// With a `smoothing` of 0 a key missing in `a` has a ratio of 0, and one missing in `b` a ratio of infinity.
+pub fn ratio_histograms(
+    a: &Histogram<u8>,
+    b: &Histogram<u8>,
+    smoothing: f64,
+) -> Result<BTreeMap<Vec<u8>, f64>, BinvizError> {
+    check_key_lengths(a, b)?;
+    let count = |histogram: &Histogram<u8>, key: &Vec<u8>| {
+        histogram.get(key).copied().unwrap_or(0) as f64 + smoothing
+    };
+    Ok(a.keys()
+        .chain(b.keys())
+        .map(|key| (key.clone(), count(a, key) / count(b, key)))
+        .collect())
+}
+
+/// Display the `count` keys whose counts increased the most from `old` to `new`,
+/// and the `count` keys whose counts decreased the most, as two tables.
+///
+/// The ratios are those of [`ratio_histograms`] with the given `smoothing`.
+#[cfg(feature = "tables")]
+pub fn display_histogram_diff(
+    old: &Histogram<u8>,
+    new: &Histogram<u8>,
+    count: usize,
+    smoothing: f64,
+) -> Result<String, BinvizError> {
+    let diff = diff_histograms(new, old)?;
+    let ratios = ratio_histograms(new, old, smoothing)?;
+    let mut changes: Vec<(&Vec<u8>, i64)> = diff
+        .iter()
+        .filter(|(_, delta)| **delta != 0)
+        .map(|(key, delta)| (key, *delta))
+        .collect();
+    changes.sort_by_key(|(_, delta)| Reverse(*delta));
+    let increases = changes.iter().filter(|(_, delta)| *delta > 0).take(count);
+    let decreases = changes
+        .iter()
+        .rev()
+        .filter(|(_, delta)| *delta < 0)
+        .take(count);
+    Ok(format!(
+        "Largest increases:\n{}\n\nLargest decreases:\n{}",
+        diff_table(old, new, &ratios, increases),
+        diff_table(old, new, &ratios, decreases)
+    ))
+}
+
+/// Build the table of the given changes, see [`display_histogram_diff`].
+#[cfg(feature = "tables")]
+fn diff_table<'a, I>(
+    old: &Histogram<u8>,
+    new: &Histogram<u8>,
+    ratios: &BTreeMap<Vec<u8>, f64>,
+    changes: I,
+) -> Table
+where
+    I: Iterator<Item = &'a (&'a Vec<u8>, i64)>,
+{
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(["Rank", "Hex", "Old", "New", "Delta", "Ratio"]);
+    for (i, (key, delta)) in changes.enumerate() {
+        table.add_row([
+            format!("{}", i),
+            format!("0x{}", to_hex(key)),
+            format!("{}", old.get(*key).copied().unwrap_or(0)),
+            format!("{}", new.get(*key).copied().unwrap_or(0)),
+            format!("{:+}", delta),
+            format!("{:.5}", ratios[*key]),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_histogram_from_bytes;
+
+    #[test]
+    fn key_only_in_b_has_a_negative_difference() {
+        let a = calculate_histogram_from_bytes(b"aab", 1, 1);
+        let b = calculate_histogram_from_bytes(b"abcc", 1, 1);
+        let diff = diff_histograms(&a, &b).unwrap();
+        assert_eq!(
+            diff,
+            BTreeMap::from([(b"a".to_vec(), 1), (b"b".to_vec(), 0), (b"c".to_vec(), -2)])
+        );
+    }
+
+    #[test]
+    fn empty_histograms_have_no_differences() {
+        let empty = Histogram::new();
+        let a = calculate_histogram_from_bytes(b"ab", 1, 1);
+        assert!(diff_histograms(&empty, &empty).unwrap().is_empty());
+        assert!(ratio_histograms(&empty, &empty, 1.0).unwrap().is_empty());
+        assert_eq!(diff_histograms(&empty, &a).unwrap()[b"a".as_slice()], -1);
+        assert_eq!(
+            ratio_histograms(&a, &empty, 1.0).unwrap()[b"b".as_slice()],
+            2.0
+        );
+    }
+
+    #[test]
+    fn mismatched_key_lengths_are_an_error() {
+        let bytes = calculate_histogram_from_bytes(b"abc", 1, 1);
+        let pairs = calculate_histogram_from_bytes(b"abc", 2, 1);
+        assert!(matches!(
+            diff_histograms(&bytes, &pairs),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 1,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            ratio_histograms(&pairs, &bytes, 1.0),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn missing_keys_without_smoothing_have_a_ratio_of_zero_or_infinity() {
+        let a = calculate_histogram_from_bytes(b"aab", 1, 1);
+        let b = calculate_histogram_from_bytes(b"bc", 1, 1);
+        let ratios = ratio_histograms(&a, &b, 0.0).unwrap();
+        assert_eq!(ratios[b"a".as_slice()], f64::INFINITY);
+        assert_eq!(ratios[b"b".as_slice()], 1.0);
+        assert_eq!(ratios[b"c".as_slice()], 0.0);
+        let smoothed = ratio_histograms(&a, &b, 1.0).unwrap();
+        assert_eq!(smoothed[b"a".as_slice()], 3.0);
+        assert_eq!(smoothed[b"c".as_slice()], 0.5);
+    }
+}
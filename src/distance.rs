@@ -0,0 +1,156 @@
+use std::collections::BTreeSet;
+
+use crate::{check_key_lengths, BinvizError, Histogram};
+
+/// Normalize the counts of every key in `a` or `b` to probabilities, as pairs of the probability
+/// in `a` and in `b`. Keys missing in one histogram have a probability of 0 there.
+fn probability_pairs(a: &Histogram<u8>, b: &Histogram<u8>) -> Result<Vec<(f64, f64)>, BinvizError> {
+    check_key_lengths(a, b)?;
+    let a_total = a.values().sum::<usize>() as f64;
+    let b_total = b.values().sum::<usize>() as f64;
+    if a_total == 0.0 || b_total == 0.0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let keys: BTreeSet<&Vec<u8>> = a.keys().chain(b.keys()).collect();
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            (
+                a.get(key).copied().unwrap_or(0) as f64 / a_total,
+                b.get(key).copied().unwrap_or(0) as f64 / b_total,
+            )
+        })
+        .collect())
+}
+
+/// Calculate the Hellinger distance between the distributions of `a` and `b`.
+///
+/// The distance is symmetric and lies in [0, 1], 0 for the same distribution and 1 for histograms
+/// without a key in common.
+pub fn hellinger(a: &Histogram<u8>, b: &Histogram<u8>) -> Result<f64, BinvizError> {
+    // Summing the squared differences rather than taking 1 minus the Bhattacharyya coefficient,
+    // whose rounding error the square root would blow up for nearly equal distributions.
+    let squares: f64 = probability_pairs(a, b)?
+        .into_iter()
+        .map(|(p, q)| (p.sqrt() - q.sqrt()).powi(2))
+        .sum();
+    Ok((squares / 2.0).clamp(0.0, 1.0).sqrt())
+}
+
+/// Calculate the total variation distance between the distributions of `a` and `b`,
+/// the largest difference in probability the two assign to any set of keys.
+///
+/// The distance is symmetric and lies in [0, 1], 0 for the same distribution and 1 for histograms
+/// without a key in common.
+pub fn total_variation(a: &Histogram<u8>, b: &Histogram<u8>) -> Result<f64, BinvizError> {
+    let distance: f64 = probability_pairs(a, b)?
+        .into_iter()
+        .map(|(p, q)| (p - q).abs())
+        .sum::<f64>()
+        / 2.0;
+    Ok(distance.clamp(0.0, 1.0))
+}
+
+/// Calculate the Jensen-Shannon divergence between the distributions of `a` and `b`, in bits per key:
+/// the average Kullback-Leibler divergence of both from their mixture, so keys missing in one need no smoothing.
+///
+/// The divergence is symmetric and lies in [0, 1], 0 for the same distribution and 1 for histograms
+/// without a key in common.
+pub fn jensen_shannon(a: &Histogram<u8>, b: &Histogram<u8>) -> Result<f64, BinvizError> {
+    let divergence: f64 = probability_pairs(a, b)?
+        .into_iter()
+        .map(|(p, q)| {
+            let mixture = (p + q) / 2.0;
+            let term = |probability: f64| {
+                if probability > 0.0 {
+                    probability * (probability / mixture).log2()
+                } else {
+                    0.0
+                }
+            };
+            (term(p) + term(q)) / 2.0
+        })
+        .sum();
+    Ok(divergence.clamp(0.0, 1.0))
+}
+
+/// Calculate the cosine similarity of the counts of `a` and `b`, as vectors with an element per key.
+///
+/// The similarity lies in [0, 1] as counts aren't negative, 1 for the same distribution and 0 for histograms
+/// without a key in common.
+pub fn cosine_similarity(a: &Histogram<u8>, b: &Histogram<u8>) -> Result<f64, BinvizError> {
+    let pairs = probability_pairs(a, b)?;
+    let dot: f64 = pairs.iter().map(|(p, q)| p * q).sum();
+    let norm = |square: fn(&(f64, f64)) -> f64| pairs.iter().map(square).sum::<f64>().sqrt();
+    let similarity = dot / (norm(|(p, _)| p * p) * norm(|(_, q)| q * q));
+    Ok(similarity.clamp(0.0, 1.0))
+}
+
+/// Display the Hellinger and total variation distance of the input to a reference.
+#[cfg(feature = "tables")]
+pub fn display_distances(hellinger: f64, total_variation: f64) -> String {
+    format!(
+        "Hellinger distance to the reference is {:.5}, total variation distance is {:.5}.",
+        hellinger, total_variation
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_histogram_from_bytes;
+
+    type Distance = fn(&Histogram<u8>, &Histogram<u8>) -> Result<f64, BinvizError>;
+
+    const DISTANCES: [Distance; 2] = [hellinger, total_variation];
+
+    #[test]
+    fn identical_distributions_are_at_distance_zero() {
+        let a = calculate_histogram_from_bytes(b"aabbbc", 1, 1);
+        // The same distribution with twice the counts.
+        let b = calculate_histogram_from_bytes(b"aabbbcaabbbc", 1, 1);
+        for distance in DISTANCES {
+            assert_eq!(distance(&a, &a).unwrap(), 0.0);
+            assert!(distance(&a, &b).unwrap() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn disjoint_distributions_are_at_distance_one() {
+        let a = calculate_histogram_from_bytes(b"aab", 1, 1);
+        let b = calculate_histogram_from_bytes(b"xyz", 1, 1);
+        for distance in DISTANCES {
+            assert!((distance(&a, &b).unwrap() - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn distances_are_symmetric() {
+        let a = calculate_histogram_from_bytes(b"aaaabbc", 1, 1);
+        let b = calculate_histogram_from_bytes(b"abccdd", 1, 1);
+        for distance in DISTANCES {
+            let there = distance(&a, &b).unwrap();
+            assert!(there > 0.0 && there < 1.0);
+            assert_eq!(there, distance(&b, &a).unwrap());
+        }
+        // Half of the probability of `a` moves from `a` to `b`.
+        let half = calculate_histogram_from_bytes(b"ab", 1, 1);
+        let only_a = calculate_histogram_from_bytes(b"a", 1, 1);
+        assert!((total_variation(&half, &only_a).unwrap() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn empty_or_mismatched_histograms_are_errors() {
+        let a = calculate_histogram_from_bytes(b"ab", 1, 1);
+        for distance in DISTANCES {
+            assert!(matches!(
+                distance(&a, &Histogram::new()),
+                Err(BinvizError::EmptyInput)
+            ));
+            assert!(matches!(
+                distance(&a, &calculate_histogram_from_bytes(b"ab", 2, 1)),
+                Err(BinvizError::KeyLengthMismatch { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+use crate::{check_key_lengths, BinvizError, Histogram};
+
+/// How keys that have a count in one histogram but are missing in the other are handled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// Add this pseudo count to every key present in either histogram before normalizing,
+    /// 1 is Laplace smoothing.
+    Additive(f64),
+    /// Fail with [`BinvizError::MissingReferenceKey`] instead.
+    Strict,
+}
+
+impl FromStr for Smoothing {
+    type Err = String;
+
+    /// Parse `strict`, or a non-negative pseudo count for additive smoothing.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("strict") {
+            return Ok(Smoothing::Strict);
+        }
+        match s.trim().parse::<f64>() {
+            Ok(pseudo_count) if pseudo_count.is_finite() && pseudo_count >= 0.0 => {
+                Ok(Smoothing::Additive(pseudo_count))
+            }
+            _ => Err(format!(
+                "`{}` isn't a smoothing, expected `strict` or a non-negative pseudo count",
+                s
+            )),
+        }
+    }
+}
+
+/// Calculate the Kullback-Leibler divergence of `p` from `q`, in bits per key.
+///
+/// The counts of both histograms are normalized to probabilities first, `smoothing` decides what happens
+/// to keys of `p` missing in `q`. With [`Smoothing::Additive`] of 0 such keys give an infinite divergence.
+/// The divergence is 0 for histograms with the same distribution, and isn't symmetric in `p` and `q`.
+pub fn kl_divergence(
+    p: &Histogram<u8>,
+    q: &Histogram<u8>,
+    smoothing: Smoothing,
+) -> Result<f64, BinvizError> {
+    check_key_lengths(p, q)?;
+    let p_total = p.values().sum::<usize>() as f64;
+    let q_total = q.values().sum::<usize>() as f64;
+    if p_total == 0.0 || q_total == 0.0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let term = |p_prob: f64, q_prob: f64| {
+        if p_prob > 0.0 {
+            p_prob * (p_prob / q_prob).log2()
+        } else {
+            0.0
+        }
+    };
+    match smoothing {
+        Smoothing::Strict => p
+            .iter()
+            .filter(|(_, &freq)| freq > 0)
+            .map(|(key, &freq)| match q.get(key).copied().unwrap_or(0) {
+                0 => Err(BinvizError::MissingReferenceKey { key: key.clone() }),
+                q_freq => Ok(term(freq as f64 / p_total, q_freq as f64 / q_total)),
+            })
+            .sum(),
+        Smoothing::Additive(pseudo_count) => {
+            let keys: BTreeSet<&Vec<u8>> = p.keys().chain(q.keys()).collect();
+            let extra = pseudo_count * keys.len() as f64;
+            let probability = |histogram: &Histogram<u8>, total: f64, key: &Vec<u8>| {
+                (histogram.get(key).copied().unwrap_or(0) as f64 + pseudo_count) / (total + extra)
+            };
+            Ok(keys
+                .into_iter()
+                .map(|key| term(probability(p, p_total, key), probability(q, q_total, key)))
+                .sum())
+        }
+    }
+}
+
+/// Display the Kullback-Leibler divergence of the input from a reference, see [`kl_divergence`].
+#[cfg(feature = "tables")]
+pub fn display_kl_divergence(divergence: f64) -> String {
+    format!(
+        "Kullback-Leibler divergence from the reference is {:.5} bits per key.",
+        divergence
+    )
+}
+
+/// Calculate the cross-entropy of `sample` against `reference`, in bits per key: the average amount of bits
+/// per key when encoding the sample with a code optimal for the reference.
+///
+/// The counts of both histograms are normalized to probabilities first. With [`Smoothing::Additive`] the pseudo count
+/// is added to the count of every key of either histogram in the reference only, so keys missing in the reference
+/// cost many but finitely many bits. The excess over the entropy of the sample is its divergence from the reference.
+pub fn cross_entropy(
+    sample: &Histogram<u8>,
+    reference: &Histogram<u8>,
+    smoothing: Smoothing,
+) -> Result<f64, BinvizError> {
+    check_key_lengths(sample, reference)?;
+    let sample_total = sample.values().sum::<usize>() as f64;
+    let reference_total = reference.values().sum::<usize>() as f64;
+    if sample_total == 0.0 || reference_total == 0.0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let (pseudo_count, extra) = match smoothing {
+        Smoothing::Strict => (0.0, 0.0),
+        Smoothing::Additive(pseudo_count) => {
+            let keys: BTreeSet<&Vec<u8>> = sample.keys().chain(reference.keys()).collect();
+            (pseudo_count, pseudo_count * keys.len() as f64)
+        }
+    };
+    sample
+        .iter()
+        .filter(|(_, &freq)| freq > 0)
+        .map(|(key, &freq)| {
+            let reference_freq = reference.get(key).copied().unwrap_or(0) as f64 + pseudo_count;
+            if smoothing == Smoothing::Strict && reference_freq == 0.0 {
+                return Err(BinvizError::MissingReferenceKey { key: key.clone() });
+            }
+            let probability = freq as f64 / sample_total;
+            Ok(-probability * (reference_freq / (reference_total + extra)).log2())
+        })
+        .sum()
+}
+
+/// Display the cross-entropy of the input against a reference, and its excess over the `entropy` of the input,
+/// see [`cross_entropy`].
+#[cfg(feature = "tables")]
+pub fn display_cross_entropy(cross_entropy: f64, entropy: f64) -> String {
+    format!(
+        "Cross-entropy against the reference is {:.5} bits per key, exceeding the entropy of the input by {:.5} bits per key.",
+        cross_entropy,
+        (cross_entropy - entropy).max(0.0)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram_of(counts: &[(&str, usize)]) -> Histogram<u8> {
+        counts
+            .iter()
+            .map(|(key, count)| (key.as_bytes().to_vec(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn identical_histograms_do_not_diverge() {
+        let p = histogram_of(&[("a", 5), ("b", 3), ("c", 1)]);
+        let scaled = histogram_of(&[("a", 50), ("b", 30), ("c", 10)]);
+        for smoothing in [Smoothing::Strict, Smoothing::Additive(0.0)] {
+            assert_eq!(kl_divergence(&p, &p, smoothing).unwrap(), 0.0);
+            assert!(kl_divergence(&p, &scaled, smoothing).unwrap().abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn disjoint_histograms_diverge_with_smoothing() {
+        let p = histogram_of(&[("a", 1)]);
+        let q = histogram_of(&[("b", 1)]);
+        assert!(matches!(
+            kl_divergence(&p, &q, Smoothing::Strict),
+            Err(BinvizError::MissingReferenceKey { key }) if key == b"a"
+        ));
+        // Laplace smoothing turns the counts into 2/3 and 1/3, and the reverse.
+        let divergence = kl_divergence(&p, &q, Smoothing::Additive(1.0)).unwrap();
+        assert!((divergence - 1.0 / 3.0).abs() < 1e-12, "{}", divergence);
+        // A smaller pseudo count trusts the counts more, so the supports diverge more.
+        assert!(kl_divergence(&p, &q, Smoothing::Additive(0.01)).unwrap() > divergence);
+    }
+
+    #[test]
+    fn divergence_is_asymmetric() {
+        let p = histogram_of(&[("a", 3), ("b", 1)]);
+        let q = histogram_of(&[("a", 1), ("b", 1)]);
+        let forward = kl_divergence(&p, &q, Smoothing::Strict).unwrap();
+        let backward = kl_divergence(&q, &p, Smoothing::Strict).unwrap();
+        assert!((forward - (0.75 * 1.5f64.log2() - 0.25)).abs() < 1e-12);
+        assert!((backward - (0.5 * (2.0f64 / 3.0).log2() + 0.5)).abs() < 1e-12);
+        assert!((forward - backward).abs() > 0.01);
+    }
+
+    #[test]
+    fn mismatched_or_empty_histograms_are_an_error() {
+        let p = histogram_of(&[("a", 1)]);
+        let pairs = histogram_of(&[("ab", 1)]);
+        assert!(matches!(
+            kl_divergence(&p, &pairs, Smoothing::Additive(1.0)),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 1,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            kl_divergence(&p, &Histogram::new(), Smoothing::Additive(1.0)),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn cross_entropy_exceeds_entropy_by_the_divergence() {
+        let p = histogram_of(&[("a", 3), ("b", 1)]);
+        let q = histogram_of(&[("a", 1), ("b", 1)]);
+        let entropy = crate::calculate_entropy_histogram(&p, crate::EntropyUnit::Bits).unwrap();
+        let divergence = kl_divergence(&p, &q, Smoothing::Strict).unwrap();
+        let cross_entropy = cross_entropy(&p, &q, Smoothing::Strict).unwrap();
+        assert!((cross_entropy - entropy - divergence).abs() < 1e-12);
+    }
+
+    #[test]
+    fn smoothing_parses() {
+        assert_eq!("strict".parse::<Smoothing>(), Ok(Smoothing::Strict));
+        assert_eq!("1".parse::<Smoothing>(), Ok(Smoothing::Additive(1.0)));
+        assert!("-1".parse::<Smoothing>().is_err());
+        assert!("laplace".parse::<Smoothing>().is_err());
+    }
+}
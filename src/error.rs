@@ -0,0 +1,220 @@
+use std::{error::Error, io, path::PathBuf};
+
+use crate::storage::to_hex;
+#[cfg(feature = "images")]
+use crate::ImageFormat;
+
+/// Everything that can go wrong while analyzing a file.
+#[derive(Debug, thiserror::Error)]
+pub enum BinvizError {
+    /// Reading from `path`, or anything else with it than writing the output to it, failed.
+    #[error("`{}`: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    /// Reading from a reader without a path failed.
+    #[error("couldn't read the input: {0}")]
+    Read(#[source] io::Error),
+    /// Writing the output to `path` failed.
+    #[error("couldn't write `{}`: {source}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    /// Encoding or saving an image to `path` failed.
+    #[cfg(feature = "images")]
+    #[error("couldn't save image `{}`: {source}", path.display())]
+    Image {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// The thread pool for a parallel computation couldn't be created.
+    #[cfg(feature = "parallel")]
+    #[error("couldn't create thread pool: {0}")]
+    ThreadPool(#[source] rayon::ThreadPoolBuildError),
+    /// Drawing on the terminal or reading keys from it failed.
+    #[cfg(feature = "cli")]
+    #[error("couldn't use the terminal: {0}")]
+    Terminal(#[source] io::Error),
+    /// (De)serializing the data in `path` failed.
+    #[error("couldn't (de)serialize `{}`: {source}", path.display())]
+    Serialization {
+        path: PathBuf,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// The `offset` to start reading at lies past the end of the `size` byte input.
+    #[error("offset `{offset:#x}` lies past the end of the input of `{size}` bytes")]
+    OffsetOutOfRange { offset: u64, size: u64 },
+    /// A histogram with keys of length `found` was given where length `expected` was needed.
+    #[error("expected a histogram of {expected}-byte keys, found {found}-byte keys")]
+    KeyLengthMismatch { expected: usize, found: usize },
+    /// A window `dimension` of 0 was given, windows contain at least one byte.
+    #[error("invalid dimension `{dimension}`, the dimension must be at least 1")]
+    InvalidDimension { dimension: usize },
+    /// A `stride` of 0 was given, windows advance by at least one byte.
+    #[error("invalid stride `{stride}`, the stride must be at least 1")]
+    InvalidStride { stride: usize },
+    /// The input contains no bytes, or a histogram contains no windows.
+    #[error("the input is empty, there is nothing to analyze")]
+    EmptyInput,
+    /// The `size` byte input doesn't fill a single window of `dimension` bytes.
+    #[error(
+        "the input of `{size}` bytes is too small for windows of `{dimension}` bytes, use a dimension of at most `{size}`"
+    )]
+    FileTooSmallForDimension { size: u64, dimension: usize },
+    /// A Rényi entropy of negative or `NaN` order `alpha` was asked for.
+    #[error("invalid Rényi entropy order `{alpha}`, the order must be at least 0")]
+    InvalidRenyiOrder { alpha: f64 },
+    /// The bytes of the input don't vary, so statistics relative to their variance are undefined.
+    #[error("every byte of the input is the same, the statistic is undefined")]
+    ZeroVariance,
+    /// The `key` has a count in a histogram but not in the reference it is compared to, and no smoothing was asked for.
+    #[error(
+        "the key `0x{}` is missing in the reference, use smoothing to compare anyway",
+        to_hex(key)
+    )]
+    MissingReferenceKey { key: Vec<u8> },
+    /// An image scale `factor` of 0 or above [`crate::MAX_SCALE_FACTOR`] was given.
+    #[cfg(feature = "images")]
+    #[error(
+        "invalid scale factor `{factor}`, the factor must be between 1 and {}",
+        crate::MAX_SCALE_FACTOR
+    )]
+    InvalidScaleFactor { factor: u32 },
+    /// An image with channels of `bits` bits was to be saved in a `format` that can't store them.
+    #[cfg(feature = "images")]
+    #[error(
+        "the `{format}` format can't store {bits}-bit channels, use `png` or `tiff` or a bit depth of 8 instead"
+    )]
+    UnsupportedBitDepth { format: ImageFormat, bits: u16 },
+    /// A raw rendering of `width` 0 was asked for, rows contain at least one byte.
+    #[error("invalid width `{width}`, the width must be at least 1")]
+    InvalidWidth { width: u32 },
+    /// Watching `path` for changes failed.
+    #[cfg(feature = "cli")]
+    #[error("couldn't watch `{}`: {source}", path.display())]
+    Watch {
+        path: PathBuf,
+        source: notify::Error,
+    },
+    /// The output `path` already exists, and overwriting it wasn't asked for.
+    #[error("refusing to overwrite `{}` (use --force)", path.display())]
+    OutputExists { path: PathBuf },
+    /// Analyzing some of the `total` files of a batch failed, every one of the `failures` with its error.
+    #[error("{}", batch_message(failures, *total))]
+    Batch {
+        failures: Vec<(PathBuf, BinvizError)>,
+        total: usize,
+    },
+}
+
+impl BinvizError {
+    /// Wrap an [`io::Error`] together with the path it occurred on.
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        BinvizError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Wrap a (de)serialization error together with the path it occurred on.
+    pub fn serialization(
+        path: impl Into<PathBuf>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        BinvizError::Serialization {
+            path: path.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Wrap an [`io::Error`] writing output to `path`.
+    pub fn write(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        BinvizError::Write {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Wrap an [`image::ImageError`] together with the path it occurred on.
+    #[cfg(feature = "images")]
+    pub fn image(path: impl Into<PathBuf>, source: image::ImageError) -> Self {
+        BinvizError::Image {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Wrap a [`notify::Error`] watching `path`.
+    #[cfg(feature = "cli")]
+    pub fn watch(path: impl Into<PathBuf>, source: notify::Error) -> Self {
+        BinvizError::Watch {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+/// The message of [`BinvizError::Batch`], with every failure on its own line.
+fn batch_message(failures: &[(PathBuf, BinvizError)], total: usize) -> String {
+    let mut message = format!("`{}` of `{}` files failed", failures.len(), total);
+    for (path, err) in failures {
+        message.push_str(&format!("\n  `{}`: {}", path.display(), err));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_keep_their_path_and_source() {
+        let err = BinvizError::io("input.bin", io::Error::new(io::ErrorKind::NotFound, "gone"));
+        assert_eq!(err.to_string(), "`input.bin`: gone");
+        assert_eq!(err.source().unwrap().to_string(), "gone");
+        let err = BinvizError::write(
+            "out/image.png",
+            io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        );
+        assert_eq!(err.to_string(), "couldn't write `out/image.png`: denied");
+        let err = BinvizError::serialization("histogram.json", "expected a map");
+        assert_eq!(
+            err.to_string(),
+            "couldn't (de)serialize `histogram.json`: expected a map"
+        );
+    }
+
+    #[test]
+    fn read_error_keeps_its_source() {
+        let err = BinvizError::Read(io::Error::new(io::ErrorKind::UnexpectedEof, "cut off"));
+        assert_eq!(err.to_string(), "couldn't read the input: cut off");
+        assert_eq!(err.source().unwrap().to_string(), "cut off");
+    }
+
+    #[test]
+    fn missing_key_is_shown_in_hex() {
+        let err = BinvizError::MissingReferenceKey {
+            key: vec![0xde, 0xad],
+        };
+        assert_eq!(
+            err.to_string(),
+            "the key `0xdead` is missing in the reference, use smoothing to compare anyway"
+        );
+    }
+
+    #[test]
+    fn batch_lists_every_failure() {
+        let err = BinvizError::Batch {
+            failures: vec![
+                (PathBuf::from("a.bin"), BinvizError::EmptyInput),
+                (
+                    PathBuf::from("b.bin"),
+                    BinvizError::InvalidDimension { dimension: 0 },
+                ),
+            ],
+            total: 5,
+        };
+        assert_eq!(
+            err.to_string(),
+            "`2` of `5` files failed\n  \
+             `a.bin`: the input is empty, there is nothing to analyze\n  \
+             `b.bin`: invalid dimension `0`, the dimension must be at least 1"
+        );
+    }
+}
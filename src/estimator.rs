@@ -0,0 +1,109 @@
+use std::{f64::consts::LN_2, fmt, str::FromStr};
+
+use crate::{calculate_entropy_histogram, BinvizError, EntropyUnit, Histogram};
+
+/// How the entropy is estimated from the counts of a histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntropyEstimator {
+    /// The plug-in estimator, the entropy of the observed frequencies. It is biased low when most keys
+    /// are seen only a few times, as happens for higher dimensions of small files.
+    #[default]
+    Plugin,
+    /// The plug-in estimate plus the Miller-Madow correction `(K - 1) / 2N` nats, for `K` distinct observed keys
+    /// out of `N` counted windows. The correction vanishes as `N` grows.
+    MillerMadow,
+}
+
+impl FromStr for EntropyEstimator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plugin" | "plug-in" => Ok(EntropyEstimator::Plugin),
+            "miller-madow" => Ok(EntropyEstimator::MillerMadow),
+            _ => Err(format!(
+                "unknown entropy estimator `{}`, expected `plugin` or `miller-madow`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EntropyEstimator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntropyEstimator::Plugin => f.write_str("plugin"),
+            EntropyEstimator::MillerMadow => f.write_str("miller-madow"),
+        }
+    }
+}
+
+/// Estimate the entropy from a given n-dimensional histogram with the given `estimator`, in the given `unit`.
+///
+/// An empty histogram is an [`BinvizError::EmptyInput`] error, see [`calculate_entropy_histogram`].
+pub fn estimate_entropy<T>(
+    histogram: &Histogram<T>,
+    estimator: EntropyEstimator,
+    unit: EntropyUnit,
+) -> Result<f64, BinvizError> {
+    let entropy = calculate_entropy_histogram(histogram, unit)?;
+    match estimator {
+        EntropyEstimator::Plugin => Ok(entropy),
+        EntropyEstimator::MillerMadow => {
+            let total: usize = histogram.values().sum();
+            let observed = histogram.values().filter(|&&freq| freq > 0).count();
+            let correction = (observed - 1) as f64 / (2.0 * total as f64);
+            Ok(entropy + unit.from_bits(correction / LN_2))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_histogram_from_bytes, test_support::noise};
+
+    #[test]
+    fn miller_madow_adds_the_correction() {
+        let histogram = calculate_histogram_from_bytes(b"aabbbc", 1, 1);
+        let plugin = estimate_entropy(&histogram, EntropyEstimator::Plugin, EntropyUnit::Nats);
+        let corrected =
+            estimate_entropy(&histogram, EntropyEstimator::MillerMadow, EntropyUnit::Nats);
+        // Three keys out of six windows.
+        assert!((corrected.unwrap() - plugin.unwrap() - 2.0 / 12.0).abs() < 1e-12);
+        // A single key needs no correction.
+        let single = calculate_histogram_from_bytes(b"aaaa", 1, 1);
+        assert_eq!(
+            estimate_entropy(&single, EntropyEstimator::MillerMadow, EntropyUnit::Bits).unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn miller_madow_is_larger_and_converges() {
+        let mut previous = f64::INFINITY;
+        for len in [1 << 8, 1 << 12, 1 << 16, 1 << 20] {
+            let histogram = calculate_histogram_from_bytes(&noise(len), 1, 1);
+            let plugin =
+                estimate_entropy(&histogram, EntropyEstimator::Plugin, EntropyUnit::Bits).unwrap();
+            let corrected =
+                estimate_entropy(&histogram, EntropyEstimator::MillerMadow, EntropyUnit::Bits)
+                    .unwrap();
+            let correction = corrected - plugin;
+            assert!(correction > 0.0, "{} bytes", len);
+            assert!(correction < previous, "{} bytes", len);
+            previous = correction;
+        }
+        assert!(previous < 1e-3);
+    }
+
+    #[test]
+    fn empty_histogram_is_an_error() {
+        for estimator in [EntropyEstimator::Plugin, EntropyEstimator::MillerMadow] {
+            assert!(matches!(
+                estimate_entropy(&Histogram::<u8>::new(), estimator, EntropyUnit::Bits),
+                Err(BinvizError::EmptyInput)
+            ));
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::{
+    calculate_entropy_histogram, calculate_histogram_from_bytes, generate_image, validate_window,
+    BinvizError, EntropyUnit, Histogram, ScaleMode,
+};
+
+/// The amount of pixels of the image written by [`binviz_render_digraph`], one for every pair of bytes.
+pub const BINVIZ_DIGRAPH_PIXELS: usize = 256 * 256;
+
+/// The outcome of a call to one of the functions of the C interface, declared in the generated header `binviz.h`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinvizStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer that may not be null was null.
+    NullPointer = 1,
+    /// A dimension of 0 was given.
+    InvalidDimension = 2,
+    /// The histogram contains no windows.
+    EmptyInput = 3,
+    /// A key or histogram with a different length than needed was given.
+    KeyLengthMismatch = 4,
+    /// The output buffer is smaller than [`BINVIZ_DIGRAPH_PIXELS`].
+    BufferTooSmall = 5,
+    /// The call panicked. Only a library built with the `ffi` profile reports this,
+    /// the `release` profile aborts the process on a panic instead.
+    Panic = 6,
+    /// The call failed for another reason.
+    Other = 7,
+}
+
+impl From<BinvizError> for BinvizStatus {
+    fn from(err: BinvizError) -> Self {
+        match err {
+            BinvizError::InvalidDimension { .. } => BinvizStatus::InvalidDimension,
+            BinvizError::EmptyInput => BinvizStatus::EmptyInput,
+            BinvizError::KeyLengthMismatch { .. } => BinvizStatus::KeyLengthMismatch,
+            _ => BinvizStatus::Other,
+        }
+    }
+}
+
+/// A histogram of the windows of a buffer, created by [`binviz_histogram_new`] and freed by
+/// [`binviz_histogram_free`].
+pub struct BinvizHistogram {
+    histogram: Histogram<u8>,
+    dimension: usize,
+}
+
+/// Run `call`, turning its error or panic into the status reported to the caller, so no panic unwinds into C.
+/// Only a build that unwinds on panics can catch them, like the `ffi` profile the shared library is built with,
+/// the `release` profile aborts instead.
+fn guard<F>(call: F) -> BinvizStatus
+where
+    F: FnOnce() -> Result<(), BinvizStatus>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(())) => BinvizStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => BinvizStatus::Panic,
+    }
+}
+
+/// The bytes of the `len` byte buffer `data`, which may be null when `len` is 0.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes unless `len` is 0.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], BinvizStatus> {
+    match (data.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(BinvizStatus::NullPointer),
+        (false, _) => Ok(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Calculate the histogram of the windows of `dimension` consecutive bytes of the `len` bytes at `data`,
+/// and store it in `out`.
+///
+/// # Panics
+///
+/// Never unwinds into C, see [`BinvizStatus::Panic`].
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes unless `len` is 0, and `out` must be a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn binviz_histogram_new(
+    data: *const u8,
+    len: usize,
+    dimension: u32,
+    out: *mut *mut BinvizHistogram,
+) -> BinvizStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(BinvizStatus::NullPointer);
+        }
+        let buf = bytes(data, len)?;
+        let dimension = dimension as usize;
+        validate_window(dimension, 1)?;
+        let histogram = BinvizHistogram {
+            histogram: calculate_histogram_from_bytes(buf, dimension, 1),
+            dimension,
+        };
+        *out = Box::into_raw(Box::new(histogram));
+        Ok(())
+    })
+}
+
+/// Calculate the entropy of `histogram` in bits per window, and store it in `out`.
+///
+/// # Panics
+///
+/// Never unwinds into C, see [`BinvizStatus::Panic`].
+///
+/// # Safety
+///
+/// `histogram` must come from [`binviz_histogram_new`] and not be freed, and `out` must be a valid pointer to
+/// write to.
+#[no_mangle]
+pub unsafe extern "C" fn binviz_histogram_entropy(
+    histogram: *const BinvizHistogram,
+    out: *mut f64,
+) -> BinvizStatus {
+    guard(|| {
+        let histogram = histogram.as_ref().ok_or(BinvizStatus::NullPointer)?;
+        if out.is_null() {
+            return Err(BinvizStatus::NullPointer);
+        }
+        *out = calculate_entropy_histogram(&histogram.histogram, EntropyUnit::Bits)?;
+        Ok(())
+    })
+}
+
+/// Look up how often the `key_len` byte window at `key` occurs in `histogram`, and store it in `out`.
+/// The length of the key must be the dimension of the histogram.
+///
+/// # Panics
+///
+/// Never unwinds into C, see [`BinvizStatus::Panic`].
+///
+/// # Safety
+///
+/// `histogram` must come from [`binviz_histogram_new`] and not be freed, `key` must point to `key_len` readable
+/// bytes, and `out` must be a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn binviz_histogram_get(
+    histogram: *const BinvizHistogram,
+    key: *const u8,
+    key_len: usize,
+    out: *mut usize,
+) -> BinvizStatus {
+    guard(|| {
+        let histogram = histogram.as_ref().ok_or(BinvizStatus::NullPointer)?;
+        if out.is_null() {
+            return Err(BinvizStatus::NullPointer);
+        }
+        let key = bytes(key, key_len)?;
+        if key.len() != histogram.dimension {
+            return Err(BinvizStatus::KeyLengthMismatch);
+        }
+        *out = histogram.histogram.get(key).copied().unwrap_or(0);
+        Ok(())
+    })
+}
+
+/// Free a histogram from [`binviz_histogram_new`]. Freeing null does nothing.
+///
+/// # Safety
+///
+/// `histogram` must be null or come from [`binviz_histogram_new`], and not be freed before.
+#[no_mangle]
+pub unsafe extern "C" fn binviz_histogram_free(histogram: *mut BinvizHistogram) {
+    if !histogram.is_null() {
+        // Dropping a histogram only frees memory, which doesn't panic.
+        drop(Box::from_raw(histogram));
+    }
+}
+
+/// Render the digraph image of the 2-dimensional `histogram` as [`BINVIZ_DIGRAPH_PIXELS`] 16-bit brightnesses
+/// into the `len` pixel buffer `out`, row by row with the first byte of a pair as the column, scaled linearly
+/// relative to the average count as by default in `binviz visualize`.
+///
+/// # Panics
+///
+/// Never unwinds into C, see [`BinvizStatus::Panic`].
+///
+/// # Safety
+///
+/// `histogram` must come from [`binviz_histogram_new`] and not be freed, and `out` must point to `len` writable
+/// pixels.
+#[no_mangle]
+pub unsafe extern "C" fn binviz_render_digraph(
+    histogram: *const BinvizHistogram,
+    out: *mut u16,
+    len: usize,
+) -> BinvizStatus {
+    guard(|| {
+        let histogram = histogram.as_ref().ok_or(BinvizStatus::NullPointer)?;
+        if out.is_null() {
+            return Err(BinvizStatus::NullPointer);
+        }
+        if len < BINVIZ_DIGRAPH_PIXELS {
+            return Err(BinvizStatus::BufferTooSmall);
+        }
+        let (image, _, _) = generate_image(
+            &histogram.histogram,
+            ScaleMode::Linear,
+            ScaleMode::Linear.default_normalization(),
+        )?;
+        ptr::copy_nonoverlapping(image.as_ptr(), out, BINVIZ_DIGRAPH_PIXELS);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The histogram of `data`, which must be created successfully.
+    fn histogram(data: &[u8], dimension: u32) -> *mut BinvizHistogram {
+        let mut histogram = ptr::null_mut();
+        let status =
+            unsafe { binviz_histogram_new(data.as_ptr(), data.len(), dimension, &mut histogram) };
+        assert_eq!(status, BinvizStatus::Ok);
+        assert!(!histogram.is_null());
+        histogram
+    }
+
+    #[test]
+    fn counts_and_entropy() {
+        let histogram = histogram(b"abab", 2);
+        let mut count = usize::MAX;
+        for (key, expected) in [(b"ab", 2), (b"ba", 1), (b"aa", 0)] {
+            let status = unsafe { binviz_histogram_get(histogram, key.as_ptr(), 2, &mut count) };
+            assert_eq!((status, count), (BinvizStatus::Ok, expected));
+        }
+        let status = unsafe { binviz_histogram_get(histogram, b"a".as_ptr(), 1, &mut count) };
+        assert_eq!(status, BinvizStatus::KeyLengthMismatch);
+        let mut entropy = 0.0;
+        let status = unsafe { binviz_histogram_entropy(histogram, &mut entropy) };
+        assert_eq!(status, BinvizStatus::Ok);
+        let expected = -(2.0 / 3.0 * (2.0f64 / 3.0).log2()) - 1.0 / 3.0 * (1.0f64 / 3.0).log2();
+        assert!((entropy - expected).abs() < 1e-12);
+        unsafe { binviz_histogram_free(histogram) };
+    }
+
+    #[test]
+    fn renders_digraph() {
+        let histogram = histogram(b"abab", 2);
+        let mut pixels = vec![0; BINVIZ_DIGRAPH_PIXELS];
+        let status =
+            unsafe { binviz_render_digraph(histogram, pixels.as_mut_ptr(), pixels.len() - 1) };
+        assert_eq!(status, BinvizStatus::BufferTooSmall);
+        let status = unsafe { binviz_render_digraph(histogram, pixels.as_mut_ptr(), pixels.len()) };
+        assert_eq!(status, BinvizStatus::Ok);
+        // The first byte of a pair is the column.
+        let pixel = |first: u8, second: u8| pixels[second as usize * 256 + first as usize];
+        assert!(pixel(b'a', b'b') > pixel(b'b', b'a'));
+        assert!(pixel(b'b', b'a') > 0);
+        assert_eq!(pixels.iter().filter(|&&pixel| pixel > 0).count(), 2);
+        unsafe { binviz_histogram_free(histogram) };
+    }
+
+    #[test]
+    fn reports_invalid_arguments() {
+        let mut histogram = ptr::null_mut();
+        let mut status = unsafe { binviz_histogram_new(b"ab".as_ptr(), 2, 0, &mut histogram) };
+        assert_eq!(status, BinvizStatus::InvalidDimension);
+        status = unsafe { binviz_histogram_new(ptr::null(), 2, 1, &mut histogram) };
+        assert_eq!(status, BinvizStatus::NullPointer);
+        status = unsafe { binviz_histogram_new(b"ab".as_ptr(), 2, 1, ptr::null_mut()) };
+        assert_eq!(status, BinvizStatus::NullPointer);
+        let mut entropy = 0.0;
+        status = unsafe { binviz_histogram_entropy(ptr::null(), &mut entropy) };
+        assert_eq!(status, BinvizStatus::NullPointer);
+        assert!(histogram.is_null());
+        unsafe { binviz_histogram_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn reports_empty_input() {
+        let histogram = histogram(&[], 1);
+        let mut entropy = 0.0;
+        let status = unsafe { binviz_histogram_entropy(histogram, &mut entropy) };
+        assert_eq!(status, BinvizStatus::EmptyInput);
+        unsafe { binviz_histogram_free(histogram) };
+    }
+
+    #[test]
+    fn catches_panics() {
+        assert_eq!(guard(|| panic!("unwinding into C")), BinvizStatus::Panic);
+    }
+}
@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+/// A set of bytes, parsed from comma separated hexadecimal bytes and inclusive ranges, e.g. `00,ff` or `20-7e`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSet([bool; 256]);
+
+impl ByteSet {
+    /// Whether `byte` is in the set.
+    pub fn contains(&self, byte: u8) -> bool {
+        self.0[byte as usize]
+    }
+}
+
+impl FromIterator<u8> for ByteSet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut set = [false; 256];
+        for byte in iter {
+            set[byte as usize] = true;
+        }
+        ByteSet(set)
+    }
+}
+
+impl FromStr for ByteSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = [false; 256];
+        for item in s.split(',') {
+            let (start, end) = match item.split_once('-') {
+                Some((start, end)) => (parse_byte(start)?, parse_byte(end)?),
+                None => (parse_byte(item)?, parse_byte(item)?),
+            };
+            if start > end {
+                return Err(format!(
+                    "the byte range `{}` ends before it starts",
+                    item.trim()
+                ));
+            }
+            set[start as usize..=end as usize].fill(true);
+        }
+        Ok(ByteSet(set))
+    }
+}
+
+/// Parse a single byte in hexadecimal, optionally `0x` prefixed.
+pub(crate) fn parse_byte(value: &str) -> Result<u8, String> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    let invalid = || format!("`{}` isn't a byte in hexadecimal, e.g. `00` or `ff`", value);
+    if hex.is_empty() || hex.len() > 2 {
+        return Err(invalid());
+    }
+    u8::from_str_radix(hex, 16).map_err(|_| invalid())
+}
+
+/// Which bytes are kept in the input, filtered bytes are dropped before the windows are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteFilter {
+    /// Drop the bytes in the set.
+    Ignore(ByteSet),
+    /// Drop the bytes that are not in the set.
+    Only(ByteSet),
+}
+
+impl ByteFilter {
+    /// Whether `byte` is kept.
+    pub fn keeps(&self, byte: u8) -> bool {
+        match self {
+            ByteFilter::Ignore(set) => !set.contains(byte),
+            ByteFilter::Only(set) => set.contains(byte),
+        }
+    }
+
+    /// Copy the kept bytes of `buf`.
+    pub fn apply(&self, buf: &[u8]) -> Vec<u8> {
+        buf.iter()
+            .copied()
+            .filter(|&byte| self.keeps(byte))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_and_ranges_are_parsed() {
+        let set: ByteSet = "00, 0x0a,20-7e,ff".parse().unwrap();
+        let expected: ByteSet = [0x00, 0x0a, 0xff].into_iter().chain(0x20..=0x7e).collect();
+        assert_eq!(set, expected);
+    }
+
+    #[test]
+    fn malformed_specs_are_errors() {
+        for spec in ["00,zz", "7e-20", "1-", "", "100", "00,,01", "0x"] {
+            assert!(spec.parse::<ByteSet>().is_err(), "{:?}", spec);
+        }
+        assert_eq!(
+            "7e-20".parse::<ByteSet>(),
+            Err("the byte range `7e-20` ends before it starts".to_string())
+        );
+        assert_eq!(
+            "00,zz".parse::<ByteSet>(),
+            Err("`zz` isn't a byte in hexadecimal, e.g. `00` or `ff`".to_string())
+        );
+    }
+}
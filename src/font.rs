@@ -0,0 +1,98 @@
+use image::{ImageBuffer, Pixel};
+
+/// The width of a glyph of the embedded font in pixels, before scaling.
+pub(crate) const GLYPH_WIDTH: u32 = 3;
+
+/// The height of a glyph of the embedded font in pixels, before scaling.
+pub(crate) const GLYPH_HEIGHT: u32 = 5;
+
+/// The glyph of a character in the embedded 3×5 font, as 5 rows of 3 bits from top to bottom with the
+/// leftmost pixel in the highest bit. Letters are drawn as capitals, unknown characters are blank.
+fn glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        _ => [0; 5],
+    }
+}
+
+/// The width of `text` drawn by [`draw_text`] at `scale`, with a column of spacing between characters.
+pub(crate) fn text_width(text: &str, scale: u32) -> u32 {
+    let len = text.chars().count() as u32;
+    (len * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale
+}
+
+/// Draw `text` in the embedded font with its top left corner at (`x`, `y`), every font pixel a `scale`×`scale`
+/// block of `color`. Pixels falling outside the image are skipped.
+pub(crate) fn draw_text<P>(
+    image: &mut ImageBuffer<P, Vec<P::Subpixel>>,
+    x: u32,
+    y: u32,
+    text: &str,
+    scale: u32,
+    color: P,
+) where
+    P: Pixel,
+{
+    for (i, character) in text.chars().enumerate() {
+        let left = x + i as u32 * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in glyph(character).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (left + column * scale + dx, y + row as u32 * scale + dy);
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
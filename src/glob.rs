@@ -0,0 +1,197 @@
+use std::{
+    fmt::{self, Display},
+    path::{Component, Path},
+    str::FromStr,
+};
+
+/// A part of a [`Glob`] between slashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `**`, any amount of path components, including none.
+    AnyComponents,
+    /// A single path component, `*` matching any characters and `?` any single character in it.
+    Component(Vec<char>),
+}
+
+/// A glob pattern matched against paths relative to a folder, like `*.bin` or `target/**`.
+///
+/// `*` matches any characters within a path component, `?` any single character and `**` as a whole
+/// component any amount of components. A pattern without a slash matches a name at any depth,
+/// like `**/` before it, a pattern with a slash matches the whole path from the folder on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    segments: Vec<Segment>,
+}
+
+impl Glob {
+    /// Whether the relative `path` matches the pattern.
+    pub fn matches<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        let components: Vec<Vec<char>> = path
+            .as_ref()
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().chars().collect()),
+                _ => None,
+            })
+            .collect();
+        match_segments(&self.segments, &components)
+    }
+
+    /// Whether the relative `path` or one of the folders leading up to it matches the pattern,
+    /// so a pattern matching a folder covers everything in it.
+    pub fn matches_within<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        path.as_ref()
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| self.matches(ancestor))
+    }
+}
+
+impl FromStr for Glob {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim_end_matches('/');
+        if trimmed.is_empty() {
+            return Err("the glob pattern is empty".to_string());
+        }
+        let (anchored, trimmed) = match trimmed.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (trimmed.contains('/'), trimmed),
+        };
+        let mut segments = Vec::new();
+        if !anchored {
+            segments.push(Segment::AnyComponents);
+        }
+        for component in trimmed.split('/').filter(|component| !component.is_empty()) {
+            if component == "**" {
+                segments.push(Segment::AnyComponents);
+            } else if component.contains("**") {
+                return Err(format!(
+                    "`**` has to be a whole path component, in the glob pattern `{}`",
+                    s
+                ));
+            } else {
+                segments.push(Segment::Component(component.chars().collect()));
+            }
+        }
+        Ok(Glob {
+            pattern: s.to_string(),
+            segments,
+        })
+    }
+}
+
+impl Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Whether the path `components` match the pattern `segments`.
+fn match_segments(segments: &[Segment], components: &[Vec<char>]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((Segment::AnyComponents, rest)) => {
+            match_segments(rest, components)
+                || (!components.is_empty() && match_segments(segments, &components[1..]))
+        }
+        Some((Segment::Component(pattern), rest)) => match components.split_first() {
+            Some((name, components)) => {
+                match_component(pattern, name) && match_segments(rest, components)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Whether the path component `name` matches `pattern`, with `*` and `?` wildcards.
+fn match_component(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` in the pattern and the name position it is tried to match up to.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` match one more character and try again.
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str) -> Glob {
+        pattern.parse().unwrap()
+    }
+
+    #[test]
+    fn name_patterns_match_at_any_depth() {
+        let bin = glob("*.bin");
+        assert!(bin.matches("a.bin"));
+        assert!(bin.matches("x/y/a.bin"));
+        assert!(!bin.matches("a.binx"));
+        assert!(!bin.matches("a.bin/x"));
+        assert!(glob("?.bin").matches("x/a.bin"));
+        assert!(!glob("?.bin").matches("ab.bin"));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_match_from_the_folder_on() {
+        let src = glob("src/*.bin");
+        assert!(src.matches("src/a.bin"));
+        assert!(!src.matches("x/src/a.bin"));
+        assert!(!src.matches("src/x/a.bin"));
+        assert!(glob("src/**/*.bin").matches("src/x/y/a.bin"));
+        assert!(glob("src/**/*.bin").matches("src/a.bin"));
+    }
+
+    #[test]
+    fn folder_patterns_cover_everything_in_the_folder() {
+        let any_components = glob("target/**");
+        assert!(any_components.matches("target"));
+        assert!(any_components.matches("target/x.bin"));
+        assert!(any_components.matches("target/a/b"));
+        assert!(!any_components.matches("src/target"));
+        assert!(!any_components.matches("targets"));
+
+        let trailing_slash = glob("target/");
+        assert!(trailing_slash.matches("target"));
+        assert!(trailing_slash.matches("src/target"));
+        assert!(!trailing_slash.matches("target/x.bin"));
+        assert!(trailing_slash.matches_within("target/x.bin"));
+        assert!(!trailing_slash.matches_within("target.bin"));
+        assert_eq!(trailing_slash.to_string(), "target/");
+    }
+
+    #[test]
+    fn malformed_patterns_are_errors() {
+        assert!("".parse::<Glob>().is_err());
+        assert!("/".parse::<Glob>().is_err());
+        assert!("a**b".parse::<Glob>().is_err());
+    }
+}
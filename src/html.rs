@@ -0,0 +1,244 @@
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use image::{DynamicImage, ImageOutputFormat};
+
+use crate::BinvizError;
+
+/// The characters of the base64 alphabet, indexed by the 6-bit values they encode.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The style sheet of the reports, embedded in every report so they stand on their own.
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+th { background: #eee; }
+td { font-family: monospace; }
+img { image-rendering: pixelated; border: 1px solid #ccc; max-width: 100%; }
+figure { display: inline-block; margin: 0 1em 1em 0; }";
+
+/// Encode `bytes` as base64 with padding, as in data URIs.
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        // A chunk of n bytes fills n + 1 characters, the rest is padding.
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escape the characters of `text` that have a meaning in HTML.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A page of the results of the full analysis of a file, everything embedded, see [`HtmlReport::finish`].
+pub(crate) struct HtmlReport {
+    title: String,
+    body: String,
+}
+
+impl HtmlReport {
+    /// An empty report about `file`.
+    pub(crate) fn new(file: &Path) -> Self {
+        HtmlReport {
+            title: format!("binviz analysis of {}", file.display()),
+            body: String::new(),
+        }
+    }
+
+    /// Start a section with the heading `heading`.
+    pub(crate) fn heading(&mut self, heading: &str) {
+        self.body
+            .push_str(&format!("<h2>{}</h2>\n", escape_html(heading)));
+    }
+
+    /// Add a paragraph of `text`.
+    pub(crate) fn paragraph(&mut self, text: &str) {
+        self.body
+            .push_str(&format!("<p>{}</p>\n", escape_html(text)));
+    }
+
+    /// Add a table with the cells of `header` and `rows`.
+    pub(crate) fn table<H, R>(&mut self, header: H, rows: R)
+    where
+        H: IntoIterator,
+        H::Item: AsRef<str>,
+        R: IntoIterator,
+        R::Item: IntoIterator,
+        <R::Item as IntoIterator>::Item: AsRef<str>,
+    {
+        self.body.push_str("<table>\n<thead>\n");
+        self.body.push_str(&table_row("th", header));
+        self.body.push_str("</thead>\n<tbody>\n");
+        for cells in rows {
+            self.body.push_str(&table_row("td", cells));
+        }
+        self.body.push_str("</tbody>\n</table>\n");
+    }
+
+    /// Add `image` as a PNG in a data URI, captioned `caption`. Failing to encode it is reported for `path`,
+    /// the report the image is for.
+    pub(crate) fn image<I>(
+        &mut self,
+        image: I,
+        caption: &str,
+        path: &Path,
+    ) -> Result<(), BinvizError>
+    where
+        I: Into<DynamicImage>,
+    {
+        let mut png = Cursor::new(Vec::new());
+        image
+            .into()
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .map_err(|err| BinvizError::image(path, err))?;
+        self.body.push_str(&format!(
+            "<figure><img src=\"data:image/png;base64,{0}\" alt=\"{1}\"><figcaption>{1}</figcaption></figure>\n",
+            base64(png.get_ref()),
+            escape_html(caption)
+        ));
+        Ok(())
+    }
+
+    /// The whole page, without references to other files.
+    pub(crate) fn finish(self) -> String {
+        html_page(&self.title, &self.body)
+    }
+}
+
+/// A row of a table, with `tag` the element of the cells, `th` or `td`.
+fn table_row<I>(tag: &str, cells: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let cells: String = cells
+        .into_iter()
+        .map(|cell| format!("<{0}>{1}</{0}>", tag, escape_html(cell.as_ref())))
+        .collect();
+    format!("<tr>{}</tr>\n", cells)
+}
+
+/// A page linking the reports of the analyzed files, for every file the path of its report relative to the page,
+/// or the error analyzing it.
+pub(crate) fn render_index(files: &[(PathBuf, Result<PathBuf, String>)]) -> String {
+    let items: String = files
+        .iter()
+        .map(|(file, report)| match report {
+            Ok(report) => format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                escape_html(&report.to_string_lossy().replace('\\', "/")),
+                escape_html(&file.display().to_string())
+            ),
+            Err(err) => format!(
+                "<li>{} failed: {}</li>\n",
+                escape_html(&file.display().to_string()),
+                escape_html(err)
+            ),
+        })
+        .collect();
+    html_page("binviz analysis", &format!("<ul>\n{}</ul>\n", items))
+}
+
+/// An HTML document titled `title` with the content `body`.
+fn html_page(title: &str, body: &str) -> String {
+    let title = escape_html(title);
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n{1}\n</style>\n</head>\n<body>\n<h1>{0}</h1>\n{2}</body>\n</html>\n",
+        title, STYLE, body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageBuffer, Luma};
+
+    use super::*;
+
+    #[test]
+    fn base64_pads_to_whole_groups() {
+        // The test vectors of RFC 4648.
+        for (bytes, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(bytes.as_bytes()), encoded);
+        }
+        assert_eq!(base64(&[0xfb, 0xff, 0xbf]), "+/+/");
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(
+            escape_html("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn report_embeds_tables_and_images() {
+        let path = Path::new("reports/<file>.bin");
+        let mut report = HtmlReport::new(path);
+        report.heading("Entropy");
+        report.table(["Dimension", "Entropy"], [["1", "7.5"], ["2", "14.25"]]);
+        let image: ImageBuffer<Luma<u16>, Vec<u16>> =
+            ImageBuffer::from_fn(2, 2, |x, y| Luma([(x + 2 * y) as u16 * 1000]));
+        report.image(image.clone(), "Byte pairs", path).unwrap();
+        let page = report.finish();
+        assert!(page.contains("<title>binviz analysis of reports/&lt;file&gt;.bin</title>"));
+        assert!(page.contains("<h2>Entropy</h2>"));
+        assert!(page.contains("<tr><th>Dimension</th><th>Entropy</th></tr>"));
+        assert!(page.contains("<tr><td>1</td><td>7.5</td></tr>\n<tr><td>2</td><td>14.25</td></tr>"));
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::from(image)
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        assert!(page.contains(&format!(
+            "<img src=\"data:image/png;base64,{}\" alt=\"Byte pairs\">",
+            base64(png.get_ref())
+        )));
+        // Nothing is loaded from elsewhere.
+        assert!(!page.contains("src=\"http"));
+        assert!(!page.contains("href="));
+    }
+
+    #[test]
+    fn index_links_reports_and_lists_failures() {
+        let index = render_index(&[
+            (PathBuf::from("a.bin"), Ok(PathBuf::from("a/report.html"))),
+            (PathBuf::from("b.bin"), Err("too small".to_string())),
+        ]);
+        assert!(index.contains("<li><a href=\"a/report.html\">a.bin</a></li>"));
+        assert!(index.contains("<li>b.bin failed: too small</li>"));
+    }
+}
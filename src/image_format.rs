@@ -0,0 +1,289 @@
+use std::{
+    fmt,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use image::DynamicImage;
+
+use crate::{create_output, BinvizError};
+
+/// The file format generated images are saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFormat {
+    /// Lossless and keeps 16-bit channels.
+    #[default]
+    Png,
+    /// Uncompressed, only 8-bit channels.
+    Bmp,
+    /// Lossless and keeps 16-bit channels.
+    Tiff,
+    /// Lossless and fast to encode, only 8-bit channels.
+    Qoi,
+}
+
+impl ImageFormat {
+    /// The format belonging to the extension of `path`, if it has a known one.
+    pub fn from_path<P>(path: P) -> Option<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let extension = path.as_ref().extension()?.to_str()?;
+        extension.to_ascii_lowercase().parse().ok()
+    }
+
+    /// The usual file extension of this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Tiff => "tiff",
+            ImageFormat::Qoi => "qoi",
+        }
+    }
+
+    /// Whether this format can store channels of 16 bits without truncating them.
+    pub fn supports_16_bit(self) -> bool {
+        matches!(self, ImageFormat::Png | ImageFormat::Tiff)
+    }
+}
+
+impl From<ImageFormat> for image::ImageFormat {
+    fn from(format: ImageFormat) -> Self {
+        match format {
+            ImageFormat::Png => image::ImageFormat::Png,
+            ImageFormat::Bmp => image::ImageFormat::Bmp,
+            ImageFormat::Tiff => image::ImageFormat::Tiff,
+            ImageFormat::Qoi => image::ImageFormat::Qoi,
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(ImageFormat::Png),
+            "bmp" => Ok(ImageFormat::Bmp),
+            "tiff" | "tif" => Ok(ImageFormat::Tiff),
+            "qoi" => Ok(ImageFormat::Qoi),
+            _ => Err(format!(
+                "unknown image format `{}`, expected `png`, `bmp`, `tiff` or `qoi`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+/// How many bits the channels of a saved image have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitDepth {
+    /// 8 bits, which every viewer shows correctly.
+    Eight,
+    /// 16 bits, the full precision of the generated images.
+    #[default]
+    Sixteen,
+}
+
+impl FromStr for BitDepth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "8" => Ok(BitDepth::Eight),
+            "16" => Ok(BitDepth::Sixteen),
+            _ => Err(format!("unknown bit depth `{}`, expected `8` or `16`", s)),
+        }
+    }
+}
+
+impl fmt::Display for BitDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitDepth::Eight => f.write_str("8"),
+            BitDepth::Sixteen => f.write_str("16"),
+        }
+    }
+}
+
+/// Save an image to `path` in the given `format` with channels of `depth` bits, whatever the extension of `path`.
+///
+/// The generated images are calculated with 16-bit channels, with a `depth` of 8 they are rounded to the
+/// nearest 8-bit value, so both depths show the same normalization. Images with 16-bit channels can only be saved
+/// in formats that keep them, other formats give [`BinvizError::UnsupportedBitDepth`] instead of silently
+/// truncating the channels. Grayscale images are saved as RGB in formats without grayscale, such as QOI.
+/// An existing file is only overwritten with `force`.
+pub fn save_image<I, P>(
+    image: I,
+    path: P,
+    format: ImageFormat,
+    depth: BitDepth,
+    force: bool,
+) -> Result<(), BinvizError>
+where
+    I: Into<DynamicImage>,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let image = match (depth, image.into()) {
+        (BitDepth::Eight, image @ DynamicImage::ImageLuma16(_)) => {
+            DynamicImage::ImageLuma8(image.into_luma8())
+        }
+        (BitDepth::Eight, image @ DynamicImage::ImageRgb16(_)) => {
+            DynamicImage::ImageRgb8(image.into_rgb8())
+        }
+        (BitDepth::Eight, image @ DynamicImage::ImageRgba16(_)) => {
+            DynamicImage::ImageRgba8(image.into_rgba8())
+        }
+        (_, image) => image,
+    };
+    let image = match (format, image) {
+        (ImageFormat::Qoi, image @ DynamicImage::ImageLuma8(_)) => {
+            DynamicImage::ImageRgb8(image.into_rgb8())
+        }
+        (_, image) => image,
+    };
+    let color = image.color();
+    let bits = color.bits_per_pixel() / color.channel_count() as u16;
+    if bits > 8 && !format.supports_16_bit() {
+        return Err(BinvizError::UnsupportedBitDepth { format, bits });
+    }
+    let mut writer = BufWriter::new(create_output(path, force)?);
+    image
+        .write_to(&mut writer, image::ImageFormat::from(format))
+        .map_err(|err| BinvizError::image(path, err))?;
+    writer.flush().map_err(|err| BinvizError::write(path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_histogram_from_bytes, generate_color_image, generate_image, test_support,
+        Normalization, ScaleMode,
+    };
+
+    /// Bytes with varied counts of pairs and triples, noise narrowed down to 32 values.
+    fn noise(len: usize) -> Vec<u8> {
+        test_support::noise(len)
+            .into_iter()
+            .map(|byte| byte >> 3)
+            .collect()
+    }
+
+    /// Save `image` with both depths as PNG and read them back.
+    fn both_depths(image: DynamicImage) -> (DynamicImage, DynamicImage) {
+        let dir = tempfile::tempdir().unwrap();
+        let eight = dir.path().join("8.png");
+        let sixteen = dir.path().join("16.png");
+        save_image(
+            image.clone(),
+            &eight,
+            ImageFormat::Png,
+            BitDepth::Eight,
+            false,
+        )
+        .unwrap();
+        save_image(image, &sixteen, ImageFormat::Png, BitDepth::Sixteen, false).unwrap();
+        (image::open(eight).unwrap(), image::open(sixteen).unwrap())
+    }
+
+    #[test]
+    fn eight_bit_digraph_is_the_sixteen_bit_one_shifted() {
+        let histogram = calculate_histogram_from_bytes(&noise(1 << 14), 2, 1);
+        let (image, _, _) =
+            generate_image(&histogram, ScaleMode::Sqrt, Normalization::Maximum).unwrap();
+        let (eight, sixteen) = both_depths(image.into());
+        let (eight, sixteen) = (eight.as_luma8().unwrap(), sixteen.as_luma16().unwrap());
+        for (low, high) in eight.pixels().zip(sixteen.pixels()) {
+            assert!((low[0] as i32 - (high[0] >> 8) as i32).abs() <= 1);
+        }
+        assert!(eight
+            .pixels()
+            .any(|pixel| pixel[0] > 0 && pixel[0] < u8::MAX));
+    }
+
+    #[test]
+    fn eight_bit_trigraph_is_the_sixteen_bit_one_shifted() {
+        let histogram = calculate_histogram_from_bytes(&noise(1 << 14), 3, 1);
+        let (image, _, _) =
+            generate_color_image(&histogram, ScaleMode::Log, Normalization::Maximum).unwrap();
+        let (eight, sixteen) = both_depths(image.into());
+        let (eight, sixteen) = (eight.as_rgb8().unwrap(), sixteen.as_rgb16().unwrap());
+        for (low, high) in eight.pixels().zip(sixteen.pixels()) {
+            for channel in 0..3 {
+                assert!((low[channel] as i32 - (high[channel] >> 8) as i32).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn sixteen_bits_need_a_format_that_keeps_them() {
+        let image = DynamicImage::ImageLuma16(image::ImageBuffer::new(4, 4));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.bmp");
+        assert!(matches!(
+            save_image(
+                image.clone(),
+                &path,
+                ImageFormat::Bmp,
+                BitDepth::Sixteen,
+                false
+            ),
+            Err(BinvizError::UnsupportedBitDepth { bits: 16, .. })
+        ));
+        save_image(image, &path, ImageFormat::Bmp, BitDepth::Eight, false).unwrap();
+    }
+
+    #[test]
+    fn pixels_survive_saving_in_every_format() {
+        let histogram = calculate_histogram_from_bytes(&noise(1 << 14), 2, 1);
+        let (gray, _, _) =
+            generate_image(&histogram, ScaleMode::Sqrt, Normalization::Maximum).unwrap();
+        let histogram = calculate_histogram_from_bytes(&noise(1 << 14), 3, 1);
+        let (color, _, _) =
+            generate_color_image(&histogram, ScaleMode::Log, Normalization::Maximum).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        for format in [
+            ImageFormat::Png,
+            ImageFormat::Bmp,
+            ImageFormat::Tiff,
+            ImageFormat::Qoi,
+        ] {
+            for image in [
+                DynamicImage::from(gray.clone()),
+                DynamicImage::from(color.clone()),
+            ] {
+                let path = dir.path().join(format!("image.{}", format.extension()));
+                let depth = if format.supports_16_bit() {
+                    BitDepth::Sixteen
+                } else {
+                    BitDepth::Eight
+                };
+                save_image(image.clone(), &path, format, depth, true).unwrap();
+                let loaded = image::open(&path).unwrap();
+                if format.supports_16_bit() {
+                    assert_eq!(loaded, image, "{}", format);
+                } else {
+                    // QOI has no grayscale, so compare the colors of the formats without 16 bits.
+                    assert_eq!(loaded.to_rgb8(), image.to_rgb8(), "{}", format);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bit_depth_parses() {
+        assert_eq!("8".parse::<BitDepth>(), Ok(BitDepth::Eight));
+        assert_eq!("16".parse::<BitDepth>(), Ok(BitDepth::Sixteen));
+        assert!("12".parse::<BitDepth>().is_err());
+    }
+}
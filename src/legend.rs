@@ -0,0 +1,151 @@
+use image::{ImageBuffer, Pixel};
+
+use crate::{
+    font::{draw_text, text_width, GLYPH_HEIGHT},
+    ScaleMode,
+};
+
+/// The brightnesses labeled on a colorbar, from 0 to full brightness.
+const COLORBAR_TICKS: [f64; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+/// The width of the bar of a colorbar, in pixels.
+const COLORBAR_WIDTH: u32 = 16;
+
+/// The size of a font pixel in the labels of a colorbar.
+const COLORBAR_SCALE: u32 = 2;
+
+/// The space around the bar and labels of a colorbar, in pixels.
+const COLORBAR_PADDING: u32 = 6;
+
+/// Format a count compactly for a label, e.g. `12`, `2.5`, `3.4K` or `1.2M`.
+pub fn format_count(count: f64) -> String {
+    let (value, suffix) = if count >= 1e9 {
+        (count / 1e9, "G")
+    } else if count >= 1e6 {
+        (count / 1e6, "M")
+    } else if count >= 1e3 {
+        (count / 1e3, "K")
+    } else {
+        (count, "")
+    };
+    let value = if value >= 100.0 {
+        format!("{:.0}", value)
+    } else {
+        let value = format!("{:.1}", value);
+        value
+            .strip_suffix(".0")
+            .map(str::to_string)
+            .unwrap_or(value)
+    };
+    format!("{}{}", value, suffix)
+}
+
+/// Add a vertical colorbar in a margin right of a generated image, with full brightness at the top.
+///
+/// The `color` of every brightness from 0 to 1 is drawn in the bar, labeled with the counts of 0%, 25%, 50%,
+/// 75% and full brightness according to `scale` with `full_count` at full brightness.
+/// The image itself is unchanged in the top left.
+pub fn with_colorbar<P, F>(
+    image: &ImageBuffer<P, Vec<u16>>,
+    scale: ScaleMode,
+    full_count: f64,
+    color: F,
+) -> ImageBuffer<P, Vec<u16>>
+where
+    P: Pixel<Subpixel = u16>,
+    F: Fn(f64) -> P,
+{
+    let labels =
+        COLORBAR_TICKS.map(|brightness| format_count(scale.count_at(brightness, full_count)));
+    let label_width = labels
+        .iter()
+        .map(|label| text_width(label, COLORBAR_SCALE))
+        .max()
+        .unwrap_or(0);
+    let bar_left = image.width() + COLORBAR_PADDING;
+    let label_left = bar_left + COLORBAR_WIDTH + COLORBAR_PADDING;
+    let width = label_left + label_width + COLORBAR_PADDING;
+    let label_height = GLYPH_HEIGHT * COLORBAR_SCALE;
+    // Leave room for half a label above the top tick and below the bottom tick.
+    let height = image.height().max(label_height * 4);
+    let (top, bottom) = (label_height, height - label_height);
+    // Fully opaque white, whatever the channels of the image.
+    let white = *P::from_slice(&[u16::MAX; 4][..P::CHANNEL_COUNT as usize]);
+    let mut output = ImageBuffer::new(width, height);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        output.put_pixel(x, y, *pixel);
+    }
+    for y in top..=bottom {
+        let brightness = (bottom - y) as f64 / (bottom - top) as f64;
+        let pixel = color(brightness);
+        for x in bar_left..bar_left + COLORBAR_WIDTH {
+            output.put_pixel(x, y, pixel);
+        }
+    }
+    for (brightness, label) in COLORBAR_TICKS.iter().zip(&labels) {
+        let y = bottom - (brightness * (bottom - top) as f64).round() as u32;
+        draw_text(
+            &mut output,
+            label_left,
+            y - label_height / 2,
+            label,
+            COLORBAR_SCALE,
+            white,
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, Luma};
+
+    use super::*;
+
+    #[test]
+    fn counts_are_formatted_compactly() {
+        assert_eq!(format_count(0.0), "0");
+        assert_eq!(format_count(12.0), "12");
+        assert_eq!(format_count(2.54), "2.5");
+        assert_eq!(format_count(3_400.0), "3.4K");
+        assert_eq!(format_count(123_456.0), "123K");
+        assert_eq!(format_count(1_200_000.0), "1.2M");
+        assert_eq!(format_count(5e9), "5G");
+    }
+
+    #[test]
+    fn colorbar_widens_the_image_and_leaves_it_unchanged() {
+        let image = ImageBuffer::from_fn(256, 256, |x, y| Luma([(x * 256 + y) as u16]));
+        let gray = |brightness: f64| Luma([(brightness * u16::MAX as f64) as u16]);
+        let output = with_colorbar(&image, ScaleMode::Linear, 1000.0, gray);
+        // The widest label of 0, 250, 500, 750 and 1K.
+        let label_width = text_width("250", COLORBAR_SCALE);
+        assert_eq!(
+            output.dimensions(),
+            (
+                256 + COLORBAR_PADDING
+                    + COLORBAR_WIDTH
+                    + COLORBAR_PADDING
+                    + label_width
+                    + COLORBAR_PADDING,
+                256
+            )
+        );
+        assert_eq!(output.view(0, 0, 256, 256).to_image(), image);
+        // Full brightness at the top of the bar and black at the bottom.
+        let bar = 256 + COLORBAR_PADDING;
+        let label_height = GLYPH_HEIGHT * COLORBAR_SCALE;
+        assert_eq!(*output.get_pixel(bar, label_height), Luma([u16::MAX]));
+        assert_eq!(*output.get_pixel(bar, 256 - label_height), Luma([0]));
+    }
+
+    #[test]
+    fn colorbar_of_a_small_image_is_higher_than_the_image() {
+        let image = ImageBuffer::from_pixel(16, 16, Luma([7u16]));
+        let output = with_colorbar(&image, ScaleMode::Log, 10.0, |_| Luma([1]));
+        assert_eq!(output.height(), GLYPH_HEIGHT * COLORBAR_SCALE * 4);
+        assert_eq!(output.view(0, 0, 16, 16).to_image(), image);
+        // Below the image the margin is black.
+        assert_eq!(*output.get_pixel(0, 16), Luma([0]));
+    }
+}
@@ -1,117 +1,1606 @@
+mod alphabet;
+#[cfg(feature = "images")]
+mod analysis;
+#[cfg(feature = "images")]
+mod animation;
+#[cfg(feature = "images")]
+mod axes;
+#[cfg(feature = "images")]
+mod background;
+mod baseline;
+mod blocks;
+mod charts;
+mod class;
+mod classify;
+#[cfg(feature = "images")]
+mod colormap;
+mod compare;
+mod complexity;
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "cli")]
+mod config;
+mod counts;
+mod diff;
+mod distance;
+mod divergence;
+mod error;
+mod estimator;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod filter;
+#[cfg(feature = "images")]
+mod font;
+mod glob;
+#[cfg(feature = "images")]
+mod html;
+#[cfg(feature = "images")]
+mod image_format;
+#[cfg(feature = "images")]
+mod legend;
+mod locate;
+#[cfg(feature = "images")]
+mod map;
+mod markov;
+mod metadata;
+mod nibbles;
+#[cfg(feature = "images")]
+mod offset;
+mod output;
+#[cfg(feature = "fft")]
+mod periodicity;
+#[cfg(feature = "images")]
+mod point_cloud;
+mod profile;
+mod progress;
+mod reference;
+mod report;
+mod runs;
+mod scale;
+mod scan;
+mod sha256;
+mod statistics;
+mod storage;
+#[cfg(feature = "images")]
+mod streaming;
+mod strings;
+mod summary;
+#[cfg(test)]
+pub(crate) mod test_support;
+#[cfg(feature = "cli")]
+mod tui;
+mod unit;
+#[cfg(feature = "images")]
+mod upscale;
+mod walk;
+#[cfg(feature = "cli")]
+mod watch;
+mod words;
+mod xor;
+
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "images")]
+use std::{collections::HashSet, fs, path::PathBuf, sync::atomic::AtomicUsize, time::Instant};
 use std::{
-    collections::BTreeMap,
-    fmt::Debug,
-    fs::{self, File},
-    io::Read,
-    path::{Path, PathBuf},
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind, Read, Seek, SeekFrom, Write},
+    mem,
+    path::Path,
 };
 
-use comfy_table::{presets::ASCII_MARKDOWN, Table};
+#[cfg(feature = "images")]
 use image::{ImageBuffer, Luma, Rgb};
+#[cfg(feature = "images")]
 use log::info;
+use log::warn;
+#[cfg(feature = "images")]
+use rayon::iter::IntoParallelRefIterator;
+#[cfg(feature = "parallel")]
+use rayon::{
+    iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
+
+#[cfg(feature = "images")]
+use html::render_index;
+
+#[cfg(feature = "tables")]
+pub use alphabet::display_alphabet;
+pub use alphabet::{format_byte_ranges, get_missing_bytes};
+#[cfg(feature = "images")]
+pub use analysis::{
+    analyze_file, analyze_file_with_progress, write_file_analysis, AnalysisImage, AnalysisOptions,
+    AnalysisOptionsBuilder, FileAnalysis,
+};
+#[cfg(feature = "images")]
+pub use animation::{generate_trigraph_slices, save_animation, TrigraphSlice};
+#[cfg(feature = "images")]
+pub use axes::{draw_grid, with_axes};
+#[cfg(feature = "images")]
+pub use background::{with_background, Background};
+#[cfg(feature = "tables")]
+pub use baseline::display_baseline_deviation;
+pub use baseline::{baseline_deviations, Deviation};
+#[cfg(feature = "tables")]
+pub use blocks::display_blocks;
+pub use blocks::{
+    block_stats, block_stats_from_reader, BlockFilter, BlockStats, BlockSummary, BlocksReport,
+};
+pub use charts::{entropy_chart_svg, frequency_chart_svg, CHART_BAR_COUNT};
+pub use class::{classify_byte, ByteClass};
+#[cfg(feature = "tables")]
+pub use classify::display_classification;
+pub use classify::{
+    builtin_fingerprints, classify, classify_histograms, ClassMatch, Fingerprint, Label,
+};
+#[cfg(feature = "images")]
+pub use colormap::{apply_colormap, parse_color, ColorScheme, Colormap};
+#[cfg(feature = "tables")]
+pub use compare::display_comparison;
+pub use compare::{
+    compare_files, compare_histograms, ComparisonMetrics, ComparisonReport, COMPARISON_DIMENSIONS,
+};
+#[cfg(feature = "images")]
+pub use compare::{generate_comparison_image, generate_diff_image, DIFF_LINEAR_THRESHOLD};
+#[cfg(feature = "tables")]
+pub use complexity::display_lz_complexity;
+pub use complexity::{
+    lz_complexity, lz_complexity_from_file, lz_complexity_from_reader, LzComplexity,
+};
+#[cfg(all(feature = "compression", feature = "tables"))]
+pub use compression::display_compression;
+#[cfg(feature = "compression")]
+pub use compression::{
+    compression_profile, compression_profile_from_reader, compression_ratio, compression_sizes,
+    Codec, Compressor,
+};
+#[cfg(feature = "cli")]
+pub use config::{load_config, user_config_path, Config, LOCAL_CONFIG_FILE};
+pub use counts::{write_counts, CountsFormat};
+#[cfg(feature = "tables")]
+pub use diff::display_histogram_diff;
+pub use diff::{diff_histograms, ratio_histograms};
+#[cfg(feature = "tables")]
+pub use distance::display_distances;
+pub use distance::{cosine_similarity, hellinger, jensen_shannon, total_variation};
+pub use divergence::{cross_entropy, kl_divergence, Smoothing};
+#[cfg(feature = "tables")]
+pub use divergence::{display_cross_entropy, display_kl_divergence};
+pub use error::BinvizError;
+pub use estimator::{estimate_entropy, EntropyEstimator};
+#[cfg(feature = "ffi")]
+pub use ffi::{
+    binviz_histogram_entropy, binviz_histogram_free, binviz_histogram_get, binviz_histogram_new,
+    binviz_render_digraph, BinvizHistogram, BinvizStatus, BINVIZ_DIGRAPH_PIXELS,
+};
+pub use filter::{ByteFilter, ByteSet};
+pub use glob::Glob;
+#[cfg(feature = "images")]
+pub use image_format::{save_image, BitDepth, ImageFormat};
+#[cfg(feature = "images")]
+pub use legend::{format_count, with_colorbar};
+#[cfg(feature = "tables")]
+pub use locate::display_ngram_offsets;
+pub use locate::{find_ngram_offsets, find_ngram_offsets_from_reader, parse_ngram};
+#[cfg(feature = "images")]
+pub use map::{
+    hilbert_d2xy, map_side, morton_d2xy, morton_xy2d, render_entropy_map, render_map, render_raw,
+    with_class_legend, MapLayout, MAX_MAP_SIDE, RAW_WIDTH,
+};
+#[cfg(feature = "tables")]
+pub use markov::display_stationary_distribution;
+pub use markov::{
+    sample_markov, stationary_distribution, transition_matrix, write_transition_matrix,
+    TransitionMatrix, STATIONARY_MAX_ITERATIONS, STATIONARY_TOLERANCE,
+};
+pub use metadata::{read_image_metadata, write_image_metadata, ImageMetadata, SourceFile};
+pub use nibbles::{calculate_nibble_histogram_from_bytes, split_nibbles};
+#[cfg(feature = "images")]
+pub use nibbles::{generate_nibble_image, scale_nibble_image, NIBBLE_SCALE};
+#[cfg(feature = "images")]
+pub use offset::{
+    generate_offset_byte_image, generate_offset_byte_image_from_bytes, OFFSET_BUCKETS,
+};
+pub use output::{OutputFormat, SortOrder};
+#[cfg(all(feature = "fft", feature = "tables"))]
+pub use periodicity::display_periods;
+#[cfg(feature = "fft")]
+pub use periodicity::{
+    detect_periodicity, periods_from_spectrum, power_spectrum, DEFAULT_MAX_PERIOD,
+    PERIODICITY_MAX_SEGMENTS, PERIODICITY_SEGMENT_SIZE, PERIOD_COUNT,
+};
+#[cfg(all(feature = "fft", feature = "images"))]
+pub use periodicity::{render_spectrum, SPECTRUM_HEIGHT, SPECTRUM_WIDTH};
+#[cfg(feature = "images")]
+pub use point_cloud::export_point_cloud;
+#[cfg(feature = "tables")]
+pub use profile::display_entropy_profile;
+#[cfg(feature = "images")]
+pub use profile::render_entropy_profile;
+pub use profile::{entropy_profile, entropy_profile_from_reader};
+pub use progress::{ProgressEvent, Stage};
+pub use reference::{builtin_reference, load_reference, BUILTIN_REFERENCES};
+pub use report::{EntropyEntry, EntropyReport, FrequencyEntry, FrequencyReport, ReportHeader};
+#[cfg(feature = "tables")]
+pub use runs::display_run_lengths;
+pub use runs::{
+    run_length_stats, run_length_stats_from_reader, RunLengthReport, RunLengths, RUN_THRESHOLD,
+};
+pub use scale::{count_percentile, Normalization, ScaleMode};
+#[cfg(feature = "tables")]
+pub use scan::display_scan;
+pub use scan::{
+    scan_file, verdict, ScanEntry, ScanSort, Verdict, COMPRESSED_ENTROPY_THRESHOLD,
+    TEXT_SHARE_THRESHOLD,
+};
+pub use statistics::{
+    chi_square, chi_square_homogeneity, monte_carlo_pi, monte_carlo_pi_from_bytes,
+    monte_carlo_pi_from_reader, serial_correlation, serial_correlation_from_bytes,
+    serial_correlation_from_reader, ByteStats, MonteCarloPi, SerialCorrelation,
+    CHI_SQUARE_DEGREES_OF_FREEDOM,
+};
+#[cfg(feature = "tables")]
+pub use statistics::{
+    display_byte_stats, display_chi_square, display_monte_carlo_pi, display_serial_correlation,
+};
+pub use storage::{histogram_serde, load_histogram, save_histogram, Format};
+#[cfg(feature = "images")]
+pub use streaming::generate_image_streaming;
+#[cfg(feature = "tables")]
+pub use strings::display_strings;
+pub use strings::{
+    extract_strings, extract_strings_from_reader, Charset, StringExtractor, MAX_STRING_LENGTH,
+};
+#[cfg(feature = "tables")]
+pub use summary::display_file_summaries;
+pub use summary::{FileStats, FileSummary};
+#[cfg(feature = "cli")]
+pub use tui::{
+    braille_digraph, frequency_bars, run_tui, visible_blocks, BAR_RESOLUTION, MAX_TUI_BLOCK_SIZE,
+    MIN_TUI_BLOCK_SIZE,
+};
+pub use unit::EntropyUnit;
+#[cfg(feature = "images")]
+pub use upscale::{upscale_nearest, validate_scale_factor, MAX_SCALE_FACTOR};
+pub use walk::{collect_files, CollectedFiles, WalkOptions, WalkedFile};
+#[cfg(feature = "cli")]
+pub use watch::{watch_files, WATCH_DEBOUNCE};
+#[cfg(feature = "tables")]
+pub use words::display_most_frequent_words;
+pub use words::{
+    calculate_word_histogram, calculate_word_histogram_from_bytes, decode_words,
+    high_byte_histogram, Endian,
+};
+#[cfg(feature = "tables")]
+pub use xor::display_xor_keys;
+pub use xor::{detect_xor_key, XOR_KEY_COUNT, XOR_PREVIEW_LENGTH};
+
+pub type Histogram<T> = BTreeMap<Vec<T>, usize>;
+
+/// A generated image, together with the total count of the histogram
+/// and the count that is shown at full brightness, higher counts are clamped to full brightness.
+#[cfg(feature = "images")]
+pub type Visualization<P> = (ImageBuffer<P, Vec<u16>>, usize, f64);
+
+/// The header and the rows of a table, before it is rendered.
+#[cfg(feature = "tables")]
+pub(crate) type TableCells = (Vec<String>, Vec<Vec<String>>);
+
+/// The amount of bytes read at once by [`calculate_histogram_from_reader`].
+pub const CHUNK_SIZE: usize = 1 << 20;
+
+/// The amount of bytes of a file [`calculate_histogram_parallel`] reads and splits over the threads at once.
+#[cfg(feature = "parallel")]
+pub const PARALLEL_BLOCK_SIZE: usize = 64 << 20;
+
+/// The amount of least frequent bytes listed in the output of [`full_analysis`].
+pub const LEAST_FREQUENT_COUNT: usize = 5;
+
+/// The amount of bytes with the longest runs listed in the output of [`full_analysis`].
+pub const RUN_LENGTH_COUNT: usize = 10;
+
+/// The percentile of the counts shown at full brightness in the image of [`full_analysis`],
+/// so images of different files are comparable.
+pub const FULL_ANALYSIS_PERCENTILE: f64 = 99.0;
+
+/// The size of the blocks in the entropy map of [`full_analysis`], every block is a pixel.
+pub const ENTROPY_MAP_BLOCK_SIZE: usize = 256;
+
+/// The amount of characters of the strings listed in the output of [`full_analysis`], like `strings`.
+pub const MIN_STRING_LENGTH: usize = 4;
 
-type Histogram<T> = BTreeMap<Vec<T>, usize>;
+/// The folder [`full_analysis`] writes its results to by default, in the current folder, see [`AnalysisOptions::output_dir`].
+pub const FULL_ANALYSIS_FOLDER: &str = "output";
+
+/// The name of the summary table of the files analyzed by [`full_analysis`] in the [`AnalysisOptions::output_dir`], as markdown.
+pub const SUMMARY_TEXT_FILE: &str = "summary.txt";
+
+/// The name of the summary table of the files analyzed by [`full_analysis`] in the [`AnalysisOptions::output_dir`], as CSV.
+pub const SUMMARY_CSV_FILE: &str = "summary.csv";
+
+/// The name of the HTML report in the folder of every file analyzed by [`full_analysis`] with [`AnalysisOptions::html`].
+pub const HTML_REPORT_FILE: &str = "report.html";
+
+/// The name of the page linking the HTML reports of [`full_analysis`], in the [`AnalysisOptions::output_dir`].
+pub const HTML_INDEX_FILE: &str = "index.html";
+
+/// The amount of most frequent bytes listed in the HTML report of [`full_analysis`] by default.
+pub const HTML_FREQUENCY_COUNT: usize = 32;
+
+/// The amount of bytes between progress reports of [`full_analysis_with_progress`].
+pub const PROGRESS_GRANULARITY: usize = 16 << 20;
 
 /// Calculate the n-dimensional histogram of (consecutive) bytes of a given file.
-pub fn calculate_histogram<P>(file: P, dimension: usize) -> Histogram<u8>
+///
+/// Consecutive windows start `stride` bytes apart: a stride of 1 counts every overlapping window,
+/// a stride equal to `dimension` counts non-overlapping chunks. Trailing bytes that don't fill a
+/// whole window are ignored. Note that the total count, and hence the entropy, depends on the stride.
+///
+/// The file is read in chunks of [`CHUNK_SIZE`] bytes, so the memory used is bounded by that and the histogram,
+/// whatever the size of the file.
+pub fn calculate_histogram<P>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+) -> Result<Histogram<u8>, BinvizError>
 where
-    P: AsRef<Path> + Debug,
+    P: AsRef<Path>,
 {
-    let mut histogram = BTreeMap::new();
-    let mut handle = File::open(&file).expect(&format!("Couldn't open file: {:?}", file));
-    let mut buf = Vec::new();
+    calculate_histogram_with_progress(file, dimension, stride, usize::MAX, |_| {})
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a given file,
+/// reporting the progress to `progress` after every `granularity` bytes and once more at the end.
+///
+/// The last [`ProgressEvent::Bytes`] event always covers the whole file.
+/// The file is read in chunks like [`calculate_histogram`], of which the result is this.
+pub fn calculate_histogram_with_progress<P, F>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+    granularity: usize,
+    progress: F,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+    F: FnMut(ProgressEvent),
+{
+    let mut histograms =
+        calculate_histograms_with_progress(file, &[dimension], stride, granularity, progress)?;
+    Ok(histograms.remove(0))
+}
+
+/// Calculate the histograms of the windows of each of the `dimensions` of a given file at once,
+/// reading it only once, in the order of the `dimensions`. The progress is reported like
+/// [`calculate_histogram_with_progress`] does.
+pub fn calculate_histograms_with_progress<P, F>(
+    file: P,
+    dimensions: &[usize],
+    stride: usize,
+    granularity: usize,
+    progress: F,
+) -> Result<Vec<Histogram<u8>>, BinvizError>
+where
+    P: AsRef<Path>,
+    F: FnMut(ProgressEvent),
+{
+    let file = file.as_ref();
+    for &dimension in dimensions {
+        validate_window(dimension, stride)?;
+    }
+    let largest = dimensions.iter().copied().max().unwrap_or(1);
+    let mut builders: Vec<HistogramBuilder> = dimensions
+        .iter()
+        .map(|&dimension| HistogramBuilder::with_stride(dimension, stride))
+        .collect();
+    read_file_with_progress(file, largest, granularity, progress, |chunk| {
+        for builder in &mut builders {
+            builder.update(chunk);
+        }
+    })?;
+    Ok(builders.into_iter().map(HistogramBuilder::finish).collect())
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a given file,
+/// reading it in chunks of [`CHUNK_SIZE`] bytes instead of loading it into memory at once.
+#[deprecated(note = "`calculate_histogram` reads the file in chunks itself, use that instead")]
+pub fn calculate_histogram_streaming<P>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    calculate_histogram(file, dimension, stride)
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of the `length` bytes
+/// starting at `offset` in a given file, or up to the end of the file if `length` is `None`.
+///
+/// Only the selected range is read, by seeking to `offset`. Windows never extend past the range.
+/// An `offset` past the end of the file is an error, a range extending past it is truncated with a warning.
+pub fn calculate_histogram_range<P>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+    offset: u64,
+    length: Option<u64>,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    validate_window(dimension, stride)?;
+    let file = file.as_ref();
+    let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let size = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    let length = resolve_range(size, offset, length)?;
+    validate_input_size(length, dimension)?;
     handle
-        .read_to_end(&mut buf)
-        .expect(&format!("Couldn't `read_to_end` on: {:?}", handle));
-    for byte in buf.windows(dimension) {
-        histogram
-            .entry(byte.to_vec())
-            .and_modify(|x| *x += 1)
-            .or_insert(1);
+        .seek(SeekFrom::Start(offset))
+        .map_err(|err| BinvizError::io(file, err))?;
+    calculate_histogram_from_reader(handle.take(length), dimension, stride)
+        .map_err(|err| BinvizError::io(file, err))
+}
+
+/// Resolve the length of the range of `length` bytes starting at `offset` in an input of `size` bytes,
+/// see [`calculate_histogram_range`].
+pub fn resolve_range(size: u64, offset: u64, length: Option<u64>) -> Result<u64, BinvizError> {
+    if offset > size {
+        return Err(BinvizError::OffsetOutOfRange { offset, size });
+    }
+    let available = size - offset;
+    match length {
+        Some(length) if length > available => {
+            warn!(
+                "range of `{}` bytes at offset `{:#x}` extends past the end of the input, truncating it to `{}` bytes",
+                length, offset, available
+            );
+            Ok(available)
+        }
+        Some(length) => Ok(length),
+        None => Ok(available),
     }
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a given file,
+/// by memory-mapping it read-only and counting the windows directly over the mapping.
+///
+/// Falls back to reading the file if it can't be mapped, e.g. because it is a pipe, from the handle already
+/// opened so nothing written to a pipe in between is lost. The result is identical to [`calculate_histogram`].
+#[cfg(feature = "mmap")]
+pub fn calculate_histogram_mmap<P>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    validate_window(dimension, stride)?;
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    // SAFETY: the mapping is only read, modifying the file while we read it can at worst
+    // produce a histogram of inconsistent data.
+    match unsafe { memmap2::Mmap::map(&handle) } {
+        Ok(map) => {
+            validate_input_size(map.len() as u64, dimension)?;
+            Ok(calculate_histogram_from_bytes(&map, dimension, stride))
+        }
+        Err(err) => {
+            warn!(
+                "couldn't memory-map `{}`, reading it instead: {}",
+                file.display(),
+                err
+            );
+            let mut buf = Vec::new();
+            (&handle)
+                .read_to_end(&mut buf)
+                .map_err(|err| BinvizError::io(file, err))?;
+            validate_input_size(buf.len() as u64, dimension)?;
+            Ok(calculate_histogram_from_bytes(&buf, dimension, stride))
+        }
+    }
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a byte slice.
+///
+/// # Panics
+///
+/// Panics if `dimension` or `stride` is 0, see [`validate_window`].
+pub fn calculate_histogram_from_bytes(
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+) -> Histogram<u8> {
+    let mut builder = HistogramBuilder::with_stride(dimension, stride);
+    builder.update(buf);
+    builder.finish()
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes read from `reader`,
+/// in chunks of [`CHUNK_SIZE`] bytes.
+///
+/// The result is identical to [`calculate_histogram_from_bytes`] on everything `reader` produces.
+pub fn calculate_histogram_from_reader<R>(
+    mut reader: R,
+    dimension: usize,
+    stride: usize,
+) -> io::Result<Histogram<u8>>
+where
+    R: Read,
+{
+    let mut builder = HistogramBuilder::with_stride(dimension, stride);
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => builder.update(&chunk[..read]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a given file, only keeping the bytes
+/// for which `predicate` returns `true`. The file is read in chunks, see [`calculate_histogram_from_reader_filtered`].
+pub fn calculate_histogram_filtered<P, F>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+    predicate: F,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+    F: Fn(u8) -> bool,
+{
+    validate_window(dimension, stride)?;
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let metadata = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?;
+    // The size of anything but a regular file, like a pipe, is only known once it is read.
+    if metadata.is_file() {
+        validate_input_size(metadata.len(), dimension)?;
+    }
+    let (histogram, processed) = count_reader_filtered(handle, dimension, stride, predicate)
+        .map_err(|err| BinvizError::io(file, err))?;
+    validate_input_size(processed, dimension)?;
+    Ok(histogram)
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a byte slice, only keeping the bytes
+/// for which `predicate` returns `true`.
+///
+/// The other bytes are dropped before windowing, so windows span the bytes around a dropped byte
+/// and the stride counts kept bytes.
+pub fn calculate_histogram_from_bytes_filtered<F>(
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+    predicate: F,
+) -> Histogram<u8>
+where
+    F: Fn(u8) -> bool,
+{
+    let kept: Vec<u8> = buf
+        .iter()
+        .copied()
+        .filter(|&byte| predicate(byte))
+        .collect();
+    calculate_histogram_from_bytes(&kept, dimension, stride)
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes read from `reader`, only keeping the bytes
+/// for which `predicate` returns `true`.
+///
+/// The result is identical to [`calculate_histogram_from_bytes_filtered`] on everything `reader` produces.
+pub fn calculate_histogram_from_reader_filtered<R, F>(
+    reader: R,
+    dimension: usize,
+    stride: usize,
+    predicate: F,
+) -> io::Result<Histogram<u8>>
+where
+    R: Read,
+    F: Fn(u8) -> bool,
+{
+    count_reader_filtered(reader, dimension, stride, predicate).map(|(histogram, _)| histogram)
+}
+
+/// Count the kept windows read from `reader` like [`calculate_histogram_from_reader_filtered`],
+/// together with the amount of bytes read, filtered or not.
+fn count_reader_filtered<R, F>(
+    reader: R,
+    dimension: usize,
+    stride: usize,
+    predicate: F,
+) -> io::Result<(Histogram<u8>, u64)>
+where
+    R: Read,
+    F: Fn(u8) -> bool,
+{
+    let mut builder = HistogramBuilder::with_stride(dimension, stride);
+    let mut kept = Vec::with_capacity(CHUNK_SIZE);
+    let mut processed = 0;
+    read_chunks(reader, |chunk| {
+        processed += chunk.len() as u64;
+        kept.clear();
+        kept.extend(chunk.iter().copied().filter(|&byte| predicate(byte)));
+        builder.update(&kept);
+    })?;
+    Ok((builder.finish(), processed))
+}
+
+/// Read a given file of at least `min_size` bytes in chunks, passing every chunk to `f` and reporting
+/// the progress like [`calculate_histogram_with_progress`] does.
+pub(crate) fn read_file_with_progress<F, G>(
+    file: &Path,
+    min_size: usize,
+    granularity: usize,
+    mut progress: F,
+    mut f: G,
+) -> Result<(), BinvizError>
+where
+    F: FnMut(ProgressEvent),
+    G: FnMut(&[u8]),
+{
+    let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let metadata = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?;
+    // The size of anything but a regular file, like a pipe, is only known once it is read.
+    let total = metadata.len();
+    if metadata.is_file() {
+        validate_input_size(total, min_size)?;
+    }
+    let granularity = granularity.max(1);
+    let mut chunk = vec![0; CHUNK_SIZE.min(granularity)];
+    let (mut processed, mut unreported) = (0, 0);
+    loop {
+        let want = chunk.len().min(granularity - unreported);
+        let read = match handle.read(&mut chunk[..want]) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(BinvizError::io(file, err)),
+        };
+        f(&chunk[..read]);
+        processed += read as u64;
+        unreported += read;
+        if unreported == granularity {
+            progress(ProgressEvent::Bytes {
+                processed,
+                total: total.max(processed),
+            });
+            unreported = 0;
+        }
+    }
+    validate_input_size(processed, min_size)?;
+    if unreported > 0 {
+        progress(ProgressEvent::Bytes {
+            processed,
+            total: processed,
+        });
+    }
+    Ok(())
+}
+
+/// Read everything `reader` produces in chunks of [`CHUNK_SIZE`] bytes, passing every chunk to `f`.
+pub fn read_chunks<R, F>(mut reader: R, mut f: F) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(&[u8]),
+{
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(read) => f(&chunk[..read]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a given file,
+/// splitting the work over `threads` threads (or all available cores when `threads` is 0).
+///
+/// The file is read in blocks of [`PARALLEL_BLOCK_SIZE`] bytes, so the memory used stays bounded like that
+/// of [`calculate_histogram`]. Every thread counts the windows starting in its own part of a block
+/// into a partial histogram, looking `dimension - 1` bytes into the next part, after which the partial histograms
+/// are merged. The result is identical to [`calculate_histogram`].
+#[cfg(feature = "parallel")]
+pub fn calculate_histogram_parallel<P>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+    threads: usize,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    calculate_histogram_parallel_with_progress(file, dimension, stride, threads, 0, |_| {})
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a given file in parallel like
+/// [`calculate_histogram_parallel`], reporting the progress to `progress` after every thread counted
+/// another `granularity` bytes, from whichever thread did, and once more at the end.
+///
+/// The last [`ProgressEvent::Bytes`] event always covers the whole file.
+#[cfg(feature = "parallel")]
+pub fn calculate_histogram_parallel_with_progress<P, F>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+    threads: usize,
+    granularity: usize,
+    progress: F,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+    F: Fn(ProgressEvent) + Sync,
+{
+    validate_window(dimension, stride)?;
+    let pool = thread_pool(threads)?;
+    count_file_parallel(
+        file.as_ref(),
+        dimension,
+        stride,
+        &pool,
+        PARALLEL_BLOCK_SIZE,
+        granularity,
+        &progress,
+    )
+}
+
+/// Count every `stride`-th window of `dimension` bytes in `file` on the threads of `pool`, reading it
+/// in blocks of `block_size` bytes, see [`calculate_histogram_parallel_with_progress`].
+#[cfg(feature = "parallel")]
+fn count_file_parallel<F>(
+    file: &Path,
+    dimension: usize,
+    stride: usize,
+    pool: &ThreadPool,
+    block_size: usize,
+    granularity: usize,
+    progress: &F,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    F: Fn(ProgressEvent) + Sync,
+{
+    let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let metadata = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?;
+    let total = metadata.len();
+    if metadata.is_file() {
+        validate_input_size(total, dimension)?;
+    }
+    let counted = AtomicU64::new(0);
+    let mut histogram = BTreeMap::new();
+    // The bytes kept from the previous block, in which no window has been counted from `skip` on.
+    let (mut buf, mut skip) = (Vec::new(), 0);
+    let mut processed = 0;
+    loop {
+        let read = (&mut handle)
+            .take(block_size as u64)
+            .read_to_end(&mut buf)
+            .map_err(|err| BinvizError::io(file, err))?;
+        if read == 0 {
+            break;
+        }
+        processed += read as u64;
+        counted.store(processed - read as u64, Ordering::Relaxed);
+        let windows = (buf.len() + 1).saturating_sub(dimension);
+        let next = if windows > skip {
+            let block = count_parallel(
+                pool,
+                &buf[skip..],
+                dimension,
+                stride,
+                granularity,
+                &counted,
+                total.max(processed),
+                progress,
+            );
+            merge_into(&mut histogram, block);
+            skip + (windows - skip).div_ceil(stride) * stride
+        } else {
+            skip
+        };
+        // Like `HistogramBuilder`, only keep the bytes that windows still to be counted start in.
+        let consumed = next.min(buf.len());
+        buf.drain(..consumed);
+        skip = next - consumed;
+    }
+    validate_input_size(processed, dimension)?;
+    progress(ProgressEvent::Bytes {
+        processed,
+        total: processed,
+    });
+    Ok(histogram)
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) bytes of a byte slice in parallel like
+/// [`calculate_histogram_parallel_with_progress`], so several histograms can be calculated from a single read.
+///
+/// The result is identical to [`calculate_histogram_from_bytes`].
+#[cfg(feature = "parallel")]
+pub fn calculate_histogram_from_bytes_parallel_with_progress<F>(
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+    threads: usize,
+    granularity: usize,
+    progress: F,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    F: Fn(ProgressEvent) + Sync,
+{
+    validate_window(dimension, stride)?;
+    let pool = thread_pool(threads)?;
+    let total = buf.len() as u64;
+    let histogram = count_parallel(
+        &pool,
+        buf,
+        dimension,
+        stride,
+        granularity,
+        &AtomicU64::new(0),
+        total,
+        &progress,
+    );
+    progress(ProgressEvent::Bytes {
+        processed: total,
+        total,
+    });
+    Ok(histogram)
+}
+
+/// A pool of `threads` threads, or of as many as there are cores when `threads` is 0.
+#[cfg(feature = "parallel")]
+fn thread_pool(threads: usize) -> Result<ThreadPool, BinvizError> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(BinvizError::ThreadPool)
+}
+
+/// Count every `stride`-th window of `dimension` bytes in `buf` on the threads of `pool`, every thread counting
+/// the windows starting in its own part of `buf` into a partial histogram and the partial histograms merged.
+///
+/// Every thread adds the bytes it counted to `processed` after every `granularity` bytes, unless that is 0,
+/// and reports the sum, up to `total`, to `progress`.
+#[cfg(feature = "parallel")]
+#[allow(clippy::too_many_arguments)]
+fn count_parallel<F>(
+    pool: &ThreadPool,
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+    granularity: usize,
+    processed: &AtomicU64,
+    total: u64,
+    progress: &F,
+) -> Histogram<u8>
+where
+    F: Fn(ProgressEvent) + Sync,
+{
+    let windows = (buf.len() + 1).saturating_sub(dimension);
+    // Every part has to start on a multiple of `stride`, to count the same windows as a single pass.
+    let part = windows
+        .div_ceil(pool.current_num_threads())
+        .div_ceil(stride)
+        .max(1)
+        * stride;
+    pool.install(|| {
+        (0..windows)
+            .into_par_iter()
+            .step_by(part)
+            .map(|start| {
+                let end = (start + part).min(windows);
+                let mut builder = HistogramBuilder::with_stride(dimension, stride);
+                let part = &buf[start..end + dimension - 1];
+                if granularity == 0 {
+                    builder.update(part);
+                } else {
+                    for chunk in part.chunks(granularity) {
+                        builder.update(chunk);
+                        // The bytes shared with the next part are counted twice, so stay below the total.
+                        let counted = processed.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                        progress(ProgressEvent::Bytes {
+                            processed: (counted + chunk.len() as u64).min(total),
+                            total,
+                        });
+                    }
+                }
+                builder.finish()
+            })
+            .reduce(BTreeMap::new, |mut left, right| {
+                merge_into(&mut left, right);
+                left
+            })
+    })
+}
+
+/// Calculate the combined n-dimensional histogram of all given files.
+///
+/// The files are read one at a time and merged into the result as we go,
+/// windows spanning the boundary between two files are not counted.
+pub fn calculate_histogram_many<P>(
+    files: &[P],
+    dimension: usize,
+    stride: usize,
+) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let mut histogram = BTreeMap::new();
+    for file in files {
+        let other = calculate_histogram(file, dimension, stride)?;
+        merge_into(&mut histogram, other);
+    }
+    Ok(histogram)
+}
+
+/// Add all counts of `other` to `histogram`, the keys of both histograms must have the same length.
+pub fn merge_histograms(
+    histogram: &mut Histogram<u8>,
+    other: &Histogram<u8>,
+) -> Result<(), BinvizError> {
+    check_key_lengths(histogram, other)?;
+    for (key, freq) in other {
+        *histogram.entry(key.clone()).or_insert(0) += freq;
+    }
+    Ok(())
+}
+
+/// Check that windows of `dimension` bytes starting `stride` bytes apart can be counted,
+/// both have to be at least 1.
+pub fn validate_window(dimension: usize, stride: usize) -> Result<(), BinvizError> {
+    if dimension == 0 {
+        return Err(BinvizError::InvalidDimension { dimension });
+    }
+    if stride == 0 {
+        return Err(BinvizError::InvalidStride { stride });
+    }
+    Ok(())
+}
+
+/// Check that writing to `path` doesn't overwrite an existing file or folder, unless `force` allows it.
+pub fn validate_output<P>(path: P, force: bool) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    if !force && path.exists() {
+        return Err(BinvizError::OutputExists {
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+/// Create the output file `path` to write to, failing with [`BinvizError::OutputExists`] if it already exists,
+/// unless `force` allows overwriting it.
+///
+/// The check and the creation are a single step, so a file appearing after [`validate_output`] isn't overwritten.
+pub fn create_output<P>(path: P, force: bool) -> Result<File, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let mut options = OpenOptions::new();
+    if force {
+        options.write(true).create(true).truncate(true);
+    } else {
+        options.write(true).create_new(true);
+    }
+    options.open(path).map_err(|err| {
+        if err.kind() == ErrorKind::AlreadyExists {
+            BinvizError::OutputExists {
+                path: path.to_path_buf(),
+            }
+        } else {
+            BinvizError::write(path, err)
+        }
+    })
+}
+
+/// Write `contents` to the output file `path`, see [`create_output`].
+pub fn write_output<P, C>(path: P, contents: C, force: bool) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+    C: AsRef<[u8]>,
+{
+    let path = path.as_ref();
+    create_output(path, force)?
+        .write_all(contents.as_ref())
+        .map_err(|err| BinvizError::write(path, err))
+}
+
+/// Check that an input of `size` bytes fills at least one window of `dimension` bytes.
+pub fn validate_input_size(size: u64, dimension: usize) -> Result<(), BinvizError> {
+    if size == 0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    if size < dimension as u64 {
+        return Err(BinvizError::FileTooSmallForDimension { size, dimension });
+    }
+    Ok(())
+}
+
+/// Check that the keys of the histogram are `dimension` bytes long, an empty histogram matches any dimension.
+pub fn validate_dimension(histogram: &Histogram<u8>, dimension: usize) -> Result<(), BinvizError> {
+    match histogram.keys().next() {
+        Some(key) if key.len() != dimension => Err(BinvizError::KeyLengthMismatch {
+            expected: dimension,
+            found: key.len(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check that the keys of both histograms have the same length, empty histograms match anything.
+pub(crate) fn check_key_lengths(
+    left: &Histogram<u8>,
+    right: &Histogram<u8>,
+) -> Result<(), BinvizError> {
+    match (left.keys().next(), right.keys().next()) {
+        (Some(left), Some(right)) if left.len() != right.len() => {
+            Err(BinvizError::KeyLengthMismatch {
+                expected: left.len(),
+                found: right.len(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Add all counts of `other` to `histogram`.
+fn merge_into(histogram: &mut Histogram<u8>, other: Histogram<u8>) {
+    for (key, freq) in other {
+        *histogram.entry(key).or_insert(0) += freq;
+    }
+}
+
+/// Incrementally calculates the n-dimensional histogram of (consecutive) bytes,
+/// for data that arrives in pieces.
+///
+/// The last `dimension - 1` bytes of every update are kept around,
+/// so windows straddling two updates are still counted and the result is identical to
+/// [`calculate_histogram_from_bytes`] on all the data concatenated.
+#[derive(Debug, Clone)]
+pub struct HistogramBuilder {
+    histogram: Histogram<u8>,
+    dimension: usize,
+    stride: usize,
+    buf: Vec<u8>,
+    /// The amount of bytes at the start of `buf` before the next window that should be counted.
+    skip: usize,
+    /// The counts of single bytes or byte pairs, indexed like [`dense_to_histogram`], once an update was large
+    /// enough to count them densely. They are only added to the histogram when finishing.
+    dense: Vec<usize>,
+}
+
+impl HistogramBuilder {
+    /// Start a histogram counting every overlapping window of `dimension` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension` is 0.
+    pub fn new(dimension: usize) -> Self {
+        Self::with_stride(dimension, 1)
+    }
+
+    /// Start a histogram counting windows of `dimension` bytes that start `stride` bytes apart.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimension` or `stride` is 0, check them with [`validate_window`] first.
+    pub fn with_stride(dimension: usize, stride: usize) -> Self {
+        if let Err(err) = validate_window(dimension, stride) {
+            panic!("{}", err);
+        }
+        HistogramBuilder {
+            histogram: BTreeMap::new(),
+            dimension,
+            stride,
+            buf: Vec::new(),
+            skip: 0,
+            dense: Vec::new(),
+        }
+    }
+
+    /// Count all windows that are completed by `data`.
+    ///
+    /// Only the windows straddling the bytes kept from the previous update are counted from a copy,
+    /// the rest of `data` is counted in place.
+    pub fn update(&mut self, mut data: &[u8]) {
+        if !self.buf.is_empty() {
+            let kept = self.buf.len();
+            let head = data.len().min(self.dimension.saturating_sub(1));
+            let mut buf = mem::take(&mut self.buf);
+            buf.extend_from_slice(&data[..head]);
+            let consumed = self.count(&buf);
+            if consumed < kept {
+                // `data` is too short to complete all windows, so it is entirely part of `buf`.
+                buf.drain(..consumed);
+                self.buf = buf;
+                return;
+            }
+            data = &data[consumed - kept..];
+        }
+        let consumed = self.count(data);
+        self.buf.extend_from_slice(&data[consumed..]);
+    }
+
+    /// Count the windows in `buf` starting from `skip`, returning how many bytes of `buf`
+    /// no window that still has to be counted starts in.
+    fn count(&mut self, buf: &[u8]) -> usize {
+        if buf.len() > self.skip {
+            count_windows(
+                &mut self.histogram,
+                &mut self.dense,
+                &buf[self.skip..],
+                self.dimension,
+                self.stride,
+            );
+        }
+        let windows = (buf.len() + 1).saturating_sub(self.dimension);
+        let next = if windows > self.skip {
+            self.skip + (windows - self.skip).div_ceil(self.stride) * self.stride
+        } else {
+            self.skip
+        };
+        let consumed = next.min(buf.len());
+        self.skip = next - consumed;
+        consumed
+    }
+
+    /// Finish counting and return the histogram, an incomplete trailing window is ignored.
+    pub fn finish(mut self) -> Histogram<u8> {
+        add_dense_counts(&mut self.histogram, &self.dense, self.dimension);
+        self.histogram
+    }
+}
+
+/// Count every `stride`-th window of `dimension` consecutive bytes in `buf` into the histogram.
+///
+/// Single bytes and byte pairs are counted into the `dense` counts instead, which is a lot faster than looking up
+/// every window in the histogram, once `buf` is large enough for that to pay off or they already are in use.
+/// Other windows are counted by the slices of `buf` they are, so only a new key of the histogram allocates.
+fn count_windows(
+    histogram: &mut Histogram<u8>,
+    dense: &mut Vec<usize>,
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+) {
+    match dimension {
+        1 if !dense.is_empty() || buf.len() >= 1 << 8 => {
+            dense.resize(1 << 8, 0);
+            add_byte_counts(dense, buf, stride);
+        }
+        2 if !dense.is_empty() || buf.len() >= 1 << 16 => {
+            dense.resize(1 << 16, 0);
+            add_byte_pair_counts(dense, buf, stride);
+        }
+        _ => {
+            let mut counts: HashMap<&[u8], usize> = HashMap::new();
+            for window in buf.windows(dimension).step_by(stride) {
+                *counts.entry(window).or_insert(0) += 1;
+            }
+            for (window, freq) in counts {
+                match histogram.get_mut(window) {
+                    Some(count) => *count += freq,
+                    None => {
+                        histogram.insert(window.to_vec(), freq);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Count the occurrences of every `stride`-th byte in `buf`, indexed by the byte value.
+pub fn count_bytes(buf: &[u8], stride: usize) -> [usize; 256] {
+    let mut counts = [0; 256];
+    add_byte_counts(&mut counts, buf, stride);
+    counts
+}
+
+/// Add the occurrences of every `stride`-th byte in `buf` to `counts`, see [`count_bytes`].
+fn add_byte_counts(counts: &mut [usize], buf: &[u8], stride: usize) {
+    for &byte in buf.iter().step_by(stride) {
+        counts[byte as usize] += 1;
+    }
+}
+
+/// Count the occurrences of every `stride`-th pair of consecutive bytes in `buf`,
+/// indexed by `(first << 8) | second`.
+pub fn count_byte_pairs(buf: &[u8], stride: usize) -> Box<[usize; 1 << 16]> {
+    let mut counts: Box<[usize; 1 << 16]> = vec![0; 1 << 16]
+        .into_boxed_slice()
+        .try_into()
+        .expect("the vector has exactly 65536 elements");
+    add_byte_pair_counts(&mut counts[..], buf, stride);
+    counts
+}
+
+/// Add the occurrences of every `stride`-th pair of consecutive bytes in `buf` to `counts`,
+/// see [`count_byte_pairs`].
+fn add_byte_pair_counts(counts: &mut [usize], buf: &[u8], stride: usize) {
+    for pair in buf.windows(2).step_by(stride) {
+        counts[((pair[0] as usize) << 8) | pair[1] as usize] += 1;
+    }
+}
+
+/// Convert dense counts into a histogram, given the dense counts is indexed
+/// by the big-endian value of `dimension` bytes.
+pub fn dense_to_histogram(counts: &[usize], dimension: usize) -> Histogram<u8> {
+    let mut histogram = BTreeMap::new();
+    add_dense_counts(&mut histogram, counts, dimension);
     histogram
 }
 
+/// Add the nonzero dense counts to the histogram, see [`dense_to_histogram`].
+fn add_dense_counts(histogram: &mut Histogram<u8>, counts: &[usize], dimension: usize) {
+    for (index, &freq) in counts.iter().enumerate() {
+        if freq > 0 {
+            let key = index.to_be_bytes()[mem::size_of::<usize>() - dimension..].to_vec();
+            *histogram.entry(key).or_insert(0) += freq;
+        }
+    }
+}
+
+/// Calculate the (negated) contribution `p log p` of a symbol with probability `p` to the entropy,
+/// with the logarithm in the base of `unit`.
+///
+/// Follows the convention `0 log 0 = 0`, so symbols that never occur contribute exactly 0 instead of `NaN`.
 #[inline(always)]
-pub fn calculate_entropy(probability: f64) -> f64 {
-    probability.log2() * probability
+pub fn calculate_entropy(probability: f64, unit: EntropyUnit) -> f64 {
+    if probability > 0.0 {
+        unit.log(probability) * probability
+    } else {
+        0.0
+    }
 }
 
-/// Calculate the entropy from a given n-dimensional histogram.
-pub fn calculate_entropy_histogram(histogram: &Histogram<u8>) -> f64 {
+/// Calculate the entropy from a given n-dimensional histogram, in the given `unit`.
+///
+/// An empty histogram, or one whose counts are all 0, has no entropy, it is an [`BinvizError::EmptyInput`] error.
+pub fn calculate_entropy_histogram<T>(
+    histogram: &Histogram<T>,
+    unit: EntropyUnit,
+) -> Result<f64, BinvizError> {
     let total: usize = histogram.values().sum();
+    if total == 0 {
+        return Err(BinvizError::EmptyInput);
+    }
     let entropy = histogram
-        .iter()
-        .map(|(_, freq)| {
+        .values()
+        .map(|freq| {
             let probability = (*freq as f64) / (total as f64);
-            calculate_entropy(probability)
+            calculate_entropy(probability, unit)
         })
         .sum::<f64>();
-    -entropy
+    // Subtract from 0 instead of negating, so a single symbol has 0 entropy instead of -0.
+    Ok(0.0 - entropy)
 }
 
-pub fn get_most_frequent_bytes(histogram: &Histogram<u8>) -> Vec<(&Vec<u8>, &usize)> {
-    let mut vector: Vec<(&Vec<u8>, &usize)> = histogram.into_iter().collect();
-    vector.sort_by(|x, y| y.1.cmp(x.1));
+/// Calculate the min-entropy from a given n-dimensional histogram, `-log2` of the probability of the most frequent key.
+///
+/// This is the Rényi entropy of order infinity, a lower bound of the Shannon entropy that measures how hard
+/// the most likely key is to guess. An empty histogram is an [`BinvizError::EmptyInput`] error.
+pub fn calculate_min_entropy<T>(histogram: &Histogram<T>) -> Result<f64, BinvizError> {
+    let total: usize = histogram.values().sum();
+    let max = histogram.values().max().copied().unwrap_or(0);
+    if total == 0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    Ok(0.0 - (max as f64 / total as f64).log2())
+}
+
+/// Calculate the Rényi entropy of order `alpha` from a given n-dimensional histogram.
+///
+/// Order 1 is the Shannon entropy of [`calculate_entropy_histogram`], order infinity the min-entropy of
+/// [`calculate_min_entropy`] and order 0 the logarithm of the amount of distinct keys. Higher orders weigh
+/// frequent keys more. A negative or `NaN` order is an [`BinvizError::InvalidRenyiOrder`] error.
+pub fn calculate_renyi_entropy<T>(
+    histogram: &Histogram<T>,
+    alpha: f64,
+) -> Result<f64, BinvizError> {
+    if alpha.is_nan() || alpha < 0.0 {
+        return Err(BinvizError::InvalidRenyiOrder { alpha });
+    }
+    if alpha == 1.0 {
+        return calculate_entropy_histogram(histogram, EntropyUnit::Bits);
+    }
+    if alpha.is_infinite() {
+        return calculate_min_entropy(histogram);
+    }
+    let total: usize = histogram.values().sum();
+    if total == 0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let sum = histogram
+        .values()
+        .filter(|&&freq| freq > 0)
+        .map(|&freq| (freq as f64 / total as f64).powf(alpha))
+        .sum::<f64>();
+    // Adding 0 turns the -0 of a single key into 0.
+    Ok(sum.log2() / (1.0 - alpha) + 0.0)
+}
+
+/// The name of the Rényi entropy of order `alpha`, for table headers.
+pub fn renyi_entropy_name(alpha: f64) -> String {
+    if alpha.is_infinite() {
+        "Min-Entropy".to_string()
+    } else {
+        format!("Rényi Entropy (α = {})", alpha)
+    }
+}
+
+/// Sort the keys of a histogram with their counts in the given `order`, see [`SortOrder`].
+pub fn get_most_frequent_bytes<T>(
+    histogram: &Histogram<T>,
+    order: SortOrder,
+) -> Vec<(&Vec<T>, &usize)>
+where
+    T: Ord,
+{
+    let mut vector: Vec<(&Vec<T>, &usize)> = histogram.iter().collect();
+    vector.sort_by(|x, y| order.compare((x.0, *x.1), (y.0, *y.1)));
     vector
 }
 
-pub fn display_entropies<P>(file: P, count: usize) -> String
+/// Display the n-dimensional entropies of a given file, for n in 1..=count, as a table in the given `unit`.
+///
+/// The entropy rate H_n - H_{n-1} is the entropy of a byte given the n - 1 bytes before it,
+/// where it plateaus adding more context no longer predicts the next byte any better.
+/// Every order in `renyi` adds a column with the Rényi entropy of that order, see [`calculate_renyi_entropy`].
+/// The entropies are those of the given `estimator`, for an estimator other than [`EntropyEstimator::Plugin`]
+/// a column with the plug-in entropy is added, to see how much the estimates differ for higher dimensions.
+#[cfg(feature = "tables")]
+pub fn display_entropies<P>(
+    file: P,
+    count: usize,
+    stride: usize,
+    renyi: &[f64],
+    unit: EntropyUnit,
+    estimator: EntropyEstimator,
+    format: OutputFormat,
+) -> Result<String, BinvizError>
 where
-    P: AsRef<Path> + Debug,
+    P: AsRef<Path>,
 {
-    let mut table = Table::new();
-    table.load_preset(ASCII_MARKDOWN);
-    table.set_header(["Dimension", "Entropy", "Relative Entropy"]);
-    for i in 1..=count {
-        let histogram = calculate_histogram(&file, i);
-        let entropy = calculate_entropy_histogram(&histogram);
-        let rel_entropy = entropy / (8.0f64 * (i as f64));
-        table.add_row([
+    let (header, rows) = entropy_table(file, count, stride, renyi, unit, estimator, format)?;
+    Ok(format.render(header, rows))
+}
+
+/// The header and rows of the table of [`display_entropies`], with the numbers formatted for `format`.
+#[cfg(feature = "tables")]
+pub(crate) fn entropy_table<P>(
+    file: P,
+    count: usize,
+    stride: usize,
+    renyi: &[f64],
+    unit: EntropyUnit,
+    estimator: EntropyEstimator,
+    format: OutputFormat,
+) -> Result<TableCells, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let mut header = vec![
+        "Dimension".to_string(),
+        format!("Entropy ({})", unit),
+        "Relative Entropy".to_string(),
+        format!("Entropy Rate ({})", unit),
+    ];
+    if estimator != EntropyEstimator::Plugin {
+        header.push(format!("Plug-in Entropy ({})", unit));
+    }
+    header.extend(renyi.iter().map(|&alpha| renyi_entropy_name(alpha)));
+    if count == 0 {
+        return Err(BinvizError::InvalidDimension { dimension: count });
+    }
+    // Read the file once, counting the windows of all dimensions at once.
+    let dimensions: Vec<usize> = (1..=count).collect();
+    let histograms =
+        calculate_histograms_with_progress(file, &dimensions, stride, usize::MAX, |_| {})?;
+    let mut rows = Vec::with_capacity(count);
+    // The entropy of the previous dimension, for the entropy rate H_n - H_{n-1}.
+    let mut previous_entropy = 0.0;
+    for (i, histogram) in (1..=count).zip(histograms) {
+        let entropy = estimate_entropy(&histogram, estimator, unit)?;
+        let rel_entropy = entropy / unit.from_bits(8.0 * i as f64);
+        // Clamp rounding errors, which would show up as -0.00000 when the rate is 0.
+        let entropy_rate = (entropy - previous_entropy).max(0.0);
+        previous_entropy = entropy;
+        let per_window = format!("{} per {} byte(s)", unit, i);
+        let mut row = vec![
             format!("{}", i),
-            format!("{:.5} (bits per {} byte(s))", entropy, i),
-            format!("{:.5}", rel_entropy),
-        ]);
+            format.value(entropy, Some(&per_window)),
+            format.value(rel_entropy, None),
+            format.value(entropy_rate, Some(&format!("{} per byte", unit))),
+        ];
+        if estimator != EntropyEstimator::Plugin {
+            let plugin_entropy = calculate_entropy_histogram(&histogram, unit)?;
+            row.push(format.value(plugin_entropy, Some(&per_window)));
+        }
+        for &alpha in renyi {
+            let renyi_entropy = unit.from_bits(calculate_renyi_entropy(&histogram, alpha)?);
+            row.push(format.value(renyi_entropy, Some(&per_window)));
+        }
+        rows.push(row);
     }
-    table.to_string()
+    Ok((header, rows))
 }
 
-pub fn display_most_frequent(histogram: &Histogram<u8>) -> String {
-    debug_assert!(histogram.into_iter().all(|x| x.0.len() == 1));
+/// Display the keys of an n-dimensional histogram from most to least frequent, as a table in the given `format`.
+///
+/// Every key is shown as its bytes in decimal and hexadecimal, separated by spaces, as ASCII text
+/// with non-printable bytes escaped, and as the [`ByteClass`] of its bytes, under the plural column names
+/// `Bytes` and `Classes` for keys of more than one byte. The rows are sorted in the given `order`,
+/// with `top` only the first that many rows are shown, in a markdown table followed by a line summarizing
+/// the remaining keys.
+#[cfg(feature = "tables")]
+pub fn display_most_frequent(
+    histogram: &Histogram<u8>,
+    top: Option<usize>,
+    order: SortOrder,
+    format: OutputFormat,
+) -> String {
     let total: usize = histogram.values().sum();
-    let most_freq = get_most_frequent_bytes(histogram);
-    let mut table = Table::new();
-    table.load_preset(ASCII_MARKDOWN);
-    table.set_header(["Rank", "Byte", "Hex", "Text", "Relative Frequency"]);
-    for (i, (byte, freq)) in most_freq.into_iter().enumerate() {
-        let probability = (*freq as f64) / (total as f64);
-        table.add_row([
-            format!("{}", i),
-            format!("{}", byte[0]),
-            format!("{:#x}", byte[0]),
-            format!("{:?}", byte[0] as char),
-            format!("{:.5}", probability),
-        ]);
+    let ((header, rows), remainder) = frequency_table(histogram, top, order, format);
+    with_remainder(format.render(header, rows), remainder, total, format)
+}
+
+/// The header and rows of the table of [`display_most_frequent`], with the numbers formatted for `format`,
+/// and the amount and combined count of the keys left out by `top`, if any.
+#[cfg(feature = "tables")]
+pub(crate) fn frequency_table(
+    histogram: &Histogram<u8>,
+    top: Option<usize>,
+    order: SortOrder,
+    format: OutputFormat,
+) -> (TableCells, Option<(usize, usize)>) {
+    let total: usize = histogram.values().sum();
+    let (bytes, classes) = match histogram.keys().next().map_or(1, Vec::len) {
+        1 => ("Byte", "Class"),
+        _ => ("Bytes", "Classes"),
+    };
+    let mut most_freq = get_most_frequent_bytes(histogram, order);
+    let remainder = truncate_most_frequent(&mut most_freq, top);
+    let rows = most_freq
+        .into_iter()
+        .enumerate()
+        .map(|(i, (key, freq))| {
+            let probability = (*freq as f64) / (total as f64);
+            let mut row = vec![format!("{}", i)];
+            row.extend(key_cells(key));
+            row.push(format.value(probability, None));
+            row
+        })
+        .collect();
+    let header = ["Rank", bytes, "Hex", "Text", classes, "Relative Frequency"]
+        .map(String::from)
+        .to_vec();
+    ((header, rows), remainder)
+}
+
+/// The Byte, Hex, Text and Class cells of a key in the table of [`display_most_frequent`].
+#[cfg(feature = "tables")]
+pub(crate) fn key_cells(key: &[u8]) -> [String; 4] {
+    [
+        join_formatted(key, |byte| format!("{}", byte)),
+        join_formatted(key, |byte| format!("{:#04x}", byte)),
+        format!("'{}'", key.escape_ascii()),
+        join_formatted(key, |byte| classify_byte(*byte).to_string()),
+    ]
+}
+
+/// Append the summary of the keys removed by [`truncate_most_frequent`] to a markdown table.
+#[cfg(feature = "tables")]
+pub(crate) fn with_remainder(
+    table: String,
+    remainder: Option<(usize, usize)>,
+    total: usize,
+    format: OutputFormat,
+) -> String {
+    match remainder {
+        Some(remainder) if format == OutputFormat::Markdown => {
+            format!("{}\n{}", table, display_remainder(remainder, total))
+        }
+        _ => table,
+    }
+}
+
+/// Format every element of the key, separated by spaces.
+#[cfg(feature = "tables")]
+pub(crate) fn join_formatted<T, F>(key: &[T], f: F) -> String
+where
+    F: Fn(&T) -> String,
+{
+    key.iter().map(f).collect::<Vec<_>>().join(" ")
+}
+
+/// Truncate the keys sorted by [`get_most_frequent_bytes`] to the first `top`,
+/// returning the amount of removed keys and their combined count, if any were removed.
+pub(crate) fn truncate_most_frequent<T>(
+    most_freq: &mut Vec<(&Vec<T>, &usize)>,
+    top: Option<usize>,
+) -> Option<(usize, usize)> {
+    let top = top.filter(|&top| top < most_freq.len())?;
+    let removed = most_freq.split_off(top);
+    Some((removed.len(), removed.iter().map(|(_, freq)| **freq).sum()))
+}
+
+/// Summarize the keys removed by [`truncate_most_frequent`], out of `total` counted windows.
+#[cfg(feature = "tables")]
+pub(crate) fn display_remainder((count, freq): (usize, usize), total: usize) -> String {
+    format!(
+        "… and {} more distinct values covering {:.1}% of windows.",
+        count,
+        freq as f64 / total as f64 * 100.0
+    )
+}
+
+/// Convert a brightness, where 1 is full brightness, to the value of a 16-bit channel.
+/// Brightnesses above 1 are clamped to full brightness, and negative or `NaN` brightnesses to black.
+#[cfg(feature = "images")]
+pub(crate) fn to_channel(brightness: f64) -> u16 {
+    if brightness.is_nan() {
+        return 0;
     }
-    table.to_string()
+    (brightness.clamp(0.0, 1.0) * (u16::MAX as f64)) as u16
 }
 
+/// Generate the digraph image of a histogram, every pair of bytes is a pixel with a brightness set by its count
+/// according to `scale`, relative to the count at full brightness chosen by `normalization`.
+#[cfg(feature = "images")]
 pub fn generate_image(
     dihistogram: &Histogram<u8>,
-) -> (ImageBuffer<Luma<u16>, Vec<u16>>, usize, f64) {
-    debug_assert!(dihistogram.into_iter().all(|x| x.0.len() == 2));
-    let mut image = ImageBuffer::new(256, 256);
-    let len = dihistogram.values().len();
+    scale: ScaleMode,
+    normalization: Normalization,
+) -> Result<Visualization<Luma<u16>>, BinvizError> {
+    validate_dimension(dihistogram, 2)?;
+    if dihistogram.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
     let total: usize = dihistogram.values().sum();
-    let avg_total = (total as f64) / (len as f64);
-    for slice in dihistogram.keys() {
-        if let Some(freq) = dihistogram.get(slice) {
-            let brightness = (*freq as f64) / avg_total * (u16::MAX as f64);
-            let pixel = Luma([brightness as u16]);
-            image.put_pixel(slice[0] as u32, slice[1] as u32, pixel);
-        }
+    let full_count = normalization.full_count(dihistogram);
+    Ok((
+        render_digraph(dihistogram, scale, full_count),
+        total,
+        full_count,
+    ))
+}
+
+/// Draw the digraph image of a histogram with `full_count` at full brightness, see [`generate_image`].
+#[cfg(feature = "images")]
+pub(crate) fn render_digraph(
+    dihistogram: &Histogram<u8>,
+    scale: ScaleMode,
+    full_count: f64,
+) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    let mut image = ImageBuffer::new(256, 256);
+    for (slice, freq) in dihistogram {
+        let brightness = scale.brightness(*freq, full_count);
+        let pixel = Luma([to_channel(brightness)]);
+        image.put_pixel(slice[0] as u32, slice[1] as u32, pixel);
     }
-    (image, total, avg_total)
+    image
 }
 
 // [u8; 3] -> usize
@@ -120,92 +1609,1484 @@ pub fn generate_image(
 // slice[2] right now: red component
 // value right now: blue component
 // A pixel just existing adds full green component, for easier distinction vs not existent pixels.
+// The blue component is scaled according to `scale` and `normalization`.
+#[cfg(feature = "images")]
 pub fn generate_color_image(
     trihistogram: &Histogram<u8>,
-) -> (ImageBuffer<Rgb<u16>, Vec<u16>>, usize, f64) {
-    debug_assert!(trihistogram.into_iter().all(|x| x.0.len() == 3));
+    scale: ScaleMode,
+    normalization: Normalization,
+) -> Result<Visualization<Rgb<u16>>, BinvizError> {
+    validate_dimension(trihistogram, 3)?;
+    if trihistogram.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
     let mut image = ImageBuffer::new(256, 256);
-    let len = trihistogram.values().len();
     let total: usize = trihistogram.values().sum();
-    let avg_total = (total as f64) / (len as f64);
-    for slice in trihistogram.keys() {
-        if let Some(freq) = trihistogram.get(slice) {
-            // dividing by avg_total makes it so we actually see something, by the pixel overflows if *freq* is more the the average value.
-            // by len takes it into account properly?????
-            let brightness_2 = (*freq as f64) * (u16::MAX as f64) / (avg_total as f64);
-            let brightness_1 = (slice[2] as f64) * (u16::MAX as f64) / (u8::MAX as f64);
-            let pixel = Rgb([brightness_1 as u16, 0, brightness_2 as u16]);
-            image.put_pixel(slice[0] as u32, slice[1] as u32, pixel);
-        }
+    let full_count = normalization.full_count(trihistogram);
+    for (slice, freq) in trihistogram {
+        // dividing by avg_total makes it so we actually see something, by the pixel overflows if *freq* is more the the average value.
+        // by len takes it into account properly?????
+        let brightness_2 = scale.brightness(*freq, full_count);
+        let brightness_1 = (slice[2] as f64) / (u8::MAX as f64);
+        let pixel = Rgb([to_channel(brightness_1), 0, to_channel(brightness_2)]);
+        image.put_pixel(slice[0] as u32, slice[1] as u32, pixel);
     }
-    (image, total, avg_total)
+    Ok((image, total, full_count))
 }
 
+#[cfg(feature = "images")]
 pub fn generate_color_image_quartic(
     trihistogram: &Histogram<u8>,
-) -> (ImageBuffer<Rgb<u16>, Vec<u16>>, usize, f64) {
-    debug_assert!(trihistogram.into_iter().all(|x| x.0.len() == 4));
+) -> Result<Visualization<Rgb<u16>>, BinvizError> {
+    validate_dimension(trihistogram, 4)?;
+    if trihistogram.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
     let mut image = ImageBuffer::new(256, 256);
     let len = trihistogram.values().len();
     let total: usize = trihistogram.values().sum();
     let avg_total = (total as f64) / (len as f64);
-    for slice in trihistogram.keys() {
-        if let Some(freq) = trihistogram.get(slice) {
-            let brightness_1 = (slice[2] as f64) * (u16::MAX as f64) / (u8::MAX as f64);
-            let brightness_2 = (slice[3] as f64) * (u16::MAX as f64) / (avg_total as f64);
-            let brightness_3 = (*freq as f64) * (u16::MAX as f64) / (avg_total as f64);
-            let pixel = Rgb([
-                brightness_1 as u16,
-                brightness_2 as u16,
-                brightness_3 as u16,
-            ]);
-            image.put_pixel(slice[0] as u32, slice[1] as u32, pixel);
-        }
-    }
-    (image, total, avg_total)
-}
-
-/// Perform a full analysis on all the files provided.
-pub fn full_analysis(files: Vec<PathBuf>) {
-    for file in &files {
-        // Create a folder for each file to store the analysis results.
-        let folder_name = file
-            .file_stem()
-            .expect("The file has no filename")
-            .to_str()
-            .expect("The path is not valid Unicode");
-        let output_folder = Path::new("output").join(folder_name);
-
-        if !output_folder.exists() {
-            fs::create_dir_all(&output_folder)
-                .expect(&format!("Couldn't `create_dir_all` on {:?}", output_folder));
-        }
-
-        // Perform the Ent subcommand.
-        let entropy_output = display_entropies(&file, 3);
-        fs::write(output_folder.join("entropy.txt"), entropy_output)
-            .expect("Couldn't write into 'entropy.txt'");
-
-        // Perform the Fre subcommand.
-        let histogram = calculate_histogram(&file, 1);
-        let most_frequent_output = display_most_frequent(&histogram);
-        fs::write(
-            output_folder.join("most_frequent.txt"),
-            most_frequent_output,
+    for (slice, freq) in trihistogram {
+        let brightness_1 = (slice[2] as f64) / (u8::MAX as f64);
+        let brightness_2 = (slice[3] as f64) / avg_total;
+        let brightness_3 = (*freq as f64) / avg_total;
+        let pixel = Rgb([
+            to_channel(brightness_1),
+            to_channel(brightness_2),
+            to_channel(brightness_3),
+        ]);
+        image.put_pixel(slice[0] as u32, slice[1] as u32, pixel);
+    }
+    Ok((image, total, avg_total))
+}
+
+/// Perform a full analysis on all the files provided and the files in the folders provided,
+/// found as `walk` says, on `jobs` files at once (or as many as there are cores when `jobs` is 0),
+/// producing what `options` says. Existing results are only overwritten with `force`.
+#[cfg(feature = "images")]
+pub fn full_analysis(
+    files: Vec<PathBuf>,
+    walk: WalkOptions,
+    jobs: usize,
+    force: bool,
+    options: AnalysisOptions,
+) -> Result<Vec<FileSummary>, BinvizError> {
+    full_analysis_with_progress(files, walk, jobs, force, options, |_| {})
+}
+
+/// Perform a full analysis on all the files provided and the files in the folders provided, reporting
+/// every [`Stage`] that starts, the bytes counted for the frequencies and the visualization and the files done
+/// to `progress`, from the threads analyzing the files.
+///
+/// The results of every file go in a folder named after it in the [`AnalysisOptions::output_dir`], the folders of the files found
+/// in a folder are nested like the folders they were found in, under a folder named after that folder.
+/// Files with the same name up to their extension get folders named after their whole file name instead.
+/// A file whose folder already exists fails with [`BinvizError::OutputExists`], unless `force` allows
+/// overwriting the results in it.
+///
+/// A file failing doesn't stop the analysis of the others. Once all files are done, a summary of every file
+/// is returned in the order they were found, with its statistics or why it failed. The error is only
+/// for failing to find the files or to start the analysis, or to write the index of the HTML reports.
+#[cfg(feature = "images")]
+pub fn full_analysis_with_progress<F>(
+    files: Vec<PathBuf>,
+    walk: WalkOptions,
+    jobs: usize,
+    force: bool,
+    options: AnalysisOptions,
+    progress: F,
+) -> Result<Vec<FileSummary>, BinvizError>
+where
+    F: Fn(ProgressEvent) + Sync,
+{
+    let collected = collect_files(&files, &walk)?;
+    let total = collected.files.len();
+    // The folders are chosen up front, so files analyzed at the same time never share one.
+    let output_dir = &options.output_dir;
+    let output_folders = output_folders(&collected.files, output_dir)?;
+    let index_path = output_dir.join(HTML_INDEX_FILE);
+    if options.html {
+        validate_output(&index_path, force)?;
+    }
+    let pool = thread_pool(jobs)?;
+    let done = AtomicUsize::new(0);
+    progress(ProgressEvent::Files { analyzed: 0, total });
+    let summaries: Vec<FileSummary> = pool.install(|| {
+        collected
+            .files
+            .par_iter()
+            .zip(&output_folders)
+            .map(|(walked, output_folder)| {
+                let start = Instant::now();
+                let outcome = validate_output(output_folder, force).and_then(|_| {
+                    analyze_into_folder(&walked.path, output_folder, &options, force, &progress)
+                });
+                let analyzed = done.fetch_add(1, Ordering::Relaxed) + 1;
+                progress(ProgressEvent::Files { analyzed, total });
+                FileSummary {
+                    path: walked.path.clone(),
+                    duration: start.elapsed(),
+                    outcome,
+                }
+            })
+            .collect()
+    });
+    if options.html {
+        let reports: Vec<(PathBuf, Result<PathBuf, String>)> = summaries
+            .iter()
+            .zip(&output_folders)
+            .map(|(summary, output_folder)| {
+                let report = match &summary.outcome {
+                    // The index is in the output folder too, so the reports are linked relative to it.
+                    Ok(_) => Ok(output_folder
+                        .strip_prefix(output_dir)
+                        .unwrap_or(output_folder)
+                        .join(HTML_REPORT_FILE)),
+                    Err(err) => Err(err.to_string()),
+                };
+                (summary.path.clone(), report)
+            })
+            .collect();
+        fs::create_dir_all(output_dir).map_err(|err| BinvizError::write(output_dir, err))?;
+        write_output(&index_path, render_index(&reports), force)?;
+    }
+    let failed = summaries
+        .iter()
+        .filter(|summary| summary.outcome.is_err())
+        .count();
+    info!(
+        "`{}` files matched, `{}` files and folders skipped by the include and exclude patterns, `{}` files analyzed, `{}` failed.",
+        total,
+        collected.filtered,
+        total - failed,
+        failed
+    );
+    Ok(summaries)
+}
+
+/// The folders in `output_dir` the results of the `files` go in, see [`full_analysis_with_progress`].
+#[cfg(feature = "images")]
+fn output_folders(files: &[WalkedFile], output_dir: &Path) -> Result<Vec<PathBuf>, BinvizError> {
+    let mut taken = HashSet::new();
+    let mut folders = Vec::with_capacity(files.len());
+    for walked in files {
+        let file = &walked.path;
+        let (Some(stem), Some(name)) = (walked.relative.file_stem(), walked.relative.file_name())
+        else {
+            return Err(BinvizError::io(
+                file,
+                io::Error::new(ErrorKind::InvalidInput, "the path has no file name"),
+            ));
+        };
+        let mut parent = output_dir.to_path_buf();
+        if let Some(root_name) = walked.root.as_deref().and_then(Path::file_name) {
+            parent.push(root_name);
+        }
+        if let Some(relative_parent) = walked.relative.parent() {
+            parent.push(relative_parent);
+        }
+        let mut folder = parent.join(stem);
+        if taken.contains(&folder) {
+            folder = parent.join(name);
+            // Only the same file given twice has the same name as well.
+            let mut n = 2;
+            while taken.contains(&folder) {
+                folder = parent.join(format!("{}-{}", name.to_string_lossy(), n));
+                n += 1;
+            }
+            info!(
+                "another file is named like `{}`, saving its results to `{}`.",
+                file.display(),
+                folder.display()
+            );
+        }
+        taken.insert(folder.clone());
+        folders.push(folder);
+    }
+    Ok(folders)
+}
+
+/// Analyze `file` for [`full_analysis_with_progress`], writing the results to `output_folder`
+/// and returning the statistics for its summary.
+#[cfg(feature = "images")]
+fn analyze_into_folder<F>(
+    file: &Path,
+    output_folder: &Path,
+    options: &AnalysisOptions,
+    force: bool,
+    progress: &F,
+) -> Result<FileStats, BinvizError>
+where
+    F: Fn(ProgressEvent) + Sync,
+{
+    let analysis = analyze_file_with_progress(file, options, progress)?;
+    write_file_analysis(&analysis, output_folder, options, force)?;
+    info!("Analysis for '{}' is complete.", file.display());
+    Ok(analysis.stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_support::file_with;
+
+    /// `len` bytes of deterministic noise, with a run of zeros every 4096 bytes so repeated windows occur too.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut buf = test_support::noise(len);
+        for (i, byte) in buf.iter_mut().enumerate() {
+            if i % 4096 < 64 {
+                *byte = 0;
+            }
+        }
+        buf
+    }
+
+    #[test]
+    fn chunked_matches_in_memory() {
+        // Longer than a chunk, and not a multiple of one, so windows straddle the chunk boundary.
+        // Few distinct bytes, so the histograms of the higher dimensions stay small.
+        let buf: Vec<u8> = noise(CHUNK_SIZE + 12_345)
+            .iter()
+            .map(|byte| byte % 16)
+            .collect();
+        let file = file_with(&buf);
+        for dimension in 1..=4 {
+            for stride in [1, 3] {
+                let chunked = calculate_histogram(file.path(), dimension, stride).unwrap();
+                let in_memory = calculate_histogram_from_bytes(&buf, dimension, stride);
+                assert_eq!(
+                    chunked, in_memory,
+                    "dimension {}, stride {}",
+                    dimension, stride
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn progress_is_reported_every_granularity_bytes() {
+        for (len, last) in [(10_500, 10_500), (10_000, 10_000)] {
+            let file = file_with(&noise(len));
+            let mut events = Vec::new();
+            calculate_histogram_with_progress(file.path(), 2, 1, 1000, |event| events.push(event))
+                .unwrap();
+            let mut expected: Vec<u64> = (1000..=len as u64).step_by(1000).collect();
+            if expected.last() != Some(&last) {
+                expected.push(last);
+            }
+            let expected: Vec<ProgressEvent> = expected
+                .into_iter()
+                .map(|processed| ProgressEvent::Bytes {
+                    processed,
+                    total: len as u64,
+                })
+                .collect();
+            assert_eq!(events, expected);
+        }
+    }
+
+    #[test]
+    fn histograms_of_several_dimensions_match_one_at_a_time() {
+        let file = file_with(&noise(CHUNK_SIZE + 1000));
+        let histograms =
+            calculate_histograms_with_progress(file.path(), &[3, 1, 2], 2, usize::MAX, |_| {})
+                .unwrap();
+        for (dimension, histogram) in [3, 1, 2].into_iter().zip(histograms) {
+            assert_eq!(
+                histogram,
+                calculate_histogram(file.path(), dimension, 2).unwrap()
+            );
+        }
+        assert!(matches!(
+            calculate_histograms_with_progress(file_with(b"ab").path(), &[1, 3], 1, 1, |_| {}),
+            Err(BinvizError::FileTooSmallForDimension {
+                size: 2,
+                dimension: 3
+            })
+        ));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn analysis_counts_its_histograms_while_calculating_the_entropies() {
+        let buf = noise(10_000);
+        let file = file_with(&buf);
+        let stages = std::sync::Mutex::new(Vec::new());
+        let analysis =
+            analyze_file_with_progress(file.path(), &AnalysisOptions::default(), |event| {
+                let mut stages = stages.lock().unwrap();
+                match event {
+                    ProgressEvent::Stage { stage, .. } => stages.push((stage, 0)),
+                    ProgressEvent::FileBytes { processed, .. } => {
+                        stages.last_mut().unwrap().1 = processed
+                    }
+                    _ => {}
+                }
+            })
+            .unwrap();
+        // The file is only counted once, while calculating the entropies.
+        let stages = stages.into_inner().unwrap();
+        assert_eq!(stages[0], (Stage::Entropy, buf.len() as u64));
+        assert!(stages[1..].iter().all(|&(_, processed)| processed == 0));
+        assert_eq!(
+            analysis.histogram,
+            calculate_histogram_from_bytes(&buf, 1, 1)
+        );
+        assert_eq!(
+            analysis.pair_histogram,
+            Some(calculate_histogram_from_bytes(&buf, 2, 1))
+        );
+        assert_eq!(analysis.entropies.len(), 3);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn analysis_in_one_pass_matches_reading_the_file_for_every_statistic() {
+        // Noise with strings and runs, longer than a chunk so they span chunks.
+        let mut buf = noise(CHUNK_SIZE + 1000);
+        buf.splice(
+            CHUNK_SIZE - 3..CHUNK_SIZE - 3,
+            b"a string across chunks".iter().copied(),
+        );
+        buf.extend([0; 100]);
+        let file = file_with(&buf);
+        let path = file.path();
+        let analysis = analyze_file(path, &AnalysisOptions::default()).unwrap();
+        assert_eq!(analysis.serial_correlation, serial_correlation(path).ok());
+        assert_eq!(analysis.monte_carlo_pi, monte_carlo_pi(path).ok());
+        assert_eq!(
+            analysis.lz_complexity,
+            lz_complexity_from_file(path).unwrap()
+        );
+        assert_eq!(
+            analysis.entropy_profile,
+            entropy_profile(path, ENTROPY_MAP_BLOCK_SIZE, ENTROPY_MAP_BLOCK_SIZE).unwrap()
+        );
+        assert_eq!(analysis.run_lengths, run_length_stats(path).unwrap());
+        assert_eq!(
+            analysis.strings,
+            extract_strings(path, MIN_STRING_LENGTH, Charset::Ascii).unwrap()
+        );
+        assert!(analysis
+            .strings
+            .iter()
+            .any(|(_, string)| string.contains("a string across chunks")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn histogram_of_a_pipe_is_read_to_its_end() {
+        let buf = noise(CHUNK_SIZE * 2 + 17);
+        let histogram = with_pipe_of(&buf, |fifo| calculate_histogram(fifo, 3, 2)).unwrap();
+        assert_eq!(histogram, calculate_histogram_from_bytes(&buf, 3, 2));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn filtered_histogram_of_a_pipe_is_read_to_its_end() {
+        let buf = noise(CHUNK_SIZE + 17);
+        let keeps = |byte: u8| byte != 0;
+        let histogram =
+            with_pipe_of(&buf, |fifo| calculate_histogram_filtered(fifo, 2, 1, keeps)).unwrap();
+        assert_eq!(
+            histogram,
+            calculate_histogram_from_bytes_filtered(&buf, 2, 1, keeps)
+        );
+        // The size of a pipe is only checked once it is read.
+        assert!(matches!(
+            with_pipe_of(&[], |fifo| calculate_histogram_filtered(fifo, 2, 1, keeps)),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    /// Call `f` with the path of a named pipe, to which `buf` is written meanwhile.
+    #[cfg(unix)]
+    fn with_pipe_of<T>(buf: &[u8], f: impl FnOnce(&Path) -> T) -> T {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("fifo");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let writer = {
+            let (fifo, buf) = (fifo.clone(), buf.to_vec());
+            std::thread::spawn(move || fs::write(fifo, buf).unwrap())
+        };
+        let result = f(&fifo);
+        writer.join().unwrap();
+        result
+    }
+
+    #[test]
+    fn windows_are_counted_across_chunks() {
+        let mut buf = vec![0; 2 * CHUNK_SIZE];
+        buf[CHUNK_SIZE - 1] = 1;
+        buf[CHUNK_SIZE] = 2;
+        let file = file_with(&buf);
+        let histogram = calculate_histogram(file.path(), 2, 1).unwrap();
+        assert_eq!(histogram[&[1, 2][..]], 1);
+        assert_eq!(histogram.values().sum::<usize>(), buf.len() - 1);
+    }
+
+    /// The histogram counted window by window into the tree, without the dense arrays or borrowed keys.
+    fn sparse_histogram(buf: &[u8], dimension: usize, stride: usize) -> Histogram<u8> {
+        let mut histogram = Histogram::new();
+        for window in buf.windows(dimension).step_by(stride) {
+            *histogram.entry(window.to_vec()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// The histogram of `windows` of which every one is a string.
+    fn histogram_of(windows: &[&str]) -> Histogram<u8> {
+        let mut histogram = Histogram::new();
+        for window in windows {
+            *histogram.entry(window.as_bytes().to_vec()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    #[test]
+    fn stride_one_counts_every_window() {
+        assert_eq!(
+            calculate_histogram_from_bytes(b"abcde", 2, 1),
+            histogram_of(&["ab", "bc", "cd", "de"])
+        );
+    }
+
+    #[test]
+    fn stride_of_the_dimension_counts_chunks() {
+        // The trailing `g` doesn't fill a window.
+        assert_eq!(
+            calculate_histogram_from_bytes(b"abcdefg", 3, 3),
+            histogram_of(&["abc", "def"])
+        );
+    }
+
+    #[test]
+    fn stride_beyond_the_dimension_skips_bytes() {
+        assert_eq!(
+            calculate_histogram_from_bytes(b"abcdefg", 2, 3),
+            histogram_of(&["ab", "de"])
+        );
+    }
+
+    #[test]
+    fn stride_carries_across_updates() {
+        let buf = noise(10_000);
+        for (dimension, stride) in [(1, 2), (2, 3), (3, 3), (4, 7)] {
+            let mut builder = HistogramBuilder::with_stride(dimension, stride);
+            for chunk in buf.chunks(997) {
+                builder.update(chunk);
+            }
+            assert_eq!(
+                builder.finish(),
+                calculate_histogram_from_bytes(&buf, dimension, stride)
+            );
+        }
+    }
+
+    #[test]
+    fn stride_changes_total_and_entropy() {
+        let overlapping = calculate_histogram_from_bytes(b"abababab", 2, 1);
+        let chunked = calculate_histogram_from_bytes(b"abababab", 2, 2);
+        assert_eq!(overlapping.values().sum::<usize>(), 7);
+        assert_eq!(chunked.values().sum::<usize>(), 4);
+        // Overlapping pairs alternate between `ab` and `ba`, chunks are always `ab`.
+        assert!(calculate_entropy_histogram(&overlapping, EntropyUnit::Bits).unwrap() > 0.9);
+        assert_eq!(
+            calculate_entropy_histogram(&chunked, EntropyUnit::Bits).unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn stride_of_zero_is_an_error() {
+        assert!(matches!(
+            validate_window(2, 0),
+            Err(BinvizError::InvalidStride { stride: 0 })
+        ));
+        let file = file_with(b"abab");
+        assert!(matches!(
+            calculate_histogram(file.path(), 2, 0),
+            Err(BinvizError::InvalidStride { stride: 0 })
+        ));
+    }
+
+    #[test]
+    fn dense_and_sparse_agree() {
+        // Below and above the lengths from which single bytes and pairs are counted densely.
+        for len in [255, 256, 65_535, 65_536, 200_001] {
+            let buf = noise(len);
+            for dimension in [1, 2] {
+                for stride in [1, 2, 3] {
+                    assert_eq!(
+                        calculate_histogram_from_bytes(&buf, dimension, stride),
+                        sparse_histogram(&buf, dimension, stride),
+                        "{} bytes, dimension {}, stride {}",
+                        len,
+                        dimension,
+                        stride
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn borrowed_keys_agree_with_allocated_keys() {
+        // Noise has mostly distinct windows, the repeated text mostly recurring ones.
+        let text = b"the borrowed key of a window only allocates once it is new. ".repeat(500);
+        for buf in [noise(20_000), text] {
+            for dimension in 1..=4 {
+                for stride in [1, 3] {
+                    assert_eq!(
+                        calculate_histogram_from_bytes(&buf[..1000], dimension, stride),
+                        sparse_histogram(&buf[..1000], dimension, stride),
+                        "dimension {}, stride {}",
+                        dimension,
+                        stride
+                    );
+                }
+                // Later updates add to the keys of earlier ones.
+                let mut builder = HistogramBuilder::new(dimension);
+                for chunk in buf.chunks(999) {
+                    builder.update(chunk);
+                }
+                assert_eq!(builder.finish(), sparse_histogram(&buf, dimension, 1));
+            }
+        }
+    }
+
+    #[test]
+    fn dense_counts_convert_to_the_histogram() {
+        let buf = noise(100_000);
+        assert_eq!(
+            dense_to_histogram(&count_bytes(&buf, 1), 1),
+            sparse_histogram(&buf, 1, 1)
+        );
+        assert_eq!(
+            dense_to_histogram(&count_byte_pairs(&buf, 1)[..], 2),
+            sparse_histogram(&buf, 2, 1)
+        );
+        // The first byte of a pair is the high byte of its index.
+        assert_eq!(count_byte_pairs(&[1, 2], 1)[0x0102], 1);
+        assert_eq!(
+            dense_to_histogram(&count_byte_pairs(&[1, 2], 1)[..], 2),
+            histogram_of(&["\u{1}\u{2}"])
+        );
+    }
+
+    #[test]
+    fn dense_and_sparse_updates_agree() {
+        let buf = noise(200_000);
+        for dimension in [1, 2] {
+            // Some updates are long enough to be counted densely, others aren't.
+            let mut builder = HistogramBuilder::new(dimension);
+            for chunk in buf.chunks(70_000) {
+                let (short, long) = chunk.split_at(100);
+                builder.update(short);
+                builder.update(long);
+            }
+            assert_eq!(builder.finish(), sparse_histogram(&buf, dimension, 1));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid dimension `0`")]
+    fn builder_of_dimension_0_panics() {
+        HistogramBuilder::new(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid stride `0`")]
+    fn builder_of_stride_0_panics() {
+        HistogramBuilder::with_stride(2, 0);
+    }
+
+    /// The histogram of `a` followed by `b`, without the windows spanning the join.
+    fn concatenated_histogram(a: &[u8], b: &[u8], dimension: usize) -> Histogram<u8> {
+        let joined = [a, b].concat();
+        let mut histogram = calculate_histogram_from_bytes(&joined, dimension, 1);
+        for start in a.len().saturating_sub(dimension - 1)..a.len() {
+            let window = joined[start..start + dimension].to_vec();
+            match histogram.get_mut(&window) {
+                Some(1) => {
+                    histogram.remove(&window);
+                }
+                Some(count) => *count -= 1,
+                None => unreachable!("every window of the concatenation is counted"),
+            }
+        }
+        histogram
+    }
+
+    #[test]
+    fn merging_equals_concatenating() {
+        let (a, b) = (
+            noise(5000),
+            b"the quick brown fox jumps over the lazy dog".repeat(20),
+        );
+        for dimension in 1..=3 {
+            let mut merged = calculate_histogram_from_bytes(&a, dimension, 1);
+            merge_histograms(
+                &mut merged,
+                &calculate_histogram_from_bytes(&b, dimension, 1),
+            )
+            .unwrap();
+            assert_eq!(
+                merged,
+                concatenated_histogram(&a, &b, dimension),
+                "dimension {}",
+                dimension
+            );
+        }
+    }
+
+    #[test]
+    fn merging_single_bytes_equals_concatenating_exactly() {
+        let (a, b) = (noise(5000), noise(300));
+        let mut merged = calculate_histogram_from_bytes(&a, 1, 1);
+        merge_histograms(&mut merged, &calculate_histogram_from_bytes(&b, 1, 1)).unwrap();
+        assert_eq!(
+            merged,
+            calculate_histogram_from_bytes(&[a, b].concat(), 1, 1)
+        );
+    }
+
+    #[test]
+    fn merging_different_dimensions_is_an_error() {
+        let mut histogram = calculate_histogram_from_bytes(b"abc", 1, 1);
+        let unchanged = histogram.clone();
+        assert!(matches!(
+            merge_histograms(
+                &mut histogram,
+                &calculate_histogram_from_bytes(b"abc", 2, 1)
+            ),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 1,
+                found: 2
+            })
+        ));
+        assert_eq!(histogram, unchanged);
+        // An empty histogram has no key length, so it merges with anything.
+        merge_histograms(&mut histogram, &Histogram::new()).unwrap();
+        let mut empty = Histogram::new();
+        merge_histograms(&mut empty, &unchanged).unwrap();
+        assert_eq!(empty, unchanged);
+    }
+
+    #[test]
+    fn many_files_merge_as_they_are_read() {
+        let (a, b) = (noise(5000), noise(7000));
+        let files = [file_with(&a), file_with(&b)];
+        let paths: Vec<_> = files.iter().map(|file| file.path()).collect();
+        for dimension in 1..=2 {
+            assert_eq!(
+                calculate_histogram_many(&paths, dimension, 1).unwrap(),
+                concatenated_histogram(&a, &b, dimension)
+            );
+        }
+    }
+
+    #[test]
+    fn dimension_of_zero_is_an_error() {
+        let file = file_with(b"abc");
+        assert!(matches!(
+            calculate_histogram(file.path(), 0, 1),
+            Err(BinvizError::InvalidDimension { dimension: 0 })
+        ));
+    }
+
+    #[test]
+    fn dimension_beyond_the_file_is_an_error() {
+        let file = file_with(b"abc");
+        assert!(matches!(
+            calculate_histogram(file.path(), 4, 1),
+            Err(BinvizError::FileTooSmallForDimension {
+                size: 3,
+                dimension: 4
+            })
+        ));
+        // A window as long as the file is counted once.
+        assert_eq!(
+            calculate_histogram(file.path(), 3, 1).unwrap(),
+            histogram_of(&["abc"])
+        );
+    }
+
+    #[test]
+    fn empty_file_is_an_error() {
+        let file = file_with(b"");
+        assert!(matches!(
+            calculate_histogram(file.path(), 1, 1),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn entropy_of_an_empty_histogram_is_an_error() {
+        assert!(matches!(
+            calculate_entropy_histogram(&Histogram::<u8>::new(), EntropyUnit::Bits),
+            Err(BinvizError::EmptyInput)
+        ));
+        let zeros: Histogram<u8> = [(vec![0], 0), (vec![1], 0)].into_iter().collect();
+        assert!(matches!(
+            calculate_entropy_histogram(&zeros, EntropyUnit::Bits),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn impossible_and_certain_symbols_have_no_entropy() {
+        for unit in [EntropyUnit::Bits, EntropyUnit::Nats, EntropyUnit::Dits] {
+            assert_eq!(calculate_entropy(0.0, unit), 0.0);
+            assert_eq!(calculate_entropy(1.0, unit), 0.0);
+        }
+        let certain: Histogram<u8> = [(vec![7], 100), (vec![8], 0)].into_iter().collect();
+        assert_eq!(
+            calculate_entropy_histogram(&certain, EntropyUnit::Bits).unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn uniform_bytes_have_8_bits_of_entropy() {
+        let uniform: Histogram<u8> = (0..=255u8).map(|byte| (vec![byte], 3)).collect();
+        assert_eq!(
+            calculate_entropy_histogram(&uniform, EntropyUnit::Bits).unwrap(),
+            8.0
+        );
+        let nats = calculate_entropy_histogram(&uniform, EntropyUnit::Nats).unwrap();
+        assert!((nats - 8.0 * std::f64::consts::LN_2).abs() < 1e-12);
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn nats_are_bits_times_ln_2() {
+        let file = file_with(&noise(10_000));
+        let table = |unit| {
+            let (header, rows) = entropy_table(
+                file.path(),
+                3,
+                1,
+                &[2.0],
+                unit,
+                EntropyEstimator::MillerMadow,
+                OutputFormat::Csv,
+            )
+            .unwrap();
+            let values: Vec<Vec<f64>> = rows
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.parse().unwrap()).collect())
+                .collect();
+            (header, values)
+        };
+        let (_, bits) = table(EntropyUnit::Bits);
+        let (header, nats) = table(EntropyUnit::Nats);
+        assert_eq!(header[1], "Entropy (nats)");
+        for (bits, nats) in bits.iter().zip(&nats) {
+            // The relative entropy has no unit.
+            assert!((nats[2] - bits[2]).abs() < 1e-12);
+            // The entropy, the entropy rate, the plug-in entropy and the Rényi entropy, up to rounding.
+            for column in [1, 3, 4, 5] {
+                let ratio = nats[column] / bits[column];
+                assert!(
+                    (ratio - std::f64::consts::LN_2).abs() < 1e-9,
+                    "{}: {}",
+                    header[column],
+                    ratio
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn entropy_rate_of_alternating_bytes_drops_to_zero() {
+        let file = file_with(&b"AB".repeat(5000));
+        let (header, rows) = entropy_table(
+            file.path(),
+            3,
+            1,
+            &[],
+            EntropyUnit::Bits,
+            EntropyEstimator::Plugin,
+            OutputFormat::Csv,
+        )
+        .unwrap();
+        assert_eq!(header[3], "Entropy Rate (bits)");
+        let rates: Vec<f64> = rows.iter().map(|row| row[3].parse().unwrap()).collect();
+        // A byte is either letter, after which the next byte is certain.
+        assert!((rates[0] - 1.0).abs() < 1e-9, "{:?}", rates);
+        assert!(rates[1] < 1e-3, "{:?}", rates);
+        assert!(rates[2] < 1e-3, "{:?}", rates);
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn entropy_table_counts_every_dimension_in_one_pass() {
+        // Longer than a chunk, so windows of every dimension straddle the chunk boundary.
+        let buf = noise(CHUNK_SIZE + 1001);
+        let file = file_with(&buf);
+        for stride in [1, 2] {
+            let (_, rows) = entropy_table(
+                file.path(),
+                3,
+                stride,
+                &[],
+                EntropyUnit::Bits,
+                EntropyEstimator::Plugin,
+                OutputFormat::Csv,
+            )
+            .unwrap();
+            for (dimension, row) in (1..=3).zip(&rows) {
+                let histogram = calculate_histogram_from_bytes(&buf, dimension, stride);
+                let entropy = calculate_entropy_histogram(&histogram, EntropyUnit::Bits).unwrap();
+                assert_eq!(row[1], entropy.to_string(), "dimension {}", dimension);
+            }
+        }
+        assert!(matches!(
+            entropy_table(
+                file_with(b"ab").path(),
+                3,
+                1,
+                &[],
+                EntropyUnit::Bits,
+                EntropyEstimator::Plugin,
+                OutputFormat::Csv,
+            ),
+            Err(BinvizError::FileTooSmallForDimension {
+                size: 2,
+                dimension: 3
+            })
+        ));
+    }
+
+    /// Parse CSV the way a spreadsheet would: quoted fields may contain separators and line breaks,
+    /// with quotes inside them doubled.
+    #[cfg(feature = "tables")]
+    fn parse_csv(csv: &str) -> Vec<Vec<String>> {
+        let (mut records, mut record, mut field) = (Vec::new(), Vec::new(), String::new());
+        let (mut chars, mut quoted) = (csv.chars().peekable(), false);
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (_, '"') => quoted = !quoted,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                _ => field.push(c),
+            }
+        }
+        record.push(field);
+        records.push(record);
+        records
+    }
+
+    #[test]
+    fn ties_are_ordered_by_key() {
+        // Inserted out of order, with two ties of 2 and a tie of 1 between keys of different first bytes.
+        let histogram: Histogram<u8> = [
+            (vec![9, 0], 2),
+            (vec![3, 1], 1),
+            (vec![5, 5], 7),
+            (vec![1, 9], 2),
+            (vec![3, 0], 2),
+            (vec![0, 4], 1),
+        ]
+        .into_iter()
+        .collect();
+        let keys = |order| -> Vec<Vec<u8>> {
+            get_most_frequent_bytes(&histogram, order)
+                .into_iter()
+                .map(|(key, _)| key.clone())
+                .collect()
+        };
+        let descending = [[5, 5], [1, 9], [3, 0], [9, 0], [0, 4], [3, 1]];
+        assert_eq!(keys(SortOrder::CountDescending), descending);
+        // Without a baseline deviation sorts like descending counts.
+        assert_eq!(keys(SortOrder::Deviation), descending);
+        assert_eq!(
+            keys(SortOrder::CountAscending),
+            [[0, 4], [3, 1], [1, 9], [3, 0], [9, 0], [5, 5]]
+        );
+        assert_eq!(
+            keys(SortOrder::Key),
+            [[0, 4], [1, 9], [3, 0], [3, 1], [5, 5], [9, 0]]
+        );
+    }
+
+    #[test]
+    fn truncation_counts_the_removed_keys() {
+        // Counts 1 to 10, 55 in total.
+        let histogram: Histogram<u8> = (1..=10u8).map(|i| (vec![i], i as usize)).collect();
+        let mut most_freq = get_most_frequent_bytes(&histogram, SortOrder::CountDescending);
+        // The 3 most frequent keys count 10 + 9 + 8 = 27, the other 7 keys the remaining 28.
+        assert_eq!(
+            truncate_most_frequent(&mut most_freq, Some(3)),
+            Some((7, 28))
+        );
+        assert_eq!(most_freq.len(), 3);
+        for top in [None, Some(10), Some(11)] {
+            let mut most_freq = get_most_frequent_bytes(&histogram, SortOrder::CountDescending);
+            assert_eq!(truncate_most_frequent(&mut most_freq, top), None);
+            assert_eq!(most_freq.len(), 10);
+        }
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn summary_covers_the_complement_of_the_shown_rows() {
+        let histogram: Histogram<u8> = (1..=10u8).map(|i| (vec![i], i as usize)).collect();
+        let csv = display_most_frequent(
+            &histogram,
+            Some(3),
+            SortOrder::CountDescending,
+            OutputFormat::Csv,
+        );
+        let shown: f64 = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit(',').next().unwrap().parse::<f64>().unwrap())
+            .sum();
+        assert_eq!(csv.lines().count(), 1 + 3);
+        let complement = format!("{:.1}%", (1.0 - shown) * 100.0);
+        assert_eq!(complement, "50.9%");
+        let markdown = display_most_frequent(
+            &histogram,
+            Some(3),
+            SortOrder::CountDescending,
+            OutputFormat::Markdown,
+        );
+        assert!(markdown.ends_with(&format!(
+            "… and 7 more distinct values covering {} of windows.",
+            complement
+        )));
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn pairs_and_triples_of_null_and_high_bytes_are_listed() {
+        // 101 bytes alternating between 0x00 and 0xff.
+        let mut buf = b"\x00\xff".repeat(50);
+        buf.push(0x00);
+        let table = |dimension| {
+            let histogram = calculate_histogram_from_bytes(&buf, dimension, 1);
+            let ((header, rows), remainder) = frequency_table(
+                &histogram,
+                None,
+                SortOrder::CountDescending,
+                OutputFormat::Csv,
+            );
+            assert_eq!(remainder, None);
+            (header, rows)
+        };
+
+        let (header, rows) = table(2);
+        assert_eq!(
+            header,
+            [
+                "Rank",
+                "Bytes",
+                "Hex",
+                "Text",
+                "Classes",
+                "Relative Frequency"
+            ]
+        );
+        assert_eq!(
+            rows,
+            [
+                [
+                    "0",
+                    "0 255",
+                    "0x00 0xff",
+                    "'\\x00\\xff'",
+                    "null high",
+                    "0.5"
+                ],
+                [
+                    "1",
+                    "255 0",
+                    "0xff 0x00",
+                    "'\\xff\\x00'",
+                    "high null",
+                    "0.5"
+                ],
+            ]
+        );
+
+        let (header, rows) = table(3);
+        assert_eq!(header[1], "Bytes");
+        assert_eq!(
+            rows,
+            [
+                [
+                    "0".to_string(),
+                    "0 255 0".to_string(),
+                    "0x00 0xff 0x00".to_string(),
+                    "'\\x00\\xff\\x00'".to_string(),
+                    "null high null".to_string(),
+                    (50.0 / 99.0).to_string(),
+                ],
+                [
+                    "1".to_string(),
+                    "255 0 255".to_string(),
+                    "0xff 0x00 0xff".to_string(),
+                    "'\\xff\\x00\\xff'".to_string(),
+                    "high null high".to_string(),
+                    (49.0 / 99.0).to_string(),
+                ],
+            ]
+        );
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn frequency_csv_parses_back_to_the_histogram() {
+        // Keys with commas, quotes and line breaks in their text.
+        let histogram: Histogram<u8> = [
+            (b",\"".to_vec(), 5),
+            (b"a\n".to_vec(), 3),
+            (b"\"\"".to_vec(), 2),
+            (vec![0x00, 0xff], 1),
+        ]
+        .into_iter()
+        .collect();
+        let csv = display_most_frequent(
+            &histogram,
+            None,
+            SortOrder::CountDescending,
+            OutputFormat::Csv,
+        );
+        let records = parse_csv(&csv);
+        assert_eq!(
+            records[0],
+            [
+                "Rank",
+                "Bytes",
+                "Hex",
+                "Text",
+                "Classes",
+                "Relative Frequency"
+            ]
+        );
+        assert_eq!(records.len(), 1 + histogram.len());
+        let mut parsed = Histogram::new();
+        for (rank, record) in records[1..].iter().enumerate() {
+            assert_eq!(record.len(), 6, "{:?}", record);
+            assert_eq!(record[0], rank.to_string());
+            let key: Vec<u8> = record[1]
+                .split(' ')
+                .map(|byte| byte.parse().unwrap())
+                .collect();
+            assert_eq!(record[3], format!("'{}'", key.escape_ascii()));
+            // The relative frequency is a raw number, times the total of 11 it is the count again.
+            let frequency: f64 = record[5].parse().unwrap();
+            parsed.insert(key, (frequency * 11.0).round() as usize);
+        }
+        assert_eq!(parsed, histogram);
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn entropy_csv_parses_back_to_the_entropies() {
+        let buf = noise(10_000);
+        let file = file_with(&buf);
+        let csv = display_entropies(
+            file.path(),
+            2,
+            1,
+            &[2.0],
+            EntropyUnit::Bits,
+            EntropyEstimator::Plugin,
+            OutputFormat::Csv,
         )
-        .expect("Couldn't write into `most_frequent.txt`");
-
-        // Perform the Vis subcommand.
-        let dihistogram = calculate_histogram(&file, 2);
-        let (image, total, avg_total) = generate_image(&dihistogram);
-        image
-            .save(output_folder.join("image.png"))
-            .expect("Couldn't save image into `image.png`");
-        info!("`{}` byte pairs in the visualization.", total);
-        info!(
-            "full brightness means `{}` byte pairs at that location.",
-            avg_total
-        );
-        info!("Analysis for '{}' is complete.", file.display());
+        .unwrap();
+        let records = parse_csv(&csv);
+        assert_eq!(records.len(), 1 + 2);
+        for (dimension, record) in (1..=2).zip(&records[1..]) {
+            assert_eq!(record.len(), records[0].len());
+            assert_eq!(record[0], dimension.to_string());
+            let histogram = calculate_histogram_from_bytes(&buf, dimension, 1);
+            let entropy = calculate_entropy_histogram(&histogram, EntropyUnit::Bits).unwrap();
+            assert_eq!(record[1].parse::<f64>().unwrap(), entropy);
+            let renyi = calculate_renyi_entropy(&histogram, 2.0).unwrap();
+            assert_eq!(record[4].parse::<f64>().unwrap(), renyi);
+        }
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn image_of_an_empty_histogram_is_an_error() {
+        let empty = Histogram::new();
+        assert!(matches!(
+            generate_image(&empty, ScaleMode::Linear, Normalization::Average),
+            Err(BinvizError::EmptyInput)
+        ));
+        assert!(matches!(
+            generate_color_image(&empty, ScaleMode::Linear, Normalization::Average),
+            Err(BinvizError::EmptyInput)
+        ));
+        assert!(matches!(
+            generate_color_image_quartic(&empty),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.bin");
+        match calculate_histogram(&missing, 1, 1) {
+            Err(BinvizError::Io { path, source }) => {
+                assert_eq!(path, missing);
+                assert_eq!(source.kind(), ErrorKind::NotFound);
+            }
+            other => panic!("expected an Io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn existing_output_is_only_overwritten_with_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("output.txt");
+        write_output(&path, "first", false).unwrap();
+        match write_output(&path, "second", false) {
+            Err(BinvizError::OutputExists { path: existing }) => assert_eq!(existing, path),
+            other => panic!("expected an OutputExists error, got {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        write_output(&path, "2nd", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2nd");
+    }
+
+    #[test]
+    fn directory_is_an_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        match calculate_histogram(dir.path(), 1, 1) {
+            Err(BinvizError::Io { path, .. }) => assert_eq!(path, dir.path()),
+            other => panic!("expected an Io error, got {:?}", other),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_file_is_an_io_error() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = file_with(b"abc");
+        fs::set_permissions(file.path(), fs::Permissions::from_mode(0o000)).unwrap();
+        // Root can read the file regardless of its permissions, so there is nothing to test then.
+        if File::open(file.path()).is_ok() {
+            return;
+        }
+        match calculate_histogram(file.path(), 1, 1) {
+            Err(BinvizError::Io { path, source }) => {
+                assert_eq!(path, file.path());
+                assert_eq!(source.kind(), ErrorKind::PermissionDenied);
+            }
+            other => panic!("expected an Io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn offset_past_the_end_is_an_error() {
+        assert!(matches!(
+            resolve_range(10, 11, None),
+            Err(BinvizError::OffsetOutOfRange {
+                offset: 11,
+                size: 10
+            })
+        ));
+        let file = file_with(b"0123456789");
+        assert!(matches!(
+            calculate_histogram_range(file.path(), 1, 1, 11, Some(1)),
+            Err(BinvizError::OffsetOutOfRange {
+                offset: 11,
+                size: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn negative_renyi_order_is_an_error() {
+        let histogram = histogram_of(&["a", "b"]);
+        for alpha in [-1.0, f64::NAN] {
+            assert!(matches!(
+                calculate_renyi_entropy(&histogram, alpha),
+                Err(BinvizError::InvalidRenyiOrder { .. })
+            ));
+        }
+        assert_eq!(calculate_renyi_entropy(&histogram, 0.0).unwrap(), 1.0);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn image_of_the_wrong_key_length_is_an_error() {
+        let bytes = histogram_of(&["a", "b"]);
+        let pairs = histogram_of(&["ab", "ba"]);
+        let triples = histogram_of(&["abc"]);
+        assert!(matches!(
+            generate_image(&bytes, ScaleMode::Linear, Normalization::Average),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            generate_color_image(&pairs, ScaleMode::Linear, Normalization::Average),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            generate_color_image_quartic(&triples),
+            Err(BinvizError::KeyLengthMismatch {
+                expected: 4,
+                found: 3
+            })
+        ));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn saving_into_a_missing_folder_is_a_write_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("image.png");
+        let image: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::new(2, 2);
+        match save_image(image, &path, ImageFormat::Png, BitDepth::Sixteen, false) {
+            Err(BinvizError::Write { path: failed, .. }) => assert_eq!(failed, path),
+            other => panic!("expected a Write error, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn invalid_image_sizes_are_errors() {
+        assert!(matches!(
+            validate_scale_factor(0),
+            Err(BinvizError::InvalidScaleFactor { factor: 0 })
+        ));
+        assert!(matches!(
+            validate_scale_factor(MAX_SCALE_FACTOR + 1),
+            Err(BinvizError::InvalidScaleFactor { .. })
+        ));
+        assert!(validate_scale_factor(MAX_SCALE_FACTOR).is_ok());
+        assert!(matches!(
+            render_raw(b"abc", 0, ColorScheme::default()),
+            Err(BinvizError::InvalidWidth { width: 0 })
+        ));
+    }
+
+    /// A digraph with one very frequent pair and two rare ones.
+    #[cfg(feature = "images")]
+    fn hot_pair_histogram() -> Histogram<u8> {
+        [
+            (vec![0, 0], 1_000_000),
+            (vec![1, 2], 1),
+            (vec![200, 100], 2),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn log_scale_reveals_rare_pairs() {
+        let histogram = hot_pair_histogram();
+        let (linear, _, _) =
+            generate_image(&histogram, ScaleMode::Linear, Normalization::Average).unwrap();
+        let (log, _, full_count) =
+            generate_image(&histogram, ScaleMode::Log, Normalization::Maximum).unwrap();
+        assert_eq!(full_count, 1_000_000.0);
+        let lit = |image: &ImageBuffer<Luma<u16>, Vec<u16>>| {
+            image
+                .enumerate_pixels()
+                .filter(|(_, _, pixel)| pixel[0] > 0)
+                .map(|(x, y, pixel)| (x, y, pixel[0]))
+                .collect::<Vec<_>>()
+        };
+        // Linearly the rare pairs are pure black next to the hot one.
+        assert_eq!(lit(&linear), [(0, 0, u16::MAX)]);
+        // ln(2) / ln(1000001) and ln(3) / ln(1000001) of full brightness.
+        assert_eq!(
+            lit(&log),
+            [(0, 0, u16::MAX), (1, 2, 3287), (200, 100, 5211)]
+        );
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn hottest_pixel_is_the_brightest() {
+        let mut histogram = hot_pair_histogram();
+        histogram.insert(vec![0, 0], usize::MAX / 4);
+        histogram.insert(vec![9, 9], 1 << 40);
+        for scale in [ScaleMode::Linear, ScaleMode::Log, ScaleMode::Sqrt] {
+            for normalization in [Normalization::Average, Normalization::Maximum] {
+                let (image, _, _) = generate_image(&histogram, scale, normalization).unwrap();
+                let hottest = image.get_pixel(0, 0)[0];
+                assert_eq!(hottest, u16::MAX, "{} {}", scale, normalization);
+                assert!(image.pixels().all(|pixel| pixel[0] <= hottest));
+                assert!(image.get_pixel(9, 9)[0] >= image.get_pixel(200, 100)[0]);
+            }
+        }
+        let triples: Histogram<u8> = [
+            (vec![0, 0, 0], usize::MAX / 4),
+            (vec![1, 2, 3], 1),
+            (vec![4, 5, 6], 2),
+        ]
+        .into_iter()
+        .collect();
+        let (image, _, full_count) =
+            generate_color_image(&triples, ScaleMode::Linear, Normalization::Maximum).unwrap();
+        assert_eq!(full_count, (usize::MAX / 4) as f64);
+        assert_eq!(image.get_pixel(0, 0)[2], u16::MAX);
+        assert_eq!(image.get_pixel(4, 5)[2], 0);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn brightness_is_clamped_to_a_channel() {
+        assert_eq!(to_channel(0.0), 0);
+        assert_eq!(to_channel(1.0), u16::MAX);
+        assert_eq!(to_channel(1e300), u16::MAX);
+        assert_eq!(to_channel(-1.0), 0);
+        assert_eq!(to_channel(f64::NAN), 0);
+        assert_eq!(to_channel(f64::INFINITY), u16::MAX);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapping_matches_reading() {
+        let buf = noise(100_003);
+        let file = file_with(&buf);
+        for dimension in 1..=3 {
+            for stride in [1, 2] {
+                assert_eq!(
+                    calculate_histogram_mmap(file.path(), dimension, stride).unwrap(),
+                    calculate_histogram(file.path(), dimension, stride).unwrap(),
+                    "dimension {}, stride {}",
+                    dimension,
+                    stride
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapping_reports_the_same_errors() {
+        let file = file_with(b"a");
+        assert!(matches!(
+            calculate_histogram_mmap(file.path(), 2, 1),
+            Err(BinvizError::FileTooSmallForDimension { .. })
+        ));
+        let empty = file_with(b"");
+        assert!(matches!(
+            calculate_histogram_mmap(empty.path(), 1, 1),
+            Err(BinvizError::EmptyInput)
+        ));
+        assert!(matches!(
+            calculate_histogram_mmap(file.path().with_extension("missing"), 1, 1),
+            Err(BinvizError::Io { .. })
+        ));
+    }
+
+    #[cfg(all(feature = "mmap", unix))]
+    #[test]
+    fn mapping_a_pipe_falls_back_to_reading() {
+        // Longer than the buffer of a pipe, so the writer is blocked until the fallback reads the pipe.
+        let buf = noise(CHUNK_SIZE * 2 + 17);
+        let histogram = with_pipe_of(&buf, |fifo| calculate_histogram_mmap(fifo, 2, 1)).unwrap();
+        assert_eq!(histogram, calculate_histogram_from_bytes(&buf, 2, 1));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_sequential() {
+        // Down to a file smaller than a part per thread, and one with a single window.
+        for len in [3, 10, 1000, 100_003] {
+            let buf = noise(len);
+            let file = file_with(&buf);
+            for dimension in 1..=3 {
+                for stride in [1, 2] {
+                    let sequential = calculate_histogram(file.path(), dimension, stride).unwrap();
+                    for threads in [1, 2, 3, 8] {
+                        let parallel =
+                            calculate_histogram_parallel(file.path(), dimension, stride, threads)
+                                .unwrap();
+                        assert_eq!(
+                            parallel, sequential,
+                            "{} bytes, dimension {}, stride {}, {} threads",
+                            len, dimension, stride, threads
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_blocks_match_sequential() {
+        let buf = noise(5003);
+        let file = file_with(&buf);
+        let pool = thread_pool(3).unwrap();
+        // Down to blocks shorter than a window, so windows straddle several blocks.
+        for block_size in [1, 2, 5, 64, 1000, 10_000] {
+            for dimension in 1..=4 {
+                for stride in [1, 2, 3] {
+                    let blocks = count_file_parallel(
+                        file.path(),
+                        dimension,
+                        stride,
+                        &pool,
+                        block_size,
+                        100,
+                        &|_| {},
+                    )
+                    .unwrap();
+                    assert_eq!(
+                        blocks,
+                        calculate_histogram_from_bytes(&buf, dimension, stride),
+                        "blocks of {} bytes, dimension {}, stride {}",
+                        block_size,
+                        dimension,
+                        stride
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_progress_covers_the_input() {
+        let buf = noise(100_000);
+        let last = std::sync::Mutex::new(None);
+        let histogram =
+            calculate_histogram_from_bytes_parallel_with_progress(&buf, 2, 1, 4, 1000, |event| {
+                *last.lock().unwrap() = Some(event)
+            })
+            .unwrap();
+        assert_eq!(histogram, calculate_histogram_from_bytes(&buf, 2, 1));
+        assert_eq!(
+            last.into_inner().unwrap(),
+            Some(ProgressEvent::Bytes {
+                processed: 100_000,
+                total: 100_000
+            })
+        );
     }
 }
@@ -0,0 +1,195 @@
+#[cfg(feature = "tables")]
+use std::io::{Seek, SeekFrom};
+use std::{
+    fs::File,
+    io::{self, ErrorKind, Read},
+    path::Path,
+};
+
+#[cfg(feature = "tables")]
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+
+#[cfg(feature = "tables")]
+use crate::storage::to_hex;
+use crate::{storage::from_hex, BinvizError, CHUNK_SIZE};
+
+/// Parse a byte sequence written in hexadecimal, optionally `0x` prefixed, e.g. `ffd8` or `0xFFD8`.
+/// Returns `None` for empty or malformed sequences.
+pub fn parse_ngram(value: &str) -> Option<Vec<u8>> {
+    let hex = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    from_hex(hex).filter(|ngram| !ngram.is_empty())
+}
+
+/// Find the offsets of all occurrences of `ngram` in a given file, stopping after `limit` occurrences if given.
+///
+/// See [`find_ngram_offsets_from_reader`], the file is read in chunks instead of loading it into memory at once.
+pub fn find_ngram_offsets<P>(
+    file: P,
+    ngram: &[u8],
+    limit: Option<usize>,
+) -> Result<Vec<u64>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    find_ngram_offsets_from_reader(handle, ngram, limit).map_err(|err| BinvizError::io(file, err))
+}
+
+/// Find the offsets of all occurrences of `ngram` in everything `reader` produces, in increasing order,
+/// stopping after `limit` occurrences if given.
+///
+/// Occurrences may overlap, every window of a stride 1 histogram that equals `ngram` is reported,
+/// so the amount of offsets equals the count of `ngram` in that histogram. An empty `ngram` never occurs.
+pub fn find_ngram_offsets_from_reader<R>(
+    mut reader: R,
+    ngram: &[u8],
+    limit: Option<usize>,
+) -> io::Result<Vec<u64>>
+where
+    R: Read,
+{
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut offsets = Vec::new();
+    if ngram.is_empty() || limit == 0 {
+        return Ok(offsets);
+    }
+    // The offset of the first byte in `buf`, which starts with the last `ngram.len() - 1` bytes
+    // of the previous chunk, so occurrences spanning two chunks are found.
+    let mut start = 0;
+    let mut buf = Vec::with_capacity(CHUNK_SIZE + ngram.len());
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => buf.extend_from_slice(&chunk[..read]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+        for (i, window) in buf.windows(ngram.len()).enumerate() {
+            if window == ngram {
+                offsets.push(start + i as u64);
+                if offsets.len() == limit {
+                    return Ok(offsets);
+                }
+            }
+        }
+        let consumed = buf.len() - buf.len().min(ngram.len() - 1);
+        buf.drain(..consumed);
+        start += consumed as u64;
+    }
+    Ok(offsets)
+}
+
+/// Display the offsets of `ngram` in `source`, together with up to `context` bytes before and after every occurrence.
+///
+/// The context is shown in hexadecimal, with the occurrence itself in brackets.
+#[cfg(feature = "tables")]
+pub fn display_ngram_offsets<R>(
+    source: &mut R,
+    ngram: &[u8],
+    offsets: &[u64],
+    context: usize,
+) -> io::Result<String>
+where
+    R: Read + Seek,
+{
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(["Rank", "Offset", "Context"]);
+    for (i, &offset) in offsets.iter().enumerate() {
+        let before = offset.min(context as u64);
+        source.seek(SeekFrom::Start(offset - before))?;
+        let mut surrounding = Vec::new();
+        source
+            .by_ref()
+            .take(before + (ngram.len() + context) as u64)
+            .read_to_end(&mut surrounding)?;
+        let (before, rest) = surrounding.split_at(before as usize);
+        let (found, after) = rest.split_at(ngram.len().min(rest.len()));
+        table.add_row([
+            format!("{}", i),
+            format!("{:#x}", offset),
+            format!(
+                "{} [{}] {}",
+                spaced_hex(before),
+                spaced_hex(found),
+                spaced_hex(after)
+            )
+            .trim()
+            .to_string(),
+        ]);
+    }
+    Ok(table.to_string())
+}
+
+/// Encode the bytes as lowercase hexadecimal, separated by spaces.
+#[cfg(feature = "tables")]
+fn spaced_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| to_hex(&[*byte]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ShortReads;
+
+    #[test]
+    fn overlapping_occurrences_are_all_found() {
+        let offsets = find_ngram_offsets_from_reader(&b"aaaa"[..], b"aa", None).unwrap();
+        assert_eq!(offsets, [0, 1, 2]);
+    }
+
+    #[test]
+    fn occurrences_across_chunks_are_found() {
+        let mut buf = vec![0; 2 * CHUNK_SIZE + 10];
+        for offset in [10, CHUNK_SIZE - 2, 2 * CHUNK_SIZE - 1] {
+            buf[offset..offset + 4].copy_from_slice(b"\xde\xad\xbe\xef");
+        }
+        let offsets = find_ngram_offsets_from_reader(buf.as_slice(), b"\xde\xad\xbe\xef", None);
+        assert_eq!(
+            offsets.unwrap(),
+            [10, CHUNK_SIZE as u64 - 2, 2 * CHUNK_SIZE as u64 - 1]
+        );
+    }
+
+    #[test]
+    fn single_byte_reads_find_the_same_occurrences() {
+        let buf = b"xabcabcabxabc";
+        let reader = ShortReads {
+            inner: &buf[..],
+            max: 1,
+        };
+        let offsets = find_ngram_offsets_from_reader(reader, b"abc", None).unwrap();
+        assert_eq!(offsets, [1, 4, 10]);
+        assert_eq!(
+            offsets,
+            find_ngram_offsets_from_reader(&buf[..], b"abc", None).unwrap()
+        );
+    }
+
+    #[test]
+    fn search_stops_at_the_limit() {
+        let buf = b"abababab";
+        assert_eq!(
+            find_ngram_offsets_from_reader(&buf[..], b"ab", Some(2)).unwrap(),
+            [0, 2]
+        );
+        assert!(find_ngram_offsets_from_reader(&buf[..], b"ab", Some(0))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            find_ngram_offsets_from_reader(&buf[..], b"ab", Some(10))
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+}
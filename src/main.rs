@@ -1,36 +1,478 @@
-use clap::Parser;
-use clap_derive::{Parser, Subcommand};
-use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use clap::{Command, CommandFactory, FromArgMatches};
+use clap_complete::{generate, generate_to, Generator, Shell};
+use clap_derive::{Args, Parser, Subcommand, ValueEnum};
 use env_logger::Env;
+use image::{DynamicImage, ImageBuffer, Luma, Pixel, Rgb};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 
-use log::info;
-use std::{fmt::Debug, path::PathBuf, time::Instant};
+use log::{info, warn, Log, Metadata, Record};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Debug,
+    fs::{self, File},
+    io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom, Take},
+    num::ParseIntError,
+    ops::RangeInclusive,
+    path::{Component, Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Instant,
+};
 
+#[cfg(feature = "mmap")]
+use binviz::calculate_histogram_mmap;
+use binviz::{
+    apply_colormap, block_stats, block_stats_from_reader, builtin_fingerprints, builtin_reference,
+    calculate_entropy_histogram, calculate_histogram_filtered, calculate_histogram_from_bytes,
+    calculate_histogram_from_reader, calculate_histogram_parallel_with_progress,
+    calculate_histogram_range, calculate_histogram_with_progress, calculate_renyi_entropy,
+    chi_square, classify_histograms, collect_files, compare_histograms, cross_entropy,
+    decode_words, detect_xor_key, display_alphabet, display_baseline_deviation, display_blocks,
+    display_byte_stats, display_chi_square, display_classification, display_comparison,
+    display_cross_entropy, display_distances, display_entropy_profile, display_file_summaries,
+    display_histogram_diff, display_kl_divergence, display_lz_complexity, display_monte_carlo_pi,
+    display_most_frequent, display_most_frequent_words, display_ngram_offsets, display_periods,
+    display_run_lengths, display_scan, display_serial_correlation, display_stationary_distribution,
+    display_strings, display_xor_keys, draw_grid, entropy_chart_svg, entropy_profile,
+    entropy_profile_from_reader, estimate_entropy, export_point_cloud, extract_strings_from_reader,
+    find_ngram_offsets, find_ngram_offsets_from_reader, frequency_chart_svg,
+    full_analysis_with_progress, generate_color_image, generate_color_image_quartic,
+    generate_comparison_image, generate_diff_image, generate_image, generate_image_streaming,
+    generate_nibble_image, generate_offset_byte_image, generate_offset_byte_image_from_bytes,
+    generate_trigraph_slices, hellinger, high_byte_histogram, kl_divergence, load_config,
+    load_histogram, load_reference, merge_histograms, parse_color, parse_ngram,
+    periods_from_spectrum, power_spectrum, read_chunks, render_entropy_map, render_entropy_profile,
+    render_map, render_raw, render_spectrum, renyi_entropy_name, resolve_range, run_length_stats,
+    run_length_stats_from_reader, run_tui, sample_markov, save_animation, save_histogram,
+    save_image, scale_nibble_image, scan_file, split_nibbles, stationary_distribution,
+    total_variation, transition_matrix, upscale_nearest, validate_dimension, validate_input_size,
+    validate_output, validate_scale_factor, validate_window, watch_files, with_axes,
+    with_background, with_class_legend, with_colorbar, write_counts, write_image_metadata,
+    write_output, write_transition_matrix, AnalysisOptions, Background, BinvizError, BitDepth,
+    BlockFilter, BlockStats, ByteFilter, ByteSet, ByteStats, Charset, ColorScheme, Colormap,
+    ComparisonReport, Config, CountsFormat, Endian, EntropyEntry, EntropyEstimator, EntropyReport,
+    EntropyUnit, Fingerprint, Format, FrequencyReport, Glob, Histogram, HistogramBuilder,
+    ImageFormat, ImageMetadata, Label, LzComplexity, MapLayout, MonteCarloPi, Normalization,
+    OutputFormat, ProgressEvent, ReportHeader, RunLengthReport, ScaleMode, ScanSort,
+    SerialCorrelation, Smoothing, SortOrder, SourceFile, WalkOptions, CHART_BAR_COUNT,
+    COMPARISON_DIMENSIONS, DEFAULT_MAX_PERIOD, FULL_ANALYSIS_FOLDER, LEAST_FREQUENT_COUNT,
+    MAX_STRING_LENGTH, MIN_STRING_LENGTH, MIN_TUI_BLOCK_SIZE, OFFSET_BUCKETS, PERIOD_COUNT,
+    PROGRESS_GRANULARITY, RAW_WIDTH, RUN_LENGTH_COUNT, RUN_THRESHOLD, SUMMARY_CSV_FILE,
+    SUMMARY_TEXT_FILE, WATCH_DEBOUNCE, XOR_KEY_COUNT,
+};
+#[cfg(feature = "compression")]
 use binviz::{
-    calculate_entropy_histogram, calculate_histogram, display_most_frequent, full_analysis,
-    generate_color_image, generate_color_image_quartic, generate_image,
+    compression_profile, compression_profile_from_reader, display_compression, Codec, Compressor,
 };
 
+/// Files larger than this many bytes have their histograms calculated in parallel.
+const PARALLEL_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// The file path that is interpreted as standard input.
+const STDIN_PATH: &str = "-";
+
+/// The name standard input is shown as where the name of a file would be.
+const STDIN_NAME: &str = "<stdin>";
+
+/// The exit code when the analysis fails, clap exits with 2 when the arguments are invalid.
+const EXIT_ANALYSIS_ERROR: i32 = 1;
+
+/// The exit code when writing an output fails, or would overwrite an existing file.
+const EXIT_OUTPUT_ERROR: i32 = 3;
+
+/// The shells `binviz completions --dir` saves the scripts of when no shell is given.
+const COMPLETION_SHELLS: [Shell; 4] = [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell];
+
+/// The progress bars drawn on standard error, set up once in [`main`].
+static PROGRESS_BARS: OnceLock<MultiProgress> = OnceLock::new();
+
+/// Which part of the input to analyze and how to read it.
+#[derive(Debug, Clone, Copy, Args)]
+struct Input {
+    /// Only analyze the input starting at this byte offset, in decimal or `0x` prefixed hexadecimal.
+    #[arg(long, value_parser = parse_number, default_value = "0")]
+    offset: u64,
+    /// Only analyze this many bytes, in decimal or `0x` prefixed hexadecimal, defaults to everything up to the end.
+    #[arg(long, value_parser = parse_number)]
+    length: Option<u64>,
+    /// Memory-map the input instead of reading it, falling back to reading if that fails.
+    #[arg(long)]
+    mmap: bool,
+    /// Drop these bytes before analyzing the input, as comma separated hexadecimal bytes and ranges, e.g. `00,ff`.
+    #[arg(long, conflicts_with = "only_bytes")]
+    ignore_bytes: Option<ByteSet>,
+    /// Only keep these bytes when analyzing the input, as comma separated hexadecimal bytes and ranges, e.g. `20-7e`.
+    #[arg(long)]
+    only_bytes: Option<ByteSet>,
+}
+
+impl Input {
+    /// Whether the whole input is analyzed.
+    fn is_full(&self) -> bool {
+        self.offset == 0 && self.length.is_none()
+    }
+
+    /// The filter dropping bytes from the input, if any.
+    fn filter(&self) -> Option<ByteFilter> {
+        match (self.ignore_bytes, self.only_bytes) {
+            (Some(set), _) => Some(ByteFilter::Ignore(set)),
+            (None, Some(set)) => Some(ByteFilter::Only(set)),
+            (None, None) => None,
+        }
+    }
+
+    /// Select this range from the buffer, see [`resolve_range`].
+    fn slice<'a>(&self, buf: &'a [u8]) -> Result<&'a [u8], BinvizError> {
+        let length = resolve_range(buf.len() as u64, self.offset, self.length)?;
+        Ok(&buf[self.offset as usize..(self.offset + length) as usize])
+    }
+}
+
+/// How the folders given to a subcommand are searched for files.
+#[derive(Debug, Clone, Args)]
+struct Walk {
+    /// Only descend this many levels of subfolders into the folders, 0 only takes the files directly in them.
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Follow symbolic links in the folders instead of skipping them, every folder is still searched once.
+    #[arg(long)]
+    follow_symlinks: bool,
+    /// Also take the files and subfolders in the folders whose names start with a dot.
+    #[arg(long)]
+    hidden: bool,
+    /// Only take the files in the folders matching this glob pattern, relative to the folder, can be repeated.
+    /// `*` and `?` match within a name, `**` any amount of subfolders, patterns without a slash match at any depth.
+    #[arg(long)]
+    include: Vec<Glob>,
+    /// Skip the files and subfolders in the folders matching this glob pattern, relative to the folder,
+    /// even if they are included, can be repeated.
+    #[arg(long)]
+    exclude: Vec<Glob>,
+}
+
+impl Walk {
+    /// The options to search the folders with.
+    fn options(self) -> WalkOptions {
+        WalkOptions {
+            max_depth: self.max_depth,
+            follow_symlinks: self.follow_symlinks,
+            include_hidden: self.hidden,
+            include: self.include,
+            exclude: self.exclude,
+        }
+    }
+}
+
+/// The smallest unit the input is split into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Granularity {
+    /// Every byte is a symbol.
+    Byte,
+    /// Every byte is split into its high and low nibble, in that order.
+    Nibble,
+}
+
+/// How the input is split into symbols.
+#[derive(Debug, Clone, Copy, Args)]
+struct Symbols {
+    /// Analyze the input per byte or per nibble.
+    #[arg(long, value_enum, default_value_t = Granularity::Byte, conflicts_with = "word_size")]
+    granularity: Granularity,
+    /// Analyze the input as bytes (1) or as 16-bit words (2), an odd trailing byte is dropped for words.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    word_size: u8,
+    /// The byte order of 16-bit words, `le` or `be`.
+    #[arg(long, default_value_t = Endian::Little)]
+    endian: Endian,
+}
+
+impl Symbols {
+    /// Whether the input is analyzed as 16-bit words.
+    fn is_words(&self) -> bool {
+        self.word_size == 2
+    }
+
+    /// Whether the input is analyzed as nibbles.
+    fn is_nibbles(&self) -> bool {
+        self.granularity == Granularity::Nibble
+    }
+
+    /// The name of a symbol and how many bits it has.
+    fn unit(&self) -> (&'static str, f64) {
+        if self.is_words() {
+            ("word", 16.0)
+        } else if self.is_nibbles() {
+            ("nibble", 4.0)
+        } else {
+            ("byte", 8.0)
+        }
+    }
+}
+
+/// Parse a number in decimal, or in hexadecimal when prefixed by `0x`.
+fn parse_number(value: &str) -> Result<u64, ParseIntError> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+}
+
+/// Format the range of `length` bytes at `offset`, e.g. `0x1000..0x5000`, or `0x1000..` up to the end.
+fn format_range(offset: u64, length: Option<u64>) -> String {
+    match length {
+        Some(length) => format!("{:#x}..{:#x}", offset, offset.saturating_add(length)),
+        None => format!("{:#x}..", offset),
+    }
+}
+
+/// Parse an order of the Rényi entropy, a non-negative number or `inf`.
+fn parse_renyi_order(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(alpha) if alpha >= 0.0 => Ok(alpha),
+        _ => Err(format!(
+            "`{}` isn't a Rényi entropy order, expected a non-negative number or `inf`",
+            value
+        )),
+    }
+}
+
+/// Parse the dimensions of the entropy table of the full analysis, a single dimension or a range `min-max`.
+fn parse_dimensions(value: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |dimension: &str| match dimension.trim().parse::<usize>() {
+        Ok(dimension) if dimension >= 1 => Ok(dimension),
+        _ => Err(format!(
+            "`{}` isn't a dimension, expected a number of at least 1",
+            dimension.trim()
+        )),
+    };
+    let (min, max) = match value.split_once('-') {
+        Some((min, max)) => (parse(min)?, parse(max)?),
+        None => (parse(value)?, parse(value)?),
+    };
+    if min > max {
+        return Err(format!("the dimensions `{}` end before they start", value));
+    }
+    Ok(min..=max)
+}
+
+/// How a histogram compares to a reference histogram.
+struct ReferenceComparison {
+    cross_entropy: f64,
+    entropy: f64,
+    divergence: f64,
+    hellinger: f64,
+    total_variation: f64,
+}
+
+/// Look up the built-in reference named `reference`, or else load the reference from that path.
+fn obtain_reference(reference: &str) -> Result<Histogram<u8>, BinvizError> {
+    match builtin_reference(reference) {
+        Some(reference) => Ok(reference),
+        None => load_reference(reference),
+    }
+}
+
+/// Compare `histogram` to a reference, see [`obtain_reference`].
+fn compare_to_reference(
+    histogram: &Histogram<u8>,
+    reference: &str,
+    smoothing: Smoothing,
+) -> Result<ReferenceComparison, BinvizError> {
+    let reference = obtain_reference(reference)?;
+    Ok(ReferenceComparison {
+        cross_entropy: cross_entropy(histogram, &reference, smoothing)?,
+        entropy: calculate_entropy_histogram(histogram, EntropyUnit::Bits)?,
+        divergence: kl_divergence(histogram, &reference, smoothing)?,
+        hellinger: hellinger(histogram, &reference)?,
+        total_variation: total_variation(histogram, &reference)?,
+    })
+}
+
+/// A byte sequence to locate, see [`parse_ngram`].
+#[derive(Debug, Clone)]
+struct Ngram(Vec<u8>);
+
+fn parse_locate(value: &str) -> Result<Ngram, String> {
+    parse_ngram(value).map(Ngram).ok_or_else(|| {
+        format!(
+            "`{}` isn't a non-empty byte sequence in hexadecimal, e.g. `ffd8`",
+            value
+        )
+    })
+}
+
+/// A sample file to take a fingerprint of, see [`parse_fingerprint`].
+#[derive(Debug, Clone)]
+struct FingerprintSample {
+    label: Label,
+    path: PathBuf,
+}
+
+/// Parse a fingerprint sample of the form `label=path`.
+fn parse_fingerprint(value: &str) -> Result<FingerprintSample, String> {
+    let (label, path) = value
+        .split_once('=')
+        .ok_or_else(|| format!("`{}` isn't of the form `label=path`", value))?;
+    Ok(FingerprintSample {
+        label: label.parse()?,
+        path: PathBuf::from(path),
+    })
+}
+
 #[derive(Debug, Clone, Subcommand)]
 enum Mode {
     Di,
     Tri,
     Quartic,
+    /// Show which byte values occur where in the file, with the offset along the x axis
+    /// and the byte value along the y axis, like a spectrogram.
+    Offset {
+        /// Split the file into this many equally large slices, one column of the image each.
+        #[arg(long, default_value_t = OFFSET_BUCKETS)]
+        buckets: u32,
+        /// Scale every column to its own count at full brightness instead of that of the whole image,
+        /// which leaves out the colorbar.
+        #[arg(long)]
+        per_column: bool,
+    },
 }
 
 #[derive(Debug, Clone, Subcommand)]
 enum CliCommand {
     /// Calculate the n-dimensional entropy of a given file, for n in 1..=count, in bits per `n` bytes.
     Entropy {
+        /// The file to analyze, `-` reads standard input.
         #[arg(short, long)]
         file: PathBuf,
         #[arg(short, long)]
         count: usize,
+        /// Advance the windows by this many bytes, 1 counts every overlapping window,
+        /// while a stride equal to the dimension counts non-overlapping chunks.
+        #[arg(short, long, default_value_t = 1)]
+        stride: usize,
+        #[command(flatten)]
+        input: Input,
+        #[command(flatten)]
+        symbols: Symbols,
+        /// Add columns with the Rényi entropies of these comma separated orders, `inf` is the min-entropy.
+        #[arg(long, value_delimiter = ',', value_parser = parse_renyi_order)]
+        renyi: Vec<f64>,
+        /// The unit of the entropies: `bits`, `nats` or `dits`.
+        #[arg(long, default_value_t = EntropyUnit::Bits)]
+        unit: EntropyUnit,
+        /// How the entropies are estimated: `plugin`, the entropy of the observed frequencies,
+        /// or `miller-madow`, which corrects its bias for sparse histograms and adds a column with the plug-in entropy.
+        #[arg(long, default_value_t = EntropyEstimator::Plugin)]
+        estimator: EntropyEstimator,
+        /// Also compress the input with deflate and report the compression ratio,
+        /// requires binviz to be built with the `compression` feature.
+        #[arg(long)]
+        compress: bool,
+        /// Print the table as `markdown`, `csv` or `tsv`, the other formats print only the table.
+        /// `json` prints a document with the entropy in bits and the relative entropy of every dimension.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+        /// Also save a line chart of the entropy per dimension to this path as SVG.
+        #[arg(long)]
+        chart_svg: Option<PathBuf>,
+        /// Keep watching the file, printing the entropies again whenever it changes, until Ctrl-C.
+        #[arg(long)]
+        watch: bool,
     },
     /// Get the bytes in sorted order according to their frequency of a given file.
     Frequency {
-        #[arg(short, long)]
-        file: PathBuf,
+        /// The file to analyze, `-` reads standard input.
+        #[arg(short, long, required_unless_present = "load_histogram")]
+        file: Option<PathBuf>,
+        /// The amount of consecutive bytes per counted value, 2 counts byte pairs.
+        #[arg(short, long, default_value_t = 1, conflicts_with = "locate")]
+        dimension: usize,
+        /// Advance the windows by this many bytes, 1 counts every overlapping window,
+        /// while a stride equal to the dimension counts non-overlapping chunks.
+        #[arg(short, long, default_value_t = 1)]
+        stride: usize,
+        #[command(flatten)]
+        input: Input,
+        #[command(flatten)]
+        symbols: Symbols,
+        /// Save the calculated histogram to this path, as JSON if it ends in `.json` and bincode otherwise.
+        #[arg(long)]
+        save_histogram: Option<PathBuf>,
+        /// Load a histogram saved with `--save-histogram` instead of reading the file.
+        #[arg(long, conflicts_with = "file")]
+        load_histogram: Option<PathBuf>,
+        /// Instead of the frequencies, list the offsets at which this hexadecimal byte sequence occurs, e.g. `ffd8`.
+        #[arg(
+            long,
+            value_parser = parse_locate,
+            conflicts_with_all = ["load_histogram", "save_histogram", "offset", "length", "ignore_bytes", "only_bytes", "granularity", "word_size"]
+        )]
+        locate: Option<Ngram>,
+        /// Stop after this many occurrences of the `--locate` byte sequence.
+        #[arg(long, requires = "locate")]
+        limit: Option<usize>,
+        /// Show this many bytes before and after every occurrence of the `--locate` byte sequence.
+        #[arg(long, requires = "locate", default_value_t = 8)]
+        context: usize,
+        /// Compare the bytes to this reference distribution, by cross-entropy, Kullback-Leibler divergence and distances.
+        /// Either a built-in reference, `uniform` or `english`, a CSV file of `byte,probability` lines,
+        /// or a histogram saved with `--save-histogram`.
+        #[arg(long, conflicts_with_all = ["locate", "word_size"])]
+        reference: Option<String>,
+        /// How bytes missing in the `--reference` are handled,
+        /// `strict` fails while a number is added to the count of every byte.
+        #[arg(long, requires = "reference", default_value = "1")]
+        smoothing: Smoothing,
+        /// Add the expected relative frequency of every byte in this baseline distribution to the table,
+        /// with the ratio and z-score of the observed relative frequency.
+        /// Either a built-in reference, `uniform` or `english`, a CSV file of `byte,probability` lines,
+        /// or a histogram saved with `--save-histogram`.
+        #[arg(long, conflicts_with_all = ["locate", "word_size"])]
+        baseline: Option<String>,
+        /// Only show this many of the most frequent values, followed by a summary of the rest.
+        #[arg(long, conflicts_with = "locate")]
+        top: Option<usize>,
+        /// Sort the values by `count-desc`, `count-asc`, `key` or `deviation`, values with equal counts are sorted by key.
+        /// Sorting by `deviation` puts the largest absolute z-scores against the `--baseline` first.
+        #[arg(long, default_value_t = SortOrder::CountDescending, conflicts_with = "locate")]
+        sort: SortOrder,
+        /// List this many of the least frequent bytes below the bytes that never occur.
+        #[arg(long, default_value_t = LEAST_FREQUENT_COUNT, conflicts_with = "locate")]
+        least: usize,
+        /// Print the table as `markdown`, `csv` or `tsv`, the other formats print only the table.
+        /// `json` prints a document with the rank, bytes, count and probability of every value and the totals.
+        #[arg(long, default_value_t = OutputFormat::Markdown, conflicts_with = "locate")]
+        format: OutputFormat,
+        /// Add the longest runs of a single repeated byte, and how much of the file they cover, e.g. padding.
+        #[arg(long, conflicts_with_all = ["locate", "load_histogram", "ignore_bytes", "only_bytes"])]
+        runs: bool,
+        /// Add the strongest periods of the bytes of up to this many bytes, 1024 if no amount is given,
+        /// e.g. the size of fixed-size records, ranked by the autocorrelation of the bytes at that distance.
+        /// Large files are analyzed in evenly spread segments.
+        #[arg(
+            long,
+            num_args = 0..=1,
+            default_missing_value = DEFAULT_MAX_PERIOD.to_string(),
+            conflicts_with_all = ["locate", "load_histogram", "word_size"]
+        )]
+        periods: Option<usize>,
+        /// Also save the power spectrum behind `--periods` to this path as PNG, where a period shows up
+        /// as bars at the multiples of its frequency.
+        #[arg(long, requires = "periods")]
+        spectrum_png: Option<PathBuf>,
+        /// Add the most likely keys the file could be XORed with, a single byte each, ranked by how much
+        /// the decoded bytes look like English text or zero padded data, with a preview of the decoded start.
+        #[arg(long, conflicts_with_all = ["locate", "load_histogram", "word_size"])]
+        detect_xor: bool,
+        /// Also save a bar chart of the most frequent values to this path as SVG, as many as `--top` or 16.
+        #[arg(long, conflicts_with = "locate")]
+        chart_svg: Option<PathBuf>,
     },
     /// Visualize the given file as an image (digraph analysis).
     ///
@@ -40,71 +482,1589 @@ enum CliCommand {
     /// This can show conditional relationships within a binary file.
     /// Distinct file formats will produce distinct recognizable patterns in the image.
     Visualize {
+        /// The file to visualize, `-` reads standard input.
+        /// Given multiple times the merged histogram of all files is visualized.
+        #[arg(short, long, required_unless_present = "load_histogram")]
+        file: Vec<PathBuf>,
+        /// Advance the windows by this many bytes, 1 counts every overlapping window,
+        /// while a stride equal to the dimension counts non-overlapping chunks.
+        #[arg(short, long, default_value_t = 1)]
+        stride: usize,
+        #[command(flatten)]
+        input: Input,
+        #[command(flatten)]
+        symbols: Symbols,
+        /// Save the calculated histogram to this path, as JSON if it ends in `.json` and bincode otherwise.
+        #[arg(long)]
+        save_histogram: Option<PathBuf>,
+        /// Load a histogram saved with `--save-histogram` instead of reading the file.
+        #[arg(long, conflicts_with = "file")]
+        load_histogram: Option<PathBuf>,
+        /// How counts map to brightness: `linear`, where the average count is full brightness,
+        /// or `log` and `sqrt` relative to the maximum count, which show rare pairs next to frequent ones.
+        #[arg(long, default_value_t = ScaleMode::Linear)]
+        scale: ScaleMode,
+        /// The count shown at full brightness, higher counts are clamped: `average`, `max` or `percentile:P`,
+        /// e.g. `percentile:99`. Defaults to the average for `linear` and the maximum for the other scales.
+        #[arg(long)]
+        normalize: Option<Normalization>,
+        /// Color the digraph and offset images with `grayscale`, `viridis`, `inferno` or `turbo`.
+        /// The trigraph images already use color, so they ignore it.
+        #[arg(long, default_value_t = Colormap::Grayscale)]
+        colormap: Colormap,
+        /// Scale the image up by this factor, so every cell becomes a block of this many pixels wide,
+        /// at most 64.
+        #[arg(long, default_value_t = 1)]
+        scale_factor: u32,
+        /// Leave out the colorbar right of the image, labeled with the counts of its brightnesses,
+        /// keeping the bare image.
+        #[arg(long)]
+        no_legend: bool,
+        /// Save the image to this path instead of `output.png`, its extension sets the format unless `--image-format` is given.
         #[arg(short, long)]
-        file: PathBuf,
+        output: Option<PathBuf>,
+        /// Save the image as `png`, `bmp`, `tiff` or `qoi`, `bmp` and `qoi` need a `--bit-depth` of 8.
+        #[arg(long)]
+        image_format: Option<ImageFormat>,
+        /// The bits per channel of the saved image, `8` for viewers that mangle 16-bit images or `16` for full precision.
+        #[arg(long, default_value_t = BitDepth::Sixteen)]
+        bit_depth: BitDepth,
+        /// Also write the exact counts behind the digraph image to this path, as a sparse `x,y,count` list
+        /// if it ends in `.csv` or `.json` and as a 16-bit PGM image with clamped counts otherwise.
+        #[arg(long)]
+        counts_output: Option<PathBuf>,
+        /// Draw faint lines over the image at the byte values 0x20 and 0x80,
+        /// separating control bytes, printable ASCII and high bytes.
+        #[arg(long)]
+        grid: bool,
+        /// Draw the grid lines at every multiple of 0x10 instead.
+        #[arg(long, requires = "grid")]
+        grid_fine: bool,
+        /// The color of the grid lines, as `#rrggbb`.
+        #[arg(long, requires = "grid", default_value = "#404040", value_parser = parse_color)]
+        grid_color: Rgb<u8>,
+        /// Draw axes around the image, with tick marks and hexadecimal labels of the symbol values.
+        #[arg(long)]
+        axes: bool,
+        /// Give the pixels of pairs that never occur this color, as `#rrggbb`, or make them `transparent`,
+        /// so they stand apart from rare pairs. The digraph image then always uses a colormap.
+        #[arg(long)]
+        background: Option<Background>,
+        /// Also export the trigraph as a 3D point cloud to this ASCII PLY file, e.g. to rotate it in MeshLab.
+        #[arg(long)]
+        export_ply: Option<PathBuf>,
+        /// Leave byte triples seen fewer than this many times out of the `--export-ply` point cloud.
+        #[arg(long, requires = "export_ply", default_value_t = 1)]
+        ply_threshold: usize,
+        /// Also save the trigraph as an animated GIF to this path, frame `k` shows the byte pairs followed by byte `k`.
+        #[arg(long)]
+        animate: Option<PathBuf>,
+        /// How long every frame of the `--animate` animation is shown, in milliseconds.
+        #[arg(long, requires = "animate", default_value_t = 50)]
+        frame_delay: u32,
+        /// Leave the frames without any byte triple out of the `--animate` animation.
+        #[arg(long, requires = "animate")]
+        skip_empty_frames: bool,
+        /// Visualize every file on its own instead of their merged histogram, saving the image of `sample.bin`
+        /// as `sample.bin.png` in `--output-dir`, in the same subfolders as relative paths like `samples/sample.bin`.
+        #[arg(long, conflicts_with_all = ["output", "load_histogram", "save_histogram", "counts_output", "export_ply", "animate"])]
+        each: bool,
+        /// The folder `--each` saves the images in, created if it doesn't exist.
+        #[arg(long, requires = "each", default_value = ".")]
+        output_dir: PathBuf,
+        /// Continue with the next file when visualizing a file with `--each` fails,
+        /// listing the failures at the end instead of stopping at the first one.
+        #[arg(long, requires = "each")]
+        keep_going: bool,
+        /// Keep watching the files, saving the images again whenever one of them changes, until Ctrl-C.
+        #[arg(long, conflicts_with = "load_histogram")]
+        watch: bool,
         #[command(subcommand)]
         mode: Mode,
     },
+    /// Calculate the entropy of consecutive windows of a given file, showing where its entropy changes,
+    /// e.g. where plaintext ends and compressed or encrypted data starts.
+    Profile {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// The amount of bytes per window.
+        #[arg(short, long, default_value_t = 4096)]
+        window_size: usize,
+        /// Start consecutive windows this many bytes apart.
+        #[arg(long, default_value_t = 4096)]
+        step: usize,
+        /// The width of the rendered entropy curve, saved to `profile.png`.
+        #[arg(long, default_value_t = 1024)]
+        width: u32,
+        /// The height of the rendered entropy curve, saved to `profile.png`.
+        #[arg(long, default_value_t = 256)]
+        height: u32,
+        /// Add a column with the deflate compression ratio of every window,
+        /// requires binviz to be built with the `compression` feature.
+        #[arg(long)]
+        compress: bool,
+        /// Also save a map of the windows colored by their entropy to `entropy_map.png`,
+        /// e.g. with windows of 256 bytes to tell packed sections from code and padding.
+        #[arg(long)]
+        entropy_map: bool,
+        /// How the windows are laid out in the entropy map, along a `hilbert` or `zorder` curve, or `raw` row by row.
+        #[arg(long, requires = "entropy_map", default_value_t = MapLayout::Hilbert)]
+        map_layout: MapLayout,
+        /// The colormap of the entropy map, from 0 to 8 bits per byte.
+        #[arg(long, requires = "entropy_map", default_value_t = Colormap::Turbo)]
+        colormap: Colormap,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// List the statistics of every block of a file: its offset range, entropy, amount of distinct bytes
+    /// and most frequent byte, followed by the lowest, highest and mean block entropy.
+    ///
+    /// With `--threshold` only the blocks above or below an entropy are listed,
+    /// to quickly locate encrypted or padded regions.
+    Blocks {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// The amount of bytes per block.
+        #[arg(short, long, default_value_t = 4096)]
+        block_size: usize,
+        /// Only list the blocks with an entropy of at least this many bits per byte.
+        #[arg(long)]
+        threshold: Option<f64>,
+        /// List the blocks with an entropy of at most the `--threshold` instead.
+        #[arg(long, requires = "threshold")]
+        below: bool,
+        /// Print the table as `markdown`, `csv` or `tsv`, the other formats print only the table.
+        /// `json` prints a document with the statistics of the listed blocks and the summary.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Compare the histograms of two files, showing the byte sequences whose counts increased
+    /// and decreased the most from the old file to the new one.
+    Diff {
+        #[arg(long)]
+        old: PathBuf,
+        #[arg(long)]
+        new: PathBuf,
+        /// The amount of consecutive bytes per histogram key.
+        #[arg(short, long, default_value_t = 1)]
+        dimension: usize,
+        /// Advance the windows by this many bytes, 1 counts every overlapping window,
+        /// while a stride equal to the dimension counts non-overlapping chunks.
+        #[arg(short, long, default_value_t = 1)]
+        stride: usize,
+        /// The amount of increases and decreases to show.
+        #[arg(short, long, default_value_t = 10)]
+        count: usize,
+        /// Added to both counts when calculating ratios, so keys missing in one file get a finite ratio.
+        #[arg(long, default_value_t = 1.0)]
+        smoothing: f64,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Compare two files by the distributions of their bytes and byte pairs, printing the Kullback-Leibler
+    /// divergence both ways, the Jensen-Shannon divergence, chi-square, cosine similarity and entropies.
+    ///
+    /// Optionally visualize both files side by side, as their digraph images with the same brightness scale,
+    /// to compare variants at a glance.
+    Compare {
+        #[arg(long)]
+        file_a: PathBuf,
+        #[arg(long)]
+        file_b: PathBuf,
+        /// How keys missing in one of the files are handled in the Kullback-Leibler divergences,
+        /// `strict` fails while a number is added to the count of every key.
+        #[arg(long, default_value = "1")]
+        smoothing: Smoothing,
+        /// Print the metrics as `markdown`, `csv`, `tsv` or `json`.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+        /// Save the side-by-side image as `compare.png` and the difference image as `diff.png` in this folder.
+        #[arg(long, conflicts_with_all = ["output", "diff_output"])]
+        images: Option<PathBuf>,
+        /// Save the side-by-side image to this path, its extension sets the format.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Save an image of the differences to this path, pairs more frequent in the second file
+        /// are red and less frequent pairs are blue, with the second file scaled to the size of the first.
+        #[arg(long)]
+        diff_output: Option<PathBuf>,
+        /// How counts map to brightness: `linear`, where the average count is full brightness,
+        /// or `log` and `sqrt` relative to the maximum count, which show rare pairs next to frequent ones.
+        #[arg(long, default_value_t = ScaleMode::Linear)]
+        scale: ScaleMode,
+        /// The count shown at full brightness in both panels, the larger of both files: `average`, `max` or `percentile:P`.
+        /// Defaults to the average for `linear` and the maximum for the other scales.
+        #[arg(long)]
+        normalize: Option<Normalization>,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Guess the kind of data in a file by comparing the distributions of its bytes and byte pairs to fingerprints
+    /// of known kinds, ranking them by the Jensen-Shannon distances: ASCII and UTF-8 text, x86-64 machine code,
+    /// JPEG, deflate compressed, random or encrypted and zero data.
+    Classify {
+        #[arg(short, long)]
+        file: PathBuf,
+        #[command(flatten)]
+        input: Input,
+        /// Also compare the file to the fingerprint of a sample file, as `label=path`, can be repeated.
+        #[arg(long, value_parser = parse_fingerprint)]
+        fingerprint: Vec<FingerprintSample>,
+        /// Only show this many of the best matches.
+        #[arg(long)]
+        top: Option<usize>,
+        /// Print the table as `markdown`, `csv`, `tsv` or `json`.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+    },
+    /// Model a given file as a first-order Markov chain, the probability of every byte following every other byte,
+    /// which the digraph shows as an image, and print the stationary distribution of the model:
+    /// the share of every byte in its output in the long run.
+    Markov {
+        /// The file to model, `-` reads standard input.
+        #[arg(short, long)]
+        file: PathBuf,
+        #[command(flatten)]
+        input: Input,
+        /// Save the transition matrix to this path as CSV, a line per byte with the probabilities
+        /// of the bytes following it.
+        #[arg(long)]
+        csv: Option<PathBuf>,
+        /// Save synthetic bytes generated by the model to this path,
+        /// decoy data with the byte pair statistics of the file.
+        #[arg(long)]
+        sample: Option<PathBuf>,
+        /// The amount of synthetic bytes, in decimal or `0x` prefixed hexadecimal, defaults to the size of the input.
+        #[arg(long, requires = "sample", value_parser = parse_number)]
+        sample_len: Option<u64>,
+        /// The seed of the synthetic bytes, the same seed generates the same bytes.
+        #[arg(long, requires = "sample", default_value_t = 0)]
+        seed: u64,
+        /// Only show this many of the most probable bytes.
+        #[arg(long)]
+        top: Option<usize>,
+        /// Print the table as `markdown`, `csv`, `tsv` or `json`.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+    },
+    /// Render a map of a given file, where every byte is a pixel colored by its value or class,
+    /// laid out so bytes close in the file are close in the image. Saved to `map.png`.
+    ///
+    /// Unlike the digraph this shows where in the file things are rather than which bytes occur together.
+    Map {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// How the bytes are laid out, along a `hilbert` or `zorder` curve, or `raw` row by row.
+        #[arg(long, default_value_t = MapLayout::Hilbert)]
+        layout: MapLayout,
+        /// The amount of bytes per row of the `raw` layout, records of this size show up as vertical stripes.
+        #[arg(long)]
+        width: Option<u32>,
+        /// Color the bytes by `class`, or by value with `grayscale`, `viridis`, `inferno` or `turbo`.
+        #[arg(long, alias = "colormap", default_value_t = ColorScheme::default())]
+        color_scheme: ColorScheme,
+        /// Add a strip below the map explaining the colors of the `class` color scheme.
+        #[arg(long)]
+        legend: bool,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// List the strings of printable ASCII, or UTF-16 text, in a given file with their offsets, like `strings`.
+    Strings {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Only list strings of at least this many characters.
+        #[arg(long, default_value_t = MIN_STRING_LENGTH)]
+        min_len: usize,
+        /// Cap longer strings at this many characters.
+        #[arg(long, default_value_t = MAX_STRING_LENGTH)]
+        max_len: usize,
+        /// Look for `ascii` strings, `utf16le` strings or `both`.
+        #[arg(long, default_value_t = Charset::Ascii)]
+        charset: Charset,
+        /// Print the table as `markdown`, `csv`, `tsv` or `json`.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+    },
+    /// Scan the files in folders for triage, ranking them in a single table by the entropy of their bytes,
+    /// with their size and a crude verdict: text, binary or compressed-or-encrypted.
+    ///
+    /// Every file is read in chunks, files that can't be read are skipped with a warning.
+    Scan {
+        /// The folders to scan, searching their subfolders, or files to scan themselves, can be repeated.
+        #[arg(short, long, required = true)]
+        dir: Vec<PathBuf>,
+        #[command(flatten)]
+        walk: Walk,
+        /// Rank the files by `entropy` or `size`, the highest first, or by `name`.
+        #[arg(long, default_value_t = ScanSort::Entropy)]
+        sort: ScanSort,
+        /// Add the chi-square statistic of the bytes against the uniform distribution and its p-value.
+        #[arg(long)]
+        chi_square: bool,
+        /// Skip the files smaller than this many bytes, in decimal or `0x` prefixed hexadecimal.
+        #[arg(long, value_parser = parse_number)]
+        min_size: Option<u64>,
+        /// Skip the files larger than this many bytes, in decimal or `0x` prefixed hexadecimal.
+        #[arg(long, value_parser = parse_number)]
+        max_size: Option<u64>,
+        /// Print the table as `markdown`, `csv`, `tsv` or `json`.
+        #[arg(long, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+    },
     /// Perform a full analysis, by performing all other commands on every file
     /// and collecting the output into folders corresponding to each file.
     Full {
+        /// The files to analyze, the files in folders are analyzed as well, searching their subfolders.
         #[arg(short, long)]
         files: Vec<PathBuf>,
+        #[command(flatten)]
+        walk: Walk,
+        /// Analyze this many files at once, 0 analyzes as many as there are cores.
+        #[arg(short, long, default_value_t = 0)]
+        jobs: usize,
+        /// Save the images as `png`, `bmp`, `tiff` or `qoi`, `bmp` and `qoi` can't store 16-bit channels.
+        #[arg(long, default_value_t = ImageFormat::Png)]
+        image_format: ImageFormat,
+        /// The dimensions of the entropy table, a single dimension or a range like `1-4`.
+        #[arg(long, default_value = "1-3", value_parser = parse_dimensions)]
+        entropy_dimensions: RangeInclusive<usize>,
+        /// Leave out the digraph image and the `counts.csv` behind it.
+        #[arg(long)]
+        no_digraph: bool,
+        /// Leave out the trigraph color image, as counting the byte triples of large files is slow.
+        #[arg(long)]
+        no_trigraph: bool,
+        /// Also write a self-contained `report.html` for every file, with its tables and images on one page,
+        /// and an `index.html` in `--output-dir` linking them.
+        #[arg(long)]
+        html: bool,
+        /// Write only the HTML reports, leaving out the text files and images.
+        #[arg(long)]
+        html_only: bool,
+        /// The folder the folders of the files and the summaries go in, created if it doesn't exist.
+        #[arg(long, default_value = FULL_ANALYSIS_FOLDER)]
+        output_dir: PathBuf,
+        /// Only list this many of the most frequent bytes, instead of all of them in `most_frequent.txt`
+        /// and 32 in the HTML reports.
+        #[arg(long)]
+        top: Option<usize>,
+        /// Order the rows of `summary.txt` and `summary.csv`, the table of all files rewritten on every run,
+        /// by `entropy` or `size`, the highest first, or by `name`. Failed files come last.
+        #[arg(long, default_value_t = ScanSort::Entropy)]
+        sort_by: ScanSort,
+    },
+    /// Explore a file in the terminal: the frequencies of its bytes, the entropy of its blocks
+    /// and its byte pairs drawn in braille.
+    ///
+    /// Select a block with the arrow keys or `g` and an offset, and press Enter to show the frequencies
+    /// and pairs of that block. `+` and `-` change the block size, `l` toggles a log scale and `q` quits.
+    Tui {
+        /// The file to explore, standard input can't be explored as the keys are read from the terminal.
+        #[arg(short, long)]
+        file: PathBuf,
+        /// The amount of bytes per block of the entropy pane, at least 64.
+        #[arg(short, long, default_value_t = 4096)]
+        block_size: usize,
+    },
+    /// Print the completion script of a shell, or save the scripts of the shells to a folder.
+    ///
+    /// The scripts are generated from the same arguments the command line is parsed with, so they are always complete.
+    Completions {
+        /// The shell to complete in: `bash`, `zsh`, `fish`, `powershell` or `elvish`, required unless `--dir` is given.
+        #[arg(required_unless_present = "dir")]
+        shell: Option<Shell>,
+        /// Save the script to this folder instead of printing it, those of bash, zsh, fish and PowerShell unless a shell is given.
+        #[arg(long)]
+        dir: Option<PathBuf>,
     },
 }
 
 #[derive(Debug, Parser)]
 struct Cli {
+    /// Don't draw progress bars, which are also left out when standard error isn't a terminal.
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Overwrite existing output files and the folders of the full analysis, instead of refusing to.
+    #[arg(long, global = true)]
+    force: bool,
+    /// Take the defaults of the options from this TOML file only, instead of from `binviz.toml` in the current folder
+    /// overriding `binviz/config.toml` in `$XDG_CONFIG_HOME` or `~/.config`. Options given on the command line win.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Don't read any config file.
+    #[arg(long, global = true, conflicts_with = "config")]
+    no_config: bool,
     #[command(subcommand)]
     command: CliCommand,
 }
 
+/// A logger printing its lines above the progress bars, instead of in the middle of them.
+struct ProgressLogger {
+    logger: env_logger::Logger,
+    bars: MultiProgress,
+}
+
+impl Log for ProgressLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.logger.matches(record) {
+            self.bars.suspend(|| self.logger.log(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.logger.flush();
+    }
+}
+
+/// The definition of the command line, from which it is parsed and the completion scripts are generated.
+fn cli_command() -> Command {
+    Cli::command()
+}
+
+/// Use the values in `config` as the defaults of the options of `command` and its subcommands,
+/// warning about the options and subcommands in it that don't exist.
+fn apply_config(mut command: Command, config: &Config) -> Command {
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for name in config.commands.keys().filter(|name| !names.contains(name)) {
+        warn!(
+            "there is no subcommand `{}`, ignoring its section in the config.",
+            name
+        );
+    }
+    let mut known: HashSet<String> = HashSet::new();
+    let (root, ids) = apply_options(command, config, "");
+    command = root;
+    known.extend(ids);
+    for name in &names {
+        let mut ids = Vec::new();
+        command = command.mut_subcommand(name, |subcommand| {
+            apply_section(subcommand, config, name, &mut ids)
+        });
+        for option in config
+            .commands
+            .get(name)
+            .into_iter()
+            .flat_map(BTreeMap::keys)
+        {
+            if !ids.contains(option) {
+                warn!(
+                    "`{}` has no option `{}`, ignoring it in the config.",
+                    name, option
+                );
+            }
+        }
+        known.extend(ids);
+    }
+    for option in config
+        .defaults
+        .keys()
+        .filter(|option| !known.contains(*option))
+    {
+        warn!(
+            "no subcommand has an option `{}`, ignoring it in the config.",
+            option
+        );
+    }
+    command
+}
+
+/// Use the values of the section `name` of `config` as the defaults of the options of `command`,
+/// returning it with the names of its options.
+///
+/// An option with a value in the config is no longer required on the command line.
+fn apply_options(mut command: Command, config: &Config, name: &str) -> (Command, Vec<String>) {
+    let ids: Vec<String> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .collect();
+    for id in &ids {
+        if let Some(values) = config.get(name, id) {
+            let values = values.to_vec();
+            command = command.mut_arg(id, |arg| arg.default_values(values).required(false));
+        }
+    }
+    (command, ids)
+}
+
+/// Use the values of the section `name` of `config` as the defaults of the options of `command`
+/// and of its nested subcommands, like the modes of `visualize`, adding the names of their options to `ids`.
+fn apply_section(command: Command, config: &Config, name: &str, ids: &mut Vec<String>) -> Command {
+    let (mut command, own) = apply_options(command, config, name);
+    ids.extend(own);
+    let nested: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for subcommand in &nested {
+        command = command.mut_subcommand(subcommand, |subcommand| {
+            apply_section(subcommand, config, name, ids)
+        });
+    }
+    command
+}
+
 fn main() {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-    let args = Cli::parse();
-    match args.command {
-        CliCommand::Entropy { file, count } => {
+    // Show the progress bars until `--quiet` is parsed, the logger has to print the warnings about the config first.
+    let bars =
+        PROGRESS_BARS.get_or_init(|| MultiProgress::with_draw_target(ProgressDrawTarget::stderr()));
+    let logger = env_logger::Builder::from_env(Env::default().default_filter_or("info")).build();
+    log::set_max_level(logger.filter());
+    log::set_boxed_logger(Box::new(ProgressLogger {
+        logger,
+        bars: bars.clone(),
+    }))
+    .expect("the logger is only set once");
+    // Find the config to read first, ignoring errors like missing options the config may give values for.
+    let matches = cli_command().ignore_errors(true).try_get_matches().ok();
+    let no_config = matches
+        .as_ref()
+        .is_some_and(|matches| matches.get_flag("no_config"));
+    let config_path = matches
+        .as_ref()
+        .and_then(|matches| matches.get_one::<PathBuf>("config").cloned());
+    // Parse the command line with the defaults from the config.
+    let mut command = cli_command();
+    if !no_config {
+        let config = load_config(config_path.as_deref()).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(EXIT_ANALYSIS_ERROR);
+        });
+        if !config.is_empty() {
+            command = apply_config(command, &config);
+        }
+    }
+    let args = Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|err| err.exit());
+    if args.quiet {
+        bars.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    if let Err(err) = run(args.command, args.force) {
+        eprintln!("error: {}", err);
+        process::exit(exit_code(&err));
+    }
+}
+
+/// The exit code of the error `err`, telling errors writing the outputs apart from the rest.
+/// A batch exits as writing the outputs failed only if that is why every file failed.
+fn exit_code(err: &BinvizError) -> i32 {
+    match err {
+        BinvizError::Write { .. }
+        | BinvizError::Image { .. }
+        | BinvizError::OutputExists { .. } => EXIT_OUTPUT_ERROR,
+        BinvizError::Batch { failures, .. }
+            if failures
+                .iter()
+                .all(|(_, err)| exit_code(err) == EXIT_OUTPUT_ERROR) =>
+        {
+            EXIT_OUTPUT_ERROR
+        }
+        _ => EXIT_ANALYSIS_ERROR,
+    }
+}
+
+/// Add a progress bar to the [`PROGRESS_BARS`], styled by `template` and labelled `message`.
+fn progress_bar(template: &str, message: String) -> ProgressBar {
+    let style = ProgressStyle::with_template(template)
+        .expect("the progress bar templates are valid")
+        .progress_chars("=> ");
+    let bar = ProgressBar::new(0).with_style(style).with_message(message);
+    match PROGRESS_BARS.get() {
+        Some(bars) => bars.add(bar),
+        None => bar,
+    }
+}
+
+/// A progress bar of the bytes of `file` counted so far, with the throughput and the time remaining.
+fn bytes_progress_bar(file: &Path) -> ProgressBar {
+    progress_bar(
+        "{msg} [{bar:40}] {decimal_bytes}/{decimal_total_bytes} ({decimal_bytes_per_sec}, {eta} left)",
+        display_name(file),
+    )
+}
+
+/// Move `bar` along with the [`ProgressEvent::Bytes`] events.
+fn track_bytes(bar: &ProgressBar, event: ProgressEvent) {
+    if let ProgressEvent::Bytes { processed, total } = event {
+        bar.set_length(total);
+        bar.set_position(processed);
+    }
+}
+
+/// Calculate the histogram of `file`, in parallel over all cores if it is larger than [`PARALLEL_THRESHOLD`].
+///
+/// The path [`STDIN_PATH`] reads from standard input instead.
+fn compute_histogram(
+    file: &Path,
+    dimension: usize,
+    stride: usize,
+    input: Input,
+) -> Result<Histogram<u8>, BinvizError> {
+    validate_window(dimension, stride)?;
+    if let Some(filter) = input.filter() {
+        if file != Path::new(STDIN_PATH) && input.is_full() {
+            return calculate_histogram_filtered(file, dimension, stride, |byte| {
+                filter.keeps(byte)
+            });
+        }
+        let buf = read_input(file, input)?;
+        return Ok(calculate_histogram_from_bytes(&buf, dimension, stride));
+    }
+    if file == Path::new(STDIN_PATH) {
+        if input.is_full() {
+            return calculate_histogram_from_reader(io::stdin().lock(), dimension, stride)
+                .map_err(|err| BinvizError::io(file, err));
+        }
+        let buf = read_stdin()?;
+        return Ok(calculate_histogram_from_bytes(
+            input.slice(&buf)?,
+            dimension,
+            stride,
+        ));
+    }
+    if !input.is_full() {
+        return calculate_histogram_range(file, dimension, stride, input.offset, input.length);
+    }
+    if input.mmap {
+        #[cfg(feature = "mmap")]
+        return calculate_histogram_mmap(file, dimension, stride);
+        #[cfg(not(feature = "mmap"))]
+        warn!("binviz was built without the `mmap` feature, reading the file instead.");
+    }
+    let len = fs::metadata(file)
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    let bar = bytes_progress_bar(file);
+    let histogram = if len > PARALLEL_THRESHOLD {
+        info!(
+            "file is larger than `{}` bytes, calculating in parallel...",
+            PARALLEL_THRESHOLD
+        );
+        calculate_histogram_parallel_with_progress(
+            file,
+            dimension,
+            stride,
+            0,
+            PROGRESS_GRANULARITY,
+            |event| track_bytes(&bar, event),
+        )
+    } else {
+        calculate_histogram_with_progress(file, dimension, stride, PROGRESS_GRANULARITY, |event| {
+            track_bytes(&bar, event)
+        })
+    };
+    bar.finish_and_clear();
+    histogram
+}
+
+/// Calculate the run length statistics of the range of `file`, with offsets in the whole file.
+///
+/// Standard input was already read for the histogram, so there are no statistics for [`STDIN_PATH`].
+fn compute_run_lengths(file: &Path, input: Input) -> Result<Option<RunLengthReport>, BinvizError> {
+    if file == Path::new(STDIN_PATH) {
+        warn!("run lengths can't be calculated for standard input, skipping them.");
+        return Ok(None);
+    }
+    if input.is_full() {
+        return run_length_stats(file).map(Some);
+    }
+    let buf = read_input(file, input)?;
+    let mut report = run_length_stats_from_reader(buf.as_slice(), RUN_THRESHOLD)
+        .map_err(|err| BinvizError::io(file, err))?;
+    if let Some((_, _, offset)) = &mut report.longest_run {
+        *offset += input.offset;
+    }
+    Ok(Some(report))
+}
+
+/// Read the bytes of `file` again for the analyses that need them in order rather than counted,
+/// `None` for standard input, which is already read for the histogram.
+fn reread_input(file: &Path, input: Input) -> Result<Option<Vec<u8>>, BinvizError> {
+    if file == Path::new(STDIN_PATH) {
+        warn!("periods and XOR keys can't be detected in standard input, skipping them.");
+        return Ok(None);
+    }
+    read_input(file, input).map(Some)
+}
+
+/// Rank the periods of `buf` up to `max_period`, saving the power spectrum to `spectrum_png` if given,
+/// an existing file is only overwritten with `force`. Constant bytes have no periods.
+fn compute_periods(
+    buf: &[u8],
+    max_period: usize,
+    spectrum_png: Option<&Path>,
+    force: bool,
+) -> Result<Vec<(usize, f64)>, BinvizError> {
+    let spectrum = power_spectrum(buf, max_period)?;
+    if let Some(path) = spectrum_png {
+        info!("saving power spectrum to `{}`...", path.display());
+        save_image(
+            render_spectrum(&spectrum),
+            path,
+            ImageFormat::Png,
+            BitDepth::Eight,
+            force,
+        )?;
+    }
+    match periods_from_spectrum(&spectrum, max_period) {
+        Err(BinvizError::ZeroVariance) => {
+            warn!("the bytes are constant, so they have no periods.");
+            Ok(Vec::new())
+        }
+        periods => periods,
+    }
+}
+
+/// Read all of standard input.
+fn read_stdin() -> Result<Vec<u8>, BinvizError> {
+    let mut buf = Vec::new();
+    io::stdin()
+        .read_to_end(&mut buf)
+        .map_err(|err| BinvizError::io(STDIN_PATH, err))?;
+    Ok(buf)
+}
+
+/// The name of `file` in titles and labels, [`STDIN_NAME`] for [`STDIN_PATH`].
+fn display_name(file: &Path) -> String {
+    if file == Path::new(STDIN_PATH) {
+        STDIN_NAME.to_string()
+    } else {
+        file.display().to_string()
+    }
+}
+
+/// Read the range of the input, without the filtered bytes.
+/// The path [`STDIN_PATH`] reads from standard input.
+///
+/// A file is only read from the start of its range to the end of it, see [`open_range`].
+fn read_input(file: &Path, input: Input) -> Result<Vec<u8>, BinvizError> {
+    if file == Path::new(STDIN_PATH) {
+        let buf = read_stdin()?;
+        let range = input.slice(&buf)?;
+        return Ok(match input.filter() {
+            Some(filter) => filter.apply(range),
+            None => range.to_vec(),
+        });
+    }
+    let mut range = open_range(file, input)?;
+    let mut buf = Vec::new();
+    range
+        .read_to_end(&mut buf)
+        .map_err(|err| BinvizError::io(file, err))?;
+    Ok(match input.filter() {
+        Some(filter) => filter.apply(&buf),
+        None => buf,
+    })
+}
+
+/// Open `file` at the offset of the range of the input, limited to the length of the range.
+fn open_range(file: &Path, input: Input) -> Result<Take<File>, BinvizError> {
+    let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    if input.is_full() {
+        return Ok(handle.take(u64::MAX));
+    }
+    let size = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    let length = resolve_range(size, input.offset, input.length)?;
+    handle
+        .seek(SeekFrom::Start(input.offset))
+        .map_err(|err| BinvizError::io(file, err))?;
+    Ok(handle.take(length))
+}
+
+/// Read the range of the input of `file` in chunks, without the filtered bytes, passing every chunk to `f`.
+///
+/// Unlike [`read_input`], a file is never held in memory as a whole,
+/// only standard input is read at once to select its range.
+fn read_input_chunks<F>(file: &Path, input: Input, mut f: F) -> Result<(), BinvizError>
+where
+    F: FnMut(&[u8]),
+{
+    if file == Path::new(STDIN_PATH) {
+        f(&read_input(file, input)?);
+        return Ok(());
+    }
+    let range = open_range(file, input)?;
+    let filter = input.filter();
+    let mut kept = Vec::new();
+    read_chunks(range, |chunk| match filter {
+        Some(filter) => {
+            kept.clear();
+            kept.extend(chunk.iter().copied().filter(|&byte| filter.keeps(byte)));
+            f(&kept);
+        }
+        None => f(chunk),
+    })
+    .map_err(|err| BinvizError::io(file, err))
+}
+
+/// Find and display the offsets of `ngram` in `file`, see [`display_ngram_offsets`].
+///
+/// The path [`STDIN_PATH`] reads from standard input instead, which is buffered to show the context.
+fn locate_ngram(
+    file: &Path,
+    ngram: &[u8],
+    limit: Option<usize>,
+    context: usize,
+) -> Result<String, BinvizError> {
+    info!("start: locating byte sequence...");
+    let start_locate = Instant::now();
+    let output = if file == Path::new(STDIN_PATH) {
+        let buf = read_stdin()?;
+        let offsets = find_ngram_offsets_from_reader(buf.as_slice(), ngram, limit)
+            .map_err(|err| BinvizError::io(file, err))?;
+        info!("found `{}` occurrences.", offsets.len());
+        display_ngram_offsets(&mut Cursor::new(buf), ngram, &offsets, context)
+    } else {
+        let offsets = find_ngram_offsets(file, ngram, limit)?;
+        info!("found `{}` occurrences.", offsets.len());
+        let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+        display_ngram_offsets(&mut handle, ngram, &offsets, context)
+    }
+    .map_err(|err| BinvizError::io(file, err))?;
+    let elapsed_locate = start_locate.elapsed();
+    info!(
+        "end: finished locating byte sequence, with elapsed time: {:?}",
+        elapsed_locate
+    );
+    Ok(output)
+}
+
+/// Calculate the combined histogram of 16-bit words of all `files`.
+fn compute_word_histogram(
+    files: &[PathBuf],
+    dimension: usize,
+    stride: usize,
+    input: Input,
+    endian: Endian,
+) -> Result<Histogram<u16>, BinvizError> {
+    validate_window(dimension, stride)?;
+    let mut histogram = Histogram::new();
+    for file in files {
+        // A window of `dimension` words is a window of twice as many bytes starting at an even offset,
+        // so the bytes are counted in those windows and decoded afterwards.
+        let mut builder = HistogramBuilder::with_stride(2 * dimension, 2 * stride);
+        let mut processed = 0;
+        read_input_chunks(file, input, |chunk| {
+            processed += chunk.len();
+            builder.update(chunk);
+        })?;
+        if processed % 2 == 1 {
+            info!("dropping the odd trailing byte that doesn't fill a whole word.");
+        }
+        for (bytes, freq) in builder.finish() {
+            *histogram.entry(decode_words(&bytes, endian)).or_insert(0) += freq;
+        }
+    }
+    Ok(histogram)
+}
+
+/// Calculate the combined histogram of nibbles of all `files`.
+fn compute_nibble_histogram(
+    files: &[PathBuf],
+    dimension: usize,
+    stride: usize,
+    input: Input,
+) -> Result<Histogram<u8>, BinvizError> {
+    validate_window(dimension, stride)?;
+    let mut histogram = Histogram::new();
+    for file in files {
+        let mut builder = HistogramBuilder::with_stride(dimension, stride);
+        read_input_chunks(file, input, |chunk| builder.update(&split_nibbles(chunk)))?;
+        merge_histograms(&mut histogram, &builder.finish())?;
+    }
+    Ok(histogram)
+}
+
+/// The statistics shown below the entropy table, `None` where they are undefined for the input.
+struct Statistics {
+    serial_correlation: Option<f64>,
+    monte_carlo_pi: Option<(f64, f64)>,
+    lz_complexity: (usize, f64),
+    #[cfg(feature = "compression")]
+    compression: Option<(u64, u64)>,
+}
+
+/// Incrementally calculates the [`Statistics`] of the input, compressing it too if `compress` is set.
+struct StatisticsBuilder {
+    correlation: SerialCorrelation,
+    estimate: MonteCarloPi,
+    complexity: LzComplexity,
+    #[cfg(feature = "compression")]
+    compressor: Option<Compressor>,
+}
+
+impl StatisticsBuilder {
+    fn new(compress: bool) -> Self {
+        #[cfg(not(feature = "compression"))]
+        if compress {
+            warn!("binviz was built without the `compression` feature, skipping compression.");
+        }
+        StatisticsBuilder {
+            correlation: SerialCorrelation::new(),
+            estimate: MonteCarloPi::new(),
+            complexity: LzComplexity::new(),
+            #[cfg(feature = "compression")]
+            compressor: compress.then(|| Compressor::new(Codec::Deflate)),
+        }
+    }
+
+    /// Add the next bytes of the input.
+    fn update(&mut self, chunk: &[u8]) {
+        self.correlation.update(chunk);
+        self.estimate.update(chunk);
+        self.complexity.update(chunk);
+        #[cfg(feature = "compression")]
+        if let Some(compressor) = &mut self.compressor {
+            compressor.update(chunk);
+        }
+    }
+
+    fn finish(self) -> Statistics {
+        Statistics {
+            // Both can only fail because the input is empty, constant or too short.
+            serial_correlation: self.correlation.finish().ok(),
+            monte_carlo_pi: self.estimate.finish().ok(),
+            lz_complexity: self.complexity.finish(),
+            #[cfg(feature = "compression")]
+            compression: self.compressor.map(Compressor::finish),
+        }
+    }
+}
+
+/// The histograms of the dimensions 1 up to the count of the entropy subcommand, of bytes or nibbles, or of words.
+enum DimensionHistograms {
+    Bytes(Vec<Histogram<u8>>),
+    Words(Vec<Histogram<u16>>),
+}
+
+/// Count the windows of `symbols` of the dimensions 1 up to `count` of the input, reading it only once and passing
+/// its bytes to `statistics` too, if given. The `buffered` input is used if it was already read.
+fn compute_dimension_histograms(
+    file: &Path,
+    input: Input,
+    buffered: Option<&[u8]>,
+    (count, stride): (usize, usize),
+    symbols: &Symbols,
+    mut statistics: Option<&mut StatisticsBuilder>,
+) -> Result<DimensionHistograms, BinvizError> {
+    // A window of `dimension` words is a window of twice as many bytes starting at an even offset,
+    // so the bytes are counted in those windows and decoded afterwards.
+    let width = if symbols.is_words() { 2 } else { 1 };
+    let mut builders = (1..=count)
+        .map(|dimension| {
+            validate_window(dimension, stride)?;
+            Ok(HistogramBuilder::with_stride(
+                width * dimension,
+                width * stride,
+            ))
+        })
+        .collect::<Result<Vec<_>, BinvizError>>()?;
+    let nibbles = symbols.is_nibbles();
+    let mut processed = 0;
+    let mut update = |chunk: &[u8]| {
+        processed += chunk.len() as u64;
+        if let Some(statistics) = &mut statistics {
+            statistics.update(chunk);
+        }
+        let split;
+        let chunk = if nibbles {
+            split = split_nibbles(chunk);
+            &split
+        } else {
+            chunk
+        };
+        for builder in &mut builders {
+            builder.update(chunk);
+        }
+    };
+    match buffered {
+        Some(buf) => update(buf),
+        None => {
+            let bar = bytes_progress_bar(file);
+            if input.filter().is_none() {
+                bar.set_length(range_length(file, input)?);
+            }
+            read_input_chunks(file, input, |chunk| {
+                update(chunk);
+                bar.inc(chunk.len() as u64);
+            })?;
+            bar.finish_and_clear();
+        }
+    }
+    if symbols.is_words() {
+        if processed % 2 == 1 {
+            info!("dropping the odd trailing byte that doesn't fill a whole word.");
+        }
+        return Ok(DimensionHistograms::Words(
+            builders
+                .into_iter()
+                .map(|builder| {
+                    let mut histogram = Histogram::new();
+                    for (bytes, freq) in builder.finish() {
+                        *histogram
+                            .entry(decode_words(&bytes, symbols.endian))
+                            .or_insert(0) += freq;
+                    }
+                    histogram
+                })
+                .collect(),
+        ));
+    }
+    if !nibbles {
+        validate_input_size(processed, count)?;
+    }
+    Ok(DimensionHistograms::Bytes(
+        builders.into_iter().map(HistogramBuilder::finish).collect(),
+    ))
+}
+
+/// The amount of bytes in the range of `file`, without warning about a range past its end like [`resolve_range`].
+fn range_length(file: &Path, input: Input) -> Result<u64, BinvizError> {
+    let size = fs::metadata(file)
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    let available = size.saturating_sub(input.offset);
+    Ok(input
+        .length
+        .map_or(available, |length| length.min(available)))
+}
+
+/// Calculate the deflate compression ratios of the windows of the entropy profile,
+/// of the `buffered` input if it was already read.
+fn compression_ratios(
+    file: &Path,
+    buffered: Option<&[u8]>,
+    window_size: usize,
+    step: usize,
+) -> Result<Vec<f64>, BinvizError> {
+    #[cfg(feature = "compression")]
+    {
+        let profile = match buffered {
+            Some(buf) => compression_profile_from_reader(buf, window_size, step, Codec::Deflate)
+                .map_err(|err| BinvizError::io(file, err))?,
+            None => compression_profile(file, window_size, step, Codec::Deflate)?,
+        };
+        Ok(profile.into_iter().map(|(_, ratio)| ratio).collect())
+    }
+    #[cfg(not(feature = "compression"))]
+    {
+        let _ = (file, buffered, window_size, step);
+        warn!(
+            "binviz was built without the `compression` feature, skipping the compression ratios."
+        );
+        Ok(Vec::new())
+    }
+}
+
+/// Calculate the Rényi entropies of the histogram of the given orders, in the given `unit`.
+fn renyi_entropies<T>(
+    histogram: &Histogram<T>,
+    orders: &[f64],
+    unit: EntropyUnit,
+) -> Result<Vec<f64>, BinvizError> {
+    orders
+        .iter()
+        .map(|&alpha| calculate_renyi_entropy(histogram, alpha).map(|bits| unit.from_bits(bits)))
+        .collect()
+}
+
+/// Calculate the plug-in entropy of the histogram in the given `unit`, if `estimator` estimates it differently.
+fn plugin_entropy<T>(
+    histogram: &Histogram<T>,
+    estimator: EntropyEstimator,
+    unit: EntropyUnit,
+) -> Result<Option<f64>, BinvizError> {
+    if estimator == EntropyEstimator::Plugin {
+        return Ok(None);
+    }
+    calculate_entropy_histogram(histogram, unit).map(Some)
+}
+
+/// Estimate the entropy of the histogram in the given `unit`, logging how long it took.
+fn timed_entropy<T>(
+    histogram: &Histogram<T>,
+    estimator: EntropyEstimator,
+    unit: EntropyUnit,
+) -> Result<f64, BinvizError> {
+    info!("start: calculating entropy of histogram...");
+    let start_calc_entropy = Instant::now();
+    let entropy = estimate_entropy(histogram, estimator, unit)?;
+    let elapsed_calc_entropy = start_calc_entropy.elapsed();
+    info!(
+        "end: finished calculating entropy of histogram, with elapsed time: {:?}",
+        elapsed_calc_entropy
+    );
+    Ok(entropy)
+}
+
+/// Calculate the combined histogram of all `files`, see [`compute_histogram`].
+fn compute_merged_histogram(
+    files: &[PathBuf],
+    dimension: usize,
+    stride: usize,
+    input: Input,
+) -> Result<Histogram<u8>, BinvizError> {
+    let mut histogram = Histogram::new();
+    for file in files {
+        merge_histograms(
+            &mut histogram,
+            &compute_histogram(file, dimension, stride, input)?,
+        )?;
+    }
+    Ok(histogram)
+}
+
+/// Load the histogram from `load` if given, checking it has the right dimension,
+/// or calculate it from `files` otherwise, see [`compute_merged_histogram`].
+/// The histogram is saved to `save` if given, an existing file is only overwritten with `force`.
+fn obtain_histogram(
+    files: &[PathBuf],
+    load: Option<&Path>,
+    save: Option<&Path>,
+    dimension: usize,
+    stride: usize,
+    input: Input,
+    force: bool,
+) -> Result<Histogram<u8>, BinvizError> {
+    let histogram = match load {
+        Some(path) => {
+            info!("loading histogram from `{}`...", path.display());
+            let histogram = load_histogram(path)?;
+            validate_dimension(&histogram, dimension)?;
+            histogram
+        }
+        None => compute_merged_histogram(files, dimension, stride, input)?,
+    };
+    if let Some(path) = save {
+        info!("saving histogram to `{}`...", path.display());
+        save_histogram(path, &histogram, Format::from_path(path), force)?;
+    }
+    Ok(histogram)
+}
+
+/// Obtain the histogram of the symbols, see [`obtain_histogram`].
+/// For 16-bit words the histogram of the high bytes of the words is returned.
+#[allow(clippy::too_many_arguments)]
+fn obtain_symbol_histogram(
+    files: &[PathBuf],
+    load: Option<&Path>,
+    save: Option<&Path>,
+    dimension: usize,
+    stride: usize,
+    input: Input,
+    symbols: Symbols,
+    force: bool,
+) -> Result<Histogram<u8>, BinvizError> {
+    if symbols.is_words() {
+        if load.is_some() || save.is_some() {
+            warn!("saving and loading histograms only supports bytes, ignoring it for words.");
+        }
+        let histogram = compute_word_histogram(files, dimension, stride, input, symbols.endian)?;
+        return Ok(high_byte_histogram(&histogram));
+    }
+    if symbols.is_nibbles() && load.is_none() {
+        let histogram = compute_nibble_histogram(files, dimension, stride, input)?;
+        if let Some(path) = save {
+            info!("saving histogram to `{}`...", path.display());
+            save_histogram(path, &histogram, Format::from_path(path), force)?;
+        }
+        return Ok(histogram);
+    }
+    obtain_histogram(files, load, save, dimension, stride, input, force)
+}
+
+/// How the visualize subcommand decorates and saves a generated image, see [`finish_image`].
+struct ImageOptions<'a> {
+    scale_factor: u32,
+    grid: bool,
+    grid_fine: bool,
+    grid_color: Rgb<u16>,
+    axes: bool,
+    values: u32,
+    symbol: &'a str,
+    no_legend: bool,
+    output: &'a Path,
+    image_format: ImageFormat,
+    bit_depth: BitDepth,
+    force: bool,
+}
+
+/// Visualize every file of a visualize `command` with `--each` on its own, see [`run`],
+/// saving the images under names derived from the files and reporting the failures at the end with `--keep-going`.
+fn visualize_each(command: &CliCommand, force: bool) -> Result<(), BinvizError> {
+    let CliCommand::Visualize {
+        file: files,
+        image_format,
+        output_dir,
+        keep_going,
+        ..
+    } = command
+    else {
+        unreachable!("only the visualize subcommand visualizes every file");
+    };
+    fs::create_dir_all(output_dir).map_err(|err| BinvizError::write(output_dir, err))?;
+    let extension = image_format.unwrap_or_default().extension();
+    let outputs = each_outputs(files, output_dir, extension);
+    let mut failures = Vec::new();
+    for (i, (path, image)) in files.iter().zip(outputs).enumerate() {
+        info!(
+            "visualizing file `{}` of `{}`, `{}`...",
+            i + 1,
+            files.len(),
+            display_name(path)
+        );
+        // Relative paths keep their subfolders, which may not exist yet.
+        let folder = image.parent().unwrap_or(output_dir);
+        let created = fs::create_dir_all(folder).map_err(|err| BinvizError::write(folder, err));
+        let mut single = command.clone();
+        if let CliCommand::Visualize {
+            file, output, each, ..
+        } = &mut single
+        {
+            *file = vec![path.clone()];
+            *output = Some(image);
+            *each = false;
+        }
+        match created.and_then(|_| run(single, force)) {
+            Ok(()) => {}
+            Err(err) if *keep_going => {
+                warn!(
+                    "couldn't visualize `{}`, continuing: {}",
+                    display_name(path),
+                    err
+                );
+                failures.push((path, err));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    info!(
+        "`{}` of `{}` files visualized.",
+        files.len() - failures.len(),
+        files.len()
+    );
+    if !failures.is_empty() {
+        eprintln!(
+            "failed to visualize `{}` of `{}` files:",
+            failures.len(),
+            files.len()
+        );
+        for (path, err) in failures {
+            eprintln!("  `{}`: {}", display_name(path), err);
+        }
+    }
+    Ok(())
+}
+
+/// The images of the `files` of [`visualize_each`] in `output_dir`, named after the files with `extension` added.
+///
+/// Relative paths within the current folder keep their subfolders, so `a/x.bin` and `b/x.bin` become
+/// `a/x.bin.png` and `b/x.bin.png`. Other paths only keep their file name, numbered like `x.bin-2.png`
+/// if another file already has it.
+fn each_outputs(files: &[PathBuf], output_dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut taken = HashSet::new();
+    files
+        .iter()
+        .map(|path| {
+            let relative = path.is_relative()
+                && path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)));
+            let name = match path.file_name() {
+                _ if path.as_path() == Path::new(STDIN_PATH) => PathBuf::from("stdin"),
+                _ if relative => path.clone(),
+                Some(name) => PathBuf::from(name),
+                None => PathBuf::from(path.to_string_lossy().as_ref()),
+            };
+            let with_extension = |name: &Path| {
+                let mut name = name.as_os_str().to_owned();
+                name.push(".");
+                name.push(extension);
+                output_dir.join(name)
+            };
+            let mut output = with_extension(&name);
+            let mut n = 2;
+            while taken.contains(&output) {
+                let mut numbered = name.as_os_str().to_owned();
+                numbered.push(format!("-{}", n));
+                output = with_extension(Path::new(&numbered));
+                n += 1;
+            }
+            if n > 2 {
+                info!(
+                    "another file is named like `{}`, saving its image to `{}`.",
+                    path.display(),
+                    output.display()
+                );
+            }
+            taken.insert(output.clone());
+            output
+        })
+        .collect()
+}
+
+/// Convert an opaque `color` to a pixel of any of the 16-bit image types, using its luminance for grayscale.
+fn to_pixel<P>(color: Rgb<u16>) -> P
+where
+    P: Pixel<Subpixel = u16>,
+{
+    let channels = if P::CHANNEL_COUNT < 3 {
+        [color.to_luma().0[0], u16::MAX, 0, 0]
+    } else {
+        [color.0[0], color.0[1], color.0[2], u16::MAX]
+    };
+    *P::from_slice(&channels[..P::CHANNEL_COUNT as usize])
+}
+
+/// Scale up a generated image, draw the grid, axes and colorbar over it as `options` asks and save it,
+/// with the `metadata` if it is saved as PNG. The colorbar shows the counts on the scale of the `metadata`,
+/// in the colors `color` gives their brightnesses.
+fn finish_image<P, F>(
+    image: &ImageBuffer<P, Vec<u16>>,
+    options: &ImageOptions,
+    metadata: &ImageMetadata,
+    color: F,
+) -> Result<(), BinvizError>
+where
+    P: Pixel<Subpixel = u16>,
+    F: Fn(f64) -> P,
+    DynamicImage: From<ImageBuffer<P, Vec<u16>>>,
+{
+    let mut image = upscale_nearest(image, options.scale_factor)?;
+    if options.grid {
+        draw_grid(&mut image, options.grid_fine, to_pixel(options.grid_color));
+    }
+    if options.axes {
+        let white = to_pixel(Rgb([u16::MAX; 3]));
+        image = with_axes(&image, options.values, options.symbol, white);
+    }
+    if !options.no_legend {
+        image = with_colorbar(&image, metadata.scale, metadata.full_count, color);
+    }
+    save_image(
+        image,
+        options.output,
+        options.image_format,
+        options.bit_depth,
+        options.force,
+    )?;
+    if options.image_format == ImageFormat::Png {
+        write_image_metadata(options.output, metadata)?;
+    }
+    Ok(())
+}
+
+/// Run an entropy or visualize `command` with `--watch`, see [`run`], and again whenever one of its files changes
+/// until Ctrl-C, overwriting its outputs. Failures after the first run are logged rather than stopping the watch,
+/// as the file may be changed again.
+fn watch_command(mut command: CliCommand, force: bool) -> Result<(), BinvizError> {
+    let files = match &mut command {
+        CliCommand::Entropy { file, watch, .. } => {
+            *watch = false;
+            vec![file.clone()]
+        }
+        CliCommand::Visualize { file, watch, .. } => {
+            *watch = false;
+            file.clone()
+        }
+        _ => unreachable!("only the entropy and visualize subcommands watch their files"),
+    };
+    if files.iter().any(|file| file == Path::new(STDIN_PATH)) {
+        return Err(BinvizError::io(
+            STDIN_PATH,
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "standard input can't be watched, give a file instead",
+            ),
+        ));
+    }
+    run(command.clone(), force)?;
+    static STOP: AtomicBool = AtomicBool::new(false);
+    ctrlc::set_handler(|| STOP.store(true, Ordering::SeqCst))
+        .expect("the Ctrl-C handler is only set once");
+    info!(
+        "watching `{}` for changes, press Ctrl-C to stop...",
+        files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join("`, `")
+    );
+    watch_files(&files, WATCH_DEBOUNCE, &STOP, || {
+        info!("the input changed, running again...");
+        // The outputs are our own from the previous run, so they are overwritten.
+        if let Err(err) = run(command.clone(), true) {
+            warn!(
+                "running again failed, watching for the next change: {}",
+                err
+            );
+        }
+    })?;
+    info!("stopped watching.");
+    Ok(())
+}
+
+/// Check that none of the given `outputs` exist, unless `force` allows overwriting them, see [`validate_output`].
+fn validate_outputs<'a, I>(outputs: I, force: bool) -> Result<(), BinvizError>
+where
+    I: IntoIterator<Item = Option<&'a Path>>,
+{
+    outputs
+        .into_iter()
+        .flatten()
+        .try_for_each(|output| validate_output(output, force))
+}
+
+fn run(command: CliCommand, force: bool) -> Result<(), BinvizError> {
+    if let CliCommand::Entropy { watch: true, .. } | CliCommand::Visualize { watch: true, .. } =
+        command
+    {
+        return watch_command(command, force);
+    }
+    if let CliCommand::Visualize { each: true, .. } = command {
+        return visualize_each(&command, force);
+    }
+    match command {
+        CliCommand::Entropy {
+            file,
+            count,
+            stride,
+            input,
+            symbols,
+            renyi,
+            unit,
+            estimator,
+            compress,
+            format,
+            chart_svg,
+            ..
+        } => {
             info!("start: executing entropy subcommand...");
+            validate_outputs([chart_svg.as_deref()], force)?;
             let start_entropy_command = Instant::now();
             info!("start: initializing empty table with headers...");
             let start_table = Instant::now();
-            let mut table = Table::new();
-            table.load_preset(ASCII_MARKDOWN);
-            table.set_header(["Dimension", "Entropy", "Relative Entropy"]);
+            let mut rows = Vec::with_capacity(count);
+            let mut header = vec![
+                "Dimension".to_string(),
+                format!("Entropy ({})", unit),
+                "Relative Entropy".to_string(),
+                format!("Entropy Rate ({})", unit),
+            ];
+            if estimator != EntropyEstimator::Plugin {
+                header.push(format!("Plug-in Entropy ({})", unit));
+            }
+            header.extend(renyi.iter().map(|&alpha| renyi_entropy_name(alpha)));
             let elapsed_table = start_table.elapsed();
             info!(
                 "end: finished initializing empty table with headers, with elapsed time: {:?}",
                 elapsed_table
             );
+            if count == 0 {
+                return Err(BinvizError::InvalidDimension { dimension: count });
+            }
+            if format == OutputFormat::Json {
+                if !renyi.is_empty() || estimator != EntropyEstimator::Plugin {
+                    warn!("the JSON document only has the estimated entropies, ignoring the Rényi and plug-in entropies.");
+                }
+                if unit != EntropyUnit::Bits {
+                    warn!("the JSON document always has the entropies in bits, ignoring `--unit`.");
+                }
+            }
+            // Standard input can only be read once, so it is buffered,
+            // a file is read in chunks for all dimensions and the statistics at once instead.
+            let buffered = if file == Path::new(STDIN_PATH) {
+                Some(read_input(&file, input)?)
+            } else {
+                None
+            };
+            if input.mmap && buffered.is_none() {
+                info!("reading the file once for all dimensions instead of memory-mapping it.");
+            }
+            // Only the table is printed in the other formats, so the output can be parsed as a whole.
+            let mut statistics =
+                (format == OutputFormat::Markdown).then(|| StatisticsBuilder::new(compress));
+            info!(
+                "start: calculating histograms of dimensions `1` up to `{}`...",
+                count
+            );
+            let start_histograms = Instant::now();
+            let histograms = compute_dimension_histograms(
+                &file,
+                input,
+                buffered.as_deref(),
+                (count, stride),
+                &symbols,
+                statistics.as_mut(),
+            )?;
+            let elapsed_histograms = start_histograms.elapsed();
+            info!(
+                "end: finished calculating histograms, with elapsed time: {:?}",
+                elapsed_histograms
+            );
+            let statistics = statistics.map(StatisticsBuilder::finish);
+            let (symbol, symbol_bits) = symbols.unit();
             info!("start: calculating the actual entries of the table...");
             let start_collecting = Instant::now();
+            // The entropy of the previous dimension, for the entropy rate H_n - H_{n-1}.
+            let mut previous_entropy = 0.0;
+            let mut entropies = Vec::with_capacity(count);
+            let mut entries = Vec::with_capacity(count);
             for i in 1..=count {
-                info!("start: calculating histogram of dimension `{}`...", i);
-                let start_histogram = Instant::now();
-                let histogram = calculate_histogram(&file, i);
-                let elapsed_histogram = start_histogram.elapsed();
-                info!(
-                    "end: finished calculating histogram of dimension `{}`, with elapsed time: {:?}",
-                    i, elapsed_histogram
-                );
-                info!("start: calculating entropy of histogram...");
-                let start_calc_entropy = Instant::now();
-                let entropy = calculate_entropy_histogram(&histogram);
-                let elapsed_calc_entropy = start_calc_entropy.elapsed();
-                info!(
-                    "end: finished calculating entropy of histogram, with elapsed time: {:?}",
-                    elapsed_calc_entropy
-                );
+                let (entropy, plugin_entropy, renyi_entropies) = match &histograms {
+                    DimensionHistograms::Bytes(histograms) => (
+                        timed_entropy(&histograms[i - 1], estimator, unit)?,
+                        plugin_entropy(&histograms[i - 1], estimator, unit)?,
+                        renyi_entropies(&histograms[i - 1], &renyi, unit)?,
+                    ),
+                    DimensionHistograms::Words(histograms) => (
+                        timed_entropy(&histograms[i - 1], estimator, unit)?,
+                        plugin_entropy(&histograms[i - 1], estimator, unit)?,
+                        renyi_entropies(&histograms[i - 1], &renyi, unit)?,
+                    ),
+                };
                 info!(
                     "start: additionally calculating relative entropy and adding entry to table..."
                 );
                 let start_entry_add = Instant::now();
-                let rel_entropy = entropy / (8.0f64 * (i as f64));
-                table.add_row([
+                let rel_entropy = entropy / unit.from_bits(symbol_bits * i as f64);
+                // Clamp rounding errors, which would show up as -0.00000 when the rate is 0.
+                let entropy_rate = (entropy - previous_entropy).max(0.0);
+                previous_entropy = entropy;
+                entropies.push(entropy);
+                entries.push(EntropyEntry {
+                    dimension: i,
+                    entropy_bits: entropy / unit.from_bits(1.0),
+                    relative_entropy: rel_entropy,
+                });
+                let per_window = format!("{} per {} {}(s)", unit, i, symbol);
+                let mut row = vec![
                     format!("{}", i),
-                    format!("{:.5} (bits per {} byte(s))", entropy, i),
-                    format!("{:.5}", rel_entropy),
-                ]);
+                    format.value(entropy, Some(&per_window)),
+                    format.value(rel_entropy, None),
+                    format.value(entropy_rate, Some(&format!("{} per {}", unit, symbol))),
+                ];
+                row.extend(
+                    plugin_entropy
+                        .map(|plugin_entropy| format.value(plugin_entropy, Some(&per_window))),
+                );
+                row.extend(
+                    renyi_entropies
+                        .iter()
+                        .map(|&renyi_entropy| format.value(renyi_entropy, Some(&per_window))),
+                );
+                rows.push(row);
                 let elapsed_entry_add = start_entry_add.elapsed();
                 info!("end: finished calculating relative entropy and adding entry to table, with elapsed time: {:?}", elapsed_entry_add);
             }
@@ -113,50 +2073,402 @@ fn main() {
                 "end: finished collecting the actual entries of the table, with elapsed time: {:?}",
                 elapsed_collecting
             );
+            if let Some(path) = &chart_svg {
+                info!("saving entropy chart to `{}`...", path.display());
+                let title = format!("Entropy of {}", display_name(&file));
+                write_output(path, entropy_chart_svg(&entropies, unit, &title), force)?;
+            }
             let elapsed_entropy_command = start_entropy_command.elapsed();
             info!(
                 "end: finished executing entropy subcommand, with elapsed time: {:?}",
                 elapsed_entropy_command
             );
-            println!("{}", table);
+            if format == OutputFormat::Json {
+                let report = EntropyReport {
+                    header: ReportHeader::new(Some(&file)),
+                    entropies: entries,
+                };
+                println!("{}", report);
+            } else {
+                println!("{}", format.render(header, rows));
+            }
+            if let Some(statistics) = statistics {
+                println!();
+                println!(
+                    "{}",
+                    display_serial_correlation(statistics.serial_correlation)
+                );
+                println!("{}", display_monte_carlo_pi(statistics.monte_carlo_pi));
+                println!("{}", display_lz_complexity(statistics.lz_complexity));
+                #[cfg(feature = "compression")]
+                if let Some(sizes) = statistics.compression {
+                    println!("{}", display_compression(Codec::Deflate, sizes));
+                }
+            }
         }
-        CliCommand::Frequency { file } => {
+        CliCommand::Frequency {
+            file,
+            dimension,
+            stride,
+            input,
+            symbols,
+            save_histogram,
+            load_histogram,
+            locate,
+            limit,
+            context,
+            reference,
+            smoothing,
+            baseline,
+            top,
+            sort,
+            least,
+            format,
+            runs,
+            periods,
+            spectrum_png,
+            detect_xor,
+            chart_svg,
+        } => {
             info!("start: executing frequency subcommand...");
+            validate_outputs(
+                [
+                    chart_svg.as_deref(),
+                    save_histogram.as_deref(),
+                    spectrum_png.as_deref(),
+                ],
+                force,
+            )?;
             let start_freq_command = Instant::now();
 
+            if sort == SortOrder::Deviation && baseline.is_none() {
+                warn!("sorting by deviation requires a `--baseline`, sorting by count instead.");
+            }
+            // The path the frequencies are of, for the header of the JSON document.
+            let input_path = file.as_deref().or(load_histogram.as_deref());
+            if format == OutputFormat::Json && baseline.is_some() {
+                warn!("the JSON document only has the frequencies, ignoring `--baseline`.");
+            }
+            if let Some(Ngram(ngram)) = locate {
+                let file = file.expect("`--locate` conflicts with `--load-histogram`");
+                let output = locate_ngram(&file, &ngram, limit, context)?;
+                let elapsed_freq_command = start_freq_command.elapsed();
+                info!(
+                    "end: finished executing frequency subcommand, with elapsed time: {:?}",
+                    elapsed_freq_command
+                );
+                println!("{}", output);
+                return Ok(());
+            }
+
+            if symbols.is_words() {
+                if load_histogram.is_some() || save_histogram.is_some() {
+                    warn!(
+                        "saving and loading histograms only supports bytes, ignoring it for words."
+                    );
+                }
+                let histogram = compute_word_histogram(
+                    file.as_slice(),
+                    dimension,
+                    stride,
+                    input,
+                    symbols.endian,
+                )?;
+                if chart_svg.is_some() {
+                    warn!("charts only support bytes, ignoring `--chart-svg` for words.");
+                }
+                if format == OutputFormat::Json {
+                    println!(
+                        "{}",
+                        FrequencyReport::new(&histogram, top, sort, input_path)
+                    );
+                } else {
+                    println!(
+                        "{}",
+                        display_most_frequent_words(&histogram, top, sort, format)
+                    );
+                }
+                return Ok(());
+            }
             info!("start: calculating histogram...");
             let start_histogram = Instant::now();
-            let histogram = calculate_histogram(&file, 1);
+            let histogram = obtain_symbol_histogram(
+                file.as_slice(),
+                load_histogram.as_deref(),
+                save_histogram.as_deref(),
+                dimension,
+                stride,
+                input,
+                symbols,
+                force,
+            )?;
             let elapsed_histogram = start_histogram.elapsed();
             info!(
                 "end: finished calculating histogram, with elapsed time: {:?}",
                 elapsed_histogram
             );
+            // The chi-square test is against the uniform distribution of all 256 byte values,
+            // and the moments are of byte values.
+            let statistics =
+                if symbols.is_nibbles() || dimension != 1 || format != OutputFormat::Markdown {
+                    None
+                } else {
+                    Some((chi_square(&histogram)?, ByteStats::compute(&histogram)?))
+                };
+            let comparison = reference
+                .filter(|_| format == OutputFormat::Markdown)
+                .map(|reference| compare_to_reference(&histogram, &reference, smoothing))
+                .transpose()?;
+            let run_lengths = match &file {
+                Some(file) if runs && format == OutputFormat::Markdown => {
+                    compute_run_lengths(file, input)?
+                }
+                _ => None,
+            };
+            let needs_bytes = periods.is_some() || detect_xor;
+            if needs_bytes && format != OutputFormat::Markdown {
+                warn!("periods and XOR keys are only listed in markdown tables, skipping them.");
+            }
+            let buf = match &file {
+                Some(file) if needs_bytes && format == OutputFormat::Markdown => {
+                    reread_input(file, input)?
+                }
+                _ => None,
+            };
+            let periods = match (&buf, periods) {
+                (Some(buf), Some(max_period)) => Some(compute_periods(
+                    buf,
+                    max_period,
+                    spectrum_png.as_deref(),
+                    force,
+                )?),
+                _ => None,
+            };
+            let xor_keys = match &buf {
+                Some(buf) if detect_xor => Some(detect_xor_key(buf)?),
+                _ => None,
+            };
+            if let Some(path) = &chart_svg {
+                info!("saving frequency chart to `{}`...", path.display());
+                let name = file.as_ref().or(load_histogram.as_ref());
+                let title = match name {
+                    Some(name) => format!("Most frequent values of {}", display_name(name)),
+                    None => "Most frequent values".to_string(),
+                };
+                let chart = frequency_chart_svg(&histogram, top.unwrap_or(CHART_BAR_COUNT), &title);
+                write_output(path, chart, force)?;
+            }
+            let table = match baseline {
+                _ if format == OutputFormat::Json => {
+                    FrequencyReport::new(&histogram, top, sort, input_path).to_string()
+                }
+                Some(baseline) => {
+                    let baseline = obtain_reference(&baseline)?;
+                    display_baseline_deviation(&histogram, &baseline, top, sort, format)?
+                }
+                None => display_most_frequent(&histogram, top, sort, format),
+            };
             let elapsed_freq_command = start_freq_command.elapsed();
             info!(
                 "end: finished executing frequency subcommand, with elapsed time: {:?}",
                 elapsed_freq_command
             );
-            println!("{}", display_most_frequent(&histogram));
+            println!("{}", table);
+            if let Some(((statistic, p_value), stats)) = statistics {
+                println!();
+                println!("{}", display_chi_square(statistic, p_value));
+                println!();
+                println!("{}", display_byte_stats(&stats));
+                println!();
+                println!("{}", display_alphabet(&histogram, least));
+            }
+            if let Some(report) = run_lengths {
+                println!();
+                println!("{}", display_run_lengths(&report, RUN_LENGTH_COUNT, format));
+            }
+            if let Some(periods) = periods {
+                println!();
+                println!("{}", display_periods(&periods, PERIOD_COUNT, format));
+            }
+            if let (Some(keys), Some(buf)) = (xor_keys, &buf) {
+                println!();
+                println!("{}", display_xor_keys(&keys, buf, XOR_KEY_COUNT, format));
+            }
+            if let Some(comparison) = comparison {
+                println!();
+                println!(
+                    "{}",
+                    display_cross_entropy(comparison.cross_entropy, comparison.entropy)
+                );
+                println!("{}", display_kl_divergence(comparison.divergence));
+                println!(
+                    "{}",
+                    display_distances(comparison.hellinger, comparison.total_variation)
+                );
+            }
         }
-        CliCommand::Visualize { file, mode } => {
+        CliCommand::Visualize {
+            file,
+            stride,
+            input,
+            symbols,
+            save_histogram,
+            load_histogram,
+            scale,
+            normalize,
+            colormap,
+            scale_factor,
+            no_legend,
+            output,
+            image_format,
+            bit_depth,
+            counts_output,
+            grid,
+            grid_fine,
+            grid_color,
+            axes,
+            background,
+            export_ply,
+            ply_threshold,
+            animate,
+            frame_delay,
+            skip_empty_frames,
+            mode,
+            ..
+        } => {
             info!("start: executing visualize subcommand...");
             let start_vis_command = Instant::now();
+            let normalization = normalize.unwrap_or_else(|| scale.default_normalization());
+            // Nibble images show 16 values along each axis, scaled up to the size of byte images.
+            let (symbol, _) = symbols.unit();
+            let values = if symbols.is_nibbles() { 16 } else { 256 };
+            let grid_color = Rgb(grid_color.0.map(|channel| channel as u16 * 257));
+            validate_scale_factor(scale_factor)?;
+            let image_format = image_format
+                .or_else(|| output.as_deref().and_then(ImageFormat::from_path))
+                .unwrap_or_default();
+            let output = output
+                .unwrap_or_else(|| PathBuf::from(format!("output.{}", image_format.extension())));
+            validate_outputs(
+                [
+                    Some(output.as_path()),
+                    save_histogram.as_deref(),
+                    counts_output.as_deref(),
+                    export_ply.as_deref(),
+                    animate.as_deref(),
+                ],
+                force,
+            )?;
+            let (offset, length) = match load_histogram {
+                Some(_) if !input.is_full() => {
+                    warn!("a loaded histogram can't be restricted to a range, ignoring `--offset` and `--length`.");
+                    (0, None)
+                }
+                Some(_) => (0, None),
+                None => (input.offset, input.length),
+            };
+            let range = format_range(offset, length);
+            if length.is_some() || offset > 0 {
+                info!("only visualizing the bytes in the range `{}`.", range);
+            }
+            // The source files are only hashed for the metadata of PNG images.
+            let sources = if image_format == ImageFormat::Png {
+                file.iter()
+                    .filter(|path| path.as_path() != Path::new(STDIN_PATH))
+                    .map(SourceFile::from_path)
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                Vec::new()
+            };
+            let options = ImageOptions {
+                scale_factor,
+                grid,
+                grid_fine,
+                grid_color,
+                axes,
+                values,
+                symbol,
+                no_legend,
+                output: &output,
+                image_format,
+                bit_depth,
+                force,
+            };
+            if colormap != Colormap::Grayscale && !matches!(mode, Mode::Di | Mode::Offset { .. }) {
+                warn!("colormaps only apply to the digraph and offset images, ignoring it.");
+            }
+            if export_ply.is_some() && !matches!(mode, Mode::Tri) {
+                warn!("point clouds are exported from the trigraph, ignoring `--export-ply`.");
+            }
+            if animate.is_some() && !matches!(mode, Mode::Tri) {
+                warn!("animations are made of the trigraph, ignoring `--animate`.");
+            }
+            if counts_output.is_some() && !matches!(mode, Mode::Di) {
+                warn!("counts are written for the digraph, ignoring `--counts-output`.");
+            }
             match mode {
                 Mode::Tri => {
                     info!("calculating trihistogram...");
-                    let trihistogram = calculate_histogram(&file, 3);
+                    let trihistogram = obtain_symbol_histogram(
+                        &file,
+                        load_histogram.as_deref(),
+                        save_histogram.as_deref(),
+                        3,
+                        stride,
+                        input,
+                        symbols,
+                        force,
+                    )?;
                     info!("finished calculating trihistogram.");
+                    if let Some(path) = &export_ply {
+                        info!("exporting point cloud to `{}`...", path.display());
+                        let vertices =
+                            export_point_cloud(&trihistogram, path, ply_threshold, force)?;
+                        info!("`{}` byte triples exported to the point cloud.", vertices);
+                    }
+                    if let Some(path) = &animate {
+                        info!("saving animation to `{}`...", path.display());
+                        let frames = generate_trigraph_slices(&trihistogram, scale, normalization)?;
+                        let written =
+                            save_animation(&frames, path, frame_delay, skip_empty_frames, force)?;
+                        info!("animation of `{}` frames saved.", written);
+                    }
                     info!("generating image...");
-                    let (image, total, avg_total) = generate_color_image(&trihistogram);
+                    let (mut image, total, full_brightness) =
+                        generate_color_image(&trihistogram, scale, normalization)?;
+                    if symbols.is_nibbles() {
+                        image = scale_nibble_image(&image);
+                    }
                     info!("finished generating image.");
-                    info!("saving image to `.\\output.png`...");
-                    image.save("output.png").expect("Couldn't save image");
-                    info!("image saved to '.\\output.png'.");
-                    info!("`{}` byte pairs visualized.", total);
+                    info!("saving image to `{}`...", output.display());
+                    let metadata = ImageMetadata::new(
+                        sources,
+                        3,
+                        scale,
+                        normalization,
+                        total,
+                        full_brightness,
+                    )
+                    .with_range(offset, length);
+                    // The count is shown in the blue channel.
+                    let blue = |brightness: f64| Rgb([0, 0, (brightness * u16::MAX as f64) as u16]);
+                    match background {
+                        Some(background) => {
+                            let image = with_background(&image, &trihistogram, values, background);
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                blue(brightness).to_rgba()
+                            })
+                        }
+                        None => finish_image(&image, &options, &metadata, blue),
+                    }?;
+                    info!("image saved to `{}`.", output.display());
+                    info!(
+                        "`{}` byte pairs visualized, from the range `{}`.",
+                        total, range
+                    );
                     info!(
                         "full brightness means `{:.4}` byte pairs at that location.",
-                        avg_total
+                        full_brightness
                     );
                     let elapsed_vis_command = start_vis_command.elapsed();
                     info!(
@@ -165,19 +2477,91 @@ fn main() {
                     )
                 }
                 Mode::Di => {
-                    info!("calculating dihistogram...");
-                    let dihistogram = calculate_histogram(&file, 2);
-                    info!("finished calculating dihistogram.");
+                    // A single whole file of bytes is streamed into the image, unless the histogram is needed.
+                    let streaming = matches!(file.as_slice(), [path] if path.as_path() != Path::new(STDIN_PATH))
+                        && input.is_full()
+                        && input.filter().is_none()
+                        && !input.mmap
+                        && stride == 1
+                        && !symbols.is_nibbles()
+                        && !symbols.is_words()
+                        && save_histogram.is_none()
+                        && counts_output.is_none()
+                        && background.is_none();
+                    let dihistogram = if streaming {
+                        None
+                    } else {
+                        info!("calculating dihistogram...");
+                        let dihistogram = obtain_symbol_histogram(
+                            &file,
+                            load_histogram.as_deref(),
+                            save_histogram.as_deref(),
+                            2,
+                            stride,
+                            input,
+                            symbols,
+                            force,
+                        )?;
+                        info!("finished calculating dihistogram.");
+                        if let Some(path) = &counts_output {
+                            info!("writing counts to `{}`...", path.display());
+                            write_counts(&dihistogram, path, CountsFormat::from_path(path), force)?;
+                        }
+                        Some(dihistogram)
+                    };
                     info!("generating image...");
-                    let (image, total, avg_total) = generate_image(&dihistogram);
+                    let (image, total, full_brightness) = match &dihistogram {
+                        None => {
+                            info!(
+                                "streaming the byte pairs of `{}` into the image...",
+                                file[0].display()
+                            );
+                            generate_image_streaming(&file[0], scale, normalization)?
+                        }
+                        Some(dihistogram) if symbols.is_nibbles() => {
+                            generate_nibble_image(dihistogram, scale, normalization)?
+                        }
+                        Some(dihistogram) => generate_image(dihistogram, scale, normalization)?,
+                    };
                     info!("finished generating image.");
-                    info!("saving image to `.\\output.png`...");
-                    image.save("output.png").expect("Couldn't save image");
-                    info!("image saved to '.\\output.png'.");
-                    info!("`{}` byte pairs visualized.", total);
+                    info!("saving image to `{}`...", output.display());
+                    let metadata = ImageMetadata::new(
+                        sources,
+                        2,
+                        scale,
+                        normalization,
+                        total,
+                        full_brightness,
+                    )
+                    .with_range(offset, length);
+                    match (colormap, no_legend, background.zip(dihistogram.as_ref())) {
+                        (Colormap::Grayscale, true, None) => {
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                Luma([(brightness * u16::MAX as f64) as u16])
+                            })
+                        }
+                        (colormap, _, None) => {
+                            let image = apply_colormap(&image, colormap);
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                colormap.rgb16(brightness)
+                            })
+                        }
+                        (colormap, _, Some((background, dihistogram))) => {
+                            let image = apply_colormap(&image, colormap);
+                            let image = with_background(&image, dihistogram, values, background);
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                colormap.rgb16(brightness).to_rgba()
+                            })
+                        }
+                    }?;
+                    info!("image saved to `{}`.", output.display());
+                    info!(
+                        "`{}` byte pairs visualized, from the range `{}`.",
+                        total, range
+                    );
                     info!(
                         "full brightness means `{:.4}` byte pairs at that location.",
-                        avg_total
+                        full_brightness
                     );
                     let elapsed_vis_command = start_vis_command.elapsed();
                     info!(
@@ -187,18 +2571,145 @@ fn main() {
                 }
                 Mode::Quartic => {
                     info!("calculating quartic-hihistogram...");
-                    let trihistogram = calculate_histogram(&file, 4);
+                    let trihistogram = obtain_symbol_histogram(
+                        &file,
+                        load_histogram.as_deref(),
+                        save_histogram.as_deref(),
+                        4,
+                        stride,
+                        input,
+                        symbols,
+                        force,
+                    )?;
                     info!("finished calculating quartic-histogram.");
                     info!("generating image...");
-                    let (image, total, avg_total) = generate_color_image_quartic(&trihistogram);
+                    let (mut image, total, full_brightness) =
+                        generate_color_image_quartic(&trihistogram)?;
+                    if symbols.is_nibbles() {
+                        image = scale_nibble_image(&image);
+                    }
                     info!("finished generating image.");
-                    info!("saving image to `.\\output.png`...");
-                    image.save("output.png").expect("Couldn't save image");
-                    info!("image saved to '.\\output.png'.");
-                    info!("`{}` byte pairs visualized.", total);
+                    info!("saving image to `{}`...", output.display());
+                    // The count is shown linearly in the blue channel.
+                    let blue = |brightness: f64| Rgb([0, 0, (brightness * u16::MAX as f64) as u16]);
+                    let metadata = ImageMetadata::new(
+                        sources,
+                        4,
+                        ScaleMode::Linear,
+                        Normalization::Average,
+                        total,
+                        full_brightness,
+                    )
+                    .with_range(offset, length);
+                    match background {
+                        Some(background) => {
+                            let image = with_background(&image, &trihistogram, values, background);
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                blue(brightness).to_rgba()
+                            })
+                        }
+                        None => finish_image(&image, &options, &metadata, blue),
+                    }?;
+                    info!("image saved to `{}`.", output.display());
+                    info!(
+                        "`{}` byte pairs visualized, from the range `{}`.",
+                        total, range
+                    );
                     info!(
                         "full brightness means `{:.4}` byte pairs at that location.",
-                        avg_total
+                        full_brightness
+                    );
+                    let elapsed_vis_command = start_vis_command.elapsed();
+                    info!(
+                        "end: finished executing visualize subcommand, with elapsed time: {:?}",
+                        elapsed_vis_command
+                    )
+                }
+                Mode::Offset {
+                    buckets,
+                    per_column,
+                } => {
+                    let path =
+                        match (file.as_slice(), &load_histogram) {
+                            (_, Some(path)) => return Err(BinvizError::io(
+                                path,
+                                io::Error::new(
+                                    ErrorKind::InvalidInput,
+                                    "offset images are made of the file itself, not of a histogram",
+                                ),
+                            )),
+                            ([path], None) => path,
+                            (paths, None) => {
+                                warn!(
+                                "offset images show a single file, only visualizing the first one."
+                            );
+                                &paths[0]
+                            }
+                        };
+                    if stride != 1
+                        || symbols.is_nibbles()
+                        || symbols.is_words()
+                        || save_histogram.is_some()
+                    {
+                        warn!("offset images count every byte, ignoring the stride, symbol and histogram options.");
+                    }
+                    if grid || axes || background.is_some() {
+                        warn!("offset images have no byte values along the x axis, ignoring `--grid`, `--axes` and `--background`.");
+                    }
+                    let options = ImageOptions {
+                        grid: false,
+                        axes: false,
+                        no_legend: no_legend || per_column,
+                        ..options
+                    };
+                    info!("generating image...");
+                    // Standard input, ranges and filtered bytes are read into memory.
+                    let (image, total, full_brightness) = if path.as_path() == Path::new(STDIN_PATH)
+                        || !input.is_full()
+                        || input.filter().is_some()
+                    {
+                        generate_offset_byte_image_from_bytes(
+                            &read_input(path, input)?,
+                            buckets,
+                            scale,
+                            normalization,
+                            per_column,
+                        )?
+                    } else {
+                        generate_offset_byte_image(path, buckets, scale, normalization, per_column)?
+                    };
+                    info!("finished generating image.");
+                    info!("saving image to `{}`...", output.display());
+                    let metadata = ImageMetadata::new(
+                        sources,
+                        1,
+                        scale,
+                        normalization,
+                        total,
+                        full_brightness,
+                    );
+                    match colormap {
+                        Colormap::Grayscale if options.no_legend => {
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                Luma([(brightness * u16::MAX as f64) as u16])
+                            })
+                        }
+                        colormap => {
+                            let image = apply_colormap(&image, colormap);
+                            finish_image(&image, &options, &metadata, |brightness| {
+                                colormap.rgb16(brightness)
+                            })
+                        }
+                    }?;
+                    info!("image saved to `{}`.", output.display());
+                    info!(
+                        "`{}` bytes visualized in `{}` columns.",
+                        total,
+                        image.width()
+                    );
+                    info!(
+                        "full brightness means `{:.4}` bytes at that location.",
+                        full_brightness
                     );
                     let elapsed_vis_command = start_vis_command.elapsed();
                     info!(
@@ -208,6 +2719,605 @@ fn main() {
                 }
             }
         }
-        CliCommand::Full { files } => full_analysis(files),
+        CliCommand::Blocks {
+            file,
+            block_size,
+            threshold,
+            below,
+            format,
+            input,
+        } => {
+            info!("start: executing blocks subcommand...");
+            let start_blocks_command = Instant::now();
+            validate_window(block_size, block_size)?;
+            let blocks =
+                if file == Path::new(STDIN_PATH) || !input.is_full() || input.filter().is_some() {
+                    let buf = read_input(&file, input)?;
+                    validate_input_size(buf.len() as u64, 1)?;
+                    let blocks = block_stats_from_reader(buf.as_slice(), block_size)
+                        .map_err(|err| BinvizError::io(&file, err))?;
+                    // Report offsets in the whole input rather than in the selected range.
+                    blocks
+                        .into_iter()
+                        .map(|block| BlockStats {
+                            offset: block.offset + input.offset,
+                            ..block
+                        })
+                        .collect()
+                } else {
+                    block_stats(&file, block_size)?
+                };
+            let filter = match threshold {
+                Some(threshold) if below => BlockFilter::Below(threshold),
+                Some(threshold) => BlockFilter::Above(threshold),
+                None => BlockFilter::All,
+            };
+            println!(
+                "{}",
+                display_blocks(&blocks, block_size, filter, format, Some(&file))
+            );
+            let elapsed_blocks_command = start_blocks_command.elapsed();
+            info!(
+                "end: finished executing blocks subcommand, with elapsed time: {:?}",
+                elapsed_blocks_command
+            );
+        }
+        CliCommand::Diff {
+            old,
+            new,
+            dimension,
+            stride,
+            count,
+            smoothing,
+            input,
+        } => {
+            info!("start: executing diff subcommand...");
+            let start_diff_command = Instant::now();
+            let old_histogram = compute_histogram(&old, dimension, stride, input)?;
+            let new_histogram = compute_histogram(&new, dimension, stride, input)?;
+            let output = display_histogram_diff(&old_histogram, &new_histogram, count, smoothing)?;
+            let elapsed_diff_command = start_diff_command.elapsed();
+            info!(
+                "end: finished executing diff subcommand, with elapsed time: {:?}",
+                elapsed_diff_command
+            );
+            println!("{}", output);
+        }
+        CliCommand::Profile {
+            file,
+            window_size,
+            step,
+            width,
+            height,
+            compress,
+            entropy_map,
+            map_layout,
+            colormap,
+            input,
+        } => {
+            info!("start: executing profile subcommand...");
+            validate_outputs(
+                [
+                    Some(Path::new("profile.png")),
+                    Some(Path::new("entropy_map.png")).filter(|_| entropy_map),
+                ],
+                force,
+            )?;
+            let start_profile_command = Instant::now();
+            let buffered =
+                if file == Path::new(STDIN_PATH) || !input.is_full() || input.filter().is_some() {
+                    Some(read_input(&file, input)?)
+                } else {
+                    None
+                };
+            let (profile, size) = match &buffered {
+                Some(buf) => {
+                    validate_window(window_size, step)?;
+                    validate_input_size(buf.len() as u64, 1)?;
+                    let profile = entropy_profile_from_reader(buf.as_slice(), window_size, step)
+                        .map_err(|err| BinvizError::io(&file, err))?;
+                    // Report offsets in the whole input rather than in the selected range.
+                    let profile = profile
+                        .into_iter()
+                        .map(|(offset, entropy)| (offset + input.offset, entropy))
+                        .collect::<Vec<_>>();
+                    (profile, input.offset + buf.len() as u64)
+                }
+                None => {
+                    let size = fs::metadata(&file)
+                        .map_err(|err| BinvizError::io(&file, err))?
+                        .len();
+                    (entropy_profile(&file, window_size, step)?, size)
+                }
+            };
+            let ratios = if compress {
+                compression_ratios(&file, buffered.as_deref(), window_size, step)?
+            } else {
+                Vec::new()
+            };
+            info!("rendering entropy curve...");
+            let image = render_entropy_profile(&profile, width, height);
+            info!("saving entropy curve to `.\\profile.png`...");
+            save_image(
+                image,
+                "profile.png",
+                ImageFormat::Png,
+                BitDepth::Eight,
+                force,
+            )?;
+            info!("entropy curve saved to '.\\profile.png'.");
+            if entropy_map {
+                info!("saving entropy map to `.\\entropy_map.png`...");
+                save_image(
+                    render_entropy_map(&profile, map_layout, colormap)?,
+                    "entropy_map.png",
+                    ImageFormat::Png,
+                    BitDepth::Eight,
+                    force,
+                )?;
+                info!("entropy map saved to '.\\entropy_map.png'.");
+            }
+            let elapsed_profile_command = start_profile_command.elapsed();
+            info!(
+                "end: finished executing profile subcommand, with elapsed time: {:?}",
+                elapsed_profile_command
+            );
+            println!(
+                "{}",
+                display_entropy_profile(&profile, window_size, size, &ratios)
+            );
+        }
+        CliCommand::Compare {
+            file_a,
+            file_b,
+            smoothing,
+            format,
+            images,
+            output,
+            diff_output,
+            scale,
+            normalize,
+            input,
+        } => {
+            info!("start: executing compare subcommand...");
+            let (output, diff_output) = match &images {
+                Some(folder) => (
+                    Some(folder.join("compare.png")),
+                    Some(folder.join("diff.png")),
+                ),
+                None => (output, diff_output),
+            };
+            validate_outputs([output.as_deref(), diff_output.as_deref()], force)?;
+            if output.is_none()
+                && diff_output.is_none()
+                && (normalize.is_some() || scale != ScaleMode::Linear)
+            {
+                warn!("`--scale` and `--normalize` only apply to the side-by-side image, ignoring them");
+            }
+            let start_compare_command = Instant::now();
+            // Standard input can only be read once, so buffer it for all dimensions.
+            let stdin = if file_a == Path::new(STDIN_PATH) || file_b == Path::new(STDIN_PATH) {
+                Some(read_input(Path::new(STDIN_PATH), input)?)
+            } else {
+                None
+            };
+            let histogram = |file: &Path, dimension: usize| match &stdin {
+                Some(buf) if file == Path::new(STDIN_PATH) => {
+                    Ok(calculate_histogram_from_bytes(buf, dimension, 1))
+                }
+                _ => compute_histogram(file, dimension, 1, input),
+            };
+            let mut histograms = Vec::new();
+            let mut dimensions = Vec::new();
+            for dimension in COMPARISON_DIMENSIONS {
+                let a = histogram(&file_a, dimension)?;
+                let b = histogram(&file_b, dimension)?;
+                dimensions.push(compare_histograms(&a, &b, dimension, smoothing)?);
+                histograms.push((a, b));
+            }
+            let report = ComparisonReport::new(&file_a, &file_b, dimensions);
+            println!("{}", display_comparison(&report, format));
+            let (a, b) = histograms
+                .pop()
+                .expect("the last compared dimension is that of the digraph");
+            let normalization = normalize.unwrap_or_else(|| scale.default_normalization());
+            let name = |file: &Path| {
+                if file == Path::new(STDIN_PATH) {
+                    return STDIN_NAME.to_string();
+                }
+                file.file_name()
+                    .unwrap_or(file.as_os_str())
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            if let Some(folder) = &images {
+                fs::create_dir_all(folder).map_err(|err| BinvizError::write(folder, err))?;
+            }
+            if let Some(path) = &output {
+                let image = generate_comparison_image(
+                    &a,
+                    &b,
+                    (&name(&file_a), &name(&file_b)),
+                    scale,
+                    normalization,
+                )?;
+                info!("saving comparison to `{}`...", path.display());
+                let format = ImageFormat::from_path(path).unwrap_or_default();
+                save_image(image, path, format, BitDepth::Sixteen, force)?;
+                info!("comparison saved to `{}`.", path.display());
+            }
+            if let Some(path) = &diff_output {
+                info!("saving difference image to `{}`...", path.display());
+                let format = ImageFormat::from_path(path).unwrap_or_default();
+                save_image(
+                    generate_diff_image(&a, &b)?,
+                    path,
+                    format,
+                    BitDepth::Sixteen,
+                    force,
+                )?;
+                info!("difference image saved to `{}`.", path.display());
+            }
+            let elapsed_compare_command = start_compare_command.elapsed();
+            info!(
+                "end: finished executing compare subcommand, with elapsed time: {:?}",
+                elapsed_compare_command
+            );
+        }
+        CliCommand::Classify {
+            file,
+            input,
+            fingerprint,
+            top,
+            format,
+        } => {
+            info!("start: executing classify subcommand...");
+            let start_classify_command = Instant::now();
+            let mut fingerprints = builtin_fingerprints();
+            for sample in fingerprint {
+                info!(
+                    "taking the fingerprint `{}` of `{}`...",
+                    sample.label,
+                    sample.path.display()
+                );
+                fingerprints.push(Fingerprint::from_file(sample.label, &sample.path)?);
+            }
+            // Standard input can only be read once, so buffer it for both dimensions.
+            let (monograph, digraph) = if file == Path::new(STDIN_PATH) {
+                let buf = read_input(&file, input)?;
+                (
+                    calculate_histogram_from_bytes(&buf, 1, 1),
+                    calculate_histogram_from_bytes(&buf, 2, 1),
+                )
+            } else {
+                (
+                    compute_histogram(&file, 1, 1, input)?,
+                    compute_histogram(&file, 2, 1, input)?,
+                )
+            };
+            let mut matches = classify_histograms(&monograph, &digraph, &fingerprints)?;
+            if let Some(top) = top {
+                matches.truncate(top);
+            }
+            println!("{}", display_classification(&matches, format));
+            let elapsed_classify_command = start_classify_command.elapsed();
+            info!(
+                "end: finished executing classify subcommand, with elapsed time: {:?}",
+                elapsed_classify_command
+            );
+        }
+        CliCommand::Markov {
+            file,
+            input,
+            csv,
+            sample,
+            sample_len,
+            seed,
+            top,
+            format,
+        } => {
+            info!("start: executing markov subcommand...");
+            validate_outputs([csv.as_deref(), sample.as_deref()], force)?;
+            let start_markov_command = Instant::now();
+            let dihistogram = compute_histogram(&file, 2, 1, input)?;
+            let matrix = transition_matrix(&dihistogram)?;
+            if let Some(csv) = &csv {
+                info!("saving transition matrix to `{}`...", csv.display());
+                write_transition_matrix(&matrix, csv, force)?;
+            }
+            if let Some(sample) = &sample {
+                // Every byte but the last starts a pair.
+                let len =
+                    sample_len.map_or(dihistogram.values().sum::<usize>() + 1, |len| len as usize);
+                info!(
+                    "saving `{}` synthetic bytes to `{}`...",
+                    len,
+                    sample.display()
+                );
+                write_output(sample, sample_markov(&matrix, len, seed), force)?;
+            }
+            let distribution = stationary_distribution(&matrix);
+            let elapsed_markov_command = start_markov_command.elapsed();
+            info!(
+                "end: finished executing markov subcommand, with elapsed time: {:?}",
+                elapsed_markov_command
+            );
+            println!(
+                "{}",
+                display_stationary_distribution(&distribution, top, format)
+            );
+        }
+        CliCommand::Map {
+            file,
+            layout,
+            width,
+            color_scheme,
+            legend,
+            input,
+        } => {
+            info!("start: executing map subcommand...");
+            validate_outputs([Some(Path::new("map.png"))], force)?;
+            let start_map_command = Instant::now();
+            let buf = read_input(&file, input)?;
+            let mut image = match layout {
+                MapLayout::Raw => render_raw(&buf, width.unwrap_or(RAW_WIDTH), color_scheme)?,
+                _ => {
+                    if width.is_some() {
+                        warn!("the width only applies to the `raw` layout, ignoring it.");
+                    }
+                    render_map(&buf, layout, color_scheme)?
+                }
+            };
+            info!(
+                "`{}` bytes in a {}×{} map.",
+                buf.len(),
+                image.width(),
+                image.height()
+            );
+            if legend {
+                match color_scheme {
+                    ColorScheme::ByteClass => image = with_class_legend(&image),
+                    ColorScheme::Value(_) => {
+                        warn!("the legend explains the `class` color scheme, skipping it.")
+                    }
+                }
+            }
+            info!("saving map to `.\\map.png`...");
+            save_image(image, "map.png", ImageFormat::Png, BitDepth::Eight, force)?;
+            info!("map saved to '.\\map.png'.");
+            let elapsed_map_command = start_map_command.elapsed();
+            info!(
+                "end: finished executing map subcommand, with elapsed time: {:?}",
+                elapsed_map_command
+            );
+        }
+        CliCommand::Strings {
+            file,
+            min_len,
+            max_len,
+            charset,
+            format,
+        } => {
+            info!("start: executing strings subcommand...");
+            let start_strings_command = Instant::now();
+            let strings = if file == Path::new(STDIN_PATH) {
+                extract_strings_from_reader(io::stdin().lock(), min_len, max_len, charset)
+                    .map_err(|err| BinvizError::io(STDIN_PATH, err))?
+            } else {
+                let handle = File::open(&file).map_err(|err| BinvizError::io(&file, err))?;
+                extract_strings_from_reader(handle, min_len, max_len, charset)
+                    .map_err(|err| BinvizError::io(&file, err))?
+            };
+            let elapsed_strings_command = start_strings_command.elapsed();
+            info!(
+                "end: finished executing strings subcommand, with elapsed time: {:?}",
+                elapsed_strings_command
+            );
+            println!("{}", display_strings(&strings, format));
+        }
+        CliCommand::Scan {
+            dir,
+            walk,
+            sort,
+            chi_square,
+            min_size,
+            max_size,
+            format,
+        } => {
+            info!("start: executing scan subcommand...");
+            let start_scan_command = Instant::now();
+            let collected = collect_files(&dir, &walk.options())?;
+            let bar = progress_bar(
+                "{msg} [{bar:40}] {pos}/{len} files ({eta} left)",
+                "scanning".to_string(),
+            );
+            bar.set_length(collected.files.len() as u64);
+            let (mut entries, mut skipped, mut failed) = (Vec::new(), 0, 0);
+            for file in &collected.files {
+                bar.inc(1);
+                let size = match fs::metadata(&file.path) {
+                    Ok(metadata) => metadata.len(),
+                    Err(err) => {
+                        warn!("skipping `{}`: {}", file.path.display(), err);
+                        failed += 1;
+                        continue;
+                    }
+                };
+                if min_size.is_some_and(|min_size| size < min_size)
+                    || max_size.is_some_and(|max_size| size > max_size)
+                {
+                    skipped += 1;
+                    continue;
+                }
+                match scan_file(&file.path, chi_square) {
+                    Ok(entry) => entries.push(entry),
+                    Err(err) => {
+                        warn!("skipping `{}`: {}", file.path.display(), err);
+                        failed += 1;
+                    }
+                }
+            }
+            bar.finish_and_clear();
+            info!(
+                "`{}` files found, `{}` skipped by size, `{}` scanned, `{}` unreadable.",
+                collected.files.len(),
+                skipped,
+                entries.len(),
+                failed
+            );
+            println!("{}", display_scan(&entries, sort, format));
+            let elapsed_scan_command = start_scan_command.elapsed();
+            info!(
+                "end: finished executing scan subcommand, with elapsed time: {:?}",
+                elapsed_scan_command
+            );
+        }
+        CliCommand::Full {
+            files,
+            walk,
+            jobs,
+            image_format,
+            entropy_dimensions,
+            no_digraph,
+            no_trigraph,
+            html,
+            html_only,
+            output_dir,
+            top,
+            sort_by,
+        } => {
+            let walk = walk.options();
+            let mut builder = AnalysisOptions::builder()
+                .image_format(image_format)
+                .entropy_dimensions(entropy_dimensions)
+                .digraph(!no_digraph)
+                .trigraph(!no_trigraph)
+                .html(html || html_only)
+                .text_and_images(!html_only)
+                .output_dir(&output_dir);
+            if let Some(top) = top {
+                builder = builder.top_n(top);
+            }
+            let options = builder.build();
+            let files_bar = progress_bar(
+                "{msg} [{bar:40}] {pos}/{len} files ({eta} left)",
+                "analyzing".to_string(),
+            );
+            // A bar per file whose bytes are being counted, as several files are analyzed at once.
+            let bytes_bars: Mutex<HashMap<PathBuf, ProgressBar>> = Mutex::new(HashMap::new());
+            let analysis =
+                full_analysis_with_progress(files, walk, jobs, force, options, |event| {
+                    let mut bytes_bars = bytes_bars.lock().expect("progress bars aren't poisoned");
+                    match event {
+                        ProgressEvent::Files { analyzed, total } => {
+                            files_bar.set_length(total as u64);
+                            files_bar.set_position(analyzed as u64);
+                        }
+                        ProgressEvent::Stage { file, stage } => {
+                            if let Some(bar) = bytes_bars.remove(&file) {
+                                bar.finish_and_clear();
+                            }
+                            files_bar.set_message(format!("{} ({:?})", display_name(&file), stage));
+                        }
+                        ProgressEvent::FileBytes {
+                            file,
+                            processed,
+                            total,
+                        } => {
+                            let bar = bytes_bars
+                                .entry(file)
+                                .or_insert_with_key(|file| bytes_progress_bar(file));
+                            track_bytes(bar, ProgressEvent::Bytes { processed, total });
+                        }
+                        ProgressEvent::Bytes { .. } => {}
+                    }
+                });
+            for bar in bytes_bars
+                .into_inner()
+                .expect("progress bars aren't poisoned")
+                .into_values()
+            {
+                bar.finish_and_clear();
+            }
+            files_bar.finish_and_clear();
+            let summaries = analysis?;
+            let folder = output_dir.as_path();
+            fs::create_dir_all(folder).map_err(|err| BinvizError::write(folder, err))?;
+            for (name, format) in [
+                (SUMMARY_TEXT_FILE, OutputFormat::Markdown),
+                (SUMMARY_CSV_FILE, OutputFormat::Csv),
+            ] {
+                let path = folder.join(name);
+                write_output(
+                    &path,
+                    display_file_summaries(&summaries, sort_by, format),
+                    force,
+                )?;
+            }
+            info!(
+                "summary of `{}` files saved to `{}` and `{}`.",
+                summaries.len(),
+                folder.join(SUMMARY_TEXT_FILE).display(),
+                folder.join(SUMMARY_CSV_FILE).display()
+            );
+            let total = summaries.len();
+            let failures: Vec<(PathBuf, BinvizError)> = summaries
+                .into_iter()
+                .filter_map(|summary| summary.outcome.err().map(|err| (summary.path, err)))
+                .collect();
+            if !failures.is_empty() {
+                return Err(BinvizError::Batch { failures, total });
+            }
+        }
+        CliCommand::Tui { file, block_size } => {
+            if file == Path::new(STDIN_PATH) {
+                return Err(BinvizError::io(
+                    STDIN_PATH,
+                    io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "standard input can't be explored, give a file instead",
+                    ),
+                ));
+            }
+            if block_size < MIN_TUI_BLOCK_SIZE {
+                warn!(
+                    "blocks of `{}` bytes are too small to explore, using `{}` bytes instead.",
+                    block_size, MIN_TUI_BLOCK_SIZE
+                );
+            }
+            run_tui(&file, block_size)?;
+        }
+        CliCommand::Completions { shell, dir } => {
+            let mut cli = cli_command();
+            let name = cli.get_name().to_string();
+            match dir {
+                None => {
+                    let shell = shell.expect("a shell is required without `--dir`");
+                    generate(shell, &mut cli, name, &mut io::stdout());
+                }
+                Some(dir) => {
+                    let shells = match shell {
+                        Some(shell) => vec![shell],
+                        None => COMPLETION_SHELLS.to_vec(),
+                    };
+                    let paths: Vec<PathBuf> = shells
+                        .iter()
+                        .map(|shell| dir.join(shell.file_name(&name)))
+                        .collect();
+                    validate_outputs(paths.iter().map(|path| Some(path.as_path())), force)?;
+                    fs::create_dir_all(&dir).map_err(|err| BinvizError::write(&dir, err))?;
+                    for shell in shells {
+                        let path = generate_to(shell, &mut cli, &name, &dir)
+                            .map_err(|err| BinvizError::write(&dir, err))?;
+                        info!(
+                            "completion script for {} saved to `{}`.",
+                            shell,
+                            path.display()
+                        );
+                    }
+                }
+            }
+        }
     }
+    Ok(())
 }
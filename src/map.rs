@@ -0,0 +1,389 @@
+use std::{fmt, str::FromStr};
+
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+use crate::{
+    font::{draw_text, text_width, GLYPH_HEIGHT},
+    BinvizError, ColorScheme, Colormap,
+};
+
+/// The largest side of a file map, larger inputs have several bytes per pixel.
+pub const MAX_MAP_SIDE: u32 = 4096;
+
+/// The width of a raw rendering when none is given, see [`render_raw`].
+pub const RAW_WIDTH: u32 = 256;
+
+/// How the bytes of the input are laid out in a file map, see [`render_map`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapLayout {
+    /// Along a Hilbert curve, so bytes close in the input are close in the image.
+    #[default]
+    Hilbert,
+    /// Along a Z-order (Morton) curve, which is blockier than the Hilbert curve but keeps records aligned
+    /// to powers of two in aligned squares, and the offset of a pixel is its interleaved coordinates.
+    ZOrder,
+    /// Row by row, byte `i` at (`i mod width`, `i / width`), so records of a fixed size show up as vertical stripes
+    /// when the width matches their size, see [`render_raw`]. In a square file map the width is the side.
+    Raw,
+}
+
+impl MapLayout {
+    /// The coordinates of position `d` on this curve filling a `side`×`side` square.
+    pub fn d2xy(self, side: u32, d: u64) -> (u32, u32) {
+        match self {
+            MapLayout::Hilbert => hilbert_d2xy(side, d),
+            MapLayout::ZOrder => morton_d2xy(d),
+            MapLayout::Raw => ((d % side as u64) as u32, (d / side as u64) as u32),
+        }
+    }
+}
+
+impl FromStr for MapLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hilbert" => Ok(MapLayout::Hilbert),
+            "zorder" | "z-order" | "morton" => Ok(MapLayout::ZOrder),
+            "raw" => Ok(MapLayout::Raw),
+            _ => Err(format!(
+                "unknown layout `{}`, expected `hilbert`, `zorder` or `raw`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MapLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapLayout::Hilbert => f.write_str("hilbert"),
+            MapLayout::ZOrder => f.write_str("zorder"),
+            MapLayout::Raw => f.write_str("raw"),
+        }
+    }
+}
+
+/// The coordinates of position `d` on the Hilbert curve filling a `side`×`side` square,
+/// `side` is a power of two and `d` less than `side * side`. The curve starts at (0, 0) and ends at (side - 1, 0).
+pub fn hilbert_d2xy(side: u32, d: u64) -> (u32, u32) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = d;
+    let mut s = 1;
+    while s < side {
+        let rx = 1 & (t / 2) as u32;
+        let ry = 1 & (t as u32 ^ rx);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+/// The coordinates of position `d` on the Z-order curve, the even bits of `d` form `x` and the odd bits `y`.
+/// The first `side * side` positions fill a `side`×`side` square for every power of two `side`.
+pub fn morton_d2xy(d: u64) -> (u32, u32) {
+    (compact_bits(d), compact_bits(d >> 1))
+}
+
+/// The position of (`x`, `y`) on the Z-order curve, the inverse of [`morton_d2xy`].
+pub fn morton_xy2d(x: u32, y: u32) -> u64 {
+    spread_bits(x) | (spread_bits(y) << 1)
+}
+
+/// Gather the even bits of `d` into the low half.
+fn compact_bits(d: u64) -> u32 {
+    let mut d = d & 0x5555_5555_5555_5555;
+    d = (d | (d >> 1)) & 0x3333_3333_3333_3333;
+    d = (d | (d >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    d = (d | (d >> 4)) & 0x00ff_00ff_00ff_00ff;
+    d = (d | (d >> 8)) & 0x0000_ffff_0000_ffff;
+    d = (d | (d >> 16)) & 0x0000_0000_ffff_ffff;
+    d as u32
+}
+
+/// Spread the bits of `x` over the even bits, the inverse of [`compact_bits`].
+fn spread_bits(x: u32) -> u64 {
+    let mut d = x as u64;
+    d = (d | (d << 16)) & 0x0000_ffff_0000_ffff;
+    d = (d | (d << 8)) & 0x00ff_00ff_00ff_00ff;
+    d = (d | (d << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    d = (d | (d << 2)) & 0x3333_3333_3333_3333;
+    d = (d | (d << 1)) & 0x5555_5555_5555_5555;
+    d
+}
+
+/// The side of the smallest square image with a power of two side that has a pixel for each of `len` bytes,
+/// capped at [`MAX_MAP_SIDE`]. Empty inputs get a side of 1.
+pub fn map_side(len: u64) -> u32 {
+    let mut side: u32 = 1;
+    while (side as u64) * (side as u64) < len && side < MAX_MAP_SIDE {
+        side *= 2;
+    }
+    side
+}
+
+/// Render a file map of the bytes, laying them out as pixels along the curve of `layout` in the smallest
+/// fitting square, see [`map_side`]. Positions past the end of the input are transparent.
+///
+/// Every pixel shows the mean color of its bytes in the color `scheme`, only inputs larger than
+/// [`MAX_MAP_SIDE`] squared have more than one byte per pixel.
+pub fn render_map(
+    buf: &[u8],
+    layout: MapLayout,
+    scheme: ColorScheme,
+) -> Result<RgbaImage, BinvizError> {
+    if buf.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let side = map_side(buf.len() as u64);
+    let pixels = side as usize * side as usize;
+    let bytes_per_pixel = buf.len().div_ceil(pixels);
+    let mut image = ImageBuffer::from_pixel(side, side, Rgba([0, 0, 0, 0]));
+    for (d, bytes) in buf.chunks(bytes_per_pixel).enumerate() {
+        let mut sum = [0; 3];
+        for &byte in bytes {
+            let color = scheme.color(byte).0;
+            for channel in 0..3 {
+                sum[channel] += color[channel] as usize;
+            }
+        }
+        let [r, g, b] = sum.map(|channel| (channel / bytes.len()) as u8);
+        let (x, y) = layout.d2xy(side, d as u64);
+        image.put_pixel(x, y, Rgba([r, g, b, u8::MAX]));
+    }
+    Ok(image)
+}
+
+/// Render the bytes as they are, row by row with `width` bytes per row, byte `i` at (`i mod width`, `i / width`)
+/// in the color `scheme`. The image is `ceil(len / width)` rows high, the rest of the last row is transparent.
+///
+/// Unlike [`render_map`] every byte gets its own pixel, whatever the size of the input.
+pub fn render_raw(buf: &[u8], width: u32, scheme: ColorScheme) -> Result<RgbaImage, BinvizError> {
+    if width == 0 {
+        return Err(BinvizError::InvalidWidth { width });
+    }
+    if buf.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let height = buf.len().div_ceil(width as usize) as u32;
+    let mut image = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0]));
+    for (i, &byte) in buf.iter().enumerate() {
+        let [r, g, b] = scheme.color(byte).0;
+        let (x, y) = ((i % width as usize) as u32, (i / width as usize) as u32);
+        image.put_pixel(x, y, Rgba([r, g, b, u8::MAX]));
+    }
+    Ok(image)
+}
+
+/// Render a map of an entropy profile, see [`crate::entropy_profile`], every window is a pixel laid out along
+/// the curve of `layout` in the smallest fitting square and colored by its entropy from 0 to 8 bits per byte
+/// through `colormap`, e.g. [`Colormap::Turbo`] from blue to red. Positions past the last window are transparent.
+///
+/// Profiles of more than [`MAX_MAP_SIDE`] squared windows show the mean entropy of several windows per pixel.
+pub fn render_entropy_map(
+    profile: &[(u64, f64)],
+    layout: MapLayout,
+    colormap: Colormap,
+) -> Result<RgbaImage, BinvizError> {
+    if profile.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let side = map_side(profile.len() as u64);
+    let pixels = side as usize * side as usize;
+    let windows_per_pixel = profile.len().div_ceil(pixels);
+    let mut image = ImageBuffer::from_pixel(side, side, Rgba([0, 0, 0, 0]));
+    for (d, windows) in profile.chunks(windows_per_pixel).enumerate() {
+        let entropy =
+            windows.iter().map(|&(_, entropy)| entropy).sum::<f64>() / windows.len() as f64;
+        let [r, g, b] = colormap.rgb8(entropy / 8.0).0;
+        let (x, y) = layout.d2xy(side, d as u64);
+        image.put_pixel(x, y, Rgba([r, g, b, u8::MAX]));
+    }
+    Ok(image)
+}
+
+/// The labels of the colors of [`ColorScheme::ByteClass`] in [`with_class_legend`], with a byte of that color.
+const CLASS_LEGEND: [(&str, u8); 5] = [
+    ("00", 0x00),
+    ("FF", 0xff),
+    ("text", b'A'),
+    ("ctrl", 0x01),
+    ("high", 0x80),
+];
+
+/// The size of a font pixel in legends.
+const LEGEND_SCALE: u32 = 2;
+
+/// The space around and between the entries of a legend, in pixels.
+const LEGEND_PADDING: u32 = 4;
+
+/// Add a strip below a file map explaining the colors of [`ColorScheme::ByteClass`].
+/// The image is widened if the legend doesn't fit, the map itself is unchanged in the top left.
+pub fn with_class_legend(image: &RgbaImage) -> RgbaImage {
+    let swatch = GLYPH_HEIGHT * LEGEND_SCALE;
+    let legend_width = CLASS_LEGEND
+        .iter()
+        .map(|(label, _)| {
+            swatch + LEGEND_PADDING + text_width(label, LEGEND_SCALE) + LEGEND_PADDING * 2
+        })
+        .sum::<u32>()
+        + LEGEND_PADDING;
+    let width = image.width().max(legend_width);
+    let height = image.height() + swatch + LEGEND_PADDING * 2;
+    let mut legend = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0]));
+    for (x, y, pixel) in image.enumerate_pixels() {
+        legend.put_pixel(x, y, *pixel);
+    }
+    let top = image.height();
+    for y in top..height {
+        for x in 0..width {
+            legend.put_pixel(x, y, Rgba([160, 160, 160, u8::MAX]));
+        }
+    }
+    let mut x = LEGEND_PADDING;
+    for (label, byte) in CLASS_LEGEND {
+        let [r, g, b] = ColorScheme::ByteClass.color(byte).0;
+        for dy in 0..swatch {
+            for dx in 0..swatch {
+                legend.put_pixel(x + dx, top + LEGEND_PADDING + dy, Rgba([r, g, b, u8::MAX]));
+            }
+        }
+        x += swatch + LEGEND_PADDING;
+        draw_text(
+            &mut legend,
+            x,
+            top + LEGEND_PADDING,
+            label,
+            LEGEND_SCALE,
+            Rgba([0, 0, 0, u8::MAX]),
+        );
+        x += text_width(label, LEGEND_SCALE) + LEGEND_PADDING * 2;
+    }
+    legend
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, io::Cursor};
+
+    use super::*;
+    use crate::{entropy_profile_from_reader, test_support::noise};
+
+    #[test]
+    fn hilbert_curve_visits_every_pixel_once() {
+        for side in [1, 2, 4, 8, 32] {
+            let points: Vec<(u32, u32)> = (0..side as u64 * side as u64)
+                .map(|d| hilbert_d2xy(side, d))
+                .collect();
+            assert!(points.iter().all(|&(x, y)| x < side && y < side));
+            let distinct: HashSet<_> = points.iter().collect();
+            assert_eq!(distinct.len(), points.len(), "side {}", side);
+            // Every step moves to a neighbouring pixel.
+            for pair in points.windows(2) {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                assert_eq!(x0.abs_diff(x1) + y0.abs_diff(y1), 1, "side {}", side);
+            }
+            assert_eq!(points[0], (0, 0));
+            assert_eq!(*points.last().unwrap(), (side - 1, 0));
+        }
+    }
+
+    #[test]
+    fn z_order_curve_fills_every_square_and_round_trips() {
+        for side in [1u32, 2, 4, 8, 32] {
+            let points: Vec<(u32, u32)> = (0..side as u64 * side as u64).map(morton_d2xy).collect();
+            assert!(points.iter().all(|&(x, y)| x < side && y < side));
+            let distinct: HashSet<_> = points.iter().collect();
+            assert_eq!(distinct.len(), points.len(), "side {}", side);
+            for (d, &(x, y)) in points.iter().enumerate() {
+                assert_eq!(morton_xy2d(x, y), d as u64);
+            }
+        }
+        assert_eq!(morton_d2xy(0b1110), (0b10, 0b11));
+        assert_eq!(morton_xy2d(u32::MAX, u32::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn raw_rendering_puts_every_byte_in_its_own_pixel() {
+        let scheme = ColorScheme::Value(Colormap::Grayscale);
+        let image = render_raw(&[0x00, 0x40, 0x80, 0xff, 0x10], 2, scheme).unwrap();
+        assert_eq!(image.dimensions(), (2, 3));
+        let gray = |value: u8| Rgba([value, value, value, u8::MAX]);
+        let pixels: Vec<Rgba<u8>> = image.pixels().copied().collect();
+        assert_eq!(
+            pixels,
+            [
+                gray(0x00),
+                gray(0x40),
+                gray(0x80),
+                gray(0xff),
+                gray(0x10),
+                // The rest of the last row.
+                Rgba([0, 0, 0, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_rendering_colors_bytes_by_the_scheme() {
+        let image = render_raw(b"A\0\xff", 4, ColorScheme::ByteClass).unwrap();
+        assert_eq!(image.dimensions(), (4, 1));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([55, 126, 184, u8::MAX]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([0, 0, 0, u8::MAX]));
+        assert_eq!(*image.get_pixel(2, 0), Rgba([255, 255, 255, u8::MAX]));
+        assert_eq!(image.get_pixel(3, 0)[3], 0);
+    }
+
+    #[test]
+    fn raw_rendering_of_a_whole_row_has_no_padding() {
+        let image = render_raw(&[1; 12], 4, ColorScheme::default()).unwrap();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert!(image.pixels().all(|pixel| pixel[3] == u8::MAX));
+        assert!(matches!(
+            render_raw(&[1], 0, ColorScheme::default()),
+            Err(BinvizError::InvalidWidth { width: 0 })
+        ));
+        assert!(matches!(
+            render_raw(&[], 4, ColorScheme::default()),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn entropy_map_of_zeros_then_noise_has_two_halves() {
+        let buf = [vec![0; 32768], noise(32768)].concat();
+        // 256 windows, which fill a 16×16 map.
+        let profile = entropy_profile_from_reader(Cursor::new(buf), 256, 256).unwrap();
+        assert_eq!(profile.len(), 256);
+        let zero = Colormap::Turbo.rgb8(0.0).0;
+        for layout in [MapLayout::Hilbert, MapLayout::ZOrder, MapLayout::Raw] {
+            let map = render_entropy_map(&profile, layout, Colormap::Turbo).unwrap();
+            assert_eq!(map.dimensions(), (16, 16));
+            for d in 0..256 {
+                let (x, y) = layout.d2xy(16, d);
+                let Rgba([r, g, b, a]) = *map.get_pixel(x, y);
+                assert_eq!(a, u8::MAX);
+                if d < 128 {
+                    // The zeros are the blue end of the colormap.
+                    assert_eq!([r, g, b], zero, "{} {}", layout, d);
+                } else {
+                    // The noise has close to 8 bits per byte, the red end.
+                    assert!(r > 2 * b && r > 100, "{} {} {:?}", layout, d, [r, g, b]);
+                }
+            }
+        }
+        // The Hilbert curve fills the left half of the map first.
+        let map = render_entropy_map(&profile, MapLayout::Hilbert, Colormap::Turbo).unwrap();
+        assert_eq!(map.get_pixel(0, 15).0[..3], zero);
+        assert_ne!(map.get_pixel(15, 15).0[..3], zero);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{create_output, validate_dimension, BinvizError, Histogram};
+
+/// The probabilities of the first-order Markov model of bytes, `matrix[a][b]` is the probability
+/// that byte `b` follows byte `a`. Every row sums to 1.
+pub type TransitionMatrix = [[f64; 256]; 256];
+
+/// The change between two steps below which [`stationary_distribution`] considers the distribution converged,
+/// summed over all bytes.
+pub const STATIONARY_TOLERANCE: f64 = 1e-12;
+
+/// The amount of steps after which [`stationary_distribution`] stops, converged or not.
+pub const STATIONARY_MAX_ITERATIONS: usize = 100_000;
+
+/// Calculate the transition matrix of the first-order Markov model of which the 2-dimensional `dihistogram`
+/// counts the transitions: every row is the counts of the pairs starting with its byte, normalized to sum to 1.
+///
+/// A byte that is never followed by another, as it doesn't occur or only at the end, gets a uniform row,
+/// so every row is a probability distribution.
+pub fn transition_matrix(
+    dihistogram: &Histogram<u8>,
+) -> Result<Box<TransitionMatrix>, BinvizError> {
+    validate_dimension(dihistogram, 2)?;
+    if dihistogram.values().all(|&count| count == 0) {
+        return Err(BinvizError::EmptyInput);
+    }
+    let mut totals = [0usize; 256];
+    for (key, &count) in dihistogram {
+        totals[key[0] as usize] += count;
+    }
+    let mut matrix = zeroed_matrix();
+    for (key, &count) in dihistogram {
+        let from = key[0] as usize;
+        matrix[from][key[1] as usize] = count as f64 / totals[from] as f64;
+    }
+    for (row, &total) in matrix.iter_mut().zip(&totals) {
+        if total == 0 {
+            row.fill(1.0 / 256.0);
+        }
+    }
+    Ok(matrix)
+}
+
+/// A transition matrix of zeros, on the heap as it is half a megabyte.
+fn zeroed_matrix() -> Box<TransitionMatrix> {
+    vec![[0.0; 256]; 256]
+        .into_boxed_slice()
+        .try_into()
+        .expect("the vector has exactly 256 rows")
+}
+
+/// Calculate the stationary distribution of the Markov model: the probability of every byte
+/// that a step of the model leaves unchanged, the long run share of every byte in its output.
+///
+/// Found by power iteration from the uniform distribution until it changes less than [`STATIONARY_TOLERANCE`],
+/// or at most [`STATIONARY_MAX_ITERATIONS`] steps. Every step stays put with probability 1/2, which has the same
+/// stationary distribution but converges for periodic models too, like the alternation of "ABAB".
+/// Of a model with several closed sets of bytes, the distribution depends on the start.
+pub fn stationary_distribution(matrix: &TransitionMatrix) -> [f64; 256] {
+    let mut distribution = [1.0 / 256.0; 256];
+    for _ in 0..STATIONARY_MAX_ITERATIONS {
+        let mut next = distribution.map(|probability| probability / 2.0);
+        for (row, &probability) in matrix.iter().zip(&distribution) {
+            if probability > 0.0 {
+                for (next, &transition) in next.iter_mut().zip(row) {
+                    *next += probability / 2.0 * transition;
+                }
+            }
+        }
+        let change: f64 = next
+            .iter()
+            .zip(&distribution)
+            .map(|(next, probability)| (next - probability).abs())
+            .sum();
+        distribution = next;
+        if change < STATIONARY_TOLERANCE {
+            break;
+        }
+    }
+    distribution
+}
+
+/// Generate `len` synthetic bytes from the Markov model, the first drawn from its stationary distribution
+/// and every next one from the row of the byte before it, with the same bytes for the same `seed`.
+pub fn sample_markov(matrix: &TransitionMatrix, len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    let mut bytes = Vec::with_capacity(len);
+    if len == 0 {
+        return bytes;
+    }
+    let mut byte = draw(&cumulative(&stationary_distribution(matrix)), &mut state);
+    bytes.push(byte);
+    let mut rows = zeroed_matrix();
+    for (cumulative_row, row) in rows.iter_mut().zip(matrix) {
+        *cumulative_row = cumulative(row);
+    }
+    while bytes.len() < len {
+        byte = draw(&rows[byte as usize], &mut state);
+        bytes.push(byte);
+    }
+    bytes
+}
+
+/// The running sums of `probabilities`, so a byte can be drawn by a binary search.
+fn cumulative(probabilities: &[f64; 256]) -> [f64; 256] {
+    let mut sum = 0.0;
+    probabilities.map(|probability| {
+        sum += probability;
+        sum
+    })
+}
+
+/// Draw a byte with the probabilities of which `cumulative` is the running sums, advancing the random `state`.
+fn draw(cumulative: &[f64; 256], state: &mut u64) -> u8 {
+    let target = next_uniform(state) * cumulative[255];
+    // The first byte whose running sum exceeds the target, which never is a byte with probability 0.
+    match cumulative.partition_point(|&sum| sum <= target) {
+        // Rounding can leave the target at the sum, then the last possible byte is drawn.
+        256 => cumulative
+            .iter()
+            .rposition(|&sum| sum < cumulative[255])
+            .map_or(0, |before| before as u8 + 1),
+        byte => byte as u8,
+    }
+}
+
+/// A uniformly distributed number in `[0, 1)` from the splitmix64 generator, advancing the random `state`.
+fn next_uniform(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    // The top 53 bits fill the mantissa of a double exactly.
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Write the transition matrix to `path` as CSV, a header and a line per byte `from` with the probabilities
+/// of the bytes following it, bytes in decimal. An existing file is only overwritten with `force`.
+pub fn write_transition_matrix<P>(
+    matrix: &TransitionMatrix,
+    path: P,
+    force: bool,
+) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = create_output(path, force)?;
+    let mut writer = BufWriter::new(file);
+    write_csv(matrix, &mut writer)
+        .and_then(|()| writer.flush())
+        .map_err(|err| BinvizError::write(path, err))
+}
+
+/// Write the matrix as a `from,0,..,255` header and a line of probabilities per row.
+fn write_csv<W>(matrix: &TransitionMatrix, writer: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    write!(writer, "from")?;
+    for byte in 0..256 {
+        write!(writer, ",{}", byte)?;
+    }
+    writeln!(writer)?;
+    for (from, row) in matrix.iter().enumerate() {
+        write!(writer, "{}", from)?;
+        for probability in row {
+            write!(writer, ",{}", probability)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Display the stationary distribution as a table of the bytes with a probability of at least
+/// [`STATIONARY_TOLERANCE`], the most probable first, only the first `top` if given.
+/// Bytes the model never reaches keep a probability below the tolerance when the iteration stops.
+#[cfg(feature = "tables")]
+pub fn display_stationary_distribution(
+    distribution: &[f64; 256],
+    top: Option<usize>,
+    format: OutputFormat,
+) -> String {
+    let mut bytes: Vec<(u8, f64)> = (0..=255u8)
+        .zip(distribution.iter().copied())
+        .filter(|&(_, probability)| probability >= STATIONARY_TOLERANCE)
+        .collect();
+    bytes.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    bytes.truncate(top.unwrap_or(bytes.len()));
+    let rows = bytes
+        .into_iter()
+        .map(|(byte, probability)| vec![format!("{:#04x}", byte), format.value(probability, None)]);
+    format.render(["Byte", "Stationary Probability"], rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_histogram_from_bytes, test_support::noise};
+
+    /// The model of the alternation "ABAB".
+    fn alternating() -> Box<TransitionMatrix> {
+        transition_matrix(&calculate_histogram_from_bytes(b"ABAB", 2, 1)).unwrap()
+    }
+
+    #[test]
+    fn alternation_always_switches() {
+        let matrix = alternating();
+        assert_eq!(matrix[b'A' as usize][b'B' as usize], 1.0);
+        assert_eq!(matrix[b'B' as usize][b'A' as usize], 1.0);
+        assert_eq!(matrix[b'A' as usize][b'A' as usize], 0.0);
+        // A byte that never occurs gets a uniform row.
+        assert_eq!(matrix[0][0], 1.0 / 256.0);
+    }
+
+    #[test]
+    fn alternation_is_half_of_each() {
+        let distribution = stationary_distribution(&alternating());
+        assert!((distribution[b'A' as usize] - 0.5).abs() < 1e-9);
+        assert!((distribution[b'B' as usize] - 0.5).abs() < 1e-9);
+        assert!((distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn samples_of_the_alternation_alternate() {
+        let bytes = sample_markov(&alternating(), 100, 7);
+        assert_eq!(bytes.len(), 100);
+        assert!(bytes[0] == b'A' || bytes[0] == b'B');
+        assert!(bytes
+            .windows(2)
+            .all(|pair| pair[0] != pair[1] && (pair[1] == b'A' || pair[1] == b'B')));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_bytes() {
+        let matrix =
+            transition_matrix(&calculate_histogram_from_bytes(&noise(10_000), 2, 1)).unwrap();
+        let bytes = sample_markov(&matrix, 1000, 42);
+        assert_eq!(bytes, sample_markov(&matrix, 1000, 42));
+        assert_ne!(bytes, sample_markov(&matrix, 1000, 43));
+        assert!(sample_markov(&matrix, 0, 42).is_empty());
+    }
+}
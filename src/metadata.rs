@@ -0,0 +1,426 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+use crate::{sha256::Sha256, write_output, BinvizError, Normalization, ScaleMode, CHUNK_SIZE};
+
+/// The bytes every PNG file starts with.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// The keyword of the text chunk with the name and version of the program, a keyword the PNG specification defines.
+const SOFTWARE_KEYWORD: &str = "Software";
+
+const SOURCE_KEYWORD: &str = "Binviz Source";
+const SOURCE_SIZE_KEYWORD: &str = "Binviz Source Size";
+const SOURCE_SHA256_KEYWORD: &str = "Binviz Source SHA-256";
+const OFFSET_KEYWORD: &str = "Binviz Offset";
+const LENGTH_KEYWORD: &str = "Binviz Length";
+const DIMENSION_KEYWORD: &str = "Binviz Dimension";
+const SCALE_KEYWORD: &str = "Binviz Scale";
+const NORMALIZATION_KEYWORD: &str = "Binviz Normalization";
+const TOTAL_KEYWORD: &str = "Binviz Total";
+const FULL_COUNT_KEYWORD: &str = "Binviz Full Count";
+
+/// A file a histogram was calculated from, as recorded in the metadata of an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// The file name, without the folders leading up to it.
+    pub name: String,
+    /// The size in bytes.
+    pub size: u64,
+    /// The SHA-256 hash of the contents, in lowercase hexadecimal.
+    pub sha256: String,
+}
+
+impl SourceFile {
+    /// Describe the file at `path` by its name, size and SHA-256 hash, reading it in chunks.
+    pub fn from_path<P>(path: P) -> Result<Self, BinvizError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|err| BinvizError::io(path, err))?;
+        let mut hash = Sha256::new();
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut size = 0;
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .map_err(|err| BinvizError::io(path, err))?;
+            if read == 0 {
+                break;
+            }
+            hash.update(&buffer[..read]);
+            size += read as u64;
+        }
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        Ok(SourceFile {
+            name,
+            size,
+            sha256: hash.finish(),
+        })
+    }
+}
+
+/// How an image was generated, written into saved PNG images by [`write_image_metadata`]
+/// so the image can still be traced back to its source files and settings once it is shared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageMetadata {
+    /// The version of binviz that generated the image.
+    pub version: String,
+    /// The files the histogram was calculated from, empty if it was loaded or read from standard input.
+    pub sources: Vec<SourceFile>,
+    /// The byte offset in every source file the histogram starts at.
+    pub offset: u64,
+    /// The amount of bytes of every source file in the histogram, `None` for everything up to the end.
+    pub length: Option<u64>,
+    /// The amount of consecutive symbols per histogram key.
+    pub dimension: usize,
+    /// How counts map to brightness.
+    pub scale: ScaleMode,
+    /// Which count is shown at full brightness.
+    pub normalization: Normalization,
+    /// The sum of all counts in the histogram.
+    pub total: usize,
+    /// The count shown at full brightness.
+    pub full_count: f64,
+}
+
+impl ImageMetadata {
+    /// The metadata of an image generated by this version of binviz.
+    pub fn new(
+        sources: Vec<SourceFile>,
+        dimension: usize,
+        scale: ScaleMode,
+        normalization: Normalization,
+        total: usize,
+        full_count: f64,
+    ) -> Self {
+        ImageMetadata {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            sources,
+            offset: 0,
+            length: None,
+            dimension,
+            scale,
+            normalization,
+            total,
+            full_count,
+        }
+    }
+
+    /// Record that the histogram only covers `length` bytes at `offset` of the source files.
+    pub fn with_range(mut self, offset: u64, length: Option<u64>) -> Self {
+        self.offset = offset;
+        self.length = length;
+        self
+    }
+
+    /// The keywords and texts of the text chunks holding this metadata, with a chunk per field of every source file.
+    fn text_chunks(&self) -> Vec<(&'static str, String)> {
+        let mut chunks = vec![(SOFTWARE_KEYWORD, format!("binviz {}", self.version))];
+        for source in &self.sources {
+            chunks.push((SOURCE_KEYWORD, source.name.clone()));
+            chunks.push((SOURCE_SIZE_KEYWORD, source.size.to_string()));
+            chunks.push((SOURCE_SHA256_KEYWORD, source.sha256.clone()));
+        }
+        chunks.push((OFFSET_KEYWORD, self.offset.to_string()));
+        if let Some(length) = self.length {
+            chunks.push((LENGTH_KEYWORD, length.to_string()));
+        }
+        chunks.extend([
+            (DIMENSION_KEYWORD, self.dimension.to_string()),
+            (SCALE_KEYWORD, self.scale.to_string()),
+            (NORMALIZATION_KEYWORD, self.normalization.to_string()),
+            (TOTAL_KEYWORD, self.total.to_string()),
+            (FULL_COUNT_KEYWORD, self.full_count.to_string()),
+        ]);
+        chunks
+    }
+}
+
+/// The type and data of a PNG chunk.
+type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+/// Split the PNG file `bytes` into the types and data of its chunks, checking the signature but not the checksums.
+fn png_chunks(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, &'static str> {
+    let mut rest = bytes.strip_prefix(&PNG_SIGNATURE).ok_or("not a PNG file")?;
+    let mut chunks = Vec::new();
+    while !rest.is_empty() {
+        if rest.len() < 12 {
+            return Err("truncated PNG chunk");
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = [rest[4], rest[5], rest[6], rest[7]];
+        if rest.len() - 12 < length {
+            return Err("truncated PNG chunk");
+        }
+        chunks.push((kind, &rest[8..8 + length]));
+        rest = &rest[12 + length..];
+    }
+    Ok(chunks)
+}
+
+/// Encode a PNG chunk of type `kind` with its length and checksum.
+fn encode_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(12 + data.len());
+    chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(kind);
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(&crc32fast::hash(&chunk[4..]).to_be_bytes());
+    chunk
+}
+
+/// Add the `metadata` to the PNG image at `path` as uncompressed iTXt chunks, which hold UTF-8 text
+/// so file names outside of Latin-1 survive, just before the end of the image.
+///
+/// The image crate can't write text chunks, so the saved image is rewritten with the chunks inserted.
+pub fn write_image_metadata<P>(path: P, metadata: &ImageMetadata) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|err| BinvizError::io(path, err))?;
+    let chunks = png_chunks(&bytes).map_err(|err| BinvizError::serialization(path, err))?;
+    let mut output = PNG_SIGNATURE.to_vec();
+    for (kind, data) in chunks {
+        if &kind == b"IEND" {
+            for (keyword, text) in metadata.text_chunks() {
+                // The keyword, no compression, no language tag and no translated keyword.
+                let mut data = keyword.as_bytes().to_vec();
+                data.extend_from_slice(&[0, 0, 0, 0, 0]);
+                data.extend_from_slice(text.as_bytes());
+                output.extend(encode_chunk(b"iTXt", &data));
+            }
+        }
+        output.extend(encode_chunk(&kind, data));
+    }
+    // The image is rewritten in place, so it always exists.
+    write_output(path, output, true)
+}
+
+/// The keyword and text of an uncompressed tEXt or iTXt chunk.
+fn decode_text_chunk(kind: &[u8; 4], data: &[u8]) -> Option<(String, String)> {
+    let separator = data.iter().position(|&byte| byte == 0)?;
+    let keyword: String = data[..separator].iter().map(|&byte| byte as char).collect();
+    let rest = &data[separator + 1..];
+    match kind {
+        // tEXt chunks hold Latin-1 text, whose bytes are the code points.
+        b"tEXt" => Some((keyword, rest.iter().map(|&byte| byte as char).collect())),
+        b"iTXt" if rest.len() >= 2 && rest[0] == 0 => {
+            let rest = &rest[2..];
+            let language_end = rest.iter().position(|&byte| byte == 0)?;
+            let rest = &rest[language_end + 1..];
+            let translated_end = rest.iter().position(|&byte| byte == 0)?;
+            let text = String::from_utf8(rest[translated_end + 1..].to_vec()).ok()?;
+            Some((keyword, text))
+        }
+        _ => None,
+    }
+}
+
+/// Read the metadata [`write_image_metadata`] added to the PNG image at `path`.
+///
+/// Images without it, or with a field that can't be parsed, give [`BinvizError::Serialization`].
+pub fn read_image_metadata<P>(path: P) -> Result<ImageMetadata, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|err| BinvizError::io(path, err))?;
+    let chunks = png_chunks(&bytes).map_err(|err| BinvizError::serialization(path, err))?;
+    let texts: Vec<(String, String)> = chunks
+        .iter()
+        .filter_map(|(kind, data)| decode_text_chunk(kind, data))
+        .collect();
+    let all = |keyword: &str| -> Vec<&str> {
+        texts
+            .iter()
+            .filter(|(found, _)| found == keyword)
+            .map(|(_, text)| text.as_str())
+            .collect()
+    };
+    let field = |keyword: &str| -> Result<&str, BinvizError> {
+        all(keyword).first().copied().ok_or_else(|| {
+            BinvizError::serialization(path, format!("missing the `{}` text chunk", keyword))
+        })
+    };
+    let invalid = |keyword: &str| {
+        BinvizError::serialization(path, format!("invalid `{}` text chunk", keyword))
+    };
+    let version = field(SOFTWARE_KEYWORD)?
+        .strip_prefix("binviz ")
+        .ok_or_else(|| invalid(SOFTWARE_KEYWORD))?
+        .to_string();
+    let (names, sizes, hashes) = (
+        all(SOURCE_KEYWORD),
+        all(SOURCE_SIZE_KEYWORD),
+        all(SOURCE_SHA256_KEYWORD),
+    );
+    if names.len() != sizes.len() || names.len() != hashes.len() {
+        return Err(invalid(SOURCE_KEYWORD));
+    }
+    let sources = names
+        .iter()
+        .zip(&sizes)
+        .zip(&hashes)
+        .map(|((name, size), sha256)| {
+            Ok(SourceFile {
+                name: name.to_string(),
+                size: size.parse().map_err(|_| invalid(SOURCE_SIZE_KEYWORD))?,
+                sha256: sha256.to_string(),
+            })
+        })
+        .collect::<Result<_, BinvizError>>()?;
+    let length = match all(LENGTH_KEYWORD).first() {
+        Some(length) => Some(length.parse().map_err(|_| invalid(LENGTH_KEYWORD))?),
+        None => None,
+    };
+    Ok(ImageMetadata {
+        version,
+        sources,
+        offset: field(OFFSET_KEYWORD)?
+            .parse()
+            .map_err(|_| invalid(OFFSET_KEYWORD))?,
+        length,
+        dimension: field(DIMENSION_KEYWORD)?
+            .parse()
+            .map_err(|_| invalid(DIMENSION_KEYWORD))?,
+        scale: field(SCALE_KEYWORD)?
+            .parse()
+            .map_err(|_| invalid(SCALE_KEYWORD))?,
+        normalization: field(NORMALIZATION_KEYWORD)?
+            .parse()
+            .map_err(|_| invalid(NORMALIZATION_KEYWORD))?,
+        total: field(TOTAL_KEYWORD)?
+            .parse()
+            .map_err(|_| invalid(TOTAL_KEYWORD))?,
+        full_count: field(FULL_COUNT_KEYWORD)?
+            .parse()
+            .map_err(|_| invalid(FULL_COUNT_KEYWORD))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "images")]
+    use image::{ImageBuffer, Luma};
+
+    use super::*;
+
+    /// Save a small grayscale PNG image in `dir`.
+    #[cfg(feature = "images")]
+    fn png_in(dir: &Path) -> std::path::PathBuf {
+        let path = dir.join("image.png");
+        let image: ImageBuffer<Luma<u16>, Vec<u16>> =
+            ImageBuffer::from_fn(4, 3, |x, y| Luma([(x * 1000 + y) as u16]));
+        image.save(&path).unwrap();
+        path
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn metadata_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = png_in(dir.path());
+        let sources = vec![
+            SourceFile {
+                name: "firmware.bin".to_string(),
+                size: 1 << 20,
+                sha256: "ab".repeat(32),
+            },
+            SourceFile {
+                name: "ファームウェア, copy.bin".to_string(),
+                size: 0,
+                sha256: "cd".repeat(32),
+            },
+        ];
+        let metadata = ImageMetadata::new(
+            sources,
+            3,
+            ScaleMode::Sqrt,
+            Normalization::Percentile(99.5),
+            123_456_789,
+            1234.5678,
+        )
+        .with_range(4096, Some(65536));
+        write_image_metadata(&path, &metadata).unwrap();
+        assert_eq!(read_image_metadata(&path).unwrap(), metadata);
+        // The image itself is unchanged.
+        let image = image::open(&path).unwrap().into_luma16();
+        assert_eq!(image.get_pixel(3, 2)[0], 3002);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn metadata_without_sources_or_length_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = png_in(dir.path());
+        let metadata = ImageMetadata::new(
+            Vec::new(),
+            2,
+            ScaleMode::Linear,
+            Normalization::Average,
+            0,
+            0.0,
+        );
+        write_image_metadata(&path, &metadata).unwrap();
+        let read = read_image_metadata(&path).unwrap();
+        assert_eq!(read, metadata);
+        assert_eq!(read.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(read.length, None);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn missing_or_invalid_metadata_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = png_in(dir.path());
+        assert!(matches!(
+            read_image_metadata(&path),
+            Err(BinvizError::Serialization { .. })
+        ));
+        let text = dir.path().join("image.txt");
+        fs::write(&text, "not an image").unwrap();
+        assert!(matches!(
+            read_image_metadata(&text),
+            Err(BinvizError::Serialization { .. })
+        ));
+        assert!(matches!(
+            write_image_metadata(
+                &text,
+                &ImageMetadata::new(
+                    Vec::new(),
+                    1,
+                    ScaleMode::Linear,
+                    Normalization::Maximum,
+                    1,
+                    1.0
+                )
+            ),
+            Err(BinvizError::Serialization { .. })
+        ));
+    }
+
+    #[test]
+    fn source_file_is_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            SourceFile::from_path(&path).unwrap(),
+            SourceFile {
+                name: "abc.txt".to_string(),
+                size: 3,
+                sha256: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_string(),
+            }
+        );
+    }
+}
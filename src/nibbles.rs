@@ -0,0 +1,104 @@
+#[cfg(feature = "images")]
+use image::{ImageBuffer, Luma, Pixel};
+
+use crate::{calculate_histogram_from_bytes, Histogram};
+#[cfg(feature = "images")]
+use crate::{generate_image, BinvizError, Normalization, ScaleMode, Visualization};
+
+/// The factor nibble images are scaled up by, so the 16×16 nibble digraph fills a 256×256 image.
+#[cfg(feature = "images")]
+pub const NIBBLE_SCALE: u32 = 16;
+
+/// Split every byte into its high and low nibble, in that order.
+pub fn split_nibbles(buf: &[u8]) -> Vec<u8> {
+    buf.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) nibbles of a byte slice,
+/// the keys only contain values in `0..=15`. Windows start `stride` nibbles apart.
+pub fn calculate_nibble_histogram_from_bytes(
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+) -> Histogram<u8> {
+    calculate_histogram_from_bytes(&split_nibbles(buf), dimension, stride)
+}
+
+/// Generate the digraph image of a nibble histogram, see [`generate_image`],
+/// scaled up with [`scale_nibble_image`].
+#[cfg(feature = "images")]
+pub fn generate_nibble_image(
+    dihistogram: &Histogram<u8>,
+    scale: ScaleMode,
+    normalization: Normalization,
+) -> Result<Visualization<Luma<u16>>, BinvizError> {
+    let (image, total, full_brightness) = generate_image(dihistogram, scale, normalization)?;
+    Ok((scale_nibble_image(&image), total, full_brightness))
+}
+
+/// Scale the top left 16×16 pixels of an image generated from a nibble histogram
+/// up by [`NIBBLE_SCALE`] using nearest neighbor, into a 256×256 image.
+#[cfg(feature = "images")]
+pub fn scale_nibble_image<P>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel,
+{
+    let side = 16 * NIBBLE_SCALE;
+    ImageBuffer::from_fn(side, side, |x, y| {
+        *image.get_pixel(x / NIBBLE_SCALE, y / NIBBLE_SCALE)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_entropy_histogram, EntropyUnit};
+
+    #[test]
+    fn splits_the_high_nibble_first() {
+        assert_eq!(split_nibbles(&[0x12, 0xab]), [0x1, 0x2, 0xa, 0xb]);
+        assert!(split_nibbles(&[]).is_empty());
+    }
+
+    #[test]
+    fn counts_nibbles_across_bytes() {
+        // The nibbles 1, 2, 2, 1, with the pair 2, 2 spanning the bytes.
+        let histogram = calculate_nibble_histogram_from_bytes(&[0x12, 0x21], 2, 1);
+        let expected: Histogram<u8> = [(vec![1, 2], 1), (vec![2, 2], 1), (vec![2, 1], 1)]
+            .into_iter()
+            .collect();
+        assert_eq!(histogram, expected);
+        let every_byte: Vec<u8> = (0..=u8::MAX).collect();
+        assert!(calculate_nibble_histogram_from_bytes(&every_byte, 3, 1)
+            .keys()
+            .flatten()
+            .all(|&nibble| nibble <= 0x0f));
+    }
+
+    #[test]
+    fn entropy_is_at_most_four_bits_per_nibble() {
+        let every_byte: Vec<u8> = (0..=u8::MAX).collect();
+        let histogram = calculate_nibble_histogram_from_bytes(&every_byte, 1, 1);
+        assert_eq!(histogram.len(), 16);
+        let entropy = calculate_entropy_histogram(&histogram, EntropyUnit::Bits).unwrap();
+        assert!((entropy - 4.0).abs() < 1e-12);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn scales_the_image_up_by_blocks() {
+        let histogram = calculate_nibble_histogram_from_bytes(&[0x12; 8], 2, 1);
+        let (image, _, _) =
+            generate_nibble_image(&histogram, ScaleMode::Linear, Normalization::Maximum).unwrap();
+        assert_eq!(image.dimensions(), (256, 256));
+        // Every pair is either 1, 2 or 2, 1, the first nibble of a pair is the column.
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let lit = matches!((x / NIBBLE_SCALE, y / NIBBLE_SCALE), (1, 2) | (2, 1));
+            assert_eq!(pixel.0[0] > 0, lit, "pixel {}, {}", x, y);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+use image::{ImageBuffer, Luma};
+
+use crate::{
+    to_channel, validate_input_size, BinvizError, Normalization, ScaleMode, Visualization,
+    CHUNK_SIZE,
+};
+
+/// The default amount of offset buckets, the width of the images of [`generate_offset_byte_image`].
+pub const OFFSET_BUCKETS: u32 = 512;
+
+/// The byte values counted per column of an offset image, see [`generate_offset_byte_image`].
+struct OffsetCounts {
+    counts: Vec<[usize; 256]>,
+    size: u64,
+    offset: u64,
+    column: u32,
+    next: u64,
+}
+
+impl OffsetCounts {
+    /// Prepare to count the bytes of an input of `size` bytes into `width` columns.
+    fn new(size: u64, width: u32) -> Self {
+        let mut counts = OffsetCounts {
+            counts: vec![[0; 256]; width as usize],
+            size,
+            offset: 0,
+            column: 0,
+            next: 0,
+        };
+        counts.next = counts.boundary(1);
+        counts
+    }
+
+    /// The first offset of column `x`, spreading the remainder of the bytes over the columns.
+    fn boundary(&self, x: u32) -> u64 {
+        (x as u128 * self.size as u128 / self.counts.len() as u128) as u64
+    }
+
+    /// Count the next `bytes` of the input.
+    fn add(&mut self, bytes: &[u8]) {
+        let width = self.counts.len() as u32;
+        for &byte in bytes {
+            while self.offset >= self.next && self.column + 1 < width {
+                self.column += 1;
+                self.next = self.boundary(self.column + 1);
+            }
+            self.counts[self.column as usize][byte as usize] += 1;
+            self.offset += 1;
+        }
+    }
+
+    /// Draw the counted columns, see [`generate_offset_byte_image`].
+    fn render(
+        self,
+        scale: ScaleMode,
+        normalization: Normalization,
+        per_column: bool,
+    ) -> Visualization<Luma<u16>> {
+        let nonzero = |column: &[usize; 256]| -> Vec<usize> {
+            column.iter().copied().filter(|&count| count > 0).collect()
+        };
+        let full_counts: Vec<f64> = if per_column {
+            self.counts
+                .iter()
+                .map(|column| normalization.full_count_of(nonzero(column)))
+                .collect()
+        } else {
+            let full_count = normalization.full_count_of(self.counts.iter().flat_map(nonzero));
+            vec![full_count; self.counts.len()]
+        };
+        let mut image = ImageBuffer::new(self.counts.len() as u32, 256);
+        for (x, (column, &full_count)) in self.counts.iter().zip(&full_counts).enumerate() {
+            for (value, &count) in column.iter().enumerate() {
+                if count > 0 {
+                    let brightness = scale.brightness(count, full_count);
+                    image.put_pixel(x as u32, value as u32, Luma([to_channel(brightness)]));
+                }
+            }
+        }
+        let full_count = full_counts
+            .iter()
+            .copied()
+            .filter(|full_count| !full_count.is_nan())
+            .fold(0.0, f64::max);
+        (image, self.offset as usize, full_count)
+    }
+}
+
+/// The amount of columns of an offset image of `size` bytes, checking there are `buckets` and bytes at all.
+fn offset_width(size: u64, buckets: u32) -> Result<u32, BinvizError> {
+    if buckets == 0 {
+        return Err(BinvizError::InvalidWidth { width: buckets });
+    }
+    validate_input_size(size, 1)?;
+    Ok((buckets as u64).min(size) as u32)
+}
+
+/// Generate a spectrogram of `file`: column `x` is the histogram of the bytes in the `x`th of `buckets`
+/// equally large slices of the file, with the byte value along the y axis, so it shows where in the file
+/// which bytes occur, e.g. where text ends and random data starts.
+///
+/// The brightness of a pixel is set by its count according to `scale`, relative to the count `normalization`
+/// chooses from all counts of the image, or from the counts of its column only with `per_column`, so columns
+/// with few distinct bytes don't outshine the rest. Files smaller than `buckets` bytes get a column per byte.
+///
+/// Gives the image, the amount of bytes and the count at full brightness,
+/// with `per_column` the largest count at full brightness of any column.
+pub fn generate_offset_byte_image<P>(
+    file: P,
+    buckets: u32,
+    scale: ScaleMode,
+    normalization: Normalization,
+    per_column: bool,
+) -> Result<Visualization<Luma<u16>>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let size = fs::metadata(file)
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    let mut counts = OffsetCounts::new(size, offset_width(size, buckets)?);
+    let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = handle
+            .read(&mut buffer)
+            .map_err(|err| BinvizError::io(file, err))?;
+        if read == 0 {
+            break;
+        }
+        counts.add(&buffer[..read]);
+    }
+    Ok(counts.render(scale, normalization, per_column))
+}
+
+/// Generate the spectrogram of the bytes in memory, see [`generate_offset_byte_image`].
+pub fn generate_offset_byte_image_from_bytes(
+    bytes: &[u8],
+    buckets: u32,
+    scale: ScaleMode,
+    normalization: Normalization,
+    per_column: bool,
+) -> Result<Visualization<Luma<u16>>, BinvizError> {
+    let size = bytes.len() as u64;
+    let mut counts = OffsetCounts::new(size, offset_width(size, buckets)?);
+    counts.add(bytes);
+    Ok(counts.render(scale, normalization, per_column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::file_with;
+
+    /// The brightness of half the full count on a linear scale.
+    const HALF: u16 = u16::MAX / 2;
+
+    /// The pixels of every column of `image` that aren't black, as `(byte value, brightness)`.
+    fn columns(image: &ImageBuffer<Luma<u16>, Vec<u16>>) -> Vec<Vec<(u32, u16)>> {
+        (0..image.width())
+            .map(|x| {
+                (0..256)
+                    .map(|y| (y, image.get_pixel(x, y).0[0]))
+                    .filter(|&(_, brightness)| brightness > 0)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn columns_count_the_bytes_of_their_slice() {
+        let bytes = [0, 0, 1, 1, 1, 2, 2, 2];
+        let (image, total, full_count) = generate_offset_byte_image_from_bytes(
+            &bytes,
+            4,
+            ScaleMode::Linear,
+            Normalization::Maximum,
+            false,
+        )
+        .unwrap();
+        assert_eq!((image.width(), image.height()), (4, 256));
+        assert_eq!((total, full_count), (8, 2.0));
+        assert_eq!(
+            columns(&image),
+            [
+                vec![(0, u16::MAX)],
+                vec![(1, u16::MAX)],
+                vec![(1, HALF), (2, HALF)],
+                vec![(2, u16::MAX)],
+            ]
+        );
+        // Every column scaled to its own count.
+        let (image, _, _) = generate_offset_byte_image_from_bytes(
+            &bytes,
+            4,
+            ScaleMode::Linear,
+            Normalization::Maximum,
+            true,
+        )
+        .unwrap();
+        assert_eq!(columns(&image)[2], [(1, u16::MAX), (2, u16::MAX)]);
+    }
+
+    #[test]
+    fn remaining_bytes_are_spread_over_the_columns() {
+        // 5 bytes in 2 columns, of 2 and 3 bytes.
+        let (image, _, _) = generate_offset_byte_image_from_bytes(
+            &[7, 7, 9, 9, 9],
+            2,
+            ScaleMode::Linear,
+            Normalization::Maximum,
+            false,
+        )
+        .unwrap();
+        let two_thirds = to_channel(2.0 / 3.0);
+        assert_eq!(
+            columns(&image),
+            [vec![(7, two_thirds)], vec![(9, u16::MAX)]]
+        );
+    }
+
+    #[test]
+    fn small_inputs_get_a_column_per_byte() {
+        let (image, total, _) = generate_offset_byte_image_from_bytes(
+            &[3, 1, 2],
+            512,
+            ScaleMode::Linear,
+            Normalization::Maximum,
+            false,
+        )
+        .unwrap();
+        assert_eq!((image.width(), total), (3, 3));
+        assert_eq!(
+            columns(&image),
+            [
+                vec![(3, u16::MAX)],
+                vec![(1, u16::MAX)],
+                vec![(2, u16::MAX)]
+            ]
+        );
+    }
+
+    #[test]
+    fn file_matches_the_bytes_in_memory() {
+        let bytes: Vec<u8> = (0..10_000u32).map(|i| (i * i % 251) as u8).collect();
+        let file = file_with(&bytes);
+        let from_file = generate_offset_byte_image(
+            file.path(),
+            100,
+            ScaleMode::Sqrt,
+            Normalization::Average,
+            false,
+        )
+        .unwrap();
+        let from_bytes = generate_offset_byte_image_from_bytes(
+            &bytes,
+            100,
+            ScaleMode::Sqrt,
+            Normalization::Average,
+            false,
+        )
+        .unwrap();
+        assert_eq!(from_file, from_bytes);
+    }
+
+    #[test]
+    fn no_buckets_or_bytes_are_errors() {
+        let linear = (ScaleMode::Linear, Normalization::Maximum);
+        assert!(matches!(
+            generate_offset_byte_image_from_bytes(&[1], 0, linear.0, linear.1, false),
+            Err(BinvizError::InvalidWidth { width: 0 })
+        ));
+        assert!(matches!(
+            generate_offset_byte_image_from_bytes(&[], 4, linear.0, linear.1, false),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+#[cfg(feature = "tables")]
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+#[cfg(feature = "tables")]
+use serde_json::{Map, Value};
+
+/// The formats tables are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// A markdown table for reading, numbers are rounded and annotated with their unit.
+    #[default]
+    Markdown,
+    /// Comma separated values with a header line, numbers are written in full without units.
+    Csv,
+    /// Tab separated values with a header line, numbers are written in full without units.
+    Tsv,
+    /// JSON, numbers are written in full without units. The entropies and frequencies have their own documents,
+    /// see [`crate::EntropyReport`] and [`crate::FrequencyReport`], other tables are an array of objects
+    /// with a field per column, holding the cells as strings.
+    Json,
+}
+
+impl OutputFormat {
+    /// Format a number for a table cell, annotated with its `unit` in a markdown table.
+    pub fn value(self, value: f64, unit: Option<&str>) -> String {
+        match (self, unit) {
+            (OutputFormat::Markdown, Some(unit)) => format!("{:.5} ({})", value, unit),
+            (OutputFormat::Markdown, None) => format!("{:.5}", value),
+            _ => format!("{}", value),
+        }
+    }
+
+    /// Render a table with the given header and rows in this format.
+    ///
+    /// CSV fields containing a comma, quote or line break are quoted, with quotes doubled.
+    /// TSV fields have their tabs and line breaks escaped as `\t`, `\n` and `\r`.
+    #[cfg(feature = "tables")]
+    pub fn render<H, R>(self, header: H, rows: R) -> String
+    where
+        H: IntoIterator,
+        H::Item: Into<String>,
+        R: IntoIterator<Item = Vec<String>>,
+    {
+        let header: Vec<String> = header.into_iter().map(Into::into).collect();
+        match self {
+            OutputFormat::Markdown => {
+                let mut table = Table::new();
+                table.load_preset(ASCII_MARKDOWN);
+                table.set_header(header);
+                for row in rows {
+                    table.add_row(row);
+                }
+                table.to_string()
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let (separator, escape): (&str, fn(&str) -> String) = match self {
+                    OutputFormat::Csv => (",", escape_csv),
+                    _ => ("\t", escape_tsv),
+                };
+                let line = |fields: &[String]| {
+                    fields
+                        .iter()
+                        .map(|field| escape(field))
+                        .collect::<Vec<_>>()
+                        .join(separator)
+                };
+                let mut lines = vec![line(&header)];
+                lines.extend(rows.into_iter().map(|row| line(&row)));
+                lines.join("\n")
+            }
+            OutputFormat::Json => {
+                let objects: Vec<Map<String, Value>> = rows
+                    .into_iter()
+                    .map(|row| {
+                        header
+                            .iter()
+                            .cloned()
+                            .zip(row.into_iter().map(Value::String))
+                            .collect()
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&objects).expect("strings always serialize to JSON")
+            }
+        }
+    }
+}
+
+/// Quote a CSV field if needed.
+#[cfg(feature = "tables")]
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escape the characters of a TSV field that would break the line into fields.
+#[cfg(feature = "tables")]
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "unknown output format `{}`, expected `markdown`, `csv`, `tsv` or `json`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Markdown => f.write_str("markdown"),
+            OutputFormat::Csv => f.write_str("csv"),
+            OutputFormat::Tsv => f.write_str("tsv"),
+            OutputFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+/// The order of the rows of a frequency table. Keys with equal counts are always ordered by key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// The most frequent keys first.
+    #[default]
+    CountDescending,
+    /// The least frequent keys first.
+    CountAscending,
+    /// By key, comparing the bytes of keys from first to last.
+    Key,
+    /// The keys deviating the most from a baseline first, by absolute z-score, see [`crate::display_baseline_deviation`].
+    /// Without a baseline the keys are sorted like [`SortOrder::CountDescending`].
+    Deviation,
+}
+
+impl SortOrder {
+    /// Compare two keys with their counts in this order.
+    pub fn compare<K>(self, a: (&K, usize), b: (&K, usize)) -> Ordering
+    where
+        K: Ord + ?Sized,
+    {
+        match self {
+            SortOrder::CountDescending | SortOrder::Deviation => {
+                b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0))
+            }
+            SortOrder::CountAscending => a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)),
+            SortOrder::Key => a.0.cmp(b.0),
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "count-desc" | "desc" => Ok(SortOrder::CountDescending),
+            "count-asc" | "asc" => Ok(SortOrder::CountAscending),
+            "key" | "value" => Ok(SortOrder::Key),
+            "deviation" | "z-score" => Ok(SortOrder::Deviation),
+            _ => Err(format!(
+                "unknown sort order `{}`, expected `count-desc`, `count-asc`, `key` or `deviation`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortOrder::CountDescending => f.write_str("count-desc"),
+            SortOrder::CountAscending => f.write_str("count-asc"),
+            SortOrder::Key => f.write_str("key"),
+            SortOrder::Deviation => f.write_str("deviation"),
+        }
+    }
+}
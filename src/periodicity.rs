@@ -0,0 +1,278 @@
+#[cfg(feature = "images")]
+use image::{ImageBuffer, Rgb};
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use crate::BinvizError;
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+
+/// The amount of bytes per segment of [`power_spectrum`], unless the periods asked for need longer segments.
+pub const PERIODICITY_SEGMENT_SIZE: usize = 1 << 16;
+
+/// The most segments [`power_spectrum`] averages, so at most this many times [`PERIODICITY_SEGMENT_SIZE`] bytes
+/// of a large input are analyzed.
+pub const PERIODICITY_MAX_SEGMENTS: usize = 64;
+
+/// The largest period looked for when none is given.
+pub const DEFAULT_MAX_PERIOD: usize = 1024;
+
+/// The amount of periods listed by the frequency subcommand.
+pub const PERIOD_COUNT: usize = 10;
+
+/// The width of the image of [`render_spectrum`].
+#[cfg(feature = "images")]
+pub const SPECTRUM_WIDTH: u32 = 1024;
+
+/// The height of the image of [`render_spectrum`].
+#[cfg(feature = "images")]
+pub const SPECTRUM_HEIGHT: u32 = 256;
+
+/// Calculate the power spectrum of the bytes as a series of numbers, averaged over segments of the input
+/// (Welch's method), to find periods of up to `max_period` bytes in it.
+///
+/// Every segment has its mean subtracted and is padded with as many zeros, so the inverse transform of the
+/// spectrum is the autocorrelation of the segments without wrapping around, see [`periods_from_spectrum`].
+/// The segments are [`PERIODICITY_SEGMENT_SIZE`] bytes, or the next power of two of 4 times `max_period` if longer,
+/// or the whole input if shorter. An input longer than [`PERIODICITY_MAX_SEGMENTS`] segments is downsampled
+/// by analyzing that many segments spread evenly over it, so its periods are still found in bytes apart,
+/// instead of averaging neighboring bytes, which would blur every period shorter than the factor.
+///
+/// The spectrum has a bin per frequency of the padded segments, the first half up to the frequency
+/// of a period of 2 bytes and the second half its mirror image.
+pub fn power_spectrum(buf: &[u8], max_period: usize) -> Result<Vec<f64>, BinvizError> {
+    if buf.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let segment_size = PERIODICITY_SEGMENT_SIZE
+        .max((4 * max_period).next_power_of_two())
+        .min(buf.len());
+    let segments = (buf.len() / segment_size).min(PERIODICITY_MAX_SEGMENTS);
+    let fft = FftPlanner::new().plan_fft_forward(2 * segment_size);
+    let mut power = vec![0.0; 2 * segment_size];
+    let mut samples = vec![Complex::default(); 2 * segment_size];
+    for segment in 0..segments {
+        let start = match segments {
+            1 => 0,
+            _ => segment * (buf.len() - segment_size) / (segments - 1),
+        };
+        let bytes = &buf[start..start + segment_size];
+        let mean = bytes.iter().map(|&byte| byte as f64).sum::<f64>() / segment_size as f64;
+        for (sample, &byte) in samples.iter_mut().zip(bytes) {
+            *sample = Complex::new(byte as f64 - mean, 0.0);
+        }
+        samples[segment_size..].fill(Complex::default());
+        fft.process(&mut samples);
+        for (power, sample) in power.iter_mut().zip(&samples) {
+            *power += sample.norm_sqr() / segments as f64;
+        }
+    }
+    Ok(power)
+}
+
+/// Rank the periods of 2 up to `max_period` bytes by their strength in the `spectrum` from [`power_spectrum`],
+/// the strongest first.
+///
+/// The strength of a period is the autocorrelation of the bytes at that lag, from -1 to 1, where 1 means
+/// the bytes repeat exactly. Only the periods stronger than their neighbors and above 0 are candidates,
+/// so data whose bytes merely change slowly, with an autocorrelation decreasing with the lag, has none.
+/// The autocorrelation is divided by the length of the segments rather than by the amount of overlapping bytes,
+/// so a multiple of a period scores a little lower than the period itself, and for data that repeats every 24 bytes
+/// 24 ranks first, ahead of 48. A divisor of the period only scores as high as the data repeats at it.
+/// Periods longer than half the segments of the spectrum aren't looked for.
+pub fn periods_from_spectrum(
+    spectrum: &[f64],
+    max_period: usize,
+) -> Result<Vec<(usize, f64)>, BinvizError> {
+    let mut autocorrelation: Vec<Complex<f64>> = spectrum
+        .iter()
+        .map(|&power| Complex::new(power, 0.0))
+        .collect();
+    FftPlanner::new()
+        .plan_fft_inverse(autocorrelation.len())
+        .process(&mut autocorrelation);
+    let variance = autocorrelation[0].re;
+    if variance <= 0.0 {
+        return Err(BinvizError::ZeroVariance);
+    }
+    let strength_at = |lag: usize| autocorrelation[lag].re / variance;
+    let longest = max_period.min(spectrum.len() / 4);
+    let mut periods: Vec<(usize, f64)> = (2..=longest)
+        .map(|period| (period, strength_at(period)))
+        .filter(|&(period, strength)| {
+            strength > 0.0
+                && strength >= strength_at(period - 1)
+                && strength >= strength_at(period + 1)
+        })
+        .collect();
+    periods.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(periods)
+}
+
+/// Rank the periods of 2 up to `max_period` bytes in `buf` by their strength, the strongest first,
+/// see [`power_spectrum`] and [`periods_from_spectrum`].
+pub fn detect_periodicity(buf: &[u8], max_period: usize) -> Result<Vec<(usize, f64)>, BinvizError> {
+    periods_from_spectrum(&power_spectrum(buf, max_period)?, max_period)
+}
+
+/// Render the first half of the `spectrum` from [`power_spectrum`] as a bar chart of [`SPECTRUM_WIDTH`] by
+/// [`SPECTRUM_HEIGHT`] pixels, from the lowest frequency on the left to that of a period of 2 bytes on the right.
+///
+/// Every column shows the highest power of the frequencies it covers, as amplitude relative to the highest
+/// power, so a period shows up as a comb of bars at the multiples of its frequency.
+#[cfg(feature = "images")]
+pub fn render_spectrum(spectrum: &[f64]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let mut image = ImageBuffer::from_pixel(SPECTRUM_WIDTH, SPECTRUM_HEIGHT, Rgb([255, 255, 255]));
+    // The mean of every segment is subtracted, so the bin of frequency 0 is left out.
+    let bins = &spectrum[1..=spectrum.len() / 2];
+    let max = bins.iter().copied().fold(0.0, f64::max);
+    if max <= 0.0 {
+        return image;
+    }
+    for x in 0..SPECTRUM_WIDTH {
+        let start = x as usize * bins.len() / SPECTRUM_WIDTH as usize;
+        let end = ((x as usize + 1) * bins.len() / SPECTRUM_WIDTH as usize).max(start + 1);
+        let power = bins[start..end.min(bins.len())]
+            .iter()
+            .copied()
+            .fold(0.0, f64::max);
+        let height = ((power / max).sqrt() * SPECTRUM_HEIGHT as f64).round() as u32;
+        for y in SPECTRUM_HEIGHT - height.min(SPECTRUM_HEIGHT)..SPECTRUM_HEIGHT {
+            image.put_pixel(x, y, Rgb([0, 0, 255]));
+        }
+    }
+    image
+}
+
+/// Display the first `top` of the `periods` from [`detect_periodicity`] as a table with their strength.
+#[cfg(feature = "tables")]
+pub fn display_periods(periods: &[(usize, f64)], top: usize, format: OutputFormat) -> String {
+    let rows = periods
+        .iter()
+        .take(top)
+        .map(|&(period, strength)| vec![period.to_string(), format.value(strength, None)]);
+    format.render(["Period (bytes)", "Strength"], rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::noise;
+
+    /// `len` bytes of records of 24 random bytes, every byte changed a little by noise.
+    fn records(len: usize) -> Vec<u8> {
+        let record = noise(24);
+        noise(len + 24)[24..]
+            .iter()
+            .enumerate()
+            .map(|(i, &jitter)| record[i % 24].wrapping_add(jitter >> 5))
+            .collect()
+    }
+
+    #[test]
+    fn records_of_24_bytes_have_a_period_of_24() {
+        let periods = detect_periodicity(&records(100_000), DEFAULT_MAX_PERIOD).unwrap();
+        assert_eq!(periods[0].0, 24);
+        assert!(periods[0].1 > 0.9, "{:?}", periods[0]);
+        // Its multiples follow, each a little weaker than the one before.
+        let multiples: Vec<usize> = periods[1..4].iter().map(|&(period, _)| period).collect();
+        assert_eq!(multiples, [48, 72, 96]);
+        assert!(periods.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn downsampled_input_keeps_its_period() {
+        // Longer than the segments analyzed, so they are spread over the input rather than adjacent.
+        let len = (PERIODICITY_MAX_SEGMENTS + 3) * PERIODICITY_SEGMENT_SIZE + 5;
+        let periods = detect_periodicity(&records(len), 100).unwrap();
+        assert_eq!(periods[0].0, 24);
+    }
+
+    #[test]
+    fn noise_has_no_strong_period() {
+        let periods = detect_periodicity(&noise(100_000), DEFAULT_MAX_PERIOD).unwrap();
+        assert!(periods.iter().all(|&(_, strength)| strength < 0.05));
+    }
+
+    #[test]
+    fn slowly_changing_bytes_have_no_period() {
+        // The autocorrelation only decreases with the lag.
+        let ramp: Vec<u8> = (0..50_000).map(|i: u32| (i / 200) as u8).collect();
+        assert!(detect_periodicity(&ramp, 100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn periods_are_limited_to_the_maximum() {
+        let periods = detect_periodicity(&records(100_000), 30).unwrap();
+        assert!(periods
+            .iter()
+            .all(|&(period, _)| (2..=30).contains(&period)));
+        assert_eq!(periods[0].0, 24);
+    }
+
+    #[test]
+    fn constant_and_empty_input_are_errors() {
+        assert!(matches!(
+            detect_periodicity(&[7; 1000], 100),
+            Err(BinvizError::ZeroVariance)
+        ));
+        assert!(matches!(
+            detect_periodicity(&[], 100),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn spectrum_is_padded_to_twice_the_segment() {
+        assert_eq!(power_spectrum(&noise(1000), 100).unwrap().len(), 2000);
+        assert_eq!(
+            power_spectrum(&noise(100_000), 100).unwrap().len(),
+            2 * PERIODICITY_SEGMENT_SIZE
+        );
+        // Long periods need longer segments.
+        assert_eq!(
+            power_spectrum(&noise(300_000), 20_000).unwrap().len(),
+            2 * (1 << 17)
+        );
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn spectrum_of_a_period_is_a_comb() {
+        let image = render_spectrum(&power_spectrum(&records(100_000), 100).unwrap());
+        assert_eq!(image.dimensions(), (SPECTRUM_WIDTH, SPECTRUM_HEIGHT));
+        let height = |x: u32| {
+            (0..SPECTRUM_HEIGHT)
+                .filter(|&y| image.get_pixel(x, y) != &Rgb([255, 255, 255]))
+                .count()
+        };
+        // The bars at the multiples of the frequency of 24 bytes, 1 / 24 of the way to that of 2 bytes,
+        // stand out of the noise between them.
+        let tallest = (0..SPECTRUM_WIDTH).map(height).max().unwrap();
+        assert_eq!(tallest, SPECTRUM_HEIGHT as usize);
+        let harmonic = |k: u32| k * SPECTRUM_WIDTH / 12;
+        for k in 1..12 {
+            let peak = (harmonic(k) - 2..=harmonic(k) + 2)
+                .map(height)
+                .max()
+                .unwrap();
+            let between = height(harmonic(k) + SPECTRUM_WIDTH / 24);
+            assert!(
+                peak > 4 * between,
+                "harmonic {}: {} and {}",
+                k,
+                peak,
+                between
+            );
+        }
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn periods_are_listed_strongest_first() {
+        let table = display_periods(&[(24, 0.95), (48, 0.9), (72, 0.85)], 2, OutputFormat::Csv);
+        assert_eq!(
+            table.lines().collect::<Vec<_>>(),
+            ["Period (bytes),Strength", "24,0.95", "48,0.9"]
+        );
+    }
+}
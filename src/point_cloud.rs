@@ -0,0 +1,123 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{create_output, validate_dimension, BinvizError, Colormap, Histogram};
+
+/// Export a trigraph histogram as a point cloud in an ASCII PLY file, to look at it in 3D in e.g. MeshLab.
+///
+/// Every key with a count of at least `threshold` is a vertex at the coordinates of its three bytes,
+/// colored by its count in [`Colormap::Inferno`] on a logarithmic scale up to the maximum count.
+/// Returns the amount of vertices written, an existing file is only overwritten with `force`.
+pub fn export_point_cloud<P>(
+    trihistogram: &Histogram<u8>,
+    path: P,
+    threshold: usize,
+    force: bool,
+) -> Result<usize, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    validate_dimension(trihistogram, 3)?;
+    let file = create_output(path, force)?;
+    write_point_cloud(trihistogram, BufWriter::new(file), threshold)
+        .map_err(|err| BinvizError::write(path, err))
+}
+
+/// Write a trigraph histogram as an ASCII PLY point cloud to `writer`, see [`export_point_cloud`].
+fn write_point_cloud<W>(
+    trihistogram: &Histogram<u8>,
+    mut writer: W,
+    threshold: usize,
+) -> io::Result<usize>
+where
+    W: Write,
+{
+    let vertices: Vec<(&Vec<u8>, usize)> = trihistogram
+        .iter()
+        .map(|(key, &count)| (key, count))
+        .filter(|&(_, count)| count >= threshold.max(1))
+        .collect();
+    let max_count = vertices.iter().map(|&(_, count)| count).max().unwrap_or(0);
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(
+        writer,
+        "comment trigraph of binviz, a vertex per byte triple"
+    )?;
+    writeln!(writer, "element vertex {}", vertices.len())?;
+    for property in ["float x", "float y", "float z"] {
+        writeln!(writer, "property {}", property)?;
+    }
+    for property in ["uchar red", "uchar green", "uchar blue"] {
+        writeln!(writer, "property {}", property)?;
+    }
+    writeln!(writer, "end_header")?;
+    for (key, count) in &vertices {
+        let brightness = (*count as f64).ln_1p() / (max_count as f64).ln_1p();
+        let [r, g, b] = Colormap::Inferno.rgb8(brightness).0;
+        writeln!(writer, "{} {} {} {} {} {}", key[0], key[1], key[2], r, g, b)?;
+    }
+    writer.flush()?;
+    Ok(vertices.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_histogram_from_bytes;
+
+    /// The PLY file of the trigraphs of `buf` with `threshold`, as text.
+    fn ply(buf: &[u8], threshold: usize) -> (usize, String) {
+        let trihistogram = calculate_histogram_from_bytes(buf, 3, 1);
+        let mut output = Vec::new();
+        let vertices = write_point_cloud(&trihistogram, &mut output, threshold).unwrap();
+        (vertices, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn header_declares_every_vertex() {
+        // The triples 1 2 3 three times, 2 3 1 and 3 1 2 twice.
+        let (vertices, ply) = ply(&[1, 2, 3, 1, 2, 3, 1, 2, 3], 0);
+        assert_eq!(vertices, 3);
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+        let header: Vec<&str> = header.lines().collect();
+        assert_eq!(header[..2], ["ply", "format ascii 1.0"]);
+        assert!(header.contains(&"element vertex 3"));
+        assert_eq!(
+            header[header.len() - 6..],
+            [
+                "property float x",
+                "property float y",
+                "property float z",
+                "property uchar red",
+                "property uchar green",
+                "property uchar blue",
+            ]
+        );
+        assert_eq!(body.lines().count(), 3);
+        for line in body.lines() {
+            assert_eq!(line.split(' ').count(), 6, "{}", line);
+        }
+    }
+
+    #[test]
+    fn threshold_leaves_out_rare_triples() {
+        let (vertices, ply) = ply(&[1, 2, 3, 1, 2, 3, 1, 2, 3], 3);
+        assert_eq!(vertices, 1);
+        assert!(ply.contains("element vertex 1\n"));
+        // The only vertex has the maximum count, so the brightest color.
+        let [r, g, b] = Colormap::Inferno.rgb8(1.0).0;
+        assert!(ply.ends_with(&format!("end_header\n1 2 3 {} {} {}\n", r, g, b)));
+    }
+
+    #[test]
+    fn empty_histogram_has_no_vertices() {
+        let (vertices, ply) = ply(&[], 0);
+        assert_eq!(vertices, 0);
+        assert!(ply.contains("element vertex 0\n"));
+        assert!(ply.ends_with("end_header\n"));
+    }
+}
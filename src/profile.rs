@@ -0,0 +1,311 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+#[cfg(feature = "tables")]
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+#[cfg(feature = "images")]
+use image::{ImageBuffer, Rgb};
+
+use crate::{
+    calculate_entropy, count_bytes, read_chunks, validate_input_size, validate_window, BinvizError,
+    EntropyUnit, CHUNK_SIZE,
+};
+
+/// Calculate the entropy of the bytes in every window of `window_size` bytes of a given file,
+/// with consecutive windows starting `step` bytes apart, see [`entropy_profile_from_reader`].
+pub fn entropy_profile<P>(
+    file: P,
+    window_size: usize,
+    step: usize,
+) -> Result<Vec<(u64, f64)>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    validate_window(window_size, step)?;
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let size = handle
+        .metadata()
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    validate_input_size(size, 1)?;
+    entropy_profile_from_reader(handle, window_size, step).map_err(|err| BinvizError::io(file, err))
+}
+
+/// Calculate the entropy of the bytes in every window of `window_size` bytes read from `reader`,
+/// with consecutive windows starting `step` bytes apart, as pairs of the offset of the window and its
+/// entropy in bits per byte.
+///
+/// The input is read in chunks of [`CHUNK_SIZE`] bytes. If the last bytes aren't covered by a whole window,
+/// a shorter window up to the end of the input is added, so an input shorter than `window_size` has one window.
+pub fn entropy_profile_from_reader<R>(
+    reader: R,
+    window_size: usize,
+    step: usize,
+) -> io::Result<Vec<(u64, f64)>>
+where
+    R: Read,
+{
+    window_profile(reader, window_size, step, window_entropy)
+}
+
+/// Calculate `f` of every window of `window_size` bytes read from `reader`, with consecutive windows starting
+/// `step` bytes apart, as pairs of the offset of the window and its value, see [`entropy_profile_from_reader`].
+pub(crate) fn window_profile<R, T, F>(
+    reader: R,
+    window_size: usize,
+    step: usize,
+    f: F,
+) -> io::Result<Vec<(u64, T)>>
+where
+    R: Read,
+    F: FnMut(&[u8]) -> T,
+{
+    let mut profile = WindowProfile::new(window_size, step, f);
+    read_chunks(reader, |chunk| profile.update(chunk))?;
+    Ok(profile.finish())
+}
+
+/// Incrementally calculates `f` of every window of an input, see [`window_profile`].
+pub(crate) struct WindowProfile<T, F> {
+    window_size: usize,
+    step: usize,
+    f: F,
+    profile: Vec<(u64, T)>,
+    /// The bytes starting at offset `start`, after first skipping `skip` more bytes when `step` exceeds the window.
+    buf: Vec<u8>,
+    start: u64,
+    skip: usize,
+    /// The offset up to which the windows so far reach.
+    covered: u64,
+}
+
+impl<T, F> WindowProfile<T, F>
+where
+    F: FnMut(&[u8]) -> T,
+{
+    pub(crate) fn new(window_size: usize, step: usize, f: F) -> Self {
+        WindowProfile {
+            window_size,
+            step,
+            f,
+            profile: Vec::new(),
+            buf: Vec::with_capacity(CHUNK_SIZE + window_size),
+            start: 0,
+            skip: 0,
+            covered: 0,
+        }
+    }
+
+    /// Add the next bytes of the input, calculating the windows they complete.
+    pub(crate) fn update(&mut self, chunk: &[u8]) {
+        let skipped = self.skip.min(chunk.len());
+        self.skip -= skipped;
+        self.buf.extend_from_slice(&chunk[skipped..]);
+        let mut consumed = 0;
+        while self.buf.len() - consumed >= self.window_size {
+            let window = &self.buf[consumed..consumed + self.window_size];
+            self.profile.push((self.start, (self.f)(window)));
+            self.covered = self.start + self.window_size as u64;
+            self.start += self.step as u64;
+            if self.step <= self.buf.len() - consumed {
+                consumed += self.step;
+            } else {
+                self.skip = self.step - (self.buf.len() - consumed);
+                consumed = self.buf.len();
+            }
+        }
+        self.buf.drain(..consumed);
+    }
+
+    /// Add a shorter window for the last bytes if no window covers them, and return the values of all windows.
+    pub(crate) fn finish(mut self) -> Vec<(u64, T)> {
+        if !self.buf.is_empty() && self.covered < self.start + self.buf.len() as u64 {
+            self.profile.push((self.start, (self.f)(&self.buf)));
+        }
+        self.profile
+    }
+}
+
+/// Calculate the entropy of the bytes in `window`, in bits per byte.
+pub(crate) fn window_entropy(window: &[u8]) -> f64 {
+    let total = window.len() as f64;
+    0.0 - count_bytes(window, 1)
+        .iter()
+        .map(|&freq| calculate_entropy(freq as f64 / total, EntropyUnit::Bits))
+        .sum::<f64>()
+}
+
+/// Display the entropy profile of an input of `size` bytes, calculated with windows of `window_size` bytes,
+/// as a table of the offset ranges of the windows and their entropy.
+///
+/// Unless `ratios` is empty, a column with the compression ratio of every window is added.
+#[cfg(feature = "tables")]
+pub fn display_entropy_profile(
+    profile: &[(u64, f64)],
+    window_size: usize,
+    size: u64,
+    ratios: &[f64],
+) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    let mut header = vec!["Start", "End", "Entropy", "Relative Entropy"];
+    if !ratios.is_empty() {
+        header.push("Compression Ratio");
+    }
+    table.set_header(header);
+    for (i, &(offset, entropy)) in profile.iter().enumerate() {
+        let end = (offset + window_size as u64).min(size);
+        let mut row = vec![
+            format!("{:#x}", offset),
+            format!("{:#x}", end),
+            format!("{:.5} (bits per byte)", entropy),
+            format!("{:.5}", entropy / 8.0),
+        ];
+        if let Some(ratio) = ratios.get(i) {
+            row.push(format!("{:.5}", ratio));
+        }
+        table.add_row(row);
+    }
+    table.to_string()
+}
+
+/// The margin around the plot area of [`render_entropy_profile`], in pixels.
+#[cfg(feature = "images")]
+const PROFILE_MARGIN: u32 = 8;
+
+/// Render the entropy profile as a line chart of `width` by `height` pixels, with the offsets on the x-axis
+/// and the entropy from 0 to 8 bits per byte on the y-axis.
+///
+/// The x-axis has ticks at every power of two offset, and a dashed line marks the maximum of 8 bits per byte.
+/// A profile of a single window is drawn as a horizontal line, an empty profile as just the axes.
+#[cfg(feature = "images")]
+pub fn render_entropy_profile(
+    profile: &[(u64, f64)],
+    width: u32,
+    height: u32,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let width = width.max(2 * PROFILE_MARGIN + 2);
+    let height = height.max(2 * PROFILE_MARGIN + 2);
+    let mut image = ImageBuffer::from_pixel(width, height, Rgb([255, 255, 255]));
+    let (left, right) = (PROFILE_MARGIN, width - 1 - PROFILE_MARGIN);
+    let (top, bottom) = (PROFILE_MARGIN, height - 1 - PROFILE_MARGIN);
+    let max_offset = profile.last().map_or(0, |&(offset, _)| offset).max(1);
+    let x =
+        |offset: u64| left + ((offset as f64 / max_offset as f64) * (right - left) as f64) as u32;
+    let y =
+        |entropy: f64| bottom - ((entropy.clamp(0.0, 8.0) / 8.0) * (bottom - top) as f64) as u32;
+
+    let axis = Rgb([0, 0, 0]);
+    for px in left..=right {
+        image.put_pixel(px, bottom, axis);
+        // Dashed reference line at the maximum entropy.
+        if px % 8 < 4 {
+            image.put_pixel(px, y(8.0), Rgb([160, 160, 160]));
+        }
+    }
+    for py in top..=bottom {
+        image.put_pixel(left, py, axis);
+    }
+    for tick in (0..u64::BITS)
+        .map(|power| 1 << power)
+        .take_while(|&tick| tick <= max_offset)
+    {
+        for py in bottom..=bottom + PROFILE_MARGIN / 2 {
+            image.put_pixel(x(tick), py, axis);
+        }
+    }
+
+    let curve = Rgb([0, 0, 255]);
+    match profile {
+        [] => {}
+        [(_, entropy)] => draw_line(&mut image, (left, y(*entropy)), (right, y(*entropy)), curve),
+        _ => {
+            for pair in profile.windows(2) {
+                let from = (x(pair[0].0), y(pair[0].1));
+                let to = (x(pair[1].0), y(pair[1].1));
+                draw_line(&mut image, from, to, curve);
+            }
+        }
+    }
+    image
+}
+
+/// Draw a straight line between two points, both inside the image.
+#[cfg(feature = "images")]
+fn draw_line(
+    image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    from: (u32, u32),
+    to: (u32, u32),
+    pixel: Rgb<u8>,
+) {
+    let (dx, dy) = (to.0 as f64 - from.0 as f64, to.1 as f64 - from.1 as f64);
+    let steps = dx.abs().max(dy.abs()).max(1.0);
+    for step in 0..=steps as u32 {
+        let t = step as f64 / steps;
+        let px = (from.0 as f64 + dx * t).round() as u32;
+        let py = (from.1 as f64 + dy * t).round() as u32;
+        image.put_pixel(px, py, pixel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{noise, ShortReads};
+
+    #[test]
+    fn profile_jumps_from_zeros_to_noise() {
+        let mut buf = vec![0; 4096];
+        buf.extend(noise(4096));
+        let reader = ShortReads {
+            inner: buf.as_slice(),
+            max: 1000,
+        };
+        let profile = entropy_profile_from_reader(reader, 1024, 512).unwrap();
+        let offsets: Vec<u64> = profile.iter().map(|&(offset, _)| offset).collect();
+        assert_eq!(offsets, (0..=7168).step_by(512).collect::<Vec<_>>());
+        for &(offset, entropy) in &profile {
+            match offset {
+                0..=3072 => assert_eq!(entropy, 0.0, "offset {}", offset),
+                // Half zeros, half noise.
+                3584 => assert!(entropy > 1.0 && entropy < 6.0, "offset {}", offset),
+                _ => assert!(entropy > 7.5, "offset {}", offset),
+            }
+        }
+    }
+
+    #[test]
+    fn input_shorter_than_a_window_has_one_window() {
+        let profile = entropy_profile_from_reader(&b"abab"[..], 1024, 1024).unwrap();
+        assert_eq!(profile, [(0, 1.0)]);
+        assert!(entropy_profile_from_reader(&b""[..], 1024, 1024)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn single_window_is_a_horizontal_line() {
+        let image = render_entropy_profile(&[(0, 4.0)], 100, 50);
+        let curve = Rgb([0, 0, 255]);
+        let blue: Vec<(u32, u32)> = image
+            .enumerate_pixels()
+            .filter(|&(_, _, &pixel)| pixel == curve)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        // Halfway between the bottom and the top of the plot, from the left to the right of it.
+        let (bottom, top) = (49 - PROFILE_MARGIN, PROFILE_MARGIN);
+        let middle = bottom - (bottom - top) / 2;
+        let expected: Vec<(u32, u32)> = (PROFILE_MARGIN..=99 - PROFILE_MARGIN)
+            .map(|x| (x, middle))
+            .collect();
+        assert_eq!(blue, expected);
+        // An empty profile only has the axes.
+        let empty = render_entropy_profile(&[], 100, 50);
+        assert!(empty.pixels().all(|&pixel| pixel != curve));
+    }
+}
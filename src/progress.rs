@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+/// A progress report of a long computation, see [`calculate_histogram_with_progress`] and
+/// [`full_analysis_with_progress`].
+///
+/// [`calculate_histogram_with_progress`]: crate::calculate_histogram_with_progress
+/// [`full_analysis_with_progress`]: crate::full_analysis_with_progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// `processed` out of `total` bytes of the input have been counted.
+    /// The last event of a computation always has `processed == total`.
+    Bytes { processed: u64, total: u64 },
+    /// `processed` out of `total` bytes of `file` have been counted, like [`ProgressEvent::Bytes`]
+    /// but naming the file, as the full analysis analyzes several files at once.
+    FileBytes {
+        file: PathBuf,
+        processed: u64,
+        total: u64,
+    },
+    /// A stage of the full analysis of `file` started.
+    Stage { file: PathBuf, stage: Stage },
+    /// `analyzed` out of `total` files of the full analysis are done,
+    /// reported once the files are found and after every file.
+    Files { analyzed: usize, total: usize },
+}
+
+/// The stages of a full analysis, in the order they run for every file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Calculating the entropies, written to `entropy.txt`. The file is read once, for the frequencies,
+    /// statistics, strings and visualizations at the same time, so the bytes are only reported during this stage.
+    Entropy,
+    /// Calculating the byte frequencies, written to `most_frequent.txt`.
+    Frequency,
+    /// Extracting the strings, written to `strings.txt`.
+    Strings,
+    /// Generating the digraph image, written to `image.png`.
+    Visualization,
+    /// Generating the trigraph color image, written to `image_color.png`, unless it is turned off.
+    Trigraph,
+}
@@ -0,0 +1,139 @@
+use std::{fs, path::Path};
+
+use crate::{filter::parse_byte, load_histogram, BinvizError, Histogram};
+
+/// The names of the built-in references, see [`builtin_reference`].
+pub const BUILTIN_REFERENCES: [&str; 2] = ["uniform", "english"];
+
+/// The total count of a reference built from probabilities, so probabilities keep 6 decimals.
+const REFERENCE_SCALE: f64 = 1_000_000.0;
+
+/// The frequencies of the letters `a` to `z` in English text, as a fraction of all letters.
+const ENGLISH_LETTERS: [f64; 26] = [
+    0.0817, 0.0129, 0.0278, 0.0425, 0.1270, 0.0223, 0.0202, 0.0609, 0.0697, 0.0015, 0.0077, 0.0403,
+    0.0241, 0.0675, 0.0751, 0.0193, 0.0010, 0.0599, 0.0633, 0.0906, 0.0276, 0.0098, 0.0236, 0.0015,
+    0.0197, 0.0007,
+];
+
+/// The frequencies of the other common printable characters in English text, as a fraction of all characters.
+const ENGLISH_OTHERS: [(u8, f64); 17] = [
+    (b' ', 0.1700),
+    (b',', 0.0075),
+    (b'.', 0.0065),
+    (b'"', 0.0025),
+    (b'\'', 0.0020),
+    (b'-', 0.0015),
+    (b'?', 0.0005),
+    (b'!', 0.0003),
+    (b';', 0.0003),
+    (b':', 0.0003),
+    (b'(', 0.0002),
+    (b')', 0.0002),
+    (b'0', 0.0006),
+    (b'1', 0.0006),
+    (b'2', 0.0004),
+    (b'5', 0.0003),
+    (b'9', 0.0003),
+];
+
+/// The fraction of the letters in English text that is uppercase.
+const ENGLISH_UPPERCASE: f64 = 0.03;
+
+/// Look up a built-in reference distribution of bytes by name, one of [`BUILTIN_REFERENCES`]:
+///
+/// - `uniform`, every byte value is equally likely, as in random data.
+/// - `english`, the approximate character frequencies of English plain text in printable ASCII.
+pub fn builtin_reference(name: &str) -> Option<Histogram<u8>> {
+    match name {
+        "uniform" => Some((0..=u8::MAX).map(|byte| (vec![byte], 1)).collect()),
+        "english" => {
+            let others: f64 = ENGLISH_OTHERS.iter().map(|(_, freq)| freq).sum();
+            let letters = (b'a'..=b'z').zip(ENGLISH_LETTERS).flat_map(|(byte, freq)| {
+                let freq = freq * (1.0 - others);
+                [
+                    (byte, freq * (1.0 - ENGLISH_UPPERCASE)),
+                    (byte.to_ascii_uppercase(), freq * ENGLISH_UPPERCASE),
+                ]
+            });
+            Some(probabilities_to_histogram(
+                letters.chain(ENGLISH_OTHERS.iter().copied()),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Load a reference distribution of bytes from `path`.
+///
+/// Files ending in `.csv` contain a line `byte,probability` per byte, with the byte in hexadecimal,
+/// e.g. `0x41,0.002`. Blank lines, lines starting with `#` and a `byte,probability` header are skipped,
+/// and the probabilities don't need to sum to 1. Other files are histograms saved by [`crate::save_histogram`].
+pub fn load_reference<P>(path: P) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    match path.extension() {
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => {
+            let contents = fs::read_to_string(path).map_err(|err| BinvizError::io(path, err))?;
+            let mut probabilities = Vec::new();
+            for (number, line) in contents.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty()
+                    || line.starts_with('#')
+                    || line.eq_ignore_ascii_case("byte,probability")
+                {
+                    continue;
+                }
+                let entry = parse_csv_entry(line).map_err(|err| {
+                    BinvizError::serialization(path, format!("line {}: {}", number + 1, err))
+                })?;
+                probabilities.push(entry);
+            }
+            if probabilities
+                .iter()
+                .all(|(_, probability)| *probability == 0.0)
+            {
+                return Err(BinvizError::EmptyInput);
+            }
+            Ok(probabilities_to_histogram(probabilities))
+        }
+        _ => load_histogram(path),
+    }
+}
+
+/// Parse a single `byte,probability` line of a CSV reference.
+fn parse_csv_entry(line: &str) -> Result<(u8, f64), String> {
+    let (byte, probability) = line
+        .split_once(',')
+        .ok_or_else(|| format!("`{}` isn't of the form `byte,probability`", line))?;
+    let byte = parse_byte(byte)?;
+    match probability.trim().parse::<f64>() {
+        Ok(probability) if probability.is_finite() && probability >= 0.0 => Ok((byte, probability)),
+        _ => Err(format!(
+            "`{}` isn't a probability, expected a non-negative number",
+            probability.trim()
+        )),
+    }
+}
+
+/// Turn probabilities of bytes into a histogram with a total count of about [`REFERENCE_SCALE`],
+/// normalizing them first. Bytes with a positive probability keep a count of at least 1.
+fn probabilities_to_histogram<I>(probabilities: I) -> Histogram<u8>
+where
+    I: IntoIterator<Item = (u8, f64)>,
+{
+    let probabilities: Vec<(u8, f64)> = probabilities.into_iter().collect();
+    let total: f64 = probabilities
+        .iter()
+        .map(|(_, probability)| probability)
+        .sum();
+    let mut histogram = Histogram::new();
+    for (byte, probability) in probabilities {
+        if probability > 0.0 {
+            let count = ((probability / total * REFERENCE_SCALE).round() as usize).max(1);
+            *histogram.entry(vec![byte]).or_insert(0) += count;
+        }
+    }
+    histogram
+}
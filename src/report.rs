@@ -0,0 +1,149 @@
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{get_most_frequent_bytes, truncate_most_frequent, Histogram, SortOrder};
+
+/// The header object of the JSON reports, identifying the tool and the input the report is about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportHeader {
+    /// Always `binviz`.
+    pub tool: String,
+    /// The version of binviz that wrote the report.
+    pub version: String,
+    /// The path of the analyzed input as given, `-` for standard input, or `null` if there was no file.
+    pub input: Option<String>,
+}
+
+impl ReportHeader {
+    /// The header of a report about `input`, written by this version of binviz.
+    pub fn new(input: Option<&Path>) -> Self {
+        ReportHeader {
+            tool: "binviz".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            input: input.map(|input| input.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+/// The entropy of a dimension in an [`EntropyReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntropyEntry {
+    /// The amount of consecutive symbols per window.
+    pub dimension: usize,
+    /// The entropy of the windows in bits, whatever the unit of the tables is.
+    pub entropy_bits: f64,
+    /// The entropy relative to the maximum entropy of the windows, between 0 and 1.
+    pub relative_entropy: f64,
+}
+
+/// The JSON document `binviz entropy --format json` prints:
+///
+/// ```json
+/// {
+///   "header": { "tool": "binviz", "version": "0.9.0", "input": "file.bin" },
+///   "entropies": [{ "dimension": 1, "entropy_bits": 7.99, "relative_entropy": 0.99 }]
+/// }
+/// ```
+///
+/// Numbers are written in full, without units. Fields are only ever added, never renamed or removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntropyReport {
+    pub header: ReportHeader,
+    /// The entropies from dimension 1 up.
+    pub entropies: Vec<EntropyEntry>,
+}
+
+/// A key of the histogram in a [`FrequencyReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrequencyEntry<T> {
+    /// The position in the sorted keys, counting from 0 like the tables.
+    pub rank: usize,
+    /// The symbols of the key as integers, one per byte, nibble or word.
+    pub bytes: Vec<T>,
+    /// How often the key occurs.
+    pub count: usize,
+    /// The count relative to the total count.
+    pub probability: f64,
+}
+
+/// The JSON document `binviz frequency --format json` prints:
+///
+/// ```json
+/// {
+///   "header": { "tool": "binviz", "version": "0.9.0", "input": "file.bin" },
+///   "frequencies": [{ "rank": 0, "bytes": [0], "count": 1024, "probability": 0.5 }],
+///   "total": 2048,
+///   "distinct": 93
+/// }
+/// ```
+///
+/// The totals cover the whole histogram, also when only the `--top` keys are listed.
+/// Numbers are written in full, without units. Fields are only ever added, never renamed or removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrequencyReport<T> {
+    pub header: ReportHeader,
+    /// The keys in the requested order.
+    pub frequencies: Vec<FrequencyEntry<T>>,
+    /// The sum of all counts.
+    pub total: usize,
+    /// The amount of distinct keys.
+    pub distinct: usize,
+}
+
+impl<T> FrequencyReport<T>
+where
+    T: Ord + Copy,
+{
+    /// The report of the keys of `histogram` of `input` in the given `order`, with `top` only the first that many.
+    pub fn new(
+        histogram: &Histogram<T>,
+        top: Option<usize>,
+        order: SortOrder,
+        input: Option<&Path>,
+    ) -> Self {
+        let total: usize = histogram.values().sum();
+        let mut most_freq = get_most_frequent_bytes(histogram, order);
+        truncate_most_frequent(&mut most_freq, top);
+        let frequencies = most_freq
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (key, &count))| FrequencyEntry {
+                rank,
+                bytes: key.clone(),
+                count,
+                probability: count as f64 / total as f64,
+            })
+            .collect();
+        FrequencyReport {
+            header: ReportHeader::new(input),
+            frequencies,
+            total,
+            distinct: histogram.len(),
+        }
+    }
+}
+
+/// Write `report` as pretty-printed JSON.
+fn write_json<R>(report: &R, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    R: Serialize,
+{
+    let json = serde_json::to_string_pretty(report).map_err(|_| fmt::Error)?;
+    f.write_str(&json)
+}
+
+impl fmt::Display for EntropyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_json(self, f)
+    }
+}
+
+impl<T> fmt::Display for FrequencyReport<T>
+where
+    T: Serialize,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_json(self, f)
+    }
+}
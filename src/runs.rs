@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use serde::{Serialize, Serializer};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{read_chunks, BinvizError};
+
+/// The length from which runs count as covering the input in [`run_length_stats`], e.g. padding.
+pub const RUN_THRESHOLD: u64 = 16;
+
+/// The runs of a single repeated byte value in an input, see [`run_length_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunLengthReport {
+    /// The longest run of every byte value, 0 for bytes that never occur.
+    #[serde(serialize_with = "serialize_per_byte")]
+    pub longest: [u64; 256],
+    /// The amount of bytes in runs of at least `threshold` bytes, per byte value.
+    #[serde(serialize_with = "serialize_per_byte")]
+    pub covered: [u64; 256],
+    /// The length from which runs are counted in `covered`.
+    pub threshold: u64,
+    /// The longest run in the input as its byte value, length and offset,
+    /// the first one if several are equally long, or `None` for empty input.
+    pub longest_run: Option<(u8, u64, u64)>,
+    /// The amount of bytes in the input.
+    pub size: u64,
+}
+
+impl RunLengthReport {
+    fn new(threshold: u64) -> Self {
+        RunLengthReport {
+            longest: [0; 256],
+            covered: [0; 256],
+            threshold,
+            longest_run: None,
+            size: 0,
+        }
+    }
+
+    /// Record a run of `len` times `byte` starting at `offset`.
+    fn add_run(&mut self, byte: u8, len: u64, offset: u64) {
+        let longest = &mut self.longest[byte as usize];
+        *longest = (*longest).max(len);
+        if len >= self.threshold {
+            self.covered[byte as usize] += len;
+        }
+        if self.longest_run.is_none_or(|(_, longest, _)| len > longest) {
+            self.longest_run = Some((byte, len, offset));
+        }
+    }
+}
+
+/// Serialize a value per byte as a sequence, serde only serializes arrays of up to 32 elements.
+fn serialize_per_byte<S>(values: &[u64; 256], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(values)
+}
+
+/// Calculate the run length statistics of a given file, counting runs of at least [`RUN_THRESHOLD`] bytes,
+/// see [`run_length_stats_from_reader`].
+pub fn run_length_stats<P>(file: P) -> Result<RunLengthReport, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    run_length_stats_from_reader(handle, RUN_THRESHOLD).map_err(|err| BinvizError::io(file, err))
+}
+
+/// Calculate the longest run of every byte value in everything `reader` produces,
+/// and how many bytes are covered by runs of at least `threshold` bytes.
+///
+/// The reader is consumed in chunks, runs spanning two chunks are counted whole, see [`RunLengths`].
+pub fn run_length_stats_from_reader<R>(reader: R, threshold: u64) -> io::Result<RunLengthReport>
+where
+    R: Read,
+{
+    let mut runs = RunLengths::new(threshold);
+    read_chunks(reader, |chunk| runs.update(chunk))?;
+    Ok(runs.finish())
+}
+
+/// Incrementally collects the runs of repeated bytes of an input, counting runs of at least `threshold` bytes
+/// as covering it, see [`RunLengthReport`].
+#[derive(Debug, Clone)]
+pub struct RunLengths {
+    report: RunLengthReport,
+    /// The byte, length and offset of the run currently being read.
+    current: Option<(u8, u64, u64)>,
+}
+
+impl RunLengths {
+    /// Create an empty report counting runs of at least `threshold` bytes.
+    pub fn new(threshold: u64) -> Self {
+        RunLengths {
+            report: RunLengthReport::new(threshold),
+            current: None,
+        }
+    }
+
+    /// Add the next bytes of the input, a run may continue from the previous bytes.
+    pub fn update(&mut self, buf: &[u8]) {
+        for &byte in buf {
+            match &mut self.current {
+                Some((run_byte, len, _)) if *run_byte == byte => *len += 1,
+                _ => {
+                    if let Some((run_byte, len, offset)) = self.current {
+                        self.report.add_run(run_byte, len, offset);
+                    }
+                    self.current = Some((byte, 1, self.report.size));
+                }
+            }
+            self.report.size += 1;
+        }
+    }
+
+    /// End the last run and return the report of all bytes added.
+    pub fn finish(mut self) -> RunLengthReport {
+        if let Some((run_byte, len, offset)) = self.current {
+            self.report.add_run(run_byte, len, offset);
+        }
+        self.report
+    }
+}
+
+/// Display the bytes with the longest runs first, as a table in the given `format`, showing only the first `top` bytes.
+/// The coverage is the fraction of the input in runs of at least the threshold.
+///
+/// In markdown the table is followed by the longest run in the input.
+#[cfg(feature = "tables")]
+pub fn display_run_lengths(report: &RunLengthReport, top: usize, format: OutputFormat) -> String {
+    let mut bytes: Vec<u8> = (0..=u8::MAX)
+        .filter(|&byte| report.longest[byte as usize] > 0)
+        .collect();
+    bytes.sort_by(|a, b| {
+        report.longest[*b as usize]
+            .cmp(&report.longest[*a as usize])
+            .then_with(|| a.cmp(b))
+    });
+    bytes.truncate(top);
+    let rows = bytes.into_iter().map(|byte| {
+        let covered = report.covered[byte as usize];
+        // Nothing is covered in empty input, rather than 0/0.
+        let coverage = if report.size == 0 {
+            0.0
+        } else {
+            covered as f64 / report.size as f64
+        };
+        vec![
+            format!("{:#04x}", byte),
+            format!("{}", report.longest[byte as usize]),
+            format!("{}", covered),
+            format.value(coverage, None),
+        ]
+    });
+    let covered_header = format!("Covered by Runs ≥ {}", report.threshold);
+    let table = format.render(
+        ["Hex", "Longest Run", covered_header.as_str(), "Coverage"],
+        rows,
+    );
+    match report.longest_run {
+        Some((byte, len, offset)) if format == OutputFormat::Markdown => format!(
+            "{}\nLongest run is {} bytes of {:#04x} at offset {:#x}.",
+            table, len, byte, offset
+        ),
+        _ => table,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ShortReads;
+
+    #[test]
+    fn zeros_then_abc() {
+        let mut buf = vec![0; 1000];
+        buf.extend_from_slice(b"abc");
+        let report = run_length_stats_from_reader(buf.as_slice(), RUN_THRESHOLD).unwrap();
+        assert_eq!(report.longest[0], 1000);
+        assert_eq!(report.covered[0], 1000);
+        for byte in b"abc" {
+            assert_eq!(report.longest[*byte as usize], 1);
+            assert_eq!(report.covered[*byte as usize], 0);
+        }
+        assert_eq!(report.longest_run, Some((0, 1000, 0)));
+        assert_eq!(report.size, 1003);
+    }
+
+    #[test]
+    fn runs_split_across_reads_are_counted_whole() {
+        let buf = b"xyyyyyyyyyyyyyyyyyyyyz";
+        let whole = run_length_stats_from_reader(&buf[..], RUN_THRESHOLD).unwrap();
+        for max in [1, 3, 5] {
+            let reader = ShortReads {
+                inner: &buf[..],
+                max,
+            };
+            assert_eq!(
+                run_length_stats_from_reader(reader, RUN_THRESHOLD).unwrap(),
+                whole
+            );
+        }
+        assert_eq!(whole.longest_run, Some((b'y', 20, 1)));
+        assert_eq!(whole.covered[b'y' as usize], 20);
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn coverage_of_empty_input_is_zero() {
+        let empty = run_length_stats_from_reader(&b""[..], RUN_THRESHOLD).unwrap();
+        assert_eq!(empty.longest_run, None);
+        let mut report = empty.clone();
+        report.longest[0] = 1;
+        for report in [empty, report] {
+            let table = display_run_lengths(&report, 10, OutputFormat::Markdown);
+            assert!(!table.contains("NaN"), "{}", table);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::{fmt, str::FromStr};
+
+use crate::Histogram;
+
+/// How the count of a pixel is mapped to its brightness in the generated images,
+/// relative to the count shown at full brightness set by a [`Normalization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleMode {
+    /// Proportional to the count, by default the average count of the histogram is full brightness.
+    #[default]
+    Linear,
+    /// `ln(1 + count) / ln(1 + full count)`, so a few very frequent pairs don't leave the rest black.
+    /// By default the maximum count is full brightness.
+    Log,
+    /// `sqrt(count / full count)`, in between linear and logarithmic.
+    /// By default the maximum count is full brightness.
+    Sqrt,
+}
+
+impl ScaleMode {
+    /// The brightness of a pixel with `count`, where 1 is full brightness at `full_count`.
+    /// Counts above `full_count` exceed 1.
+    pub fn brightness(self, count: usize, full_count: f64) -> f64 {
+        match self {
+            ScaleMode::Linear => count as f64 / full_count,
+            ScaleMode::Log => (count as f64).ln_1p() / full_count.ln_1p(),
+            ScaleMode::Sqrt => (count as f64 / full_count).sqrt(),
+        }
+    }
+
+    /// The count shown at `brightness`, the inverse of [`ScaleMode::brightness`].
+    pub fn count_at(self, brightness: f64, full_count: f64) -> f64 {
+        match self {
+            ScaleMode::Linear => brightness * full_count,
+            ScaleMode::Log => (brightness * full_count.ln_1p()).exp_m1(),
+            ScaleMode::Sqrt => brightness * brightness * full_count,
+        }
+    }
+
+    /// The normalization used with this scale when none is given.
+    pub fn default_normalization(self) -> Normalization {
+        match self {
+            ScaleMode::Linear => Normalization::Average,
+            ScaleMode::Log | ScaleMode::Sqrt => Normalization::Maximum,
+        }
+    }
+}
+
+impl FromStr for ScaleMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(ScaleMode::Linear),
+            "log" => Ok(ScaleMode::Log),
+            "sqrt" => Ok(ScaleMode::Sqrt),
+            _ => Err(format!(
+                "unknown scale `{}`, expected `linear`, `log` or `sqrt`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ScaleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaleMode::Linear => f.write_str("linear"),
+            ScaleMode::Log => f.write_str("log"),
+            ScaleMode::Sqrt => f.write_str("sqrt"),
+        }
+    }
+}
+
+/// Which count of a histogram is shown at full brightness in the generated images, higher counts are clamped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalization {
+    /// The average count of the keys in the histogram.
+    Average,
+    /// The maximum count in the histogram.
+    Maximum,
+    /// This percentile of the counts in the histogram, from 0 to 100, see [`count_percentile`].
+    Percentile(f64),
+}
+
+impl Normalization {
+    /// The count shown at full brightness for `histogram`.
+    pub fn full_count(self, histogram: &Histogram<u8>) -> f64 {
+        self.full_count_of(histogram.values().copied())
+    }
+
+    /// The count shown at full brightness for a histogram with these `counts`, one per key.
+    /// Without counts it is 0 for every normalization.
+    pub(crate) fn full_count_of<I>(self, counts: I) -> f64
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        match self {
+            Normalization::Average => {
+                let (sum, len) = counts
+                    .into_iter()
+                    .fold((0, 0), |(sum, len), count| (sum + count, len + 1));
+                if len == 0 {
+                    return 0.0;
+                }
+                sum as f64 / len as f64
+            }
+            Normalization::Maximum => counts.into_iter().max().unwrap_or(0) as f64,
+            Normalization::Percentile(percentile) => percentile_of(counts, percentile),
+        }
+    }
+}
+
+impl FromStr for Normalization {
+    type Err = String;
+
+    /// Parse `average`, `max` or `percentile:P` with `P` from 0 to 100, e.g. `percentile:99`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "average" | "avg" => Ok(Normalization::Average),
+            "max" | "maximum" => Ok(Normalization::Maximum),
+            _ => match s.strip_prefix("percentile:").map(str::parse::<f64>) {
+                Some(Ok(percentile)) if (0.0..=100.0).contains(&percentile) => {
+                    Ok(Normalization::Percentile(percentile))
+                }
+                _ => Err(format!(
+                    "unknown normalization `{}`, expected `average`, `max` or `percentile:P` with P from 0 to 100",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Normalization::Average => f.write_str("average"),
+            Normalization::Maximum => f.write_str("max"),
+            Normalization::Percentile(percentile) => write!(f, "percentile:{}", percentile),
+        }
+    }
+}
+
+/// Calculate the `percentile` of the nonzero counts of a histogram, from 0 to 100.
+///
+/// Percentiles between two counts are interpolated linearly between them, so the 50th percentile of
+/// the counts 1 and 4 is 2.5. Only the counts are sorted, at most one per key. An empty histogram gives 0.
+pub fn count_percentile(histogram: &Histogram<u8>, percentile: f64) -> f64 {
+    percentile_of(histogram.values().copied(), percentile)
+}
+
+/// Calculate the `percentile` of the nonzero `counts`, see [`count_percentile`].
+fn percentile_of<I>(counts: I, percentile: f64) -> f64
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut counts: Vec<usize> = counts.into_iter().filter(|&count| count > 0).collect();
+    if counts.is_empty() {
+        return 0.0;
+    }
+    counts.sort_unstable();
+    let rank = percentile.clamp(0.0, 100.0) / 100.0 * (counts.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    let fraction = rank - low as f64;
+    counts[low] as f64 + (counts[high] as f64 - counts[low] as f64) * fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_are_interpolated_between_counts() {
+        assert_eq!(percentile_of([1, 3], 50.0), 2.0);
+        assert_eq!(percentile_of([3, 1], 0.0), 1.0);
+        assert_eq!(percentile_of([1, 3], 100.0), 3.0);
+        assert_eq!(percentile_of([1, 4], 50.0), 2.5);
+        // Zero counts are left out.
+        assert_eq!(percentile_of([0, 0, 1, 3], 50.0), 2.0);
+    }
+
+    #[test]
+    fn no_counts_give_zero() {
+        assert_eq!(percentile_of([], 50.0), 0.0);
+        assert_eq!(percentile_of([0, 0], 99.0), 0.0);
+        for normalization in [
+            Normalization::Average,
+            Normalization::Maximum,
+            Normalization::Percentile(99.0),
+        ] {
+            assert_eq!(normalization.full_count(&Histogram::new()), 0.0);
+        }
+    }
+
+    #[test]
+    fn full_counts_of_each_normalization() {
+        let counts = [1, 2, 3, 10];
+        assert_eq!(Normalization::Average.full_count_of(counts), 4.0);
+        assert_eq!(Normalization::Maximum.full_count_of(counts), 10.0);
+        assert_eq!(Normalization::Percentile(50.0).full_count_of(counts), 2.5);
+    }
+}
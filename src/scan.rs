@@ -0,0 +1,204 @@
+use std::{
+    cmp::Ordering,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{
+    calculate_entropy_histogram, calculate_histogram, chi_square, classify_byte, BinvizError,
+    ByteClass, EntropyUnit, Histogram,
+};
+
+/// The entropy in bits per byte from which [`verdict`] considers a file compressed or encrypted.
+pub const COMPRESSED_ENTROPY_THRESHOLD: f64 = 7.5;
+
+/// The share of printable ASCII and whitespace bytes from which [`verdict`] considers a file text.
+pub const TEXT_SHARE_THRESHOLD: f64 = 0.95;
+
+/// A crude guess of what a file holds, from the distribution of its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verdict {
+    /// The file has no bytes.
+    Empty,
+    /// Nearly all bytes are printable ASCII or whitespace.
+    Text,
+    /// The bytes are close to uniformly distributed, as in compressed or encrypted data.
+    CompressedOrEncrypted,
+    /// Anything else.
+    Binary,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Empty => f.write_str("empty"),
+            Verdict::Text => f.write_str("text"),
+            Verdict::CompressedOrEncrypted => f.write_str("compressed or encrypted"),
+            Verdict::Binary => f.write_str("binary"),
+        }
+    }
+}
+
+/// Guess what a file holds from the 1-dimensional `histogram` of its bytes and their `entropy` in bits:
+/// text if at least [`TEXT_SHARE_THRESHOLD`] of the bytes is printable ASCII or whitespace,
+/// compressed or encrypted if the entropy is at least [`COMPRESSED_ENTROPY_THRESHOLD`] and binary otherwise.
+pub fn verdict(histogram: &Histogram<u8>, entropy: f64) -> Verdict {
+    let total: usize = histogram.values().sum();
+    if total == 0 {
+        return Verdict::Empty;
+    }
+    let text: usize = histogram
+        .iter()
+        .filter(|(key, _)| {
+            matches!(
+                classify_byte(key[0]),
+                ByteClass::Printable | ByteClass::Whitespace
+            )
+        })
+        .map(|(_, &count)| count)
+        .sum();
+    if text as f64 / total as f64 >= TEXT_SHARE_THRESHOLD {
+        Verdict::Text
+    } else if entropy >= COMPRESSED_ENTROPY_THRESHOLD {
+        Verdict::CompressedOrEncrypted
+    } else {
+        Verdict::Binary
+    }
+}
+
+/// The statistics of a file found by a scan, see [`scan_file`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanEntry {
+    /// The path the file was read from.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The entropy of the bytes in bits, 0 for an empty file.
+    pub entropy: f64,
+    /// The chi-square statistic of the bytes against the uniform distribution and its p-value,
+    /// if it was asked for and the file isn't empty, see [`chi_square`].
+    pub chi_square: Option<(f64, f64)>,
+    /// What the file probably holds.
+    pub verdict: Verdict,
+}
+
+/// Calculate the entropy of the bytes of `file`, and with `with_chi_square` their chi-square statistic,
+/// reading the file in chunks rather than loading it into memory.
+pub fn scan_file<P>(file: P, with_chi_square: bool) -> Result<ScanEntry, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let size = fs::metadata(file)
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    if size == 0 {
+        return Ok(ScanEntry {
+            path: file.to_path_buf(),
+            size,
+            entropy: 0.0,
+            chi_square: None,
+            verdict: Verdict::Empty,
+        });
+    }
+    let histogram = calculate_histogram(file, 1, 1)?;
+    let entropy = calculate_entropy_histogram(&histogram, EntropyUnit::Bits)?;
+    let chi_square = if with_chi_square {
+        Some(chi_square(&histogram)?)
+    } else {
+        None
+    };
+    Ok(ScanEntry {
+        path: file.to_path_buf(),
+        size,
+        entropy,
+        chi_square,
+        verdict: verdict(&histogram, entropy),
+    })
+}
+
+/// The order of the rows of a scan table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScanSort {
+    /// The files with the highest entropy first.
+    #[default]
+    Entropy,
+    /// The largest files first.
+    Size,
+    /// By path.
+    Name,
+}
+
+impl ScanSort {
+    /// Compare two entries in this order, entries that are equal otherwise are ordered by path.
+    pub fn compare(self, a: &ScanEntry, b: &ScanEntry) -> Ordering {
+        let by_path = || a.path.cmp(&b.path);
+        match self {
+            ScanSort::Entropy => b.entropy.total_cmp(&a.entropy).then_with(by_path),
+            ScanSort::Size => b.size.cmp(&a.size).then_with(by_path),
+            ScanSort::Name => by_path(),
+        }
+    }
+}
+
+impl FromStr for ScanSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "entropy" => Ok(ScanSort::Entropy),
+            "size" => Ok(ScanSort::Size),
+            "name" | "path" => Ok(ScanSort::Name),
+            _ => Err(format!(
+                "unknown scan order `{}`, expected `entropy`, `size` or `name`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ScanSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanSort::Entropy => f.write_str("entropy"),
+            ScanSort::Size => f.write_str("size"),
+            ScanSort::Name => f.write_str("name"),
+        }
+    }
+}
+
+/// Display the scanned files as a table ranked by `sort`, with the chi-square statistic and its p-value
+/// if any entry has them.
+#[cfg(feature = "tables")]
+pub fn display_scan(entries: &[ScanEntry], sort: ScanSort, format: OutputFormat) -> String {
+    let mut entries: Vec<&ScanEntry> = entries.iter().collect();
+    entries.sort_by(|a, b| sort.compare(a, b));
+    let with_chi_square = entries.iter().any(|entry| entry.chi_square.is_some());
+    let mut header = vec!["Rank", "File", "Size", "Entropy"];
+    if with_chi_square {
+        header.extend(["Chi-square", "P-value"]);
+    }
+    header.push("Verdict");
+    let rows = entries.iter().enumerate().map(|(rank, entry)| {
+        let mut row = vec![
+            rank.to_string(),
+            entry.path.display().to_string(),
+            entry.size.to_string(),
+            format.value(entry.entropy, Some("bits")),
+        ];
+        if with_chi_square {
+            match entry.chi_square {
+                Some((statistic, p_value)) => {
+                    row.extend([format.value(statistic, None), format.value(p_value, None)])
+                }
+                None => row.extend([String::new(), String::new()]),
+            }
+        }
+        row.push(entry.verdict.to_string());
+        row
+    });
+    format.render(header, rows)
+}
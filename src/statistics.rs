@@ -0,0 +1,639 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+#[cfg(feature = "tables")]
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use serde::Serialize;
+
+use crate::{check_key_lengths, read_chunks, validate_dimension, BinvizError, Histogram};
+
+/// The degrees of freedom of the chi-square test of [`chi_square`], one less than the amount of byte values.
+pub const CHI_SQUARE_DEGREES_OF_FREEDOM: usize = 255;
+
+/// Calculate the chi-square statistic of a 1-dimensional histogram against the uniform distribution
+/// of bytes, together with its p-value: the probability that truly random bytes exceed the statistic.
+///
+/// Like the `ent` tool, a p-value below 1% or above 99% means the bytes are almost certainly not random.
+pub fn chi_square(histogram: &Histogram<u8>) -> Result<(f64, f64), BinvizError> {
+    validate_dimension(histogram, 1)?;
+    let total: usize = histogram.values().sum();
+    if total == 0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let expected = total as f64 / 256.0;
+    let observed = histogram
+        .values()
+        .map(|&freq| (freq as f64 - expected).powi(2) / expected)
+        .sum::<f64>();
+    // Every byte value that doesn't occur deviates by the whole expected count.
+    let missing = (256 - histogram.len()) as f64 * expected;
+    let statistic = observed + missing;
+    let p_value =
+        upper_regularized_gamma(CHI_SQUARE_DEGREES_OF_FREEDOM as f64 / 2.0, statistic / 2.0);
+    Ok((statistic, p_value))
+}
+
+/// Calculate the chi-square statistic of the test whether `a` and `b` are samples of the same distribution,
+/// together with its p-value: the probability that two samples of the same distribution exceed the statistic.
+///
+/// The degrees of freedom are one less than the amount of keys in either histogram,
+/// the counts are scaled to make up for histograms of different totals.
+pub fn chi_square_homogeneity(
+    a: &Histogram<u8>,
+    b: &Histogram<u8>,
+) -> Result<(f64, f64), BinvizError> {
+    check_key_lengths(a, b)?;
+    let a_total = a.values().sum::<usize>() as f64;
+    let b_total = b.values().sum::<usize>() as f64;
+    if a_total == 0.0 || b_total == 0.0 {
+        return Err(BinvizError::EmptyInput);
+    }
+    let (a_scale, b_scale) = ((b_total / a_total).sqrt(), (a_total / b_total).sqrt());
+    let keys: BTreeSet<&Vec<u8>> = a.keys().chain(b.keys()).collect();
+    let statistic: f64 = keys
+        .iter()
+        .map(|key| {
+            let a_freq = a.get(*key).copied().unwrap_or(0) as f64;
+            let b_freq = b.get(*key).copied().unwrap_or(0) as f64;
+            if a_freq + b_freq == 0.0 {
+                0.0
+            } else {
+                (a_scale * a_freq - b_scale * b_freq).powi(2) / (a_freq + b_freq)
+            }
+        })
+        .sum();
+    let degrees_of_freedom = keys.len().saturating_sub(1);
+    // A single key leaves nothing to differ in.
+    let p_value = if degrees_of_freedom == 0 {
+        1.0
+    } else {
+        upper_regularized_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0)
+    };
+    Ok((statistic, p_value))
+}
+
+/// Display the chi-square statistic and its p-value, see [`chi_square`].
+#[cfg(feature = "tables")]
+pub fn display_chi_square(statistic: f64, p_value: f64) -> String {
+    format!(
+        "Chi-square distribution is {:.2} for {} degrees of freedom, randomly exceeding this value {:.2} percent of the times.",
+        statistic,
+        CHI_SQUARE_DEGREES_OF_FREEDOM,
+        p_value * 100.0
+    )
+}
+
+/// The moments of the byte values of a 1-dimensional histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ByteStats {
+    /// The arithmetic mean, 127.5 for random bytes.
+    pub mean: f64,
+    /// The (population) variance, 5461.25 for random bytes.
+    pub variance: f64,
+    /// The skewness, 0 for symmetric distributions, `None` if every byte is the same.
+    pub skewness: Option<f64>,
+    /// The excess kurtosis, 0 for the normal distribution and about -1.2 for random bytes,
+    /// `None` if every byte is the same.
+    pub excess_kurtosis: Option<f64>,
+}
+
+impl ByteStats {
+    /// Calculate the moments of the byte values of a 1-dimensional histogram.
+    ///
+    /// An empty histogram has no moments, it is an [`BinvizError::EmptyInput`] error.
+    pub fn compute(histogram: &Histogram<u8>) -> Result<Self, BinvizError> {
+        validate_dimension(histogram, 1)?;
+        let total = histogram.values().sum::<usize>() as f64;
+        if total == 0.0 {
+            return Err(BinvizError::EmptyInput);
+        }
+        let mean = histogram
+            .iter()
+            .map(|(byte, &freq)| byte[0] as f64 * freq as f64)
+            .sum::<f64>()
+            / total;
+        // The central moment of the given order.
+        let moment = |order: i32| {
+            histogram
+                .iter()
+                .map(|(byte, &freq)| (byte[0] as f64 - mean).powi(order) * freq as f64)
+                .sum::<f64>()
+                / total
+        };
+        let variance = moment(2);
+        let (skewness, excess_kurtosis) = if variance > 0.0 {
+            (
+                Some(moment(3) / variance.powf(1.5)),
+                Some(moment(4) / (variance * variance) - 3.0),
+            )
+        } else {
+            (None, None)
+        };
+        Ok(ByteStats {
+            mean,
+            variance,
+            skewness,
+            excess_kurtosis,
+        })
+    }
+}
+
+/// Display the moments of the byte values as a table, see [`ByteStats`].
+#[cfg(feature = "tables")]
+pub fn display_byte_stats(stats: &ByteStats) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(BYTE_STATS_HEADER);
+    for row in byte_stats_rows(stats) {
+        table.add_row(row);
+    }
+    table.to_string()
+}
+
+/// The header of the table of [`display_byte_stats`].
+#[cfg(feature = "tables")]
+pub(crate) const BYTE_STATS_HEADER: [&str; 3] = ["Statistic", "Value", "Random Bytes"];
+
+/// The rows of the table of [`display_byte_stats`], every statistic with its value for random bytes.
+#[cfg(feature = "tables")]
+pub(crate) fn byte_stats_rows(stats: &ByteStats) -> [[String; 3]; 4] {
+    let undefined = |value: Option<f64>| {
+        value.map_or_else(|| "undefined".to_string(), |value| format!("{:.5}", value))
+    };
+    [
+        [
+            "Mean".to_string(),
+            format!("{:.5}", stats.mean),
+            "127.5".to_string(),
+        ],
+        [
+            "Variance".to_string(),
+            format!("{:.5}", stats.variance),
+            "5461.25".to_string(),
+        ],
+        [
+            "Skewness".to_string(),
+            undefined(stats.skewness),
+            "0".to_string(),
+        ],
+        [
+            "Excess Kurtosis".to_string(),
+            undefined(stats.excess_kurtosis),
+            "-1.2".to_string(),
+        ],
+    ]
+}
+
+/// Calculate the serial correlation coefficient of the bytes of a given file, reading it in chunks,
+/// see [`SerialCorrelation`].
+pub fn serial_correlation<P>(file: P) -> Result<f64, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    read_serial_correlation(handle)
+        .map_err(|err| BinvizError::io(file, err))?
+        .finish()
+}
+
+/// Calculate the serial correlation coefficient of the bytes of a byte slice, see [`SerialCorrelation`].
+pub fn serial_correlation_from_bytes(buf: &[u8]) -> Result<f64, BinvizError> {
+    let mut correlation = SerialCorrelation::new();
+    correlation.update(buf);
+    correlation.finish()
+}
+
+/// Calculate the serial correlation coefficient of everything `reader` produces, in chunks of [`crate::CHUNK_SIZE`] bytes.
+///
+/// A failed read is a [`BinvizError::Read`] error.
+pub fn serial_correlation_from_reader<R>(reader: R) -> Result<f64, BinvizError>
+where
+    R: Read,
+{
+    read_serial_correlation(reader)
+        .map_err(BinvizError::Read)?
+        .finish()
+}
+
+/// Add everything `reader` produces to a serial correlation.
+fn read_serial_correlation<R>(reader: R) -> io::Result<SerialCorrelation>
+where
+    R: Read,
+{
+    let mut correlation = SerialCorrelation::new();
+    read_chunks(reader, |chunk| correlation.update(chunk))?;
+    Ok(correlation)
+}
+
+/// Incrementally calculates the serial correlation coefficient of bytes, the correlation of every byte with the next one,
+/// like the `ent` tool does: the last byte is correlated with the first one.
+///
+/// The coefficient is close to 0 for random bytes, close to 1 when every byte predicts the next one, e.g. in a counter,
+/// and close to -1 when bytes alternate between high and low values.
+#[derive(Debug, Clone, Default)]
+pub struct SerialCorrelation {
+    count: u64,
+    sum: f64,
+    sum_squares: f64,
+    sum_products: f64,
+    first: Option<u8>,
+    last: Option<u8>,
+}
+
+impl SerialCorrelation {
+    /// Create an empty calculation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next bytes of the input.
+    pub fn update(&mut self, buf: &[u8]) {
+        for &byte in buf {
+            let value = byte as f64;
+            if let Some(last) = self.last {
+                self.sum_products += last as f64 * value;
+            }
+            self.first.get_or_insert(byte);
+            self.last = Some(byte);
+            self.count += 1;
+            self.sum += value;
+            self.sum_squares += value * value;
+        }
+    }
+
+    /// Calculate the coefficient of all bytes added.
+    ///
+    /// An empty input is an [`BinvizError::EmptyInput`] error, and a constant input, whose bytes don't vary,
+    /// is a [`BinvizError::ZeroVariance`] error.
+    pub fn finish(self) -> Result<f64, BinvizError> {
+        let (Some(first), Some(last)) = (self.first, self.last) else {
+            return Err(BinvizError::EmptyInput);
+        };
+        let count = self.count as f64;
+        let sum_products = self.sum_products + last as f64 * first as f64;
+        let variance = count * self.sum_squares - self.sum * self.sum;
+        if variance == 0.0 {
+            return Err(BinvizError::ZeroVariance);
+        }
+        Ok((count * sum_products - self.sum * self.sum) / variance)
+    }
+}
+
+/// Display the serial correlation coefficient, see [`SerialCorrelation`], or that it is undefined for a constant input.
+#[cfg(feature = "tables")]
+pub fn display_serial_correlation(coefficient: Option<f64>) -> String {
+    match coefficient {
+        Some(coefficient) => format!(
+            "Serial correlation coefficient is {:.6} (totally uncorrelated = 0.0).",
+            coefficient
+        ),
+        None => "Serial correlation coefficient is undefined, every byte is the same.".to_string(),
+    }
+}
+
+/// Estimate pi from the bytes of a given file, reading it in chunks, see [`MonteCarloPi`].
+pub fn monte_carlo_pi<P>(file: P) -> Result<(f64, f64), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    read_monte_carlo_pi(handle)
+        .map_err(|err| BinvizError::io(file, err))?
+        .finish()
+}
+
+/// Estimate pi from the bytes of a byte slice, see [`MonteCarloPi`].
+pub fn monte_carlo_pi_from_bytes(buf: &[u8]) -> Result<(f64, f64), BinvizError> {
+    let mut estimate = MonteCarloPi::new();
+    estimate.update(buf);
+    estimate.finish()
+}
+
+/// Estimate pi from everything `reader` produces, in chunks of [`crate::CHUNK_SIZE`] bytes.
+///
+/// A failed read is a [`BinvizError::Read`] error.
+pub fn monte_carlo_pi_from_reader<R>(reader: R) -> Result<(f64, f64), BinvizError>
+where
+    R: Read,
+{
+    read_monte_carlo_pi(reader)
+        .map_err(BinvizError::Read)?
+        .finish()
+}
+
+/// Add everything `reader` produces to an estimate of pi.
+fn read_monte_carlo_pi<R>(reader: R) -> io::Result<MonteCarloPi>
+where
+    R: Read,
+{
+    let mut estimate = MonteCarloPi::new();
+    read_chunks(reader, |chunk| estimate.update(chunk))?;
+    Ok(estimate)
+}
+
+/// The amount of bytes per point of [`MonteCarloPi`], 3 bytes for each coordinate.
+const MONTE_CARLO_GROUP: usize = 6;
+
+/// The squared radius of the quarter circle of [`MonteCarloPi`], the largest 24-bit coordinate squared.
+const MONTE_CARLO_RADIUS_SQUARED: u64 = ((1 << 24) - 1) * ((1 << 24) - 1);
+
+/// Incrementally estimates pi like the `ent` tool does: every group of 6 bytes is a point with two 24-bit
+/// big endian coordinates, and the fraction of points inside the quarter circle approaches pi / 4 for random bytes.
+///
+/// A trailing group of fewer than 6 bytes is skipped.
+#[derive(Debug, Clone, Default)]
+pub struct MonteCarloPi {
+    points: u64,
+    inside: u64,
+    group: Vec<u8>,
+}
+
+impl MonteCarloPi {
+    /// Create an empty estimate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next bytes of the input.
+    pub fn update(&mut self, mut buf: &[u8]) {
+        if !self.group.is_empty() {
+            let needed = (MONTE_CARLO_GROUP - self.group.len()).min(buf.len());
+            self.group.extend_from_slice(&buf[..needed]);
+            buf = &buf[needed..];
+            if self.group.len() < MONTE_CARLO_GROUP {
+                return;
+            }
+            let group = std::mem::take(&mut self.group);
+            self.add_point(&group);
+        }
+        let groups = buf.chunks_exact(MONTE_CARLO_GROUP);
+        self.group.extend_from_slice(groups.remainder());
+        for group in groups {
+            self.add_point(group);
+        }
+    }
+
+    fn add_point(&mut self, group: &[u8]) {
+        let coordinate = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .fold(0u64, |coordinate, &byte| (coordinate << 8) | byte as u64)
+        };
+        let (x, y) = (coordinate(&group[..3]), coordinate(&group[3..]));
+        self.points += 1;
+        if x * x + y * y <= MONTE_CARLO_RADIUS_SQUARED {
+            self.inside += 1;
+        }
+    }
+
+    /// Calculate the estimate of pi and its error relative to pi in percent.
+    ///
+    /// An input without a whole group of 6 bytes is an [`BinvizError::EmptyInput`] error.
+    pub fn finish(self) -> Result<(f64, f64), BinvizError> {
+        if self.points == 0 {
+            return Err(BinvizError::EmptyInput);
+        }
+        let pi = 4.0 * self.inside as f64 / self.points as f64;
+        let error = (pi - std::f64::consts::PI).abs() / std::f64::consts::PI * 100.0;
+        Ok((pi, error))
+    }
+}
+
+/// Display the estimate of pi and its error, see [`MonteCarloPi`], or that there were too few bytes to estimate it.
+#[cfg(feature = "tables")]
+pub fn display_monte_carlo_pi(estimate: Option<(f64, f64)>) -> String {
+    match estimate {
+        Some((pi, error)) => format!(
+            "Monte Carlo value for pi is {:.9} (error {:.2} percent).",
+            pi, error
+        ),
+        None => format!(
+            "Monte Carlo value for pi is undefined, the input has fewer than {} bytes.",
+            MONTE_CARLO_GROUP
+        ),
+    }
+}
+
+/// The maximum amount of iterations of the series and continued fraction of the incomplete gamma function.
+const GAMMA_ITERATIONS: usize = 1000;
+
+/// The relative precision the incomplete gamma function is calculated to.
+const GAMMA_EPSILON: f64 = 1e-15;
+
+/// Calculate the upper regularized incomplete gamma function `Q(a, x) = Γ(a, x) / Γ(a)`, for `a > 0` and `x >= 0`.
+///
+/// Uses the series expansion of `P(a, x) = 1 - Q(a, x)` for `x < a + 1` and the continued fraction of `Q(a, x)` otherwise,
+/// which both converge quickly in their range.
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    // The common factor x^a e^-x / Γ(a) of both expansions.
+    let prefactor = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..GAMMA_ITERATIONS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * GAMMA_EPSILON {
+                break;
+            }
+        }
+        (1.0 - sum * prefactor).max(0.0)
+    } else {
+        // Modified Lentz's method.
+        let tiny = f64::MIN_POSITIVE / GAMMA_EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut fraction = d;
+        for n in 1..GAMMA_ITERATIONS {
+            let an = -(n as f64) * (n as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            fraction *= delta;
+            if (delta - 1.0).abs() < GAMMA_EPSILON {
+                break;
+            }
+        }
+        (fraction * prefactor).min(1.0)
+    }
+}
+
+/// Calculate `ln Γ(x)` for `x > 0`, using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula, the approximation is only accurate for x >= 0.5.
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::{calculate_histogram_from_bytes, test_support::noise};
+
+    #[test]
+    fn random_bytes_have_a_plausible_chi_square_p_value() {
+        let histogram = calculate_histogram_from_bytes(&noise(1 << 16), 1, 1);
+        let (_, p_value) = chi_square(&histogram).unwrap();
+        assert!(p_value > 0.01 && p_value < 0.99, "{}", p_value);
+    }
+
+    #[test]
+    fn constant_bytes_have_a_negligible_chi_square_p_value() {
+        let histogram = calculate_histogram_from_bytes(&[0; 1 << 16], 1, 1);
+        let (statistic, p_value) = chi_square(&histogram).unwrap();
+        assert_eq!(statistic, 255.0 * (1 << 16) as f64);
+        assert!(p_value < 1e-100, "{}", p_value);
+    }
+
+    #[test]
+    fn upper_regularized_gamma_matches_closed_forms() {
+        // Q(1, x) = e^-x and Q(2, x) = (1 + x) e^-x, on both sides of x = a + 1 where the expansions switch.
+        for x in [0.5, 1.5, 2.5, 10.0] {
+            assert!((upper_regularized_gamma(1.0, x) - (-x).exp()).abs() < 1e-12);
+            assert!((upper_regularized_gamma(2.0, x) - (1.0 + x) * (-x).exp()).abs() < 1e-12);
+        }
+        assert_eq!(upper_regularized_gamma(127.5, 0.0), 1.0);
+    }
+
+    #[test]
+    fn counter_is_almost_perfectly_correlated() {
+        let counter: Vec<u8> = (0..=255u8).cycle().take(256 * 64).collect();
+        let coefficient = serial_correlation_from_bytes(&counter).unwrap();
+        // Only the wrap around from 255 to 0 breaks the correlation.
+        assert!(coefficient < 1.0);
+        assert!(coefficient > 0.97, "{}", coefficient);
+    }
+
+    #[test]
+    fn random_bytes_are_uncorrelated() {
+        let coefficient = serial_correlation_from_bytes(&noise(1 << 16)).unwrap();
+        assert!(coefficient.abs() < 0.02, "{}", coefficient);
+    }
+
+    #[test]
+    fn alternating_bytes_are_anticorrelated() {
+        let alternating: Vec<u8> = [0, 255].repeat(512);
+        let coefficient = serial_correlation_from_bytes(&alternating).unwrap();
+        assert!((coefficient + 1.0).abs() < 1e-12, "{}", coefficient);
+    }
+
+    #[test]
+    fn constant_bytes_are_an_error() {
+        assert!(matches!(
+            serial_correlation_from_bytes(&[42; 1000]),
+            Err(BinvizError::ZeroVariance)
+        ));
+        assert!(matches!(
+            serial_correlation_from_bytes(&[]),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn correlation_is_independent_of_chunking() {
+        let buf = noise(10_000);
+        let mut chunked = SerialCorrelation::new();
+        for chunk in buf.chunks(333) {
+            chunked.update(chunk);
+        }
+        let whole = serial_correlation_from_bytes(&buf).unwrap();
+        assert!((chunked.finish().unwrap() - whole).abs() < 1e-12);
+        let read = serial_correlation_from_reader(&buf[..]).unwrap();
+        assert!((read - whole).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zeros_are_all_inside_the_circle() {
+        assert_eq!(
+            monte_carlo_pi_from_bytes(&[0; 600]).unwrap(),
+            (4.0, (4.0 - PI) / PI * 100.0)
+        );
+    }
+
+    #[test]
+    fn random_bytes_approach_pi() {
+        let (pi, error) = monte_carlo_pi_from_bytes(&noise(6 << 18)).unwrap();
+        assert!((pi - PI).abs() < 0.01, "{}", pi);
+        assert!(error < 0.5, "{}", error);
+    }
+
+    #[test]
+    fn points_are_grouped_across_updates() {
+        let buf = noise(6000 + 5);
+        let whole = monte_carlo_pi_from_bytes(&buf).unwrap();
+        // Chunk sizes that split the groups of 6 bytes in every possible place.
+        for size in [1, 4, 5, 7, 11, 333] {
+            let mut chunked = MonteCarloPi::new();
+            for chunk in buf.chunks(size) {
+                chunked.update(chunk);
+            }
+            assert_eq!(chunked.finish().unwrap(), whole, "chunks of {}", size);
+        }
+        assert_eq!(monte_carlo_pi_from_reader(&buf[..]).unwrap(), whole);
+    }
+
+    #[test]
+    fn less_than_a_point_is_an_error() {
+        assert!(matches!(
+            monte_carlo_pi_from_bytes(&[0; 5]),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn failed_read_is_a_read_error() {
+        /// A reader that fails right away.
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "cut off"))
+            }
+        }
+
+        assert!(matches!(
+            monte_carlo_pi_from_reader(Failing),
+            Err(BinvizError::Read(_))
+        ));
+    }
+}
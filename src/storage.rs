@@ -0,0 +1,322 @@
+use std::{
+    fmt, fs,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use bincode::Options;
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserializer, Serializer,
+};
+
+use crate::{create_output, BinvizError, Histogram};
+
+/// The magic bytes every histogram saved in the [`Format::Bincode`] format starts with.
+const BINCODE_MAGIC: &[u8; 4] = b"BVZH";
+
+/// The formats a histogram can be saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A JSON object mapping hexadecimal keys to their counts, e.g. `{"1f8b": 3}`.
+    Json,
+    /// A compact binary encoding, prefixed with a few magic bytes.
+    Bincode,
+}
+
+impl Format {
+    /// Pick the format based on the extension of `path`: JSON for `.json` and bincode otherwise.
+    pub fn from_path<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        match path.as_ref().extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Bincode,
+        }
+    }
+}
+
+/// Save the histogram to `path` in the given format, an existing file is only overwritten with `force`.
+pub fn save_histogram<P>(
+    path: P,
+    histogram: &Histogram<u8>,
+    format: Format,
+    force: bool,
+) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = create_output(path, force)?;
+    let mut writer = BufWriter::new(file);
+    let wrapped = Wrapper(histogram);
+    match format {
+        Format::Json => serde_json::to_writer(&mut writer, &wrapped)
+            .map_err(|err| BinvizError::serialization(path, err))?,
+        Format::Bincode => {
+            writer
+                .write_all(BINCODE_MAGIC)
+                .map_err(|err| BinvizError::write(path, err))?;
+            bincode_options()
+                .serialize_into(&mut writer, &wrapped)
+                .map_err(|err| BinvizError::serialization(path, err))?;
+        }
+    }
+    writer.flush().map_err(|err| BinvizError::write(path, err))
+}
+
+/// Load a histogram saved by [`save_histogram`], in either format.
+pub fn load_histogram<P>(path: P) -> Result<Histogram<u8>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let contents = fs::read(path).map_err(|err| BinvizError::io(path, err))?;
+    decode_histogram(path, &contents)
+}
+
+/// Decode the `contents` of a histogram saved by [`save_histogram`] to `path`, in either format.
+pub(crate) fn decode_histogram(path: &Path, contents: &[u8]) -> Result<Histogram<u8>, BinvizError> {
+    match contents.strip_prefix(BINCODE_MAGIC) {
+        Some(contents) => {
+            let mut deserializer = bincode::Deserializer::from_slice(contents, bincode_options());
+            histogram_serde::deserialize(&mut deserializer)
+                .map_err(|err| BinvizError::serialization(path, err))
+        }
+        None => {
+            let mut deserializer = serde_json::Deserializer::from_slice(contents);
+            let histogram = histogram_serde::deserialize(&mut deserializer)
+                .and_then(|histogram| deserializer.end().map(|()| histogram))
+                .map_err(|err| BinvizError::serialization(path, err))?;
+            Ok(histogram)
+        }
+    }
+}
+
+/// The options used for the [`Format::Bincode`] format.
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+/// Serializes a borrowed histogram through [`histogram_serde`].
+struct Wrapper<'a>(&'a Histogram<u8>);
+
+impl serde::Serialize for Wrapper<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        histogram_serde::serialize(self.0, serializer)
+    }
+}
+
+/// Serde support for histograms, for use with `#[serde(with = "histogram_serde")]`.
+///
+/// Human readable formats encode keys as hexadecimal strings, since most of them only support
+/// string keys, binary formats encode them as plain bytes. Deserializing checks that all keys have the same length.
+pub mod histogram_serde {
+    use super::*;
+
+    pub fn serialize<S>(histogram: &Histogram<u8>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let human_readable = serializer.is_human_readable();
+        let mut map = serializer.serialize_map(Some(histogram.len()))?;
+        for (key, freq) in histogram {
+            if human_readable {
+                map.serialize_entry(&to_hex(key), freq)?;
+            } else {
+                map.serialize_entry(key, freq)?;
+            }
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Histogram<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_map(HistogramVisitor { human_readable })
+    }
+}
+
+struct HistogramVisitor {
+    human_readable: bool,
+}
+
+impl HistogramVisitor {
+    /// Read the next key, decoding it from hexadecimal for human readable formats.
+    fn next_key<'de, A>(&self, access: &mut A) -> Result<Option<Vec<u8>>, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if !self.human_readable {
+            return access.next_key();
+        }
+        match access.next_key::<String>()? {
+            Some(key) => from_hex(&key)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("invalid hexadecimal key `{}`", key))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for HistogramVisitor {
+    type Value = Histogram<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map from byte sequences to counts")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut histogram = Histogram::new();
+        while let Some(key) = self.next_key(&mut access)? {
+            if let Some(first) = histogram.keys().next() {
+                if first.len() != key.len() {
+                    return Err(de::Error::custom(format!(
+                        "expected keys of {} bytes, found a key of {} bytes",
+                        first.len(),
+                        key.len()
+                    )));
+                }
+            }
+            let freq = access.next_value()?;
+            histogram.insert(key, freq);
+        }
+        Ok(histogram)
+    }
+}
+
+/// Encode the bytes as a lowercase hexadecimal string.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decode a hexadecimal string, or `None` if it isn't valid.
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_histogram_from_bytes;
+
+    /// Every byte value, none of them valid UTF-8 on their own from 0x80.
+    fn all_bytes() -> Vec<u8> {
+        (0..=u8::MAX).chain((0..=u8::MAX).rev()).collect()
+    }
+
+    #[test]
+    fn round_trips_in_both_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        for dimension in 1..=3 {
+            let histogram = calculate_histogram_from_bytes(&all_bytes(), dimension, 1);
+            for (format, name) in [(Format::Json, "h.json"), (Format::Bincode, "h.bin")] {
+                let path = dir.path().join(name);
+                save_histogram(&path, &histogram, format, true).unwrap();
+                assert_eq!(
+                    load_histogram(&path).unwrap(),
+                    histogram,
+                    "dimension {}, {:?}",
+                    dimension,
+                    format
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_non_utf8_keys() {
+        let histogram: Histogram<u8> = [(vec![0xff, 0xfe, 0x00], 3), (vec![0x80, 0xc0, 0x0a], 1)]
+            .into_iter()
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("h.json");
+        save_histogram(&path, &histogram, Format::Json, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"{"80c00a":1,"fffe00":3}"#
+        );
+        assert_eq!(load_histogram(&path).unwrap(), histogram);
+    }
+
+    #[test]
+    fn round_trips_an_empty_histogram() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("h.bin");
+        save_histogram(&path, &Histogram::new(), Format::Bincode, false).unwrap();
+        assert_eq!(load_histogram(&path).unwrap(), Histogram::new());
+    }
+
+    #[test]
+    fn picks_the_format_by_extension() {
+        assert_eq!(Format::from_path("h.json"), Format::Json);
+        assert_eq!(Format::from_path("h.JSON"), Format::Json);
+        assert_eq!(Format::from_path("h.bin"), Format::Bincode);
+        assert_eq!(Format::from_path("h"), Format::Bincode);
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let histogram = calculate_histogram_from_bytes(&all_bytes(), 2, 1);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("h.bin");
+        save_histogram(&path, &histogram, Format::Bincode, false).unwrap();
+        let bincode = fs::read(&path).unwrap();
+        let malformed: [&[u8]; 10] = [
+            b"",
+            b"not json",
+            br#"{"0g": 1}"#,
+            br#"{"abc": 1}"#,
+            br#"{"00": 1, "0000": 2}"#,
+            br#"{"00": -1}"#,
+            br#"{"00": 1} trailing"#,
+            BINCODE_MAGIC,
+            &bincode[..bincode.len() / 2],
+            &[BINCODE_MAGIC.as_slice(), &[0xff; 16]].concat(),
+        ];
+        for contents in malformed {
+            assert!(
+                matches!(
+                    decode_histogram(Path::new("h"), contents),
+                    Err(BinvizError::Serialization { .. })
+                ),
+                "{:?}",
+                contents.escape_ascii().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            load_histogram(dir.path().join("missing.bin")),
+            Err(BinvizError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let bytes = all_bytes();
+        assert_eq!(from_hex(&to_hex(&bytes)), Some(bytes));
+        assert_eq!(from_hex("0A"), Some(vec![0x0a]));
+        assert_eq!(from_hex("a"), None);
+        assert_eq!(from_hex("+1"), None);
+    }
+}
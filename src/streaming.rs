@@ -0,0 +1,152 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+use image::{ImageBuffer, Luma};
+
+use crate::{
+    to_channel, validate_input_size, BinvizError, Normalization, ScaleMode, Visualization,
+    CHUNK_SIZE,
+};
+
+/// Count every pair of consecutive bytes of `file` into a flat table indexed by `first * 256 + second`,
+/// reading it in chunks of [`CHUNK_SIZE`] bytes, so memory use doesn't grow with the size of the file.
+fn count_pairs(file: &Path) -> Result<Vec<u64>, BinvizError> {
+    let mut handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    let mut counts = vec![0u64; 256 * 256];
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut previous: Option<u8> = None;
+    loop {
+        let read = handle
+            .read(&mut buffer)
+            .map_err(|err| BinvizError::io(file, err))?;
+        if read == 0 {
+            break;
+        }
+        let mut bytes = buffer[..read].iter().copied();
+        // The pair spanning the previous chunk and this one.
+        let mut last = match previous {
+            Some(last) => last,
+            None => bytes.next().unwrap_or_default(),
+        };
+        for byte in bytes {
+            counts[(last as usize) << 8 | byte as usize] += 1;
+            last = byte;
+        }
+        previous = Some(last);
+    }
+    Ok(counts)
+}
+
+/// Generate the digraph image of `file` directly, like [`crate::generate_image`] of its histogram of byte pairs
+/// with a stride of 1, but counting the pairs into a flat table instead of a histogram,
+/// which is faster and keeps memory use flat for multi-gigabyte files such as disk images.
+///
+/// Gives the image, the amount of byte pairs and the count at full brightness.
+pub fn generate_image_streaming<P>(
+    file: P,
+    scale: ScaleMode,
+    normalization: Normalization,
+) -> Result<Visualization<Luma<u16>>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let size = fs::metadata(file)
+        .map_err(|err| BinvizError::io(file, err))?
+        .len();
+    validate_input_size(size, 2)?;
+    let counts = count_pairs(file)?;
+    let total = counts.iter().sum::<u64>() as usize;
+    let full_count = normalization.full_count_of(
+        counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| count as usize),
+    );
+    let mut image = ImageBuffer::new(256, 256);
+    for (pair, &count) in counts.iter().enumerate() {
+        if count > 0 {
+            let brightness = scale.brightness(count as usize, full_count);
+            image.put_pixel(
+                (pair >> 8) as u32,
+                (pair & 0xff) as u32,
+                Luma([to_channel(brightness)]),
+            );
+        }
+    }
+    Ok((image, total, full_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_histogram, generate_image,
+        test_support::{file_with, noise},
+    };
+
+    /// Bytes longer than a chunk with a few very frequent pairs, noise with every seventh byte zero.
+    fn fixture() -> Vec<u8> {
+        let mut buf = noise(CHUNK_SIZE + 4321);
+        for byte in buf.iter_mut().step_by(7) {
+            *byte = 0;
+        }
+        buf
+    }
+
+    /// Assert that the streaming image of `buf` equals the image of its histogram.
+    fn assert_same_image(buf: &[u8], scale: ScaleMode, normalization: Normalization) {
+        let file = file_with(buf);
+        let histogram = calculate_histogram(file.path(), 2, 1).unwrap();
+        let expected = generate_image(&histogram, scale, normalization).unwrap();
+        let streamed = generate_image_streaming(file.path(), scale, normalization).unwrap();
+        assert_eq!(streamed.1, expected.1);
+        assert_eq!(streamed.2, expected.2);
+        assert!(streamed.0 == expected.0, "{} {}", scale, normalization);
+    }
+
+    #[test]
+    fn streaming_image_matches_histogram_image() {
+        let buf = fixture();
+        for scale in [ScaleMode::Linear, ScaleMode::Log, ScaleMode::Sqrt] {
+            for normalization in [
+                Normalization::Average,
+                Normalization::Maximum,
+                Normalization::Percentile(90.0),
+            ] {
+                assert_same_image(&buf, scale, normalization);
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_image_of_small_inputs_matches_histogram_image() {
+        assert_same_image(b"ab", ScaleMode::Linear, Normalization::Average);
+        assert_same_image(
+            b"The quick brown fox jumps over the lazy dog.",
+            ScaleMode::Log,
+            Normalization::Maximum,
+        );
+        assert_same_image(&[0; 1000], ScaleMode::Linear, Normalization::Average);
+    }
+
+    #[test]
+    fn streaming_image_of_too_small_inputs_is_an_error() {
+        let file = file_with(b"a");
+        assert!(matches!(
+            generate_image_streaming(file.path(), ScaleMode::Linear, Normalization::Average),
+            Err(BinvizError::FileTooSmallForDimension {
+                size: 1,
+                dimension: 2
+            })
+        ));
+        let file = file_with(b"");
+        assert!(matches!(
+            generate_image_streaming(file.path(), ScaleMode::Linear, Normalization::Average),
+            Err(BinvizError::EmptyInput)
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+};
+
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{read_chunks, BinvizError};
+
+/// The amount of characters a string is capped at by [`extract_strings`].
+pub const MAX_STRING_LENGTH: usize = 4096;
+
+/// The encodings of text [`extract_strings`] looks for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// Runs of printable ASCII bytes, including tabs.
+    #[default]
+    Ascii,
+    /// Runs of printable ASCII characters encoded as UTF-16 in little endian,
+    /// every character followed by a zero byte.
+    Utf16Le,
+    /// Both of the above.
+    Both,
+}
+
+impl FromStr for Charset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ascii" => Ok(Charset::Ascii),
+            "utf16le" | "utf-16le" => Ok(Charset::Utf16Le),
+            "both" => Ok(Charset::Both),
+            _ => Err(format!(
+                "`{}` isn't a charset, expected `ascii`, `utf16le` or `both`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Charset::Ascii => f.write_str("ascii"),
+            Charset::Utf16Le => f.write_str("utf16le"),
+            Charset::Both => f.write_str("both"),
+        }
+    }
+}
+
+/// Whether the byte is part of a string, printable ASCII or a tab.
+fn is_string_byte(byte: u8) -> bool {
+    byte == b'\t' || (0x20..=0x7e).contains(&byte)
+}
+
+/// Collects a run of string characters, which is kept once it ends if it's long enough.
+#[derive(Debug, Clone)]
+struct Run {
+    start: u64,
+    text: String,
+    /// The amount of characters in the run, which keeps counting after the text is capped.
+    len: usize,
+}
+
+impl Run {
+    fn new() -> Self {
+        Run {
+            start: 0,
+            text: String::new(),
+            len: 0,
+        }
+    }
+
+    /// Extend the run with the character at `offset`, or end it when there is no character.
+    fn push(
+        &mut self,
+        offset: u64,
+        character: Option<u8>,
+        (min_len, max_len): (usize, usize),
+        strings: &mut Vec<(u64, String)>,
+    ) {
+        match character {
+            Some(character) => {
+                if self.len == 0 {
+                    self.start = offset;
+                }
+                if self.len < max_len {
+                    self.text.push(character as char);
+                }
+                self.len += 1;
+            }
+            None => self.finish(min_len, strings),
+        }
+    }
+
+    /// End the run, keeping it if it has at least `min_len` characters.
+    fn finish(&mut self, min_len: usize, strings: &mut Vec<(u64, String)>) {
+        if self.len >= min_len.max(1) {
+            strings.push((self.start, std::mem::take(&mut self.text)));
+        }
+        self.text.clear();
+        self.len = 0;
+    }
+}
+
+/// Extract the strings of at least `min_len` characters from a given file, with their offsets,
+/// see [`extract_strings_from_reader`]. Strings are capped at [`MAX_STRING_LENGTH`] characters.
+pub fn extract_strings<P>(
+    file: P,
+    min_len: usize,
+    charset: Charset,
+) -> Result<Vec<(u64, String)>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let file = file.as_ref();
+    let handle = File::open(file).map_err(|err| BinvizError::io(file, err))?;
+    extract_strings_from_reader(handle, min_len, MAX_STRING_LENGTH, charset)
+        .map_err(|err| BinvizError::io(file, err))
+}
+
+/// Extract the strings of at least `min_len` characters from everything `reader` produces, with their offsets,
+/// in increasing order of offset, see [`StringExtractor`].
+///
+/// The reader is consumed in chunks, strings spanning two chunks are found whole.
+pub fn extract_strings_from_reader<R>(
+    reader: R,
+    min_len: usize,
+    max_len: usize,
+    charset: Charset,
+) -> io::Result<Vec<(u64, String)>>
+where
+    R: Read,
+{
+    let mut extractor = StringExtractor::new(min_len, max_len, charset);
+    read_chunks(reader, |chunk| extractor.update(chunk))?;
+    Ok(extractor.finish())
+}
+
+/// Incrementally extracts the strings of at least `min_len` characters of an input in `charset`.
+///
+/// Only the first `max_len` characters of longer strings are kept, the rest of such a string is skipped.
+/// UTF-16 strings may start at even and odd offsets.
+#[derive(Debug, Clone)]
+pub struct StringExtractor {
+    lengths: (usize, usize),
+    ascii: bool,
+    utf16: bool,
+    strings: Vec<(u64, String)>,
+    ascii_run: Run,
+    /// The UTF-16 runs of characters starting at even and odd offsets,
+    /// with the first byte of the character currently being read.
+    utf16_runs: [(Run, Option<u8>); 2],
+    offset: u64,
+}
+
+impl StringExtractor {
+    /// Create an extraction without any strings yet.
+    pub fn new(min_len: usize, max_len: usize, charset: Charset) -> Self {
+        StringExtractor {
+            lengths: (min_len, max_len),
+            ascii: charset != Charset::Utf16Le,
+            utf16: charset != Charset::Ascii,
+            strings: Vec::new(),
+            ascii_run: Run::new(),
+            utf16_runs: [(Run::new(), None), (Run::new(), None)],
+            offset: 0,
+        }
+    }
+
+    /// Add the next bytes of the input, a string may continue from the previous bytes.
+    pub fn update(&mut self, buf: &[u8]) {
+        for &byte in buf {
+            let offset = self.offset;
+            if self.ascii {
+                let character = Some(byte).filter(|&byte| is_string_byte(byte));
+                self.ascii_run
+                    .push(offset, character, self.lengths, &mut self.strings);
+            }
+            if self.utf16 {
+                self.utf16_runs[(offset % 2) as usize].1 = Some(byte);
+                let (run, low) = &mut self.utf16_runs[(1 - offset % 2) as usize];
+                if let Some(low) = low.take() {
+                    let character = Some(low).filter(|&low| byte == 0 && is_string_byte(low));
+                    run.push(offset - 1, character, self.lengths, &mut self.strings);
+                }
+            }
+            self.offset += 1;
+        }
+    }
+
+    /// End the last strings and return all strings found, in increasing order of offset.
+    pub fn finish(mut self) -> Vec<(u64, String)> {
+        let min_len = self.lengths.0;
+        self.ascii_run.finish(min_len, &mut self.strings);
+        for (run, _) in &mut self.utf16_runs {
+            run.finish(min_len, &mut self.strings);
+        }
+        self.strings.sort_by_key(|(offset, _)| *offset);
+        self.strings
+    }
+}
+
+/// Display the strings with their offsets and lengths, as a table in the given `format`.
+#[cfg(feature = "tables")]
+pub fn display_strings(strings: &[(u64, String)], format: OutputFormat) -> String {
+    let rows = strings.iter().map(|(offset, string)| {
+        vec![
+            format!("{:#x}", offset),
+            format!("{}", string.len()),
+            string.clone(),
+        ]
+    });
+    format.render(["Offset", "Length", "String"], rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ShortReads;
+
+    /// Extract the strings of `buf` read `max` bytes at a time.
+    fn strings_of(
+        buf: &[u8],
+        max: usize,
+        min_len: usize,
+        max_len: usize,
+        charset: Charset,
+    ) -> Vec<(u64, String)> {
+        let reader = ShortReads { inner: buf, max };
+        extract_strings_from_reader(reader, min_len, max_len, charset).unwrap()
+    }
+
+    /// The `(offset, string)` pairs as owned strings.
+    fn owned(strings: &[(u64, &str)]) -> Vec<(u64, String)> {
+        strings
+            .iter()
+            .map(|&(offset, string)| (offset, string.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn strings_spanning_reads_are_found_whole() {
+        let buf = b"\x00hello\tworld\x00\x01xy\x00abcd";
+        let expected = owned(&[(1, "hello\tworld"), (17, "abcd")]);
+        for max in [1, 2, 3, 7, buf.len()] {
+            assert_eq!(strings_of(buf, max, 4, 100, Charset::Ascii), expected);
+        }
+    }
+
+    #[test]
+    fn utf16_strings_start_at_even_and_odd_offsets() {
+        let buf = b"\x01a\0b\0c\0d\0\x01e\0f\0g\0h\0";
+        let expected = owned(&[(1, "abcd"), (10, "efgh")]);
+        for max in [1, 2, 3, buf.len()] {
+            assert_eq!(strings_of(buf, max, 4, 100, Charset::Utf16Le), expected);
+        }
+    }
+
+    #[test]
+    fn long_strings_are_capped_at_max_len() {
+        let buf = b"aaaaaaaaaa\0bbbbb";
+        let expected = owned(&[(0, "aaaa"), (11, "bbbb")]);
+        for max in [1, 3, buf.len()] {
+            assert_eq!(strings_of(buf, max, 2, 4, Charset::Ascii), expected);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+#[cfg(feature = "tables")]
+use std::cmp::Ordering;
+use std::{path::PathBuf, time::Duration};
+
+use serde::Serialize;
+
+use crate::{calculate_entropy_histogram, BinvizError, EntropyUnit, Histogram};
+#[cfg(feature = "tables")]
+use crate::{OutputFormat, ScanSort};
+
+/// The statistics of a file in a [`FileSummary`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FileStats {
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The entropy of the bytes, in bits per byte.
+    pub entropy: f64,
+    /// The entropy relative to the maximum of 8 bits per byte, between 0 and 1.
+    pub relative_entropy: f64,
+    /// The amount of distinct byte values.
+    pub distinct: usize,
+    /// The most frequent byte value, the lowest of equally frequent values, `None` for an empty file.
+    pub most_frequent: Option<u8>,
+}
+
+impl FileStats {
+    /// The statistics of a file of which `histogram` is the 1-dimensional histogram.
+    pub fn from_histogram(histogram: &Histogram<u8>) -> Result<Self, BinvizError> {
+        let entropy = calculate_entropy_histogram(histogram, EntropyUnit::Bits)?;
+        // `max_by_key` picks the last of equal maxima, so go from high to low to pick the lowest byte.
+        let most_frequent = histogram
+            .iter()
+            .rev()
+            .max_by_key(|(_, &count)| count)
+            .map(|(key, _)| key[0]);
+        Ok(FileStats {
+            size: histogram.values().sum::<usize>() as u64,
+            entropy,
+            relative_entropy: entropy / 8.0,
+            distinct: histogram.len(),
+            most_frequent,
+        })
+    }
+}
+
+/// The outcome of analyzing a file in a full analysis.
+#[derive(Debug)]
+pub struct FileSummary {
+    /// The path the file was read from.
+    pub path: PathBuf,
+    /// How long analyzing the file took, also when it failed.
+    pub duration: Duration,
+    /// The statistics of the file, or why analyzing it failed.
+    pub outcome: Result<FileStats, BinvizError>,
+}
+
+/// Compare two summaries in the order `sort`, failed files after the others and by path.
+#[cfg(feature = "tables")]
+fn compare_summaries(sort: ScanSort, a: &FileSummary, b: &FileSummary) -> Ordering {
+    let by_path = || a.path.cmp(&b.path);
+    match (sort, &a.outcome, &b.outcome) {
+        (ScanSort::Name, _, _) => by_path(),
+        (ScanSort::Entropy, Ok(a), Ok(b)) => b.entropy.total_cmp(&a.entropy).then_with(by_path),
+        (ScanSort::Size, Ok(a), Ok(b)) => b.size.cmp(&a.size).then_with(by_path),
+        (_, Ok(_), Err(_)) => Ordering::Less,
+        (_, Err(_), Ok(_)) => Ordering::Greater,
+        (_, Err(_), Err(_)) => by_path(),
+    }
+}
+
+/// Display the summaries as a table ranked by `sort`, with the error of every failed file in the last column.
+#[cfg(feature = "tables")]
+pub fn display_file_summaries(
+    summaries: &[FileSummary],
+    sort: ScanSort,
+    format: OutputFormat,
+) -> String {
+    let mut summaries: Vec<&FileSummary> = summaries.iter().collect();
+    summaries.sort_by(|a, b| compare_summaries(sort, a, b));
+    let rows = summaries.iter().map(|summary| {
+        let duration = format.value(summary.duration.as_secs_f64(), Some("seconds"));
+        match &summary.outcome {
+            Ok(stats) => vec![
+                summary.path.display().to_string(),
+                stats.size.to_string(),
+                format.value(stats.entropy, Some("bits per byte")),
+                format.value(stats.relative_entropy, None),
+                stats.distinct.to_string(),
+                stats
+                    .most_frequent
+                    .map_or_else(String::new, |byte| format!("{:#04x}", byte)),
+                duration,
+                String::new(),
+            ],
+            Err(err) => {
+                let mut row = vec![summary.path.display().to_string()];
+                row.extend(std::iter::repeat_n(String::new(), 5));
+                row.extend([duration, err.to_string()]);
+                row
+            }
+        }
+    });
+    format.render(
+        [
+            "File",
+            "Size",
+            "Entropy",
+            "Relative Entropy",
+            "Distinct",
+            "Most Frequent",
+            "Duration",
+            "Error",
+        ],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_are_those_of_the_bytes() {
+        let histogram: Histogram<u8> = [(vec![b'a'], 2), (vec![b'b'], 2), (vec![0], 4)]
+            .into_iter()
+            .collect();
+        let stats = FileStats::from_histogram(&histogram).unwrap();
+        assert_eq!(stats.size, 8);
+        assert_eq!(stats.entropy, 1.5);
+        assert_eq!(stats.relative_entropy, 1.5 / 8.0);
+        assert_eq!(stats.distinct, 3);
+        assert_eq!(stats.most_frequent, Some(0));
+        // Of equally frequent bytes the lowest is the most frequent.
+        let tie: Histogram<u8> = [(vec![9], 1), (vec![3], 1)].into_iter().collect();
+        assert_eq!(
+            FileStats::from_histogram(&tie).unwrap().most_frequent,
+            Some(3)
+        );
+    }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn failed_files_come_last_in_every_order() {
+        let summary = |path: &str, size: u64, entropy: f64| FileSummary {
+            path: PathBuf::from(path),
+            duration: Duration::ZERO,
+            outcome: Ok(FileStats {
+                size,
+                entropy,
+                relative_entropy: entropy / 8.0,
+                distinct: 1,
+                most_frequent: Some(0),
+            }),
+        };
+        let summaries = [
+            FileSummary {
+                path: PathBuf::from("a.bin"),
+                duration: Duration::ZERO,
+                outcome: Err(BinvizError::EmptyInput),
+            },
+            summary("b.bin", 10, 7.0),
+            summary("c.bin", 30, 2.0),
+            summary("d.bin", 20, 5.0),
+        ];
+        let files = |sort| -> Vec<String> {
+            display_file_summaries(&summaries, sort, OutputFormat::Csv)
+                .lines()
+                .skip(1)
+                .map(|line| line.split(',').next().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            files(ScanSort::Entropy),
+            ["b.bin", "d.bin", "c.bin", "a.bin"]
+        );
+        assert_eq!(files(ScanSort::Size), ["c.bin", "d.bin", "b.bin", "a.bin"]);
+        assert_eq!(files(ScanSort::Name), ["a.bin", "b.bin", "c.bin", "d.bin"]);
+        // The failed file has no statistics, but the reason it failed, quoted as it contains a comma.
+        let csv = display_file_summaries(&summaries, ScanSort::Entropy, OutputFormat::Csv);
+        assert_eq!(
+            csv.lines().last().unwrap(),
+            format!("a.bin,,,,,,0,\"{}\"", BinvizError::EmptyInput)
+        );
+    }
+}
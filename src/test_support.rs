@@ -0,0 +1,38 @@
+//! Fixtures shared by the unit tests, and through `#[path]` by the integration tests and the benchmarks.
+
+use std::io::{self, Read, Write};
+
+use tempfile::NamedTempFile;
+
+/// `len` bytes of deterministic noise, the high bytes of a xorshift generator.
+pub(crate) fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+/// A temporary file containing `buf`.
+pub(crate) fn file_with(buf: &[u8]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(buf).unwrap();
+    file
+}
+
+/// A reader that returns at most `max` bytes of `inner` per read, to test reading across short reads.
+pub(crate) struct ShortReads<R> {
+    pub(crate) inner: R,
+    pub(crate) max: usize,
+}
+
+impl<R: Read> Read for ShortReads<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.max);
+        self.inner.read(&mut buf[..len])
+    }
+}
@@ -0,0 +1,514 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Sparkline, SparklineBar},
+    DefaultTerminal, Frame,
+};
+
+use crate::{
+    calculate_histogram, calculate_histogram_range, entropy_profile, validate_input_size,
+    BinvizError, Histogram, ScaleMode,
+};
+
+/// The smallest block size of the entropy pane of [`run_tui`].
+pub const MIN_TUI_BLOCK_SIZE: usize = 64;
+
+/// The largest block size of the entropy pane of [`run_tui`].
+pub const MAX_TUI_BLOCK_SIZE: usize = 1 << 24;
+
+/// The height of the bars of [`frequency_bars`] at the highest count.
+pub const BAR_RESOLUTION: u64 = 1000;
+
+/// The first braille character, without dots, the dots are added to it as the bits in [`BRAILLE_DOTS`].
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// The bits of the dots of a braille character, by row and column of its 4 by 2 dots.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The height of the bars of the entropy pane at 8 bits per byte, the entropies are in thousandths of a bit.
+const ENTROPY_RESOLUTION: f64 = 1000.0;
+
+/// Sum the counts of the 256 byte values of the 1-dimensional `histogram` into `buckets` bars of consecutive
+/// byte values, at most 256, each as high as its brightness in `scale` relative to the highest bar, up to
+/// [`BAR_RESOLUTION`].
+pub fn frequency_bars(histogram: &Histogram<u8>, buckets: usize, scale: ScaleMode) -> Vec<u64> {
+    let buckets = buckets.clamp(1, 256);
+    let mut counts = vec![0; buckets];
+    for (key, &count) in histogram {
+        counts[key[0] as usize * buckets / 256] += count;
+    }
+    let highest = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    counts
+        .into_iter()
+        .map(|count| (scale.brightness(count, highest) * BAR_RESOLUTION as f64).round() as u64)
+        .collect()
+}
+
+/// Draw the 2-dimensional `dihistogram` as `height` lines of `width` braille characters, the first byte
+/// of the pairs from left to right and the second from top to bottom like the digraph images.
+///
+/// Every character has 4 rows of 2 dots, so the 256 by 256 pairs are downsampled to `2 * width` by
+/// `4 * height` dots, at most one dot per pair. A dot is set if the pairs it covers have a brightness in `scale`
+/// of at least a half, relative to the average count of the dots covering any pair for a linear scale,
+/// and to the highest count otherwise.
+pub fn braille_digraph(
+    dihistogram: &Histogram<u8>,
+    width: usize,
+    height: usize,
+    scale: ScaleMode,
+) -> Vec<String> {
+    let (columns, rows) = ((2 * width).clamp(1, 256), (4 * height).clamp(1, 256));
+    let mut dots = vec![0; columns * rows];
+    for (key, &count) in dihistogram {
+        let column = key[0] as usize * columns / 256;
+        let row = key[1] as usize * rows / 256;
+        dots[row * columns + column] += count;
+    }
+    let covered: Vec<usize> = dots.iter().copied().filter(|&count| count > 0).collect();
+    let full_count = match scale {
+        ScaleMode::Linear => covered.iter().sum::<usize>() as f64 / covered.len().max(1) as f64,
+        ScaleMode::Log | ScaleMode::Sqrt => covered.iter().copied().max().unwrap_or(0) as f64,
+    };
+    (0..height)
+        .map(|line| {
+            (0..width)
+                .map(|character| {
+                    let mut bits = 0;
+                    for (dot_row, row_bits) in BRAILLE_DOTS.iter().enumerate() {
+                        for (dot_column, bit) in row_bits.iter().enumerate() {
+                            let (row, column) = (4 * line + dot_row, 2 * character + dot_column);
+                            if row >= rows || column >= columns {
+                                continue;
+                            }
+                            let count = dots[row * columns + column];
+                            if count > 0 && scale.brightness(count, full_count) >= 0.5 {
+                                bits |= bit;
+                            }
+                        }
+                    }
+                    char::from_u32(BRAILLE_BLANK + bits).expect("braille characters are valid")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The first of the blocks of the entropy pane that fit in `width` columns, keeping the `selected` block
+/// in view, centered where possible.
+pub fn visible_blocks(blocks: usize, selected: usize, width: usize) -> usize {
+    selected
+        .saturating_sub(width / 2)
+        .min(blocks.saturating_sub(width))
+}
+
+/// Parse an offset in decimal, or in hexadecimal when prefixed by `0x`.
+fn parse_offset(value: &str) -> Option<u64> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// The state of the viewer of [`run_tui`].
+struct Viewer {
+    file: PathBuf,
+    size: u64,
+    block_size: usize,
+    /// The offsets and entropies in bits per byte of the blocks.
+    profile: Vec<(u64, f64)>,
+    /// The block highlighted in the entropy pane.
+    selected: usize,
+    /// Whether the frequency and digraph panes show the selected block rather than the whole file.
+    show_block: bool,
+    scale: ScaleMode,
+    /// The histograms of the whole file or the selected block, as the panes show them.
+    histogram: Histogram<u8>,
+    dihistogram: Histogram<u8>,
+    /// The offset being typed after `g`, if any.
+    offset_input: Option<String>,
+    /// A note shown in the help pane, such as why an offset couldn't be jumped to.
+    message: Option<String>,
+}
+
+impl Viewer {
+    fn open(file: &Path, block_size: usize) -> Result<Self, BinvizError> {
+        let size = fs::metadata(file)
+            .map_err(|err| BinvizError::io(file, err))?
+            .len();
+        validate_input_size(size, 2)?;
+        let mut viewer = Viewer {
+            file: file.to_path_buf(),
+            size,
+            block_size: block_size.clamp(MIN_TUI_BLOCK_SIZE, MAX_TUI_BLOCK_SIZE),
+            profile: Vec::new(),
+            selected: 0,
+            show_block: false,
+            scale: ScaleMode::Linear,
+            histogram: Histogram::new(),
+            dihistogram: Histogram::new(),
+            offset_input: None,
+            message: None,
+        };
+        viewer.load_profile()?;
+        viewer.load_histograms()?;
+        Ok(viewer)
+    }
+
+    /// The offset of the first byte of the selected block.
+    fn selected_offset(&self) -> u64 {
+        self.profile
+            .get(self.selected)
+            .map_or(0, |&(offset, _)| offset)
+    }
+
+    /// Calculate the entropy of the blocks, keeping the selected offset in the selected block.
+    fn load_profile(&mut self) -> Result<(), BinvizError> {
+        let offset = self.selected_offset();
+        self.profile = entropy_profile(&self.file, self.block_size, self.block_size)?;
+        self.select_offset(offset);
+        Ok(())
+    }
+
+    /// Calculate the histograms the frequency and digraph panes show.
+    fn load_histograms(&mut self) -> Result<(), BinvizError> {
+        if self.show_block {
+            let offset = self.selected_offset();
+            // The last block can be shorter, down to a single byte without pairs.
+            let length = (self.block_size as u64).min(self.size - offset);
+            self.histogram = calculate_histogram_range(&self.file, 1, 1, offset, Some(length))?;
+            self.dihistogram = if length >= 2 {
+                calculate_histogram_range(&self.file, 2, 1, offset, Some(length))?
+            } else {
+                Histogram::new()
+            };
+        } else {
+            self.histogram = calculate_histogram(&self.file, 1, 1)?;
+            self.dihistogram = calculate_histogram(&self.file, 2, 1)?;
+        }
+        Ok(())
+    }
+
+    /// Select the block holding `offset`, or the last block if it lies past the end.
+    fn select_offset(&mut self, offset: u64) {
+        self.selected =
+            ((offset / self.block_size as u64) as usize).min(self.profile.len().saturating_sub(1));
+    }
+
+    /// Select the block `selected`, reloading the histograms if they show the selected block.
+    fn select(&mut self, selected: usize) -> Result<(), BinvizError> {
+        let selected = selected.min(self.profile.len().saturating_sub(1));
+        if selected != self.selected {
+            self.selected = selected;
+            if self.show_block {
+                self.load_histograms()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle the key `code`, returning whether to quit.
+    fn handle_key(&mut self, code: KeyCode) -> Result<bool, BinvizError> {
+        if let Some(input) = &mut self.offset_input {
+            match code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let input = self.offset_input.take().unwrap_or_default();
+                    match parse_offset(input.trim()) {
+                        Some(offset) if offset < self.size => {
+                            self.show_block = true;
+                            self.select_offset(offset);
+                            self.load_histograms()?;
+                        }
+                        Some(offset) => {
+                            self.message = Some(format!(
+                                "offset {:#x} lies past the end of the file of {} bytes",
+                                offset, self.size
+                            ))
+                        }
+                        None => self.message = Some(format!("`{}` isn't an offset", input)),
+                    }
+                }
+                KeyCode::Esc => self.offset_input = None,
+                _ => {}
+            }
+            return Ok(false);
+        }
+        self.message = None;
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Left => self.select(self.selected.saturating_sub(1))?,
+            KeyCode::Right => self.select(self.selected + 1)?,
+            KeyCode::Home => self.select(0)?,
+            KeyCode::End => self.select(self.profile.len())?,
+            KeyCode::Enter => {
+                self.show_block = !self.show_block;
+                self.load_histograms()?;
+            }
+            KeyCode::Char('+') | KeyCode::Char('-') => {
+                let block_size = if code == KeyCode::Char('+') {
+                    self.block_size * 2
+                } else {
+                    self.block_size / 2
+                };
+                self.block_size = block_size.clamp(MIN_TUI_BLOCK_SIZE, MAX_TUI_BLOCK_SIZE);
+                self.load_profile()?;
+                if self.show_block {
+                    self.load_histograms()?;
+                }
+            }
+            KeyCode::Char('l') => {
+                self.scale = match self.scale {
+                    ScaleMode::Linear => ScaleMode::Log,
+                    _ => ScaleMode::Linear,
+                }
+            }
+            KeyCode::Char('g') => self.offset_input = Some(String::new()),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// What the frequency and digraph panes show.
+    fn shown(&self) -> String {
+        if self.show_block {
+            let offset = self.selected_offset();
+            let end = (offset + self.block_size as u64).min(self.size);
+            format!("block {} at {:#x}..{:#x}", self.selected, offset, end)
+        } else {
+            "whole file".to_string()
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [frequency_area, entropy_area, bottom_area] = Layout::vertical([
+            Constraint::Percentage(35),
+            Constraint::Length(8),
+            Constraint::Min(6),
+        ])
+        .areas(frame.area());
+        let [digraph_area, help_area] =
+            Layout::horizontal([Constraint::Min(20), Constraint::Length(40)]).areas(bottom_area);
+        self.draw_frequencies(frame, frequency_area);
+        self.draw_entropy(frame, entropy_area);
+        self.draw_digraph(frame, digraph_area);
+        self.draw_help(frame, help_area);
+    }
+
+    fn draw_frequencies(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(format!(
+            " Byte frequencies, {} ({}) ",
+            self.shown(),
+            self.scale
+        ));
+        let bars: Vec<Bar> = frequency_bars(
+            &self.histogram,
+            area.width.saturating_sub(2) as usize,
+            self.scale,
+        )
+        .into_iter()
+        .map(|height| Bar::new(height).text_value(""))
+        .collect();
+        let chart = BarChart::default()
+            .block(block)
+            .data(BarGroup::new(bars))
+            .bar_width(1)
+            .bar_gap(0)
+            .max(BAR_RESOLUTION);
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_entropy(&self, frame: &mut Frame, area: Rect) {
+        let entropy = self.profile.get(self.selected).map_or(0.0, |&(_, e)| e);
+        let block = Block::bordered().title(format!(
+            " Entropy of {}-byte blocks, block {} of {} at {:#x}: {:.3} bits per byte ",
+            self.block_size,
+            self.selected,
+            self.profile.len(),
+            self.selected_offset(),
+            entropy
+        ));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let width = inner.width as usize;
+        let first = visible_blocks(self.profile.len(), self.selected, width);
+        let bars = self.profile.iter().enumerate().skip(first).take(width).map(
+            |(index, &(_, entropy))| {
+                let bar = SparklineBar::from((entropy * ENTROPY_RESOLUTION) as u64);
+                if index == self.selected {
+                    bar.style(Some(Style::default().fg(Color::Yellow)))
+                } else {
+                    bar
+                }
+            },
+        );
+        let sparkline = Sparkline::default()
+            .data(bars)
+            .max((8.0 * ENTROPY_RESOLUTION) as u64);
+        frame.render_widget(sparkline, inner);
+    }
+
+    fn draw_digraph(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(format!(" Byte pairs, {} ", self.shown()));
+        let inner = block.inner(area);
+        let lines = braille_digraph(
+            &self.dihistogram,
+            (inner.width as usize).min(128),
+            (inner.height as usize).min(64),
+            self.scale,
+        );
+        let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+        frame.render_widget(Paragraph::new(text).block(block), area);
+    }
+
+    fn draw_help(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![
+            Line::from(format!("{}", self.file.display())),
+            Line::from(format!("{} bytes", self.size)),
+            Line::from(""),
+            Line::from("←/→, Home/End  select a block"),
+            Line::from("Enter          show the block/file"),
+            Line::from("g              go to an offset"),
+            Line::from("+/-            block size"),
+            Line::from("l              log scale"),
+            Line::from("q              quit"),
+        ];
+        if let Some(input) = &self.offset_input {
+            lines.push(Line::from(format!("offset: {}▏", input)));
+        } else if let Some(message) = &self.message {
+            lines.push(Line::from(message.as_str()));
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" binviz ")),
+            area,
+        );
+    }
+}
+
+/// Explore `file` in the terminal until `q` is pressed: the frequencies of its bytes as a bar chart,
+/// the entropy of its blocks of `block_size` bytes and its byte pairs drawn in braille.
+/// Selecting a block of the entropy pane shows the frequencies and pairs of that block instead.
+pub fn run_tui<P>(file: P, block_size: usize) -> Result<(), BinvizError>
+where
+    P: AsRef<Path>,
+{
+    let mut viewer = Viewer::open(file.as_ref(), block_size)?;
+    let mut terminal = ratatui::try_init().map_err(BinvizError::Terminal)?;
+    let result = event_loop(&mut terminal, &mut viewer);
+    ratatui::try_restore().map_err(BinvizError::Terminal)?;
+    result
+}
+
+/// Draw the viewer and handle keys until it quits.
+fn event_loop(terminal: &mut DefaultTerminal, viewer: &mut Viewer) -> Result<(), BinvizError> {
+    loop {
+        terminal
+            .draw(|frame| viewer.draw(frame))
+            .map_err(BinvizError::Terminal)?;
+        if let Event::Key(key) = event::read().map_err(BinvizError::Terminal)? {
+            if key.kind == KeyEventKind::Press && viewer.handle_key(key.code)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_histogram_from_bytes;
+
+    /// The 2-dimensional histogram of the byte pairs in `pairs`.
+    fn pairs(pairs: &[[u8; 2]]) -> Histogram<u8> {
+        calculate_histogram_from_bytes(pairs.concat().as_slice(), 2, 2)
+    }
+
+    fn braille(bits: u32) -> char {
+        char::from_u32(BRAILLE_BLANK + bits).unwrap()
+    }
+
+    #[test]
+    fn every_pair_sets_its_own_dot() {
+        // At 128 by 64 characters there is a dot per pair, the first character covers the pairs 0..2 by 0..4.
+        for (first, second, bit) in [
+            (0, 0, 0x01),
+            (0, 1, 0x02),
+            (0, 2, 0x04),
+            (0, 3, 0x40),
+            (1, 0, 0x08),
+            (1, 1, 0x10),
+            (1, 2, 0x20),
+            (1, 3, 0x80),
+        ] {
+            let lines = braille_digraph(&pairs(&[[first, second]]), 128, 64, ScaleMode::Linear);
+            assert_eq!(lines.len(), 64);
+            assert!(lines.iter().all(|line| line.chars().count() == 128));
+            assert_eq!(
+                lines[0].chars().next(),
+                Some(braille(bit)),
+                "{:?}",
+                (first, second)
+            );
+            let set = lines
+                .iter()
+                .flat_map(|line| line.chars())
+                .filter(|&c| c != braille(0));
+            assert_eq!(set.count(), 1);
+        }
+        // The last pair is the bottom right dot of the last character.
+        let lines = braille_digraph(&pairs(&[[255, 255]]), 128, 64, ScaleMode::Linear);
+        assert_eq!(lines[63].chars().last(), Some(braille(0x80)));
+    }
+
+    #[test]
+    fn pairs_are_downsampled_into_the_dots() {
+        // A single character covers all pairs, its dots are 128 first bytes wide and 64 second bytes high.
+        let lines = braille_digraph(
+            &pairs(&[[0, 0], [127, 63], [255, 255]]),
+            1,
+            1,
+            ScaleMode::Linear,
+        );
+        assert_eq!(lines, [braille(0x01 | 0x80).to_string()]);
+        let lines = braille_digraph(&pairs(&[[128, 64]]), 1, 1, ScaleMode::Linear);
+        assert_eq!(lines, [braille(0x10).to_string()]);
+    }
+
+    #[test]
+    fn dim_dots_are_left_out() {
+        // Relative to the highest count, a pair seen once next to one seen 100 times is too dim to show.
+        let histogram = pairs(&[vec![[0, 0]; 100], vec![[255, 0]]].concat());
+        let lines = braille_digraph(&histogram, 1, 1, ScaleMode::Sqrt);
+        assert_eq!(lines, [braille(0x01).to_string()]);
+        assert_eq!(
+            braille_digraph(&Histogram::new(), 2, 1, ScaleMode::Linear),
+            [braille(0).to_string().repeat(2)]
+        );
+    }
+
+    #[test]
+    fn bars_sum_consecutive_byte_values() {
+        let histogram = calculate_histogram_from_bytes(&[0, 0, 1, 1, 200, 255], 1, 1);
+        assert_eq!(
+            frequency_bars(&histogram, 2, ScaleMode::Linear),
+            [BAR_RESOLUTION, BAR_RESOLUTION / 2]
+        );
+        let bars = frequency_bars(&histogram, 256, ScaleMode::Linear);
+        assert_eq!(bars.len(), 256);
+        assert_eq!((bars[0], bars[1], bars[2], bars[200]), (1000, 1000, 0, 500));
+        // More buckets than byte values are clamped.
+        assert_eq!(frequency_bars(&histogram, 1000, ScaleMode::Linear), bars);
+    }
+}
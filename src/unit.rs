@@ -0,0 +1,64 @@
+use std::{
+    f64::consts::{LN_2, LOG10_2},
+    fmt,
+    str::FromStr,
+};
+
+/// The unit entropies are expressed in, set by the base of the logarithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntropyUnit {
+    /// Base 2 logarithms, the maximum entropy of a byte is 8 bits.
+    #[default]
+    Bits,
+    /// Natural logarithms, the maximum entropy of a byte is 8 ln 2 nats.
+    Nats,
+    /// Base 10 logarithms, also called hartleys, the maximum entropy of a byte is 8 log10 2 dits.
+    Dits,
+}
+
+impl EntropyUnit {
+    /// The logarithm of `x` in the base of this unit.
+    #[inline(always)]
+    pub fn log(self, x: f64) -> f64 {
+        match self {
+            EntropyUnit::Bits => x.log2(),
+            EntropyUnit::Nats => x.ln(),
+            EntropyUnit::Dits => x.log10(),
+        }
+    }
+
+    /// Convert an amount of `bits` to this unit.
+    pub fn from_bits(self, bits: f64) -> f64 {
+        match self {
+            EntropyUnit::Bits => bits,
+            EntropyUnit::Nats => bits * LN_2,
+            EntropyUnit::Dits => bits * LOG10_2,
+        }
+    }
+}
+
+impl FromStr for EntropyUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bits" | "bit" => Ok(EntropyUnit::Bits),
+            "nats" | "nat" => Ok(EntropyUnit::Nats),
+            "dits" | "dit" | "hartleys" => Ok(EntropyUnit::Dits),
+            _ => Err(format!(
+                "unknown entropy unit `{}`, expected `bits`, `nats` or `dits`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EntropyUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntropyUnit::Bits => f.write_str("bits"),
+            EntropyUnit::Nats => f.write_str("nats"),
+            EntropyUnit::Dits => f.write_str("dits"),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use image::{ImageBuffer, Pixel};
+
+use crate::BinvizError;
+
+/// The largest factor [`upscale_nearest`] scales images up by, a 256×256 image becomes 16384×16384.
+pub const MAX_SCALE_FACTOR: u32 = 64;
+
+/// Check that images can be scaled up by `factor`, at least 1 and at most [`MAX_SCALE_FACTOR`].
+pub fn validate_scale_factor(factor: u32) -> Result<(), BinvizError> {
+    if factor == 0 || factor > MAX_SCALE_FACTOR {
+        return Err(BinvizError::InvalidScaleFactor { factor });
+    }
+    Ok(())
+}
+
+/// Scale an image up by `factor` using nearest neighbor, so every pixel becomes a `factor`×`factor` block
+/// and image viewers don't blur the cells when zooming. A factor of 1 returns the same image.
+pub fn upscale_nearest<P>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    factor: u32,
+) -> Result<ImageBuffer<P, Vec<P::Subpixel>>, BinvizError>
+where
+    P: Pixel,
+{
+    validate_scale_factor(factor)?;
+    Ok(ImageBuffer::from_fn(
+        image.width() * factor,
+        image.height() * factor,
+        |x, y| *image.get_pixel(x / factor, y / factor),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Luma;
+
+    use super::*;
+
+    fn checkerboard() -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        ImageBuffer::from_raw(2, 2, vec![0, 255, 255, 0]).unwrap()
+    }
+
+    #[test]
+    fn factor_one_is_the_identity() {
+        assert_eq!(upscale_nearest(&checkerboard(), 1).unwrap(), checkerboard());
+    }
+
+    #[test]
+    fn every_pixel_becomes_a_block() {
+        let image = checkerboard();
+        let upscaled = upscale_nearest(&image, 3).unwrap();
+        assert_eq!(upscaled.dimensions(), (6, 6));
+        for (x, y, pixel) in upscaled.enumerate_pixels() {
+            assert_eq!(pixel, image.get_pixel(x / 3, y / 3));
+        }
+    }
+
+    #[test]
+    fn factors_outside_the_range_are_rejected() {
+        for factor in [0, MAX_SCALE_FACTOR + 1] {
+            assert!(matches!(
+                upscale_nearest(&checkerboard(), factor),
+                Err(BinvizError::InvalidScaleFactor { factor: found }) if found == factor
+            ));
+        }
+        assert!(upscale_nearest(&checkerboard(), MAX_SCALE_FACTOR).is_ok());
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{info, warn};
+
+use crate::{BinvizError, Glob};
+
+/// How [`collect_files`] searches the folders it is given.
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Only descend this many levels of subfolders into a folder, 0 only finds the files directly in it.
+    /// Unlimited by default.
+    pub max_depth: Option<usize>,
+    /// Follow symbolic links to files and folders instead of skipping them.
+    pub follow_symlinks: bool,
+    /// Include the files and folders whose names start with a dot instead of skipping them.
+    pub include_hidden: bool,
+    /// Only include the files in the folders whose path relative to the folder, or one of the folders
+    /// leading up to them, matches one of these patterns. Everything is included if there are none.
+    pub include: Vec<Glob>,
+    /// Skip the files and subfolders in the folders whose path relative to the folder matches one of these
+    /// patterns, even if they are included.
+    pub exclude: Vec<Glob>,
+}
+
+/// The files found by [`collect_files`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectedFiles {
+    /// The files to analyze.
+    pub files: Vec<WalkedFile>,
+    /// The amount of files and subfolders skipped by the include and exclude patterns,
+    /// the files in skipped subfolders aren't counted as they aren't searched.
+    pub filtered: usize,
+}
+
+/// A file found by [`collect_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkedFile {
+    /// The path to read the file from.
+    pub path: PathBuf,
+    /// The folder the file was found in, `None` if the file was given itself.
+    pub root: Option<PathBuf>,
+    /// The path of the file relative to its `root`, or its file name if it was given itself.
+    pub relative: PathBuf,
+}
+
+/// Collect the files to analyze from `paths`, the files themselves and the regular files in the folders,
+/// searched recursively as `options` says, in the order of the paths and sorted by name within every folder.
+///
+/// Paths given directly are always included, even if they are hidden, symbolic links or don't match the include
+/// and exclude patterns, which are matched against the paths relative to the folders. Anything that isn't
+/// a regular file or folder, such as a socket, is skipped. Every folder is searched at most once when following
+/// symbolic links, so links pointing back to a folder they are in don't loop forever.
+pub fn collect_files(
+    paths: &[PathBuf],
+    options: &WalkOptions,
+) -> Result<CollectedFiles, BinvizError> {
+    let mut collected = CollectedFiles::default();
+    for path in paths {
+        let metadata = fs::metadata(path).map_err(|err| BinvizError::io(path, err))?;
+        if metadata.is_dir() {
+            let mut visited = HashSet::new();
+            walk(
+                path,
+                path,
+                Path::new(""),
+                0,
+                options,
+                &mut visited,
+                &mut collected,
+            )?;
+        } else {
+            let relative = path.file_name().unwrap_or(path.as_os_str());
+            collected.files.push(WalkedFile {
+                path: path.clone(),
+                root: None,
+                relative: PathBuf::from(relative),
+            });
+        }
+    }
+    Ok(collected)
+}
+
+/// Search the folder `folder`, at `relative` in `root` and `depth` levels below it, see [`collect_files`].
+fn walk(
+    root: &Path,
+    folder: &Path,
+    relative: &Path,
+    depth: usize,
+    options: &WalkOptions,
+    visited: &mut HashSet<PathBuf>,
+    collected: &mut CollectedFiles,
+) -> Result<(), BinvizError> {
+    let canonical = fs::canonicalize(folder).map_err(|err| BinvizError::io(folder, err))?;
+    if !visited.insert(canonical) {
+        warn!(
+            "`{}` was already searched, skipping the symbolic link to it.",
+            folder.display()
+        );
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(folder)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| BinvizError::io(folder, err))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        if !options.include_hidden && name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let file_type = entry
+            .file_type()
+            .map_err(|err| BinvizError::io(&path, err))?;
+        let file_type = if !file_type.is_symlink() {
+            file_type
+        } else if !options.follow_symlinks {
+            info!("skipping the symbolic link `{}`.", path.display());
+            continue;
+        } else {
+            match fs::metadata(&path) {
+                Ok(metadata) => metadata.file_type(),
+                Err(err) => {
+                    warn!(
+                        "skipping the broken symbolic link `{}`: {}",
+                        path.display(),
+                        err
+                    );
+                    continue;
+                }
+            }
+        };
+        let relative = relative.join(&name);
+        if (file_type.is_dir() || file_type.is_file())
+            && options.exclude.iter().any(|glob| glob.matches(&relative))
+        {
+            info!("skipping the excluded `{}`.", path.display());
+            collected.filtered += 1;
+            continue;
+        }
+        if file_type.is_dir() {
+            if options.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                walk(
+                    root,
+                    &path,
+                    &relative,
+                    depth + 1,
+                    options,
+                    visited,
+                    collected,
+                )?;
+            }
+        } else if file_type.is_file() {
+            if !options.include.is_empty()
+                && !options
+                    .include
+                    .iter()
+                    .any(|glob| glob.matches_within(&relative))
+            {
+                info!("skipping `{}`, which isn't included.", path.display());
+                collected.filtered += 1;
+                continue;
+            }
+            collected.files.push(WalkedFile {
+                path,
+                root: Some(root.to_path_buf()),
+                relative,
+            });
+        } else {
+            info!("skipping `{}`, which isn't a regular file.", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A folder with `a.bin`, `b.txt`, `src/c.bin`, `target/x.bin` and `target/deep/y.bin`.
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "a.bin",
+            "b.txt",
+            "src/c.bin",
+            "target/x.bin",
+            "target/deep/y.bin",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        dir
+    }
+
+    /// The relative paths of the files found in `dir` with the `include` and `exclude` patterns,
+    /// and the amount skipped.
+    fn collect(dir: &Path, include: &[&str], exclude: &[&str]) -> (Vec<String>, usize) {
+        let options = WalkOptions {
+            include: include.iter().map(|glob| glob.parse().unwrap()).collect(),
+            exclude: exclude.iter().map(|glob| glob.parse().unwrap()).collect(),
+            ..WalkOptions::default()
+        };
+        let collected = collect_files(&[dir.to_path_buf()], &options).unwrap();
+        (relative_paths(&collected), collected.filtered)
+    }
+
+    /// The paths of the `collected` files relative to their folders.
+    fn relative_paths(collected: &CollectedFiles) -> Vec<String> {
+        collected
+            .files
+            .iter()
+            .map(|file| file.relative.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn without_patterns_every_file_is_found() {
+        let dir = tree();
+        assert_eq!(
+            collect(dir.path(), &[], &[]),
+            (
+                vec![
+                    "a.bin".to_string(),
+                    "b.txt".to_string(),
+                    "src/c.bin".to_string(),
+                    "target/deep/y.bin".to_string(),
+                    "target/x.bin".to_string(),
+                ],
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn only_included_files_are_found() {
+        let dir = tree();
+        let (files, filtered) = collect(dir.path(), &["*.bin"], &[]);
+        assert_eq!(
+            files,
+            ["a.bin", "src/c.bin", "target/deep/y.bin", "target/x.bin"]
+        );
+        assert_eq!(filtered, 1);
+        // A folder pattern includes everything in the folder.
+        let (files, _) = collect(dir.path(), &["target/"], &[]);
+        assert_eq!(files, ["target/deep/y.bin", "target/x.bin"]);
+    }
+
+    #[test]
+    fn excluded_folders_are_skipped_whole() {
+        let dir = tree();
+        for exclude in ["target/**", "target/", "target"] {
+            let (files, filtered) = collect(dir.path(), &[], &[exclude]);
+            assert_eq!(files, ["a.bin", "b.txt", "src/c.bin"], "{}", exclude);
+            // Only the folder itself is counted, its files aren't searched.
+            assert_eq!(filtered, 1, "{}", exclude);
+        }
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let dir = tree();
+        let (files, filtered) = collect(dir.path(), &["*.bin"], &["target/", "src/c.bin"]);
+        assert_eq!(files, ["a.bin"]);
+        assert_eq!(filtered, 3);
+    }
+
+    #[test]
+    fn files_given_directly_ignore_the_patterns() {
+        let dir = tree();
+        let file = dir.path().join("b.txt");
+        let options = WalkOptions {
+            include: vec!["*.bin".parse().unwrap()],
+            ..WalkOptions::default()
+        };
+        let collected = collect_files(std::slice::from_ref(&file), &options).unwrap();
+        assert_eq!(collected.files[0].path, file);
+        assert_eq!(collected.files[0].root, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_regular_files_are_found() {
+        use std::os::unix::{fs::symlink, net::UnixListener};
+
+        let dir = tree();
+        let _socket = UnixListener::bind(dir.path().join("socket")).unwrap();
+        symlink(dir.path().join("a.bin"), dir.path().join("link.bin")).unwrap();
+        // A link back to the folder it is in.
+        symlink(dir.path(), dir.path().join("src").join("loop")).unwrap();
+        let expected = [
+            "a.bin",
+            "b.txt",
+            "src/c.bin",
+            "target/deep/y.bin",
+            "target/x.bin",
+        ];
+        assert_eq!(collect(dir.path(), &[], &[]).0, expected);
+        // Following the links finds the linked file, but doesn't search the folder again.
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..WalkOptions::default()
+        };
+        let collected = collect_files(&[dir.path().to_path_buf()], &options).unwrap();
+        assert_eq!(
+            relative_paths(&collected),
+            [
+                "a.bin",
+                "b.txt",
+                "link.bin",
+                "src/c.bin",
+                "target/deep/y.bin",
+                "target/x.bin"
+            ]
+        );
+    }
+
+    #[test]
+    fn depth_and_hidden_files_limit_the_search() {
+        let dir = tree();
+        fs::write(dir.path().join(".hidden.bin"), "hidden").unwrap();
+        let found = |options| {
+            relative_paths(&collect_files(&[dir.path().to_path_buf()], &options).unwrap())
+        };
+        let options = WalkOptions {
+            max_depth: Some(1),
+            ..WalkOptions::default()
+        };
+        assert_eq!(
+            found(options),
+            ["a.bin", "b.txt", "src/c.bin", "target/x.bin"]
+        );
+        let options = WalkOptions {
+            max_depth: Some(0),
+            include_hidden: true,
+            ..WalkOptions::default()
+        };
+        assert_eq!(found(options), [".hidden.bin", "a.bin", "b.txt"]);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::BinvizError;
+
+/// How long [`watch_files`] waits for the files to stop changing, so a file written in several steps
+/// is analyzed once.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How often [`watch_files`] checks whether it should stop while no file changes.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Call `on_change` whenever one of `files` changed and none of them changed for `debounce` since,
+/// until `stop` is set.
+///
+/// The folders of the files are watched rather than the files themselves, so a file that is deleted
+/// and created again, as editors and build tools save files, is still watched. While a file is missing
+/// `on_change` waits for it to come back. Reading the files doesn't count as a change.
+pub fn watch_files<F>(
+    files: &[PathBuf],
+    debounce: Duration,
+    stop: &AtomicBool,
+    mut on_change: F,
+) -> Result<(), BinvizError>
+where
+    F: FnMut(),
+{
+    let watched = files
+        .iter()
+        .map(|file| absolute_path(file))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).map_err(|err| BinvizError::watch(&files[0], err))?;
+    let folders: BTreeSet<&Path> = watched.iter().filter_map(|file| file.parent()).collect();
+    for folder in folders {
+        watcher
+            .watch(folder, RecursiveMode::NonRecursive)
+            .map_err(|err| BinvizError::watch(folder, err))?;
+    }
+    let mut deadline = None;
+    while !stop.load(Ordering::SeqCst) {
+        let timeout = deadline.map_or(STOP_POLL_INTERVAL, |deadline: Instant| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(STOP_POLL_INTERVAL)
+        });
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(event)) if is_change(&event, &watched) => {
+                deadline = Some(Instant::now() + debounce)
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(err)) => warn!("watching the files failed, continuing: {}", err),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            && watched.iter().all(|file| file.exists())
+        {
+            deadline = None;
+            on_change();
+        }
+    }
+    Ok(())
+}
+
+/// The absolute path of `file`, with its folder but not the file itself resolved, as the file may be replaced.
+fn absolute_path(file: &Path) -> Result<PathBuf, BinvizError> {
+    let folder = match file.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() => folder,
+        _ => Path::new("."),
+    };
+    let folder = fs::canonicalize(folder).map_err(|err| BinvizError::io(folder, err))?;
+    match file.file_name() {
+        Some(name) => Ok(folder.join(name)),
+        None => Ok(folder),
+    }
+}
+
+/// Whether `event` creates, modifies or removes one of the `watched` files.
+fn is_change(event: &Event, watched: &BTreeSet<PathBuf>) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Any
+    ) && event.paths.iter().any(|path| watched.contains(path))
+}
@@ -0,0 +1,178 @@
+use std::{fmt, path::Path, str::FromStr};
+
+use log::info;
+
+#[cfg(feature = "tables")]
+use crate::{
+    get_most_frequent_bytes, join_formatted, truncate_most_frequent, with_remainder, OutputFormat,
+    SortOrder,
+};
+use crate::{validate_window, BinvizError, Histogram};
+
+/// The byte order of multi-byte words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+impl FromStr for Endian {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "le" | "little" => Ok(Endian::Little),
+            "be" | "big" => Ok(Endian::Big),
+            _ => Err(format!("unknown endianness `{}`, expected `le` or `be`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Endian {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endian::Little => f.write_str("le"),
+            Endian::Big => f.write_str("be"),
+        }
+    }
+}
+
+/// Decode the bytes as 16-bit words, an odd trailing byte is dropped.
+pub fn decode_words(buf: &[u8], endian: Endian) -> Vec<u16> {
+    let words = buf.chunks_exact(2);
+    if !words.remainder().is_empty() {
+        info!("dropping the odd trailing byte that doesn't fill a whole word.");
+    }
+    words
+        .map(|word| match endian {
+            Endian::Little => u16::from_le_bytes([word[0], word[1]]),
+            Endian::Big => u16::from_be_bytes([word[0], word[1]]),
+        })
+        .collect()
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) 16-bit words of a given file,
+/// see [`calculate_word_histogram_from_bytes`].
+pub fn calculate_word_histogram<P>(
+    file: P,
+    dimension: usize,
+    stride: usize,
+    endian: Endian,
+) -> Result<Histogram<u16>, BinvizError>
+where
+    P: AsRef<Path>,
+{
+    validate_window(dimension, stride)?;
+    let file = file.as_ref();
+    let buf = std::fs::read(file).map_err(|err| BinvizError::io(file, err))?;
+    Ok(calculate_word_histogram_from_bytes(
+        &buf, dimension, stride, endian,
+    ))
+}
+
+/// Calculate the n-dimensional histogram of (consecutive) 16-bit words of a byte slice,
+/// windows start `stride` words apart.
+pub fn calculate_word_histogram_from_bytes(
+    buf: &[u8],
+    dimension: usize,
+    stride: usize,
+    endian: Endian,
+) -> Histogram<u16> {
+    let mut histogram = Histogram::new();
+    for words in decode_words(buf, endian).windows(dimension).step_by(stride) {
+        *histogram.entry(words.to_vec()).or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// Reduce a word histogram to the histogram of the high bytes of its words,
+/// so it can be visualized like a byte histogram.
+pub fn high_byte_histogram(histogram: &Histogram<u16>) -> Histogram<u8> {
+    let mut high = Histogram::new();
+    for (words, freq) in histogram {
+        let key = words.iter().map(|word| (word >> 8) as u8).collect();
+        *high.entry(key).or_insert(0) += freq;
+    }
+    high
+}
+
+/// Display the keys of an n-dimensional word histogram from most to least frequent, as a table,
+/// see [`crate::display_most_frequent`].
+#[cfg(feature = "tables")]
+pub fn display_most_frequent_words(
+    histogram: &Histogram<u16>,
+    top: Option<usize>,
+    order: SortOrder,
+    format: OutputFormat,
+) -> String {
+    let total: usize = histogram.values().sum();
+    let mut most_freq = get_most_frequent_bytes(histogram, order);
+    let remainder = truncate_most_frequent(&mut most_freq, top);
+    let rows = most_freq.into_iter().enumerate().map(|(i, (word, freq))| {
+        let probability = (*freq as f64) / (total as f64);
+        vec![
+            format!("{}", i),
+            join_formatted(word, |word| format!("{}", word)),
+            join_formatted(word, |word| format!("{:#06x}", word)),
+            format.value(probability, None),
+        ]
+    });
+    let table = format.render(["Rank", "Word", "Hex", "Relative Frequency"], rows);
+    with_remainder(table, remainder, total, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_decoded_in_either_byte_order() {
+        let buf = [0x01, 0x02, 0xfe, 0xff];
+        assert_eq!(decode_words(&buf, Endian::Little), [0x0201, 0xfffe]);
+        assert_eq!(decode_words(&buf, Endian::Big), [0x0102, 0xfeff]);
+    }
+
+    #[test]
+    fn odd_trailing_byte_is_dropped() {
+        assert_eq!(decode_words(&[0x01, 0x02, 0x03], Endian::Little), [0x0201]);
+        assert!(decode_words(&[0x01], Endian::Big).is_empty());
+        let histogram = calculate_word_histogram_from_bytes(
+            &[0x01, 0x02, 0x01, 0x02, 0x03],
+            1,
+            1,
+            Endian::Little,
+        );
+        assert_eq!(histogram, Histogram::from([(vec![0x0201], 2)]));
+    }
+
+    #[test]
+    fn word_histogram_counts_windows_of_words() {
+        let buf = [0x00, 0x01, 0x00, 0x02, 0x00, 0x01, 0x00, 0x02];
+        let histogram = calculate_word_histogram_from_bytes(&buf, 2, 1, Endian::Big);
+        assert_eq!(
+            histogram,
+            Histogram::from([(vec![1, 2], 2), (vec![2, 1], 1)])
+        );
+        // Windows start `stride` words apart.
+        let histogram = calculate_word_histogram_from_bytes(&buf, 2, 2, Endian::Big);
+        assert_eq!(histogram, Histogram::from([(vec![1, 2], 2)]));
+    }
+
+    #[test]
+    fn high_bytes_of_the_words_are_kept() {
+        let histogram = Histogram::from([(vec![0x1234, 0x12ff], 2), (vec![0x12aa, 0x1200], 3)]);
+        assert_eq!(
+            high_byte_histogram(&histogram),
+            Histogram::from([(vec![0x12, 0x12], 5)])
+        );
+    }
+
+    #[test]
+    fn endianness_is_parsed() {
+        assert_eq!("le".parse(), Ok(Endian::Little));
+        assert_eq!("big".parse(), Ok(Endian::Big));
+        assert!("middle".parse::<Endian>().is_err());
+        assert_eq!(Endian::Big.to_string(), "be");
+    }
+}
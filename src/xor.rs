@@ -0,0 +1,102 @@
+#[cfg(feature = "tables")]
+use crate::OutputFormat;
+use crate::{builtin_reference, count_bytes, cross_entropy, BinvizError, Histogram, Smoothing};
+
+/// The amount of keys listed by the frequency subcommand.
+pub const XOR_KEY_COUNT: usize = 5;
+
+/// The amount of decoded bytes previewed for every key by [`display_xor_keys`].
+pub const XOR_PREVIEW_LENGTH: usize = 32;
+
+/// The distribution of bytes plaintext is expected to have: the `english` reference,
+/// with 0x00 as likely as a space, as binary data is often padded with zeros.
+fn plaintext_reference() -> Histogram<u8> {
+    let mut reference = builtin_reference("english").expect("`english` is a built-in reference");
+    let space = reference.get(&[b' '][..]).copied().unwrap_or(0);
+    reference.insert(vec![0], space);
+    reference
+}
+
+/// Rank all 256 keys a single byte XOR could have encoded `buf` with by how much the decoded bytes look like
+/// plaintext, the most likely key first.
+///
+/// Every key is scored by the cross-entropy of the decoded bytes against the `english` reference with 0x00
+/// as likely as a space, in bits per byte, lower meaning more plaintext-like. So for text the key that decodes
+/// its most frequent byte to a space, and for binary data padded with zeros the key that decodes it to 0x00,
+/// that is the most frequent byte itself, typically ranks first. Bytes the reference lacks count with a pseudo
+/// count of 1 out of a million. Only the counts of the bytes are needed, so every key is scored by permuting them.
+pub fn detect_xor_key(buf: &[u8]) -> Result<Vec<(u8, f64)>, BinvizError> {
+    if buf.is_empty() {
+        return Err(BinvizError::EmptyInput);
+    }
+    let counts = count_bytes(buf, 1);
+    let reference = plaintext_reference();
+    let mut keys = (0..=u8::MAX)
+        .map(|key| {
+            let decoded: Histogram<u8> = (0..=u8::MAX)
+                .zip(counts)
+                .filter(|&(_, count)| count > 0)
+                .map(|(byte, count)| (vec![byte ^ key], count))
+                .collect();
+            cross_entropy(&decoded, &reference, Smoothing::Additive(1.0)).map(|bits| (key, bits))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    keys.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+    Ok(keys)
+}
+
+/// Display the first `top` of the `keys` from [`detect_xor_key`] as a table with their score and the first
+/// [`XOR_PREVIEW_LENGTH`] bytes of `buf` decoded with them.
+#[cfg(feature = "tables")]
+pub fn display_xor_keys(
+    keys: &[(u8, f64)],
+    buf: &[u8],
+    top: usize,
+    format: OutputFormat,
+) -> String {
+    let rows = keys
+        .iter()
+        .take(top)
+        .enumerate()
+        .map(|(rank, &(key, bits))| {
+            let preview: Vec<u8> = buf
+                .iter()
+                .take(XOR_PREVIEW_LENGTH)
+                .map(|byte| byte ^ key)
+                .collect();
+            vec![
+                rank.to_string(),
+                format!("{:#04x}", key),
+                format.value(bits, Some("bits per byte")),
+                format!("'{}'", preview.escape_ascii()),
+            ]
+        });
+    format.render(
+        ["Rank", "XOR Key", "Cross-Entropy", "Decoded Preview"],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &[u8] = b"It was the best of times, it was the worst of times, it was the age of wisdom, \
+        it was the age of foolishness, it was the epoch of belief, it was the epoch of incredulity, \
+        it was the season of Light, it was the season of Darkness.";
+
+    #[test]
+    fn key_of_english_text_ranks_first() {
+        for key in [0x00, 0x20, 0x01, 0x5a, 0xa7, 0xff] {
+            let encoded: Vec<u8> = TEXT.iter().map(|byte| byte ^ key).collect();
+            let keys = detect_xor_key(&encoded).unwrap();
+            assert_eq!(keys.len(), 256);
+            assert_eq!(keys[0].0, key, "key {:#04x}, ranked {:?}", key, &keys[..3]);
+        }
+    }
+
+    #[test]
+    fn empty_input_is_an_error() {
+        assert!(matches!(detect_xor_key(&[]), Err(BinvizError::EmptyInput)));
+    }
+}
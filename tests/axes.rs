@@ -0,0 +1,40 @@
+//! The axes around generated images, `binviz visualize --axes`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{binviz, noise};
+use image::{DynamicImage, GenericImageView};
+
+/// Visualize `input` as the digraph into `output` without a legend, with the extra `args`.
+fn visualize(input: &Path, output: &Path, args: &[&str]) -> DynamicImage {
+    binviz()
+        .args(["--quiet", "visualize", "--no-legend"])
+        .args(args)
+        .arg("-f")
+        .arg(input)
+        .arg("-o")
+        .arg(output)
+        .arg("di")
+        .assert()
+        .success();
+    image::open(output).unwrap()
+}
+
+#[test]
+fn axes_surround_the_unchanged_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("noise.bin");
+    fs::write(&input, noise(50_000)).unwrap();
+    let bare = visualize(&input, &dir.path().join("bare.png"), &[]).to_rgba16();
+    let axes = visualize(&input, &dir.path().join("axes.png"), &["--axes"]).to_rgba16();
+    assert!(axes.width() > 256 && axes.height() > 256);
+    // The image is somewhere in the margins, unchanged.
+    let found = (0..axes.width() - 255)
+        .flat_map(|x| (0..axes.height() - 255).map(move |y| (x, y)))
+        .filter(|&(x, y)| axes.get_pixel(x, y) == bare.get_pixel(0, 0))
+        .any(|(x, y)| axes.view(x, y, 256, 256).to_image() == bare);
+    assert!(found);
+}
@@ -0,0 +1,41 @@
+//! The pixels of pairs that never occur, `binviz visualize --background`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::binviz;
+
+#[test]
+fn empty_cells_take_exactly_the_background() {
+    let dir = tempfile::tempdir().unwrap();
+    let (input, output) = (dir.path().join("pairs.bin"), dir.path().join("pairs.png"));
+    // The pairs (1, 2) and (2, 1).
+    fs::write(&input, [1, 2, 1, 2]).unwrap();
+    binviz()
+        .args([
+            "--quiet",
+            "visualize",
+            "--no-legend",
+            "--background",
+            "#101030",
+            "-f",
+        ])
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("di")
+        .assert()
+        .success();
+    let image = image::open(&output).unwrap().to_rgba16();
+    assert_eq!(image.dimensions(), (256, 256));
+    let background = image::Rgba([0x1010, 0x1010, 0x3030, u16::MAX]);
+    for (x, y, &pixel) in image.enumerate_pixels() {
+        if [(1, 2), (2, 1)].contains(&(x, y)) {
+            assert_ne!(pixel, background, "({}, {})", x, y);
+        } else {
+            assert_eq!(pixel, background, "({}, {})", x, y);
+        }
+    }
+}
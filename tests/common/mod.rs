@@ -0,0 +1,17 @@
+//! Helpers shared by the tests driving the binary.
+// Every test crate includes the helpers, but not all of them use every one.
+#![allow(dead_code, unused_imports)]
+
+use assert_cmd::Command;
+
+/// The binary, ignoring any config file so the tests only depend on their arguments.
+pub fn binviz() -> Command {
+    let mut command = Command::cargo_bin("binviz").expect("the binary is built for the tests");
+    command.arg("--no-config");
+    command
+}
+
+#[path = "../../src/test_support.rs"]
+mod test_support;
+
+pub(crate) use test_support::noise;
@@ -0,0 +1,91 @@
+//! The completion scripts of `binviz completions`, generated from the same arguments the command line is parsed with.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::binviz;
+
+/// The subcommands every script completes.
+const SUBCOMMANDS: [&str; 16] = [
+    "entropy",
+    "frequency",
+    "visualize",
+    "profile",
+    "blocks",
+    "diff",
+    "compare",
+    "classify",
+    "markov",
+    "map",
+    "strings",
+    "scan",
+    "full",
+    "tui",
+    "completions",
+    "help",
+];
+
+/// The completion script `binviz completions shell` prints.
+fn script(shell: &str) -> String {
+    let output = binviz()
+        .args(["completions", shell])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn scripts_complete_every_subcommand() {
+    for shell in ["bash", "zsh", "fish", "powershell", "elvish"] {
+        let script = script(shell);
+        for subcommand in SUBCOMMANDS {
+            assert!(
+                script.contains(subcommand),
+                "{} lacks {}",
+                shell,
+                subcommand
+            );
+        }
+        // Nested subcommands and flags of subcommands are completed too.
+        assert!(script.contains("quartic"), "{} lacks quartic", shell);
+        assert!(
+            script.contains("no-trigraph"),
+            "{} lacks --no-trigraph",
+            shell
+        );
+    }
+}
+
+#[test]
+fn scripts_are_saved_to_a_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    binviz()
+        .args(["completions", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    let mut names: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["_binviz", "_binviz.ps1", "binviz.bash", "binviz.fish"]
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("binviz.bash")).unwrap(),
+        script("bash")
+    );
+}
+
+#[test]
+fn unknown_shell_is_a_usage_error() {
+    binviz().args(["completions", "tcsh"]).assert().code(2);
+    binviz().arg("completions").assert().code(2);
+}
@@ -0,0 +1,121 @@
+//! The defaults of the options from the config files: the command line wins over an explicit `--config`,
+//! which replaces the config in the current folder, which overrides the user config.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use assert_cmd::Command;
+use common::noise;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use tempfile::TempDir;
+
+/// A folder with the input `noise.bin`, a user config in `config/binviz/config.toml` and a working folder `work`.
+fn setup() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("noise.bin"), noise(4096)).unwrap();
+    fs::create_dir_all(dir.path().join("config").join("binviz")).unwrap();
+    fs::create_dir(dir.path().join("work")).unwrap();
+    dir
+}
+
+/// Write a config file setting `--top` of `binviz frequency` to `top`.
+fn write_config(path: &Path, top: usize) {
+    fs::write(path, format!("[frequency]\ntop = {}\n", top)).unwrap();
+}
+
+/// The amount of values `binviz frequency` lists with the configs in `dir` and the extra `args`.
+fn listed(dir: &Path, args: &[&str]) -> usize {
+    let output = Command::cargo_bin("binviz")
+        .unwrap()
+        .current_dir(dir.join("work"))
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("HOME", dir)
+        .args(["frequency", "--format", "csv", "-f"])
+        .arg(dir.join("noise.bin"))
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    // Every line but the header is a value.
+    String::from_utf8(output).unwrap().lines().count() - 1
+}
+
+#[test]
+fn options_are_taken_from_the_highest_config_that_sets_them() {
+    let dir = setup();
+    let path = dir.path();
+    // Without any config every byte value is listed.
+    assert_eq!(listed(path, &[]), 256);
+
+    write_config(&path.join("config").join("binviz").join("config.toml"), 5);
+    assert_eq!(listed(path, &[]), 5);
+
+    write_config(&path.join("work").join("binviz.toml"), 4);
+    assert_eq!(listed(path, &[]), 4);
+
+    let explicit = path.join("explicit.toml");
+    write_config(&explicit, 3);
+    let explicit = explicit.to_str().unwrap();
+    assert_eq!(listed(path, &["--config", explicit]), 3);
+
+    assert_eq!(listed(path, &["--config", explicit, "--top", "2"]), 2);
+    assert_eq!(listed(path, &["--top", "2"]), 2);
+    assert_eq!(listed(path, &["--no-config"]), 256);
+}
+
+#[test]
+fn explicit_config_replaces_the_others() {
+    let dir = setup();
+    let path = dir.path();
+    write_config(&path.join("config").join("binviz").join("config.toml"), 5);
+    write_config(&path.join("work").join("binviz.toml"), 4);
+    // An explicit config without the option falls back to the default, not to the other configs.
+    let explicit = path.join("explicit.toml");
+    fs::write(&explicit, "[frequency]\n").unwrap();
+    assert_eq!(listed(path, &["--config", explicit.to_str().unwrap()]), 256);
+}
+
+#[test]
+fn config_gives_required_options() {
+    let dir = setup();
+    let path = dir.path();
+    let config = path.join("explicit.toml");
+    fs::write(&config, "[entropy]\ncount = 2\n").unwrap();
+    Command::cargo_bin("binviz")
+        .unwrap()
+        .arg("--config")
+        .arg(&config)
+        .args(["entropy", "-f"])
+        .arg(path.join("noise.bin"))
+        .assert()
+        .success()
+        .stdout(contains("| 2 "))
+        .stdout(contains("| 3 ").not());
+}
+
+#[test]
+fn config_gives_options_of_the_visualize_modes() {
+    let dir = setup();
+    let path = dir.path();
+    let config = path.join("explicit.toml");
+    fs::write(&config, "[visualize]\nbuckets = 3\n").unwrap();
+    let output = path.join("offset.png");
+    Command::cargo_bin("binviz")
+        .unwrap()
+        .arg("--config")
+        .arg(&config)
+        .args(["visualize", "-f"])
+        .arg(path.join("noise.bin"))
+        .arg("-o")
+        .arg(&output)
+        .args(["offset", "--per-column"])
+        .assert()
+        .success()
+        .stderr(contains("has no option").not());
+    // A column per bucket, without the colorbar.
+    assert_eq!(image::open(&output).unwrap().width(), 3);
+}
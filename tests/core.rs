@@ -0,0 +1,138 @@
+//! The histogram, entropy and statistics core, which builds without any of the optional features:
+//! `cargo test --no-default-features --test core`.
+
+use std::io::Cursor;
+
+use binviz::{
+    calculate_entropy_histogram, calculate_histogram_from_bytes, calculate_histogram_from_reader,
+    calculate_min_entropy, calculate_renyi_entropy, chi_square, merge_histograms, BinvizError,
+    ByteStats, EntropyUnit, HistogramBuilder,
+};
+
+/// Every byte value once, in order.
+fn every_byte() -> Vec<u8> {
+    (0..=u8::MAX).collect()
+}
+
+#[test]
+fn histogram_counts_overlapping_windows() {
+    let histogram = calculate_histogram_from_bytes(b"abab", 2, 1);
+    assert_eq!(histogram.len(), 2);
+    assert_eq!(histogram[&b"ab"[..]], 2);
+    assert_eq!(histogram[&b"ba"[..]], 1);
+}
+
+#[test]
+fn histogram_skips_windows_between_strides() {
+    let histogram = calculate_histogram_from_bytes(b"abab", 2, 2);
+    assert_eq!(histogram.len(), 1);
+    assert_eq!(histogram[&b"ab"[..]], 2);
+}
+
+#[test]
+fn reader_and_builder_match_bytes() {
+    let buf: Vec<u8> = every_byte().into_iter().cycle().take(10_000).collect();
+    for dimension in 1..=3 {
+        let expected = calculate_histogram_from_bytes(&buf, dimension, 1);
+        let read = calculate_histogram_from_reader(Cursor::new(&buf), dimension, 1).unwrap();
+        assert_eq!(read, expected);
+        let mut builder = HistogramBuilder::with_stride(dimension, 1);
+        for chunk in buf.chunks(7) {
+            builder.update(chunk);
+        }
+        assert_eq!(builder.finish(), expected);
+    }
+}
+
+#[test]
+fn single_update_matches_an_update_per_byte() {
+    // Long enough for single bytes and byte pairs to be counted densely in a single update.
+    let buf: Vec<u8> = every_byte().into_iter().cycle().take(70_000).collect();
+    for dimension in 1..=4 {
+        for stride in [1, 3] {
+            let mut whole = HistogramBuilder::with_stride(dimension, stride);
+            whole.update(&buf);
+            let mut bytewise = HistogramBuilder::with_stride(dimension, stride);
+            for byte in &buf {
+                bytewise.update(std::slice::from_ref(byte));
+            }
+            assert_eq!(
+                bytewise.finish(),
+                whole.finish(),
+                "dimension {}, stride {}",
+                dimension,
+                stride
+            );
+        }
+    }
+}
+
+#[test]
+fn merged_histograms_add_counts() {
+    let mut merged = calculate_histogram_from_bytes(b"aab", 1, 1);
+    merge_histograms(&mut merged, &calculate_histogram_from_bytes(b"bc", 1, 1)).unwrap();
+    assert_eq!(merged, calculate_histogram_from_bytes(b"aabbc", 1, 1));
+    assert!(matches!(
+        merge_histograms(&mut merged, &calculate_histogram_from_bytes(b"bc", 2, 1)),
+        Err(BinvizError::KeyLengthMismatch { .. })
+    ));
+}
+
+#[test]
+fn entropy_of_every_byte_is_eight_bits() {
+    let histogram = calculate_histogram_from_bytes(&every_byte(), 1, 1);
+    let bits = calculate_entropy_histogram(&histogram, EntropyUnit::Bits).unwrap();
+    assert!((bits - 8.0).abs() < 1e-12);
+    assert!((calculate_min_entropy(&histogram).unwrap() - 8.0).abs() < 1e-12);
+}
+
+#[test]
+fn renyi_entropy_of_every_byte_is_eight_bits() {
+    let histogram = calculate_histogram_from_bytes(&every_byte(), 1, 1);
+    for alpha in [0.0, 0.5, 2.0, f64::INFINITY] {
+        let bits = calculate_renyi_entropy(&histogram, alpha).unwrap();
+        assert!((bits - 8.0).abs() < 1e-12, "order {}", alpha);
+    }
+}
+
+#[test]
+fn renyi_entropy_falls_with_the_order_for_a_dominant_byte() {
+    // One byte with probability 8/11 and three with 1/11 each.
+    let histogram = calculate_histogram_from_bytes(b"aaaaaaaabcd", 1, 1);
+    let shannon = calculate_renyi_entropy(&histogram, 1.0).unwrap();
+    let collision = calculate_renyi_entropy(&histogram, 2.0).unwrap();
+    let min = calculate_renyi_entropy(&histogram, f64::INFINITY).unwrap();
+    assert!(min < collision && collision < shannon);
+    assert!((collision - (121.0f64 / 67.0).log2()).abs() < 1e-12);
+    assert!((min - (11.0f64 / 8.0).log2()).abs() < 1e-12);
+    assert_eq!(min, calculate_min_entropy(&histogram).unwrap());
+}
+
+#[test]
+fn entropy_of_a_single_byte_is_zero() {
+    let histogram = calculate_histogram_from_bytes(&[0; 100], 1, 1);
+    assert_eq!(
+        calculate_entropy_histogram(&histogram, EntropyUnit::Bits).unwrap(),
+        0.0
+    );
+}
+
+#[test]
+fn entropy_of_nothing_is_an_error() {
+    let histogram = calculate_histogram_from_bytes(&[], 1, 1);
+    assert!(matches!(
+        calculate_entropy_histogram(&histogram, EntropyUnit::Bits),
+        Err(BinvizError::EmptyInput)
+    ));
+}
+
+#[test]
+fn statistics_of_every_byte() {
+    let histogram = calculate_histogram_from_bytes(&every_byte(), 1, 1);
+    let stats = ByteStats::compute(&histogram).unwrap();
+    assert!((stats.mean - 127.5).abs() < 1e-12);
+    assert!((stats.variance - 5461.25).abs() < 1e-9);
+    assert!(stats.skewness.unwrap().abs() < 1e-12);
+    let (statistic, _) = chi_square(&histogram).unwrap();
+    assert_eq!(statistic, 0.0);
+}
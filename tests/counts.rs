@@ -0,0 +1,74 @@
+//! The exact counts behind the digraph image, `binviz visualize --counts-output`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{binviz, noise};
+
+/// Visualize `input` writing the counts to `counts`, returning the amount of byte pairs reported as visualized.
+fn visualize(input: &Path, counts: &Path) -> usize {
+    let output = binviz()
+        .args(["visualize", "-f"])
+        .arg(input)
+        .arg("-o")
+        .arg(input.with_extension("png"))
+        .arg("--force")
+        .arg("--counts-output")
+        .arg(counts)
+        .arg("di")
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).unwrap();
+    let line = stderr
+        .lines()
+        .find(|line| line.contains("byte pairs visualized"))
+        .unwrap();
+    line.split('`').nth(1).unwrap().parse().unwrap()
+}
+
+#[test]
+fn counts_add_up_to_the_total() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("noise.bin");
+    fs::write(&input, noise(50_000)).unwrap();
+
+    let csv = dir.path().join("counts.csv");
+    let total = visualize(&input, &csv);
+    assert_eq!(total, 49_999);
+    let csv = fs::read_to_string(&csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("x,y,count"));
+    let sum: usize = lines
+        .map(|line| line.rsplit(',').next().unwrap().parse::<usize>().unwrap())
+        .sum();
+    assert_eq!(sum, total);
+
+    let json = dir.path().join("counts.json");
+    assert_eq!(visualize(&input, &json), total);
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+    let sum: u64 = entries
+        .iter()
+        .map(|entry| entry["count"].as_u64().unwrap())
+        .sum();
+    assert_eq!(sum as usize, total);
+
+    // None of the counts is large enough to be clamped.
+    let pgm = dir.path().join("counts.pgm");
+    assert_eq!(visualize(&input, &pgm), total);
+    let pgm = fs::read(&pgm).unwrap();
+    let header = b"P5\n256 256\n65535\n";
+    assert_eq!(&pgm[..header.len()], header);
+    let pixels = &pgm[header.len()..];
+    assert_eq!(pixels.len(), 256 * 256 * 2);
+    let sum: usize = pixels
+        .chunks_exact(2)
+        .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]) as usize)
+        .sum();
+    assert_eq!(sum, total);
+}
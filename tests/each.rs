@@ -0,0 +1,85 @@
+//! Visualizing several files on their own, `binviz visualize --each`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::{binviz, noise};
+use predicates::str::contains;
+
+#[test]
+fn keep_going_visualizes_the_readable_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("first.bin"), noise(4096)).unwrap();
+    fs::write(dir.path().join("second.bin"), noise(2048)).unwrap();
+    binviz()
+        .current_dir(dir.path())
+        .args(["--quiet", "visualize", "--each", "--keep-going"])
+        .args(["-f", "first.bin", "-f", "missing.bin", "-f", "second.bin"])
+        .args(["--output-dir", "images", "di"])
+        .assert()
+        .success()
+        .stderr(contains("failed to visualize `1` of `3` files:"))
+        .stderr(contains("`missing.bin`"));
+    let images = dir.path().join("images");
+    image::open(images.join("first.bin.png")).unwrap();
+    image::open(images.join("second.bin.png")).unwrap();
+    assert!(!images.join("missing.bin.png").exists());
+}
+
+#[test]
+fn without_keep_going_the_first_failure_stops() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("second.bin"), noise(2048)).unwrap();
+    binviz()
+        .current_dir(dir.path())
+        .args(["--quiet", "visualize", "--each"])
+        .args(["-f", "missing.bin", "-f", "second.bin"])
+        .args(["--output-dir", "images", "di"])
+        .assert()
+        .code(1);
+    assert!(!dir.path().join("images").join("second.bin.png").exists());
+}
+
+#[test]
+fn files_with_the_same_name_get_their_own_images() {
+    let dir = tempfile::tempdir().unwrap();
+    for folder in ["a", "b"] {
+        fs::create_dir(dir.path().join(folder)).unwrap();
+        fs::write(dir.path().join(folder).join("x.bin"), noise(4096)).unwrap();
+    }
+    // Relative paths keep their folders.
+    binviz()
+        .current_dir(dir.path())
+        .args([
+            "--quiet",
+            "visualize",
+            "--each",
+            "-f",
+            "a/x.bin",
+            "-f",
+            "b/x.bin",
+        ])
+        .args(["--output-dir", "relative", "di"])
+        .assert()
+        .success();
+    let relative = dir.path().join("relative");
+    image::open(relative.join("a").join("x.bin.png")).unwrap();
+    image::open(relative.join("b").join("x.bin.png")).unwrap();
+
+    // Other paths are numbered.
+    let absolute = dir.path().join("absolute");
+    binviz()
+        .args(["--quiet", "visualize", "--each", "-f"])
+        .arg(dir.path().join("a").join("x.bin"))
+        .arg("-f")
+        .arg(dir.path().join("b").join("x.bin"))
+        .arg("--output-dir")
+        .arg(&absolute)
+        .arg("di")
+        .assert()
+        .success();
+    image::open(absolute.join("x.bin.png")).unwrap();
+    image::open(absolute.join("x.bin-2.png")).unwrap();
+}
@@ -0,0 +1,84 @@
+//! The exit codes and the one-line errors on standard error: 1 for an analysis error, 2 for a usage error
+//! and 3 for an error writing the outputs.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::{binviz, noise};
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+#[test]
+fn missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.bin");
+    binviz()
+        .args(["entropy", "-c", "2", "-f"])
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stderr(contains(format!("error: `{}`", path.display())))
+        .stderr(contains("(os error 2)"))
+        .stderr(contains("panicked").not());
+}
+
+#[test]
+fn unwritable_output_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("noise.bin");
+    fs::write(&input, noise(1024)).unwrap();
+    // A file can't contain outputs, even for root, unlike a read-only folder.
+    let output = input.join("digraph.png");
+    binviz()
+        .args(["visualize", "-f"])
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("di")
+        .assert()
+        .code(3)
+        .stderr(contains(format!(
+            "error: couldn't write `{}`",
+            output.display()
+        )));
+    let chart = input.join("entropy.svg");
+    binviz()
+        .args(["entropy", "-c", "2", "-f"])
+        .arg(&input)
+        .arg("--chart-svg")
+        .arg(&chart)
+        .assert()
+        .code(3)
+        .stderr(contains(format!(
+            "error: couldn't write `{}`",
+            chart.display()
+        )));
+}
+
+#[test]
+fn invalid_dimension() {
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "0"])
+        .write_stdin(noise(1024))
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "error: invalid dimension `0`, the dimension must be at least 1",
+        ));
+    binviz()
+        .args(["frequency", "-f", "-", "-d", "0"])
+        .write_stdin(noise(1024))
+        .assert()
+        .code(1)
+        .stderr(contains("error: invalid dimension `0`"));
+}
+
+#[test]
+fn unparsable_dimension() {
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "two"])
+        .assert()
+        .code(2)
+        .stderr(contains("invalid value 'two' for '--count <COUNT>'"));
+}
@@ -0,0 +1,56 @@
+/* Exercises the C interface of the `ffi` feature through the generated header, run by tests/ffi.rs.
+ * binviz_ffi_test returns 0 if every check passes, or else the line of the first failing check. */
+
+#include <math.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#include "binviz.h"
+
+#define CHECK(condition) \
+    do { \
+        if (!(condition)) { \
+            return __LINE__; \
+        } \
+    } while (0)
+
+int binviz_ffi_test(void) {
+    const uint8_t data[] = "abab";
+    BinvizHistogram *histogram = NULL;
+    CHECK(binviz_histogram_new(data, 4, 2, &histogram) == BINVIZ_STATUS_OK);
+    CHECK(histogram != NULL);
+
+    size_t count = 0;
+    CHECK(binviz_histogram_get(histogram, (const uint8_t *)"ab", 2, &count) == BINVIZ_STATUS_OK);
+    CHECK(count == 2);
+    CHECK(binviz_histogram_get(histogram, (const uint8_t *)"ba", 2, &count) == BINVIZ_STATUS_OK);
+    CHECK(count == 1);
+    CHECK(binviz_histogram_get(histogram, (const uint8_t *)"aa", 2, &count) == BINVIZ_STATUS_OK);
+    CHECK(count == 0);
+    CHECK(binviz_histogram_get(histogram, (const uint8_t *)"a", 1, &count) == BINVIZ_STATUS_KEY_LENGTH_MISMATCH);
+
+    double entropy = 0.0;
+    CHECK(binviz_histogram_entropy(histogram, &entropy) == BINVIZ_STATUS_OK);
+    /* 2 of 3 pairs are "ab" and 1 is "ba". */
+    CHECK(fabs(entropy - (-(2.0 / 3.0) * log2(2.0 / 3.0) - (1.0 / 3.0) * log2(1.0 / 3.0))) < 1e-12);
+
+    uint16_t *pixels = calloc(BINVIZ_DIGRAPH_PIXELS, sizeof(uint16_t));
+    CHECK(pixels != NULL);
+    CHECK(binviz_render_digraph(histogram, pixels, BINVIZ_DIGRAPH_PIXELS - 1) == BINVIZ_STATUS_BUFFER_TOO_SMALL);
+    CHECK(binviz_render_digraph(histogram, pixels, BINVIZ_DIGRAPH_PIXELS) == BINVIZ_STATUS_OK);
+    /* The first byte of a pair is the column. */
+    CHECK(pixels['b' * 256 + 'a'] > pixels['a' * 256 + 'b']);
+    CHECK(pixels['a' * 256 + 'b'] > 0);
+    CHECK(pixels[0] == 0);
+    free(pixels);
+    binviz_histogram_free(histogram);
+
+    CHECK(binviz_histogram_new(data, 4, 0, &histogram) == BINVIZ_STATUS_INVALID_DIMENSION);
+    CHECK(binviz_histogram_new(NULL, 4, 1, &histogram) == BINVIZ_STATUS_NULL_POINTER);
+    CHECK(binviz_histogram_new(data, 4, 1, NULL) == BINVIZ_STATUS_NULL_POINTER);
+    CHECK(binviz_histogram_new(NULL, 0, 1, &histogram) == BINVIZ_STATUS_OK);
+    CHECK(binviz_histogram_entropy(histogram, &entropy) == BINVIZ_STATUS_EMPTY_INPUT);
+    binviz_histogram_free(histogram);
+    binviz_histogram_free(NULL);
+    return 0;
+}
@@ -0,0 +1,23 @@
+//! The C test program `tests/ffi.c`, compiled by the build script against the generated header:
+//! `cargo test --features ffi --test ffi`.
+#![cfg(feature = "ffi")]
+
+use std::os::raw::c_int;
+
+// The C program calls the functions exported by the library.
+extern crate binviz;
+
+#[link(name = "binviz_ffi_test", kind = "static")]
+extern "C" {
+    fn binviz_ffi_test() -> c_int;
+}
+
+#[test]
+fn c_program_passes() {
+    let failed_line = unsafe { binviz_ffi_test() };
+    assert_eq!(
+        failed_line, 0,
+        "check on line {} of tests/ffi.c failed",
+        failed_line
+    );
+}
@@ -0,0 +1,186 @@
+//! Existing outputs are only overwritten with `--force`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use common::{binviz, noise};
+use predicates::str::contains;
+
+/// What the pre-populated outputs contain before a run.
+const OLD: &[u8] = b"old results";
+
+/// Write the input `noise.bin` to `dir`.
+fn input(dir: &Path) -> PathBuf {
+    let input = dir.join("noise.bin");
+    fs::write(&input, noise(4096)).unwrap();
+    input
+}
+
+#[test]
+fn visualize_refuses_to_overwrite_without_force() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = input(dir.path());
+    let output = dir.path().join("images");
+    fs::create_dir(&output).unwrap();
+    let image = output.join("noise.bin.png");
+    fs::write(&image, OLD).unwrap();
+    binviz()
+        .args(["visualize", "--each", "-f"])
+        .arg(&input)
+        .arg("--output-dir")
+        .arg(&output)
+        .arg("di")
+        .assert()
+        .code(3)
+        .stderr(contains(format!(
+            "refusing to overwrite `{}` (use --force)",
+            image.display()
+        )));
+    assert_eq!(fs::read(&image).unwrap(), OLD);
+
+    binviz()
+        .args(["--force", "visualize", "--each", "-f"])
+        .arg(&input)
+        .arg("--output-dir")
+        .arg(&output)
+        .arg("di")
+        .assert()
+        .success();
+    image::open(&image).unwrap();
+}
+
+#[test]
+fn visualize_refuses_to_overwrite_a_saved_histogram_without_force() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = input(dir.path());
+    let histogram = dir.path().join("pairs.json");
+    fs::write(&histogram, OLD).unwrap();
+    let visualize = |force: bool| {
+        let mut command = binviz();
+        if force {
+            command.arg("--force");
+        }
+        command
+            .args(["visualize", "-f"])
+            .arg(&input)
+            .arg("-o")
+            .arg(dir.path().join("pairs.png"))
+            .arg("--save-histogram")
+            .arg(&histogram)
+            .arg("di")
+            .assert()
+    };
+    visualize(false)
+        .code(3)
+        .stderr(contains("refusing to overwrite"));
+    assert_eq!(fs::read(&histogram).unwrap(), OLD);
+    assert!(!dir.path().join("pairs.png").exists());
+
+    visualize(true).success();
+    assert_ne!(fs::read(&histogram).unwrap(), OLD);
+}
+
+#[test]
+fn full_refuses_to_overwrite_without_force() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = input(dir.path());
+    let output = dir.path().join("output");
+    let entropy = output.join("noise").join("entropy.txt");
+    fs::create_dir_all(entropy.parent().unwrap()).unwrap();
+    fs::write(&entropy, OLD).unwrap();
+    binviz()
+        .args(["--quiet", "full", "-f"])
+        .arg(&input)
+        .arg("--output-dir")
+        .arg(&output)
+        .assert()
+        .code(3)
+        .stderr(contains("refusing to overwrite"));
+    assert_eq!(fs::read(&entropy).unwrap(), OLD);
+    assert!(!output.join("noise").join("image.png").exists());
+
+    binviz()
+        .args(["--quiet", "--force", "full", "-f"])
+        .arg(&input)
+        .arg("--output-dir")
+        .arg(&output)
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&entropy).unwrap().contains("Dimension"));
+    assert!(output.join("noise").join("image.png").is_file());
+}
+
+/// Every output of every subcommand, with the arguments writing it to the output path,
+/// relative to a folder with the inputs `noise.bin` and `other.bin`.
+const OUTPUTS: &[(&str, &str)] = &[
+    ("entropy -f noise.bin -c 2 --chart-svg out.svg", "out.svg"),
+    (
+        "frequency -f noise.bin --save-histogram out.json",
+        "out.json",
+    ),
+    ("frequency -f noise.bin --chart-svg out.svg", "out.svg"),
+    (
+        "frequency -f noise.bin --periods 10 --spectrum-png out.png",
+        "out.png",
+    ),
+    ("visualize -f noise.bin -o out.png di", "out.png"),
+    (
+        "visualize -f noise.bin -o image.png --counts-output out.csv di",
+        "out.csv",
+    ),
+    ("profile -f noise.bin", "profile.png"),
+    (
+        "profile -f noise.bin -w 256 --entropy-map",
+        "entropy_map.png",
+    ),
+    (
+        "compare --file-a noise.bin --file-b other.bin -o out.png",
+        "out.png",
+    ),
+    (
+        "compare --file-a noise.bin --file-b other.bin --diff-output out.png",
+        "out.png",
+    ),
+    (
+        "compare --file-a noise.bin --file-b other.bin --images .",
+        "compare.png",
+    ),
+    ("markov -f noise.bin --csv out.csv", "out.csv"),
+    ("markov -f noise.bin --sample out.bin", "out.bin"),
+    ("map -f noise.bin", "map.png"),
+    (
+        "full -f noise.bin --output-dir out",
+        "out/noise/entropy.txt",
+    ),
+    ("completions bash --dir .", "binviz.bash"),
+];
+
+#[test]
+fn every_subcommand_refuses_to_overwrite_without_force() {
+    for (args, output) in OUTPUTS {
+        let dir = tempfile::tempdir().unwrap();
+        input(dir.path());
+        fs::write(dir.path().join("other.bin"), noise(8192)).unwrap();
+        let path = dir.path().join(output);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, OLD).unwrap();
+        let run = |force: bool| {
+            let mut command = binviz();
+            command.current_dir(dir.path()).arg("--quiet");
+            if force {
+                command.arg("--force");
+            }
+            command.args(args.split_whitespace()).assert()
+        };
+        run(false).code(3).stderr(contains("refusing to overwrite"));
+        assert_eq!(fs::read(&path).unwrap(), OLD, "{}", args);
+
+        run(true).success();
+        assert_ne!(fs::read(&path).unwrap(), OLD, "{}", args);
+    }
+}
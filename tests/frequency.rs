@@ -0,0 +1,169 @@
+//! The most frequent windows of a file, `binviz frequency -d N`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::{binviz, noise};
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use tempfile::TempDir;
+
+/// A file with an ELF magic number, 300 pairs `0xde 0xad` and every byte once: 859 windows of 2 bytes,
+/// of which 300 are `0xde 0xad` and 299 are `0xad 0xde`.
+fn fixture() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixture.bin");
+    let mut buf = b"\x7fELF".to_vec();
+    buf.extend(b"\xde\xad".repeat(300));
+    buf.extend(0..=u8::MAX);
+    fs::write(&path, buf).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn dominant_pair_comes_first() {
+    let (_dir, path) = fixture();
+    binviz()
+        .args(["frequency", "-d", "2", "--top", "3", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(contains(
+            "| Rank | Bytes   | Hex       | Text       | Classes      | Relative Frequency |",
+        ))
+        .stdout(contains(
+            "| 0    | 222 173 | 0xde 0xad | '\\xde\\xad' | high high    | 0.34924            |",
+        ))
+        .stdout(contains(
+            "| 1    | 173 222 | 0xad 0xde | '\\xad\\xde' | high high    | 0.34808            |",
+        ))
+        .stdout(contains(
+            "… and 259 more distinct values covering 30.2% of windows.",
+        ));
+}
+
+#[test]
+fn pairs_are_listed_by_count() {
+    let (_dir, path) = fixture();
+    let output = binviz()
+        .args(["frequency", "-d", "2", "--format", "csv", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let rows: Vec<&str> = output.lines().collect();
+    assert_eq!(rows[0], "Rank,Bytes,Hex,Text,Classes,Relative Frequency");
+    // 262 distinct pairs, the two dominant ones and the rest once each.
+    assert_eq!(rows.len(), 1 + 262);
+    assert!(rows[1].starts_with("0,222 173,0xde 0xad,"));
+    assert!(rows[2].starts_with("1,173 222,0xad 0xde,"));
+}
+
+#[test]
+fn triples_can_be_listed_too() {
+    let (_dir, path) = fixture();
+    binviz()
+        .args(["frequency", "-d", "3", "--top", "20", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(contains("| 0    | 173 222 173 | 0xad 0xde 0xad |"))
+        .stdout(contains("| 19   |"))
+        .stdout(contains("| 20   |").not());
+}
+
+#[test]
+fn invalid_dimensions_are_errors() {
+    let (_dir, path) = fixture();
+    binviz()
+        .args(["frequency", "-d", "0", "-f"])
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stderr(contains("invalid dimension `0`"));
+    binviz()
+        .args(["frequency", "-d", "861", "-f"])
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stderr(contains("use a dimension of at most `860`"));
+}
+
+#[test]
+fn records_of_24_bytes_have_a_period_of_24() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, spectrum) = (
+        dir.path().join("records.bin"),
+        dir.path().join("spectrum.png"),
+    );
+    // Records of 24 random bytes, every byte changed a little by noise.
+    let record = noise(24);
+    let buf: Vec<u8> = noise(20_024)[24..]
+        .iter()
+        .enumerate()
+        .map(|(i, &jitter)| record[i % 24].wrapping_add(jitter >> 5))
+        .collect();
+    fs::write(&path, buf).unwrap();
+    let output = binviz()
+        .args(["frequency", "-d", "1", "--periods", "100", "--spectrum-png"])
+        .arg(&spectrum)
+        .arg("-f")
+        .arg(&path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let periods: Vec<&str> = output
+        .lines()
+        .skip_while(|line| !line.starts_with("| Period (bytes) |"))
+        .skip(2)
+        .map(|line| line.split('|').nth(1).unwrap().trim())
+        .collect();
+    // The period first, its multiples after it.
+    assert_eq!(periods[..4], ["24", "48", "72", "96"]);
+    let spectrum = image::open(&spectrum).unwrap();
+    assert_eq!((spectrum.width(), spectrum.height()), (1024, 256));
+}
+
+#[test]
+fn constant_bytes_have_no_periods() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("zeros.bin");
+    fs::write(&path, [0; 4096]).unwrap();
+    let output = binviz()
+        .args(["frequency", "-d", "1", "--periods", "100", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stderr(contains("the bytes are constant, so they have no periods"))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    // The rest of the report is still shown, with an empty table of periods.
+    assert!(output.contains("| 0x00"), "{}", output);
+    let periods = output
+        .lines()
+        .skip_while(|line| !line.starts_with("| Period (bytes) |"))
+        .skip(2)
+        .take_while(|line| line.starts_with('|'))
+        .count();
+    assert!(output.contains("| Period (bytes) |"));
+    assert_eq!(periods, 0);
+}
+
+#[test]
+fn spectrum_needs_periods() {
+    let (_dir, path) = fixture();
+    binviz()
+        .args(["frequency", "--spectrum-png", "spectrum.png", "-f"])
+        .arg(&path)
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,378 @@
+//! The full analysis of many files at once, `binviz full --jobs N`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use common::{binviz, noise};
+
+/// Fixture files, two of which share a stem in different folders, and one too small to analyze.
+fn fixtures(dir: &Path) -> PathBuf {
+    let input = dir.join("in");
+    fs::create_dir_all(input.join("a")).unwrap();
+    fs::create_dir_all(input.join("b")).unwrap();
+    fs::write(input.join("a").join("x.bin"), noise(5000)).unwrap();
+    fs::write(input.join("b").join("x.bin"), b"hello world ".repeat(400)).unwrap();
+    fs::write(input.join("z.bin"), vec![0; 3000]).unwrap();
+    fs::write(input.join("text.txt"), b"plain text\n".repeat(300)).unwrap();
+    fs::write(input.join("tiny.bin"), b"q").unwrap();
+    input
+}
+
+/// Every file below `dir` with its contents, by its path relative to `dir`.
+fn files_below(dir: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut files = Vec::new();
+    let mut folders = vec![dir.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        for entry in fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                folders.push(path);
+            } else {
+                let contents = fs::read(&path).unwrap();
+                files.push((path.strip_prefix(dir).unwrap().to_path_buf(), contents));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// The rows of `summary.csv` without the duration, which differs between runs.
+fn summary_without_durations(contents: &[u8]) -> Vec<Vec<String>> {
+    String::from_utf8(contents.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut fields: Vec<String> = line.splitn(8, ',').map(String::from).collect();
+            fields.remove(6);
+            fields
+        })
+        .collect()
+}
+
+/// Analyze the files in `input` with `jobs` files at once into `output`, which fails as one file is too small.
+fn analyze(input: &Path, output: &Path, jobs: &str) {
+    binviz()
+        .args(["--quiet", "full", "--jobs", jobs, "-f"])
+        .arg(input)
+        .arg("--output-dir")
+        .arg(output)
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("`1` of `5` files failed"))
+        .stderr(predicates::str::contains("tiny.bin"));
+}
+
+#[test]
+fn parallel_analysis_matches_serial_analysis() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixtures(dir.path());
+    let (serial, parallel) = (dir.path().join("serial"), dir.path().join("parallel"));
+    analyze(&input, &serial, "1");
+    analyze(&input, &parallel, "4");
+    let (serial, parallel) = (files_below(&serial), files_below(&parallel));
+    let names: Vec<&PathBuf> = serial.iter().map(|(name, _)| name).collect();
+    assert_eq!(
+        names,
+        parallel.iter().map(|(name, _)| name).collect::<Vec<_>>()
+    );
+    for ((name, serial), (_, parallel)) in serial.iter().zip(&parallel) {
+        match name.file_name().unwrap().to_str().unwrap() {
+            "summary.csv" => assert_eq!(
+                summary_without_durations(serial),
+                summary_without_durations(parallel)
+            ),
+            // The table of the summary only differs in the durations as well.
+            "summary.txt" => assert_eq!(
+                String::from_utf8_lossy(serial).lines().count(),
+                String::from_utf8_lossy(parallel).lines().count()
+            ),
+            _ => assert!(serial == parallel, "{} differs", name.display()),
+        }
+    }
+}
+
+#[test]
+fn parallel_analysis_produces_every_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = fixtures(dir.path());
+    let output = dir.path().join("out");
+    analyze(&input, &output, "4");
+    // Files sharing a stem get their own folders.
+    for folder in ["in/a/x", "in/b/x", "in/z", "in/text"] {
+        for name in [
+            "counts.csv",
+            "entropy.txt",
+            "entropy_map.png",
+            "image.png",
+            "most_frequent.txt",
+            "strings.txt",
+            "image_color.png",
+        ] {
+            let path = output.join(folder).join(name);
+            assert!(path.is_file(), "{} is missing", path.display());
+        }
+    }
+    assert!(!output.join("in/tiny").exists());
+    let summary = fs::read_to_string(output.join("summary.csv")).unwrap();
+    assert_eq!(summary.lines().count(), 6);
+    assert!(
+        fs::read_to_string(output.join("in/a/x/entropy.txt")).unwrap()
+            != fs::read_to_string(output.join("in/b/x/entropy.txt")).unwrap()
+    );
+}
+
+/// The files written by the full analysis before it was split into `analyze_file` and writing the results,
+/// see `tests/fixtures/full`.
+const EXPECTED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/full");
+
+#[test]
+fn full_analysis_writes_the_same_files_as_before() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    fs::create_dir(&input).unwrap();
+    fs::write(input.join("noise.bin"), noise(1000)).unwrap();
+    fs::write(
+        input.join("text.txt"),
+        b"The quick brown fox jumps over the lazy dog.\n".repeat(100),
+    )
+    .unwrap();
+    fs::write(input.join("zeros.bin"), vec![0; 3000]).unwrap();
+    binviz()
+        .current_dir(dir.path())
+        .args(["--quiet", "full", "-f", "in"])
+        .assert()
+        .success();
+    let (expected, actual) = (
+        files_below(Path::new(EXPECTED)),
+        files_below(&dir.path().join("output")),
+    );
+    let names: Vec<&PathBuf> = expected.iter().map(|(name, _)| name).collect();
+    let mut actual_names: Vec<&PathBuf> = actual.iter().map(|(name, _)| name).collect();
+    // The table of the summary only differs in the durations, which `summary.csv` covers.
+    actual_names.retain(|name| name.as_os_str() != "summary.txt");
+    assert_eq!(names, actual_names);
+    for (name, expected) in &expected {
+        let actual = &actual.iter().find(|(other, _)| other == name).unwrap().1;
+        match name.extension().unwrap().to_str().unwrap() {
+            "csv" if name.as_os_str() == "summary.csv" => assert_eq!(
+                summary_without_durations(expected),
+                summary_without_durations(actual)
+            ),
+            // The compressed bytes depend on the version of the PNG encoder, the pixels do not.
+            "png" => assert!(
+                image::load_from_memory(expected).unwrap()
+                    == image::load_from_memory(actual).unwrap(),
+                "{} differs",
+                name.display()
+            ),
+            // The compression feature adds the compressed size to the statistics of the default features.
+            "txt" if name.ends_with("entropy.txt") => assert_eq!(
+                String::from_utf8_lossy(expected)
+                    .lines()
+                    .collect::<Vec<_>>(),
+                String::from_utf8_lossy(actual)
+                    .lines()
+                    .filter(|line| !cfg!(feature = "compression")
+                        || !line.starts_with("Compressing with "))
+                    .collect::<Vec<_>>(),
+                "{} differs",
+                name.display()
+            ),
+            _ => assert!(expected == actual, "{} differs", name.display()),
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn output_mirrors_the_input_tree_of_regular_files() {
+    use std::os::unix::{fs::symlink, net::UnixListener};
+
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    fs::create_dir_all(input.join("a").join("b")).unwrap();
+    fs::write(input.join("top.bin"), noise(3000)).unwrap();
+    fs::write(input.join("a").join("b").join("deep.bin"), noise(4000)).unwrap();
+    // Neither the socket nor the link are regular files.
+    let _socket = UnixListener::bind(input.join("a").join("socket")).unwrap();
+    symlink(input.join("top.bin"), input.join("link.bin")).unwrap();
+    let output = dir.path().join("out");
+    binviz()
+        .args(["--quiet", "full", "-f"])
+        .arg(&input)
+        .arg("--output-dir")
+        .arg(&output)
+        .assert()
+        .success();
+    let folders: Vec<PathBuf> = files_below(&output)
+        .into_iter()
+        .filter(|(name, _)| name.ends_with("entropy.txt"))
+        .map(|(name, _)| name.parent().unwrap().to_path_buf())
+        .collect();
+    assert_eq!(folders, [Path::new("in/a/b/deep"), Path::new("in/top")]);
+    let summary = fs::read_to_string(output.join("summary.csv")).unwrap();
+    assert_eq!(summary.lines().count(), 1 + 2);
+}
+
+/// The names of the files written for `noise.bin` by a full analysis with the extra `args`.
+fn outputs_with(args: &[&str]) -> Vec<String> {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("in")).unwrap();
+    fs::write(dir.path().join("in").join("noise.bin"), noise(3000)).unwrap();
+    binviz()
+        .current_dir(dir.path())
+        .args(["--quiet", "full", "-f", "in"])
+        .args(args)
+        .assert()
+        .success();
+    files_below(&dir.path().join("output").join("in").join("noise"))
+        .into_iter()
+        .map(|(name, _)| name.to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn no_digraph_leaves_out_the_image_and_its_counts() {
+    let all = outputs_with(&[]);
+    let without = outputs_with(&["--no-digraph"]);
+    let left_out: Vec<&String> = all.iter().filter(|name| !without.contains(name)).collect();
+    assert_eq!(left_out, ["counts.csv", "image.png"]);
+    // Nothing else is left out, and nothing is added.
+    assert!(without.iter().all(|name| all.contains(name)));
+    assert!(without.contains(&"image_color.png".to_string()));
+}
+
+#[test]
+fn summary_has_a_row_per_file_matching_its_outputs() {
+    let dir = tempfile::tempdir().unwrap();
+    fixtures(dir.path());
+    binviz()
+        .current_dir(dir.path())
+        .args(["--quiet", "full", "--sort-by", "name", "-f", "in"])
+        .assert()
+        .code(1);
+    let output = dir.path().join("output");
+    let summary = fs::read_to_string(output.join("summary.csv")).unwrap();
+    let rows: Vec<Vec<&str>> = summary
+        .lines()
+        .skip(1)
+        .map(|line| line.splitn(8, ',').collect())
+        .collect();
+    let files: Vec<&str> = rows.iter().map(|row| row[0]).collect();
+    assert_eq!(
+        files,
+        [
+            "in/a/x.bin",
+            "in/b/x.bin",
+            "in/text.txt",
+            "in/tiny.bin",
+            "in/z.bin"
+        ]
+    );
+    for row in &rows {
+        let path = Path::new(row[0]);
+        if path.ends_with("tiny.bin") {
+            // The failed file is listed with the reason, without statistics.
+            assert_eq!(row[1..6], ["", "", "", "", ""]);
+            assert!(row[7].contains("too small"), "{}", row[7]);
+            continue;
+        }
+        assert_eq!(row[7], "");
+        let size = fs::metadata(dir.path().join(path)).unwrap().len();
+        assert_eq!(row[1], size.to_string());
+        let folder = output.join(path.with_extension(""));
+        // The entropy of single bytes is the first row of the entropy table.
+        let entropy: f64 = row[2].parse().unwrap();
+        let entropies = fs::read_to_string(folder.join("entropy.txt")).unwrap();
+        assert!(
+            entropies.contains(&format!(
+                "| 1         | {:.5} (bits per 1 byte(s))",
+                entropy
+            )),
+            "{}",
+            entropies
+        );
+        // The most frequent byte leads the frequency table, which has a row per distinct byte.
+        let most_frequent = fs::read_to_string(folder.join("most_frequent.txt")).unwrap();
+        let table: Vec<&str> = most_frequent
+            .lines()
+            .skip(2)
+            .take_while(|line| line.starts_with('|'))
+            .collect();
+        assert_eq!(row[4], table.len().to_string());
+        assert!(table[0].contains(&format!("| {} ", row[5])), "{}", table[0]);
+    }
+}
+
+#[test]
+fn summary_is_sorted_by_size() {
+    let dir = tempfile::tempdir().unwrap();
+    fixtures(dir.path());
+    binviz()
+        .current_dir(dir.path())
+        .args(["--quiet", "full", "--sort-by", "size", "-f", "in"])
+        .assert()
+        .code(1);
+    let summary = fs::read_to_string(dir.path().join("output").join("summary.csv")).unwrap();
+    let files: Vec<&str> = summary
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+    // The largest first, 5000, 4800, 3300 and 3000 bytes, the failed file last.
+    assert_eq!(
+        files,
+        [
+            "in/a/x.bin",
+            "in/b/x.bin",
+            "in/text.txt",
+            "in/z.bin",
+            "in/tiny.bin"
+        ]
+    );
+}
+
+#[test]
+fn no_trigraph_leaves_out_the_color_image() {
+    let all = outputs_with(&[]);
+    let without = outputs_with(&["--no-trigraph"]);
+    let left_out: Vec<&String> = all.iter().filter(|name| !without.contains(name)).collect();
+    assert_eq!(left_out, ["image_color.png"]);
+    assert!(without.iter().all(|name| all.contains(name)));
+}
+
+#[test]
+fn entropy_table_has_the_given_dimensions() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("in")).unwrap();
+    fs::write(dir.path().join("in").join("noise.bin"), noise(3000)).unwrap();
+    for (range, expected) in [("2-4", &["2", "3", "4"][..]), ("1", &["1"][..])] {
+        binviz()
+            .current_dir(dir.path())
+            .args(["--quiet", "full", "--force", "--entropy-dimensions", range])
+            .args(["-f", "in"])
+            .assert()
+            .success();
+        let entropies = fs::read_to_string(dir.path().join("output/in/noise/entropy.txt")).unwrap();
+        let dimensions: Vec<&str> = entropies
+            .lines()
+            .skip(2)
+            .take_while(|line| line.starts_with('|'))
+            .map(|line| line.split('|').nth(1).unwrap().trim())
+            .collect();
+        assert_eq!(dimensions, expected, "{}", range);
+    }
+    for range in ["0", "3-2", "1-x"] {
+        binviz()
+            .current_dir(dir.path())
+            .args(["full", "--entropy-dimensions", range, "-f", "in"])
+            .assert()
+            .code(2);
+    }
+}
@@ -0,0 +1,104 @@
+//! The self-contained HTML reports of `binviz full --html`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::binviz;
+
+/// Decode base64 with padding, as in data URIs.
+fn decode_base64(encoded: &str) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let values: Vec<u32> = encoded
+        .bytes()
+        .take_while(|&c| c != b'=')
+        .map(|c| ALPHABET.iter().position(|&a| a == c).unwrap() as u32)
+        .collect();
+    let mut bytes = Vec::new();
+    for group in values.chunks(4) {
+        let bits = group
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, &value)| bits | value << (18 - 6 * i));
+        bytes.extend(&bits.to_be_bytes()[1..group.len()]);
+    }
+    bytes
+}
+
+/// The decoded images embedded in a report, by their captions.
+fn embedded_images(report: &str) -> Vec<(String, image::DynamicImage)> {
+    report
+        .split("<img src=\"data:image/png;base64,")
+        .skip(1)
+        .map(|rest| {
+            let (data, rest) = rest.split_once('"').unwrap();
+            let caption = rest
+                .strip_prefix(" alt=\"")
+                .unwrap()
+                .split('"')
+                .next()
+                .unwrap();
+            let image = image::load_from_memory(&decode_base64(data)).unwrap();
+            (caption.to_string(), image)
+        })
+        .collect()
+}
+
+/// Run the full analysis of a file of `ab` repeated into `output` with the extra arguments `args`.
+fn analyze(dir: &Path, output: &str, args: &[&str]) {
+    let file = dir.join("ab.bin");
+    fs::write(&file, b"ab".repeat(500)).unwrap();
+    binviz()
+        .args(["--quiet", "full", "-f"])
+        .arg(&file)
+        .arg("--output-dir")
+        .arg(dir.join(output))
+        .args(args)
+        .assert()
+        .success();
+}
+
+#[test]
+fn report_has_the_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    analyze(dir.path(), "out", &["--html"]);
+    let report = fs::read_to_string(dir.path().join("out/ab/report.html")).unwrap();
+    assert!(report.contains("<tr><td>1</td><td>1.00000 (bits per 1 byte(s))</td><td>0.12500</td>"));
+    assert!(report.contains(
+        "<tr><td>0</td><td>97</td><td>0x61</td><td>&#39;a&#39;</td><td>printable</td><td>0.50000</td></tr>"
+    ));
+    assert!(report.contains(
+        "<tr><td>1</td><td>98</td><td>0x62</td><td>&#39;b&#39;</td><td>printable</td><td>0.50000</td></tr>"
+    ));
+    let index = fs::read_to_string(dir.path().join("out/index.html")).unwrap();
+    assert!(index.contains("<a href=\"ab/report.html\">"));
+}
+
+#[test]
+fn report_embeds_the_images() {
+    let dir = tempfile::tempdir().unwrap();
+    analyze(dir.path(), "out", &["--html"]);
+    let report = fs::read_to_string(dir.path().join("out/ab/report.html")).unwrap();
+    let images = embedded_images(&report);
+    let captions: Vec<&str> = images.iter().map(|(caption, _)| caption.as_str()).collect();
+    assert_eq!(captions, ["Entropy map", "Byte pairs", "Byte triples"]);
+    // The embedded digraph is the saved one.
+    let saved = image::open(dir.path().join("out/ab/image.png")).unwrap();
+    assert_eq!(images[1].1, saved);
+    let pairs = images[1].1.to_luma16();
+    assert!(pairs.get_pixel(b'a'.into(), b'b'.into())[0] > 0);
+    assert!(pairs.get_pixel(b'b'.into(), b'a'.into())[0] > 0);
+    assert_eq!(pairs.get_pixel(b'a'.into(), b'a'.into())[0], 0);
+}
+
+#[test]
+fn html_only_leaves_out_the_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    analyze(dir.path(), "out", &["--html-only"]);
+    let names: Vec<String> = fs::read_dir(dir.path().join("out/ab"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, ["report.html"]);
+}
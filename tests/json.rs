@@ -0,0 +1,131 @@
+//! The JSON documents of `--format json` deserialize into structs following their documented schema.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::{binviz, noise};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Header {
+    tool: String,
+    version: String,
+    input: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Entropy {
+    dimension: usize,
+    entropy_bits: f64,
+    relative_entropy: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntropyDocument {
+    header: Header,
+    entropies: Vec<Entropy>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Frequency {
+    rank: usize,
+    bytes: Vec<u8>,
+    count: usize,
+    probability: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FrequencyDocument {
+    header: Header,
+    frequencies: Vec<Frequency>,
+    total: usize,
+    distinct: usize,
+}
+
+/// Run binviz with `args` on a file containing `buf`, and deserialize what it prints.
+fn json_of<T>(buf: &[u8], args: &[&str]) -> (T, String)
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("input.bin");
+    fs::write(&path, buf).unwrap();
+    let output = binviz()
+        .args(args)
+        .arg("-f")
+        .arg(&path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let document = serde_json::from_slice(&output).unwrap();
+    (document, path.to_string_lossy().into_owned())
+}
+
+#[test]
+fn entropy_document_round_trips() {
+    let (document, path): (EntropyDocument, _) =
+        json_of(&noise(4096), &["entropy", "-c", "3", "--format", "json"]);
+    assert_eq!(document.header.tool, "binviz");
+    assert_eq!(document.header.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(document.header.input, Some(path));
+    let dimensions: Vec<usize> = document
+        .entropies
+        .iter()
+        .map(|entropy| entropy.dimension)
+        .collect();
+    assert_eq!(dimensions, [1, 2, 3]);
+    for entropy in &document.entropies {
+        assert!(entropy.entropy_bits > 0.0);
+        assert!(entropy.entropy_bits <= 8.0 * entropy.dimension as f64);
+        let relative = entropy.entropy_bits / (8.0 * entropy.dimension as f64);
+        assert!((entropy.relative_entropy - relative).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn entropy_numbers_are_raw() {
+    let (document, _): (EntropyDocument, _) =
+        json_of(&[0, 1, 0, 1], &["entropy", "-c", "1", "--format", "json"]);
+    assert_eq!(document.entropies[0].entropy_bits, 1.0);
+    assert_eq!(document.entropies[0].relative_entropy, 0.125);
+}
+
+#[test]
+fn frequency_document_round_trips() {
+    let (document, path): (FrequencyDocument, _) = json_of(
+        b"abababxy",
+        &["frequency", "-d", "2", "--top", "2", "--format", "json"],
+    );
+    assert_eq!(document.header.tool, "binviz");
+    assert_eq!(document.header.input, Some(path));
+    // ab 3, ba 2, bx 1, xy 1, of which the top 2 are listed, but the totals cover all of them.
+    assert_eq!(document.total, 7);
+    assert_eq!(document.distinct, 4);
+    assert_eq!(document.frequencies.len(), 2);
+    let first = &document.frequencies[0];
+    assert_eq!(
+        (first.rank, first.bytes.as_slice(), first.count),
+        (0, &b"ab"[..], 3)
+    );
+    assert!((first.probability - 3.0 / 7.0).abs() < 1e-12);
+    let second = &document.frequencies[1];
+    assert_eq!(
+        (second.rank, second.bytes.as_slice(), second.count),
+        (1, &b"ba"[..], 2)
+    );
+}
+
+#[test]
+fn library_reports_match_the_schema() {
+    let (document, _): (binviz::FrequencyReport<u8>, _) =
+        json_of(b"aab", &["frequency", "--format", "json"]);
+    assert_eq!(document.total, 3);
+    assert_eq!(document.frequencies[0].bytes, b"a");
+    let (document, _): (binviz::EntropyReport, _) =
+        json_of(b"aab", &["entropy", "-c", "2", "--format", "json"]);
+    assert_eq!(document.entropies.len(), 2);
+}
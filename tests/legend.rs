@@ -0,0 +1,57 @@
+//! The colorbar next to generated images, left out with `binviz visualize --no-legend`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{binviz, noise};
+use image::{DynamicImage, GenericImageView};
+
+/// Visualize `input` as `mode` into `output` with the extra `args`.
+fn visualize(input: &Path, output: &Path, args: &[&str], mode: &str) -> DynamicImage {
+    binviz()
+        .args(["--quiet", "visualize", "--force"])
+        .args(args)
+        .arg("-f")
+        .arg(input)
+        .arg("-o")
+        .arg(output)
+        .arg(mode)
+        .assert()
+        .success();
+    image::open(output).unwrap()
+}
+
+#[test]
+fn legend_is_next_to_the_unchanged_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("noise.bin");
+    fs::write(&input, noise(50_000)).unwrap();
+    for mode in ["di", "tri"] {
+        for args in [&[][..], &["--colormap", "viridis"]] {
+            let bare = visualize(
+                &input,
+                &dir.path().join(format!("{}-bare.png", mode)),
+                &[args, &["--no-legend"]].concat(),
+                mode,
+            );
+            let legend = visualize(
+                &input,
+                &dir.path().join(format!("{}-legend.png", mode)),
+                args,
+                mode,
+            );
+            let (width, height) = bare.dimensions();
+            assert!(legend.width() > width, "{} {:?}", mode, args);
+            assert_eq!(legend.height(), height, "{} {:?}", mode, args);
+            assert_eq!(
+                legend.to_rgba16().view(0, 0, width, height).to_image(),
+                bare.to_rgba16(),
+                "{} {:?}",
+                mode,
+                args
+            );
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! The entropy of nibbles with `--granularity nibble`, relative to the 4 bits of a nibble.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::binviz;
+use predicates::str::contains;
+
+#[test]
+fn entropy_is_relative_to_four_bits_per_nibble() {
+    let every_byte: Vec<u8> = (0..=u8::MAX).collect();
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "1", "--granularity", "nibble"])
+        .write_stdin(every_byte)
+        .assert()
+        .success()
+        .stdout(contains(
+            "| 1         | 4.00000 (bits per 1 nibble(s)) | 1.00000          |",
+        ));
+}
+
+#[test]
+fn entropy_of_bytes_is_relative_to_eight_bits() {
+    // Only the nibbles 0 and 1, in all 4 combinations of a byte.
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "1", "--granularity", "nibble"])
+        .write_stdin([0x00, 0x01, 0x10, 0x11])
+        .assert()
+        .success()
+        .stdout(contains("1.00000 (bits per 1 nibble(s)) | 0.25000"));
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "1"])
+        .write_stdin([0x00, 0x01, 0x10, 0x11])
+        .assert()
+        .success()
+        .stdout(contains("2.00000 (bits per 1 byte(s)) | 0.25000"));
+}
@@ -0,0 +1,75 @@
+//! Without a terminal the progress bars are hidden, so piped output stays plain text.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::{binviz, noise};
+
+/// Whether `output` only has printable characters, newlines and tabs: no escape sequences or carriage returns
+/// that redraw a progress bar or color a log line.
+fn is_plain(output: &[u8]) -> bool {
+    output
+        .iter()
+        .all(|&byte| byte == b'\n' || byte == b'\t' || (byte >= 0x20 && byte != 0x7f))
+}
+
+#[test]
+fn piped_full_analysis_has_no_control_characters() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, buf) in [
+        ("noise.bin", noise(20_000)),
+        ("zeros.bin", vec![0; 20_000]),
+        ("text.txt", b"plain text ".repeat(2_000)),
+    ] {
+        fs::write(dir.path().join(name), buf).unwrap();
+    }
+    let output = binviz()
+        .args(["full", "--no-trigraph", "--jobs", "2", "-f"])
+        .arg(dir.path())
+        .arg("--output-dir")
+        .arg(dir.path().join("out"))
+        .env("RUST_LOG", "info")
+        .env_remove("RUST_LOG_STYLE")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    // The log lines are there, just without colors.
+    assert!(!output.stderr.is_empty());
+    assert!(is_plain(&output.stderr), "{}", output.stderr.escape_ascii());
+    assert!(is_plain(&output.stdout), "{}", output.stdout.escape_ascii());
+}
+
+#[test]
+fn piped_entropy_has_no_control_characters() {
+    let output = binviz()
+        .args(["entropy", "-f", "-", "-c", "2"])
+        .write_stdin(noise(100_000))
+        .env("RUST_LOG", "debug")
+        .env_remove("RUST_LOG_STYLE")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(is_plain(&output.stderr), "{}", output.stderr.escape_ascii());
+    assert!(is_plain(&output.stdout), "{}", output.stdout.escape_ascii());
+}
+
+#[test]
+fn quiet_full_analysis_has_no_control_characters() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("noise.bin"), noise(20_000)).unwrap();
+    let output = binviz()
+        .args(["--quiet", "full", "--no-trigraph", "-f"])
+        .arg(dir.path().join("noise.bin"))
+        .arg("--output-dir")
+        .arg(dir.path().join("out"))
+        .env_remove("RUST_LOG_STYLE")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(is_plain(&output.stderr), "{}", output.stderr.escape_ascii());
+}
@@ -0,0 +1,146 @@
+//! Reading a range of the input with `--offset` and `--length`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{binviz, noise};
+use predicates::str::contains;
+
+/// The statistics of the blocks of `binviz blocks` with the extra `args`, without their offsets.
+fn blocks(file: &Path, args: &[&str]) -> Vec<String> {
+    let output = binviz()
+        .args(["--quiet", "blocks", "--format", "csv", "-b", "1024", "-f"])
+        .arg(file)
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let columns: Vec<_> = line.split(',').collect();
+            [&columns[..1], &columns[3..]].concat().join(",")
+        })
+        .collect()
+}
+
+#[test]
+fn entropy_warns_once_about_a_truncated_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("noise.bin");
+    fs::write(&path, noise(8192)).unwrap();
+    let output = binviz()
+        .args([
+            "entropy", "--offset", "4096", "--length", "100000", "-c", "4", "-f",
+        ])
+        .arg(&path)
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert_eq!(
+        stderr.matches("extends past the end of the input").count(),
+        1
+    );
+}
+
+#[test]
+fn range_matches_a_file_of_the_slice() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, slice) = (dir.path().join("noise.bin"), dir.path().join("slice.bin"));
+    let buf = noise(10_000);
+    fs::write(&path, &buf).unwrap();
+    fs::write(&slice, &buf[1000..6000]).unwrap();
+    assert_eq!(
+        blocks(&path, &["--offset", "1000", "--length", "5000"]),
+        blocks(&slice, &[])
+    );
+}
+
+#[test]
+fn visualized_range_matches_the_image_of_the_slice() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, slice) = (dir.path().join("noise.bin"), dir.path().join("slice.bin"));
+    let buf = noise(10_000);
+    fs::write(&path, &buf).unwrap();
+    fs::write(&slice, &buf[0x1000..0x2000]).unwrap();
+    let (ranged, sliced) = (dir.path().join("ranged.png"), dir.path().join("sliced.png"));
+    binviz()
+        .args([
+            "visualize",
+            "--offset",
+            "0x1000",
+            "--length",
+            "0x1000",
+            "-f",
+        ])
+        .arg(&path)
+        .arg("-o")
+        .arg(&ranged)
+        .arg("di")
+        .assert()
+        .success()
+        .stderr(contains(
+            "`4095` byte pairs visualized, from the range `0x1000..0x2000`",
+        ));
+    binviz()
+        .args(["--quiet", "visualize", "-f"])
+        .arg(&slice)
+        .arg("-o")
+        .arg(&sliced)
+        .arg("di")
+        .assert()
+        .success();
+    assert_eq!(
+        image::open(&ranged).unwrap().into_bytes(),
+        image::open(&sliced).unwrap().into_bytes()
+    );
+    let metadata = binviz::read_image_metadata(&ranged).unwrap();
+    assert_eq!((metadata.offset, metadata.length), (0x1000, Some(0x1000)));
+}
+
+#[test]
+fn visualized_range_past_the_end_is_truncated() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("noise.bin");
+    fs::write(&path, noise(3000)).unwrap();
+    // Only the last 1000 bytes are left, with 999 pairs.
+    binviz()
+        .args(["visualize", "--offset", "2000", "--length", "5000", "-f"])
+        .arg(&path)
+        .arg("-o")
+        .arg(dir.path().join("noise.png"))
+        .arg("di")
+        .assert()
+        .success()
+        .stderr(contains(
+            "extends past the end of the input, truncating it to `1000` bytes",
+        ))
+        .stderr(contains("`999` byte pairs visualized"));
+}
+
+#[test]
+fn visualized_range_starting_past_the_end_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("noise.bin");
+    fs::write(&path, noise(3000)).unwrap();
+    binviz()
+        .args(["visualize", "--offset", "5000", "-f"])
+        .arg(&path)
+        .arg("-o")
+        .arg(dir.path().join("noise.png"))
+        .arg("di")
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "offset `0x1388` lies past the end of the input of `3000` bytes",
+        ));
+    assert!(!dir.path().join("noise.png").exists());
+}
@@ -0,0 +1,91 @@
+//! Rendering from a histogram saved with `--save-histogram` instead of from the file it was calculated from.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use binviz::{read_image_metadata, ImageMetadata};
+use common::{binviz, noise};
+use predicates::str::contains;
+
+/// Visualize with the `mode` subcommand and `input` arguments, saving the image to `output`.
+fn visualize(input: &[&str], mode: &str, output: &Path) {
+    binviz()
+        .arg("visualize")
+        .args(input)
+        .arg("-o")
+        .arg(output)
+        .arg(mode)
+        .assert()
+        .success();
+}
+
+/// The metadata of the image at `path` without the files it was calculated from,
+/// as a loaded histogram has none.
+fn metadata_without_sources(path: &Path) -> ImageMetadata {
+    ImageMetadata {
+        sources: Vec::new(),
+        ..read_image_metadata(path).unwrap()
+    }
+}
+
+#[test]
+fn loaded_histogram_renders_the_same_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("noise.bin");
+    fs::write(&input, noise(20_000)).unwrap();
+    let input = input.to_str().unwrap();
+    for (mode, histogram) in [("di", "pairs.json"), ("tri", "triples.bin")] {
+        let histogram = dir.path().join(histogram);
+        let from_file = dir.path().join(format!("{}-file.png", mode));
+        let from_histogram = dir.path().join(format!("{}-histogram.png", mode));
+        visualize(
+            &["-f", input, "--save-histogram", histogram.to_str().unwrap()],
+            mode,
+            &from_file,
+        );
+        visualize(
+            &["--load-histogram", histogram.to_str().unwrap()],
+            mode,
+            &from_histogram,
+        );
+        assert_eq!(
+            image::open(&from_file).unwrap(),
+            image::open(&from_histogram).unwrap(),
+            "{}",
+            mode
+        );
+        assert_eq!(
+            metadata_without_sources(&from_file),
+            metadata_without_sources(&from_histogram)
+        );
+        assert!(read_image_metadata(&from_histogram)
+            .unwrap()
+            .sources
+            .is_empty());
+    }
+}
+
+#[test]
+fn loaded_histogram_of_the_wrong_dimension_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let histogram = dir.path().join("bytes.json");
+    binviz()
+        .args(["frequency", "-f", "-", "--save-histogram"])
+        .arg(&histogram)
+        .write_stdin(noise(1000))
+        .assert()
+        .success();
+    binviz()
+        .args(["visualize", "--load-histogram"])
+        .arg(&histogram)
+        .arg("-o")
+        .arg(dir.path().join("digraph.png"))
+        .arg("di")
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "error: expected a histogram of 2-byte keys, found 1-byte keys",
+        ));
+}
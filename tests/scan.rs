@@ -0,0 +1,90 @@
+//! Ranking the files of a folder, `binviz scan`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{binviz, noise};
+
+/// A folder with a text file, a file of zeros and a file of random bytes.
+fn fixtures(dir: &Path) {
+    fs::write(
+        dir.join("text.txt"),
+        b"The quick brown fox jumps over the lazy dog.\n".repeat(200),
+    )
+    .unwrap();
+    fs::write(dir.join("blank.bin"), vec![0; 8192]).unwrap();
+    fs::write(dir.join("random.bin"), noise(16384)).unwrap();
+}
+
+/// The file names and verdicts of the CSV table of `binviz scan` with `args` on `dir`, in order.
+fn scan(dir: &Path, args: &[&str]) -> Vec<(String, String)> {
+    let output = binviz()
+        .args(["scan", "--format", "csv", "-d"])
+        .arg(dir)
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            let name = Path::new(fields[1]).file_name().unwrap().to_string_lossy();
+            (name.into_owned(), fields[4].to_string())
+        })
+        .collect()
+}
+
+fn names(rows: &[(String, String)]) -> Vec<&str> {
+    rows.iter().map(|(name, _)| name.as_str()).collect()
+}
+
+#[test]
+fn files_are_ranked_by_entropy() {
+    let dir = tempfile::tempdir().unwrap();
+    fixtures(dir.path());
+    let rows = scan(dir.path(), &["--sort", "entropy"]);
+    assert_eq!(
+        rows,
+        [
+            ("random.bin", "compressed or encrypted"),
+            ("text.txt", "text"),
+            ("blank.bin", "binary"),
+        ]
+        .map(|(name, verdict)| (name.to_string(), verdict.to_string()))
+    );
+}
+
+#[test]
+fn files_are_ranked_by_size_or_name() {
+    let dir = tempfile::tempdir().unwrap();
+    fixtures(dir.path());
+    assert_eq!(
+        names(&scan(dir.path(), &["--sort", "size"])),
+        ["random.bin", "text.txt", "blank.bin"]
+    );
+    assert_eq!(
+        names(&scan(dir.path(), &["--sort", "name"])),
+        ["blank.bin", "random.bin", "text.txt"]
+    );
+}
+
+#[test]
+fn files_outside_the_sizes_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    fixtures(dir.path());
+    assert_eq!(
+        names(&scan(dir.path(), &["--min-size", "9000"])),
+        ["random.bin", "text.txt"]
+    );
+    assert_eq!(
+        names(&scan(dir.path(), &["--max-size", "0x2000"])),
+        ["blank.bin"]
+    );
+}
@@ -0,0 +1,112 @@
+//! The path `-` reads standard input, for entropy, frequency and visualize.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::{binviz, noise};
+use predicates::str::contains;
+
+#[test]
+fn entropy_of_piped_input() {
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "3"])
+        .write_stdin(noise(4096))
+        .assert()
+        .success()
+        .stdout(contains("| Dimension"))
+        .stdout(contains("| 1 "))
+        .stdout(contains("| 2 "))
+        .stdout(contains("| 3 "));
+}
+
+#[test]
+fn entropy_of_piped_input_matches_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("noise.bin");
+    fs::write(&path, noise(4097)).unwrap();
+    // A file is read in chunks, standard input is buffered, for every kind of symbol and input.
+    for args in [
+        &[][..],
+        &["--word-size", "2"],
+        &["--word-size", "2", "--endian", "be", "-s", "3"],
+        &["--granularity", "nibble"],
+        &["--offset", "0x100", "--length", "1001"],
+        &["--ignore-bytes", "00-3f"],
+    ] {
+        let from_file = binviz()
+            .args(["entropy", "-c", "3"])
+            .args(args)
+            .arg("-f")
+            .arg(&path)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        binviz()
+            .args(["entropy", "-f", "-", "-c", "3"])
+            .args(args)
+            .write_stdin(noise(4097))
+            .assert()
+            .success()
+            .stdout(String::from_utf8(from_file).unwrap());
+    }
+}
+
+#[test]
+fn entropy_of_mapped_file_matches_read_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("noise.bin");
+    fs::write(&path, noise(4096)).unwrap();
+    let read = binviz()
+        .args(["entropy", "-c", "3", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    binviz()
+        .args(["entropy", "-c", "3", "--mmap", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(String::from_utf8(read).unwrap());
+}
+
+#[test]
+fn frequency_of_piped_input() {
+    binviz()
+        .args(["frequency", "-f", "-", "-d", "2"])
+        .write_stdin("abababab")
+        .assert()
+        .success()
+        .stdout(contains("Relative Frequency"))
+        .stdout(contains("'ab'"))
+        .stdout(contains("'ba'"));
+}
+
+#[test]
+fn visualize_piped_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("digraph.png");
+    binviz()
+        .args(["visualize", "-f", "-", "-o"])
+        .arg(&output)
+        .arg("di")
+        .write_stdin(noise(4096))
+        .assert()
+        .success();
+    assert!(output.is_file());
+}
+
+#[test]
+fn empty_piped_input_fails() {
+    binviz()
+        .args(["entropy", "-f", "-", "-c", "1"])
+        .write_stdin("")
+        .assert()
+        .failure();
+}
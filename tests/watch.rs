@@ -0,0 +1,97 @@
+//! Rendering again with `--watch` whenever the input changes, also when it is deleted and created again.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::{
+    fs,
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use common::noise;
+
+/// How long to wait for the watching binary to render, generous for slow machines.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The watching binary, killed when dropped.
+struct Watching(Child);
+
+impl Drop for Watching {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Start `binviz visualize --watch` rendering `input` to `output`.
+fn watch(input: &Path, output: &Path) -> Watching {
+    let child = Command::new(assert_cmd::cargo::cargo_bin("binviz"))
+        .args(["--no-config", "--quiet", "visualize", "--watch", "-f"])
+        .arg(input)
+        .arg("-o")
+        .arg(output)
+        .arg("di")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    Watching(child)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Wait until `output` was modified after `since`, or exists at all without `since`, returning its mtime.
+fn wait_for_render(output: &Path, since: Option<SystemTime>) -> SystemTime {
+    let start = Instant::now();
+    loop {
+        match modified(output) {
+            Some(mtime) if since.is_none_or(|since| mtime > since) => {
+                // Let the binary finish writing before the image is read or the input changed.
+                thread::sleep(Duration::from_millis(200));
+                return modified(output).unwrap();
+            }
+            _ if start.elapsed() > TIMEOUT => panic!("`{}` wasn't rendered", output.display()),
+            _ => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+#[test]
+fn touching_the_input_renders_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let (input, output) = (dir.path().join("noise.bin"), dir.path().join("noise.png"));
+    fs::write(&input, noise(4096)).unwrap();
+    let _watching = watch(&input, &output);
+    let first = wait_for_render(&output, None);
+
+    fs::write(&input, noise(8192)).unwrap();
+    let second = wait_for_render(&output, Some(first));
+    assert!(second > first);
+    image::open(&output).unwrap();
+}
+
+#[test]
+fn recreating_the_input_renders_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let (input, output) = (dir.path().join("noise.bin"), dir.path().join("noise.png"));
+    fs::write(&input, noise(4096)).unwrap();
+    let _watching = watch(&input, &output);
+    let first = wait_for_render(&output, None);
+
+    fs::remove_file(&input).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    fs::write(&input, noise(8192)).unwrap();
+    let second = wait_for_render(&output, Some(first));
+    assert!(second > first);
+
+    // The recreated file is still watched.
+    fs::write(&input, noise(2048)).unwrap();
+    assert!(wait_for_render(&output, Some(second)) > second);
+}
@@ -0,0 +1,49 @@
+//! Analyzing the input as 16-bit words with `--word-size 2`.
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::fs;
+
+use common::binviz;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+#[test]
+fn odd_trailing_byte_of_a_file_is_dropped_with_a_message() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("odd.bin");
+    fs::write(&path, [0x01, 0x02, 0x01, 0x02, 0x03]).unwrap();
+    for args in [
+        &["frequency", "--word-size", "2"][..],
+        &["entropy", "--word-size", "2", "-c", "1"],
+    ] {
+        binviz()
+            .args(args)
+            .arg("-f")
+            .arg(&path)
+            .assert()
+            .success()
+            .stderr(contains("dropping the odd trailing byte"));
+    }
+    // Both words are `0x0201`, the trailing `0x03` isn't counted.
+    binviz()
+        .args(["frequency", "--word-size", "2", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(contains("| 0    | 513  | 0x0201 | 1.00000"));
+}
+
+#[test]
+fn words_are_big_endian_with_endian_be() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("words.bin");
+    fs::write(&path, [0x01, 0x02, 0x01, 0x02]).unwrap();
+    binviz()
+        .args(["frequency", "--word-size", "2", "--endian", "be", "-f"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(contains("| 258  | 0x0102 |"))
+        .stderr(contains("odd trailing byte").not());
+}